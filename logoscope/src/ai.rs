@@ -1,8 +1,7 @@
 use crate::{anomaly, schema, temporal, parser, drain_adapter, param_extractor, analyzers};
-use chrono::TimeZone;
 use serde::{Serialize, Deserialize};
-use rayon::prelude::*;
-use std::collections::HashMap;
+use crate::compat::*;
+use std::collections::{BTreeMap, HashMap};
 use once_cell::sync::Lazy;
 
 // Static regex for template humanization to avoid recompilation
@@ -17,6 +16,105 @@ pub struct Summary {
     pub compression_ratio: f64,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    /// Fraction of lines with a parsed timestamp; when low, temporal sections describe only a
+    /// few lines (try `--time-key`)
+    #[serde(default)]
+    pub timestamped_ratio: f64,
+    /// Set when the input had no usable lines (empty or entirely malformed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostic: Option<String>,
+    /// JSON field auto-selected as the record time when no `--time-key` was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_key: Option<String>,
+    /// Set when timestamps go backwards in input order; temporal analysis may be unreliable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_skew: Option<ClockSkewOut>,
+    /// Lines per detected format (JSON, ELB, NGINX, APACHE, UNKNOWN), most common first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub detected_formats: Vec<CountItem>,
+    /// Lines per syslog facility (auth, cron, daemon, local0-7, ...) for `<PRI>`-prefixed input
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_facility: Vec<CountItem>,
+    /// Lines dropped at ingestion as repeats of the line before them (`--dedup-window`)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub deduped_lines: usize,
+    /// Rare patterns dropped to stay within `max_tracked_patterns`; their counts are approximate
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub evicted_patterns: usize,
+    /// Lines that had been counted under evicted patterns
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub evicted_lines: usize,
+    /// Pattern active over the longest time range (chronic background behavior)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widest_span: Option<PatternSpanOut>,
+    /// Recurring pattern confined to the shortest time range (a burst or incident)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub narrowest_span: Option<PatternSpanOut>,
+}
+
+/// Format label for a parsed line: JSON when structured, else the matched access-log format
+fn line_format(rec: &parser::ParsedRecord) -> &'static str {
+    if rec.flat_fields.is_some() { "JSON" } else { crate::smart_masking::detect_format(&rec.message).as_str() }
+}
+
+fn count_items_out<K: AsRef<str>>(counts: &HashMap<K, usize>) -> Vec<CountItem> {
+    let mut items: Vec<CountItem> = counts.iter().map(|(name, count)| CountItem { name: name.as_ref().to_string(), count: *count }).collect();
+    items.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    items
+}
+
+/// Level counts of a message template across its per-level patterns; empty unless it
+/// was logged at more than one level
+fn severity_breakdown_out<K: AsRef<str>>(levels: Option<&HashMap<K, usize>>) -> Vec<CountItem> {
+    match levels {
+        Some(counts) if counts.len() > 1 => count_items_out(counts),
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternSpanOut {
+    pub template: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub span_seconds: i64,
+    pub total_count: usize,
+}
+
+/// Fill `summary.widest_span` / `narrowest_span` from each pattern's first and last occurrence.
+/// Only patterns seen at least twice count, and both stay unset with fewer than two candidates.
+pub fn set_pattern_spans(out: &mut AiOutput) {
+    let spans: Vec<PatternSpanOut> = out.patterns.iter()
+        .filter(|p| p.total_count >= 2)
+        .filter_map(|p| {
+            let (start, end) = (p.start_time.as_ref()?, p.end_time.as_ref()?);
+            let secs = (chrono::DateTime::parse_from_rfc3339(end).ok()? - chrono::DateTime::parse_from_rfc3339(start).ok()?).num_seconds();
+            Some(PatternSpanOut { template: p.template.clone(), start_time: start.clone(), end_time: end.clone(), span_seconds: secs, total_count: p.total_count })
+        })
+        .collect();
+    if spans.len() < 2 {
+        return;
+    }
+    // Ties go to the more frequent pattern
+    out.summary.widest_span = spans.iter().max_by_key(|s| (s.span_seconds, s.total_count)).cloned();
+    out.summary.narrowest_span = spans.iter().min_by_key(|s| (s.span_seconds, std::cmp::Reverse(s.total_count))).cloned();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockSkewOut {
+    pub out_of_order_lines: usize,
+    pub out_of_order_ratio: f64,
+    pub max_backward_seconds: i64,
+}
+
+impl From<temporal::ClockSkew> for ClockSkewOut {
+    fn from(s: temporal::ClockSkew) -> Self {
+        Self {
+            out_of_order_lines: s.out_of_order_lines,
+            out_of_order_ratio: s.out_of_order_lines as f64 / s.timestamped_lines.max(1) as f64,
+            max_backward_seconds: s.max_backward_seconds,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +125,158 @@ pub struct AiOutput {
     pub anomalies: AnomaliesOut,
     pub query_interface: QueryInterfaceOut,
     pub errors: ErrorsOut,
+    /// Families of near-duplicate templates; per-pattern output is unchanged
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pattern_clusters: Vec<PatternClusterOut>,
+    /// Corpus-wide value counts per param type (`IP`, `STATUS_CODE`, ...), rolled up from every
+    /// pattern's `param_stats`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub global_params: BTreeMap<String, GlobalParamOut>,
+    /// Patterns below `min_count`, collapsed into one aggregate instead of being listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other_patterns: Option<OtherPatternsOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtherPatternsOut {
+    /// Number of collapsed patterns
+    pub patterns: usize,
+    pub total_count: usize,
+    pub frequency: f64,
+}
+
+/// Pattern entry for `SummarizeOpts::counts_only`: template, count and severity, nothing else
+fn count_only_pattern(template: String, count: usize, total: usize, severity: Option<String>) -> PatternOut {
+    PatternOut {
+        template,
+        frequency: count as f64 / total.max(1) as f64,
+        total_count: count,
+        severity,
+        severity_breakdown: Vec::new(),
+        start_time: None,
+        end_time: None,
+        spike_analysis: None,
+        temporal: None,
+        examples: Vec::new(),
+        correlations: Vec::new(),
+        pattern_stability: 0.0,
+        periodicity: None,
+        dominant_service: None,
+        dominant_service_ratio: None,
+        dominant_host: None,
+        dominant_host_ratio: None,
+        by_file: Vec::new(),
+        category: None,
+        sources: SourceBreakdown::default(),
+        drain_template: None,
+        param_stats: None,
+        parameter_anomalies: None,
+        deep_temporal: None,
+        deep_correlations: None,
+        extensions: BTreeMap::new(),
+    }
+}
+
+/// Output for `SummarizeOpts::counts_only`: the summary and per-template counts, most frequent first
+fn counts_only_output(summary: Summary, mut patterns: Vec<PatternOut>, errors: ErrorsOut, opts: &SummarizeOpts) -> AiOutput {
+    patterns.sort_by(|a, b| b.total_count.cmp(&a.total_count).then_with(|| a.template.cmp(&b.template)));
+    let mut out = AiOutput {
+        summary,
+        patterns,
+        schema_changes: Vec::new(),
+        anomalies: AnomaliesOut::default(),
+        query_interface: QueryInterfaceOut {
+            available_commands: vec!["GET_LINES_BY_PATTERN".into(), "GET_LINES_BY_TIME".into(), "GET_CONTEXT".into()],
+            suggested_investigations: Vec::new(),
+        },
+        errors,
+        pattern_clusters: Vec::new(),
+        global_params: BTreeMap::new(),
+        other_patterns: None,
+    };
+    if let Some(min) = opts.min_count { collapse_rare_patterns(&mut out, min); }
+    out
+}
+
+/// Move patterns seen fewer than `min_count` times out of `patterns` into `other_patterns`.
+/// `summary` totals and compression ratio still cover them.
+pub fn collapse_rare_patterns(out: &mut AiOutput, min_count: usize) {
+    let (kept, rare): (Vec<PatternOut>, Vec<PatternOut>) = std::mem::take(&mut out.patterns)
+        .into_iter()
+        .partition(|p| p.total_count >= min_count);
+    out.patterns = kept;
+    if !rare.is_empty() {
+        let total_count: usize = rare.iter().map(|p| p.total_count).sum();
+        out.other_patterns = Some(OtherPatternsOut {
+            patterns: rare.len(),
+            total_count,
+            frequency: total_count as f64 / out.summary.total_lines.max(1) as f64,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalParamOut {
+    pub total: usize,
+    /// Distinct values among those reported per pattern (a lower bound under `top_params`)
+    pub cardinality: usize,
+    /// Patterns the param type occurs in
+    pub patterns: usize,
+    pub values: Vec<ParamValueCount>,
+}
+
+/// Roll `param_stats` up across patterns by param type: positional names merge into their
+/// base type (`IP_2` into `IP`) and time params are skipped. Values are sorted by count.
+pub fn build_global_params(patterns: &[PatternOut]) -> BTreeMap<String, GlobalParamOut> {
+    let mut counts: BTreeMap<String, (usize, usize, HashMap<String, usize>)> = BTreeMap::new();
+    for p in patterns {
+        let Some(stats) = p.param_stats.as_ref() else { continue };
+        let mut seen: HashSet<&str> = HashSet::new();
+        for (name, st) in stats.iter().filter(|(name, _)| !param_extractor::is_time_param(name)) {
+            let base = param_extractor::get_base_param_type(name);
+            let entry = counts.entry(base.to_string()).or_default();
+            entry.0 += st.total;
+            if seen.insert(base) { entry.1 += 1; }
+            for v in &st.values {
+                *entry.2.entry(v.value.clone()).or_insert(0) += v.count;
+            }
+        }
+    }
+    counts.into_iter()
+        .map(|(name, (total, patterns, values))| {
+            let mut values: Vec<ParamValueCount> = values.into_iter().map(|(value, count)| ParamValueCount { value, count }).collect();
+            values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            (name, GlobalParamOut { total, cardinality: values.len(), patterns, values })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternClusterOut {
+    /// Most frequent member template, used as the family label
+    pub representative: String,
+    pub total_count: usize,
+    pub members: Vec<String>,
+}
+
+/// Group output patterns into families by token similarity of their templates.
+/// Members are listed by descending count; families by descending total count.
+pub fn build_pattern_clusters(patterns: &[PatternOut], threshold: f64) -> Vec<PatternClusterOut> {
+    let templates: Vec<&str> = patterns.iter().map(|p| p.template.as_str()).collect();
+    let mut clusters: Vec<PatternClusterOut> = crate::patterns::group_similar_templates(&templates, threshold)
+        .into_iter()
+        .map(|group| {
+            let mut members: Vec<&PatternOut> = group.iter().map(|&i| &patterns[i]).collect();
+            members.sort_by(|a, b| b.total_count.cmp(&a.total_count).then_with(|| a.template.cmp(&b.template)));
+            PatternClusterOut {
+                representative: members[0].template.clone(),
+                total_count: members.iter().map(|p| p.total_count).sum(),
+                members: members.iter().map(|p| p.template.clone()).collect(),
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.total_count.cmp(&a.total_count).then_with(|| a.representative.cmp(&b.representative)));
+    clusters
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +285,10 @@ pub struct PatternOut {
     pub frequency: f64,
     pub total_count: usize,
     pub severity: Option<String>,
+    /// Line counts per level for this message across its per-level patterns, most frequent
+    /// first; only present when the message was logged at more than one level
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub severity_breakdown: Vec<CountItem>,
     pub start_time: Option<String>,  // First occurrence of this pattern
     pub end_time: Option<String>,    // Last occurrence of this pattern
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -42,12 +296,30 @@ pub struct PatternOut {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temporal: Option<TemporalOut>,
     pub examples: Vec<String>,
-    #[serde(skip)]
+    /// Patterns that co-occur with this one in time (only with `--correlate`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub correlations: Vec<CorrelatedOut>,
     pub pattern_stability: f64,  // Combined metric: time consistency (60%) + frequency (40%), range 0.0-1.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub periodicity: Option<f64>,  // Inter-arrival regularity (0.0-1.0), high = cron-like schedule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dominant_service: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dominant_service_ratio: Option<f64>,  // Share of this pattern's lines from dominant_service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dominant_host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dominant_host_ratio: Option<f64>,
+    /// Input files the pattern's lines came from (top 3), when the input was tagged by file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_file: Vec<CountItem>,
+    /// `access`, `app` or `json`: which canonicalization path the pattern's lines took
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
     #[serde(skip)]
     pub sources: SourceBreakdown,
-    #[serde(skip)]
+    /// Raw Drain template behind `template`; only emitted with `SummarizeOpts::show_drain_template`
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub drain_template: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub param_stats: Option<std::collections::HashMap<String, ParamFieldStats>>,
@@ -57,6 +329,9 @@ pub struct PatternOut {
     pub deep_temporal: Option<DeepTemporalOut>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deep_correlations: Option<Vec<DeepCorrelation>>,
+    /// Results of custom analyzers (`AnalyzerRegistry::with_analyzer`), keyed by analyzer name
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub extensions: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,7 +347,7 @@ pub struct SpikeAnalysis {
 pub struct Spike {
     pub time: String,
     pub event_count: usize,
-    pub severity: f64,  // How many times above average
+    pub severity: f64,  // Peak events per burst-window bucket over the median non-burst bucket
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +370,66 @@ pub struct ParamFieldStats {
     pub is_sequence: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sequence_info: Option<SequenceInfo>,
+    /// Path/URL values grouped by route shape (ID segments replaced with `{id}`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routes: Option<Vec<ParamValueCount>>,
+    /// Latency params only: counts per bucket (<10ms, 10-100ms, 100ms-1s, >1s)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_buckets: Option<Vec<ParamValueCount>>,
+    /// Range statistics when every value is numeric
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numeric_summary: Option<NumericSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumericSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub p50: f64,
+    pub p99: f64,
+}
+
+/// Count-weighted min/max/mean/stddev/p50/p99 over param values.
+/// Returns None unless every value parses as a number (unit suffixes like `15ms` are allowed).
+pub fn compute_numeric_summary(values: &[ParamValueCount]) -> Option<NumericSummary> {
+    fn leading_number(s: &str) -> Option<f64> {
+        let end = s.char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && c == '-')))
+            .map(|(i, _)| i)
+            .unwrap_or(s.len());
+        let unit = s[end..].trim_start();
+        if !unit.chars().all(|c| c.is_alphabetic() || c == '%') { return None; }
+        s[..end].parse::<f64>().ok().filter(|v| v.is_finite())
+    }
+    let mut points: Vec<(f64, usize)> = Vec::with_capacity(values.len());
+    for v in values {
+        points.push((leading_number(v.value.trim())?, v.count));
+    }
+    let n: usize = points.iter().map(|p| p.1).sum();
+    if n == 0 { return None; }
+    points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mean = points.iter().map(|(x, c)| x * *c as f64).sum::<f64>() / n as f64;
+    let var = points.iter().map(|(x, c)| (x - mean).powi(2) * *c as f64).sum::<f64>() / n as f64;
+    // Nearest-rank percentile over the count-weighted distribution
+    let percentile = |q: f64| {
+        let rank = ((q * n as f64).ceil() as usize).max(1);
+        let mut seen = 0;
+        for (x, c) in &points {
+            seen += c;
+            if seen >= rank { return *x; }
+        }
+        points[points.len() - 1].0
+    };
+    Some(NumericSummary {
+        min: points[0].0,
+        max: points[points.len() - 1].0,
+        mean,
+        stddev: var.sqrt(),
+        p50: percentile(0.5),
+        p99: percentile(0.99),
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +459,15 @@ pub struct FieldAnomaly {
     pub total: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ratio: Option<f64>,
+    /// Values never seen for this field before (enum_drift only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_values: Vec<String>,
+    /// Raw lines that triggered the anomaly (only with `SummarizeOpts::explain_anomalies`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
+    /// Severity from 0 to 1, comparable across all anomaly kinds
+    #[serde(default)]
+    pub score: f64,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -140,19 +484,103 @@ pub struct CorrelatedOut {
     pub strength: f64,
 }
 
+/// Co-occurrence window used by the lightweight `--correlate` pass
+pub const CORRELATION_WINDOW_SECS: i64 = 10;
+/// Weakest Jaccard strength reported as a correlation
+pub const MIN_CORRELATION_STRENGTH: f64 = 0.3;
+/// Correlated patterns kept per pattern, strongest first
+pub const MAX_CORRELATIONS_PER_PATTERN: usize = 5;
+
+/// Lightweight pairwise co-occurrence correlation over pattern timestamps.
+/// Returns, per template, its strongest partners (at least two co-occurrences).
+pub fn correlate_patterns(times_by_tpl: &HashMap<String, Vec<chrono::DateTime<chrono::Utc>>>) -> HashMap<String, Vec<CorrelatedOut>> {
+    let mut related: HashMap<String, Vec<CorrelatedOut>> = HashMap::new();
+    let window = chrono::Duration::seconds(CORRELATION_WINDOW_SECS);
+    for c in crate::correlation::compute_correlations(times_by_tpl, window) {
+        if c.count < 2 || c.strength < MIN_CORRELATION_STRENGTH { continue; }
+        let strength = (c.strength * 1000.0).round() / 1000.0;
+        related.entry(c.a.clone()).or_default().push(CorrelatedOut { template: c.b.clone(), count: c.count, strength });
+        related.entry(c.b).or_default().push(CorrelatedOut { template: c.a, count: c.count, strength });
+    }
+    for list in related.values_mut() {
+        list.sort_by(|a, b| b.strength.partial_cmp(&a.strength).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.template.cmp(&b.template)));
+        list.truncate(MAX_CORRELATIONS_PER_PATTERN);
+    }
+    related
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchemaChangeOut {
     pub timestamp: Option<String>,
     pub change_type: String,
     pub field: String,
+    /// Type before the change (type_changed, field_removed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_type: Option<String>,
+    /// Type after the change (type_changed, field_added)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_type: Option<String>,
     pub impact: Option<String>,
 }
 
+impl SchemaChangeOut {
+    fn from_change(change: &schema::SchemaChange, timestamp: Option<String>) -> Self {
+        let (change_type, old_type, new_type) = match change {
+            schema::SchemaChange::FieldAdded { new_type, .. } => ("field_added", None, Some(new_type.clone())),
+            schema::SchemaChange::FieldRemoved { old_type, .. } => ("field_removed", Some(old_type.clone()), None),
+            schema::SchemaChange::TypeChanged { from_type, to_type, .. } => ("type_changed", Some(from_type.clone()), Some(to_type.clone())),
+        };
+        Self { timestamp, change_type: change_type.into(), field: change.field().to_string(), old_type, new_type, impact: Some(change.impact()) }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AnomaliesOut {
     pub pattern_anomalies: Vec<PatternAnomalyOut>,
     pub field_anomalies: Vec<FieldAnomaly>,
-    pub temporal_anomalies: Vec<String>,
+    pub temporal_anomalies: Vec<TemporalAnomalyOut>,
+    /// Tracked pattern-pair ratios (`ratio_rules`) that shifted against their baseline
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ratio_anomalies: Vec<RatioAnomalyOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatioAnomalyOut {
+    /// The rule as given: `NUMERATOR_REGEX / DENOMINATOR_REGEX`
+    pub rule: String,
+    pub current_ratio: f64,
+    pub baseline_ratio: f64,
+    pub numerator_count: usize,
+    pub denominator_count: usize,
+    /// `previous_window` (baseline frequencies) or `earlier_lines` (all but the latest quarter)
+    pub baseline: String,
+    /// Severity from 0 to 1, comparable across all anomaly kinds
+    #[serde(default)]
+    pub score: f64,
+}
+
+impl RatioAnomalyOut {
+    fn new(shift: anomaly::RatioShift, baseline: &str) -> Self {
+        Self {
+            score: anomaly::ratio_shift_score(&shift),
+            rule: shift.rule,
+            current_ratio: shift.current_ratio,
+            baseline_ratio: shift.baseline_ratio,
+            numerator_count: shift.numerator_count,
+            denominator_count: shift.denominator_count,
+            baseline: baseline.into(),
+        }
+    }
+}
+
+/// Ratio shifts against the previous window's pattern frequencies
+fn ratio_anomalies_vs_baseline(rules: &[anomaly::RatioRule], counts: &HashMap<String, usize>, baseline_frequencies: &HashMap<String, f64>) -> Vec<RatioAnomalyOut> {
+    rules.iter().filter_map(|rule| {
+        let (num, den) = rule.tally(counts.iter().map(|(t, c)| (t.as_str(), *c as f64)));
+        let baseline = rule.tally(baseline_frequencies.iter().map(|(t, f)| (t.as_str(), *f)));
+        anomaly::detect_ratio_shift(rule, (num as usize, den as usize), baseline, anomaly::RATIO_SHIFT_FACTOR, anomaly::RATIO_MIN_EVENTS)
+            .map(|s| RatioAnomalyOut::new(s, "previous_window"))
+    }).collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -161,6 +589,37 @@ pub struct PatternAnomalyOut {
     pub template: String,
     pub frequency: f64,
     pub count: usize,
+    /// Severity from 0 to 1, comparable across all anomaly kinds
+    #[serde(default)]
+    pub score: f64,
+}
+
+impl PatternAnomalyOut {
+    fn new(a: anomaly::PatternAnomaly, baseline_frequencies: &HashMap<String, f64>) -> Self {
+        Self {
+            score: anomaly::pattern_anomaly_score(&a, baseline_frequencies.get(&a.template).copied()),
+            kind: a.kind.as_str().into(),
+            template: a.template,
+            frequency: a.frequency,
+            count: a.count,
+        }
+    }
+}
+
+/// A burst in one pattern's rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemporalAnomalyOut {
+    /// Always `burst` for now
+    pub kind: String,
+    pub template: String,
+    pub start: String,
+    pub end: String,
+    /// Events in the busiest bucket of the burst
+    pub peak_rate: usize,
+    /// Peak bucket count as a multiple of the pattern's quiet rate
+    pub severity: f64,
+    /// Severity from 0 to 1, comparable across all anomaly kinds
+    pub score: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,10 +631,33 @@ pub struct QueryInterfaceOut {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuggestionOut {
     pub priority: String,
+    pub reason_code: ReasonCode,
     pub description: String,
     pub query: SuggestQuery,
 }
 
+/// Machine-readable reason behind a suggested investigation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReasonCode {
+    Burst,
+    NewPattern,
+    RarePattern,
+    DisappearedPattern,
+    SchemaChange,
+}
+
+impl ReasonCode {
+    /// Reason for a suggestion seeded from a pattern anomaly of this `kind`
+    fn for_pattern_anomaly(kind: &str) -> Self {
+        match kind {
+            "NewPattern" => Self::NewPattern,
+            "DisappearedPattern" => Self::DisappearedPattern,
+            _ => Self::RarePattern,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuggestQuery {
     pub command: String,
@@ -193,8 +675,13 @@ pub struct SuggestParams {
 pub struct ErrorsOut {
     pub total: usize,
     pub samples: Vec<ErrorSample>,
+    /// Lines that held invalid UTF-8 and were analyzed with U+FFFD replacements
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub invalid_utf8_lines: usize,
 }
 
+fn is_zero(n: &usize) -> bool { *n == 0 }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorSample {
     pub line_number: usize,
@@ -207,6 +694,21 @@ pub struct SourceBreakdown {
     pub by_host: Vec<CountItem>,
 }
 
+/// A run of consecutive input lines read from one file; see `summarize_lines_by_file`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSpan {
+    pub file: String,
+    pub lines: usize,
+}
+
+/// Most frequent source and its share of `total` lines; items are sorted by count descending
+pub fn dominant_source(items: &[CountItem], total: usize) -> (Option<String>, Option<f64>) {
+    match items.first() {
+        Some(top) if total > 0 => (Some(top.name.clone()), Some(top.count as f64 / total as f64)),
+        _ => (None, None),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CountItem {
     pub name: String,
@@ -220,8 +722,14 @@ pub struct DeepTemporalOut {
     pub time_clustering: Vec<TimeCluster>,
     pub pattern_evolution: Vec<PatternEvolution>,
     pub burst_analysis: Vec<BurstDetail>,
+    /// 7x24 counts, rows Monday..Sunday, columns hour 0-23 (UTC); only for spans over two days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekday_hour_heatmap: Option<Vec<Vec<usize>>>,
 }
 
+/// Minimum time span before a weekday/hour heatmap is meaningful
+pub const HEATMAP_MIN_SPAN_HOURS: i64 = 48;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HourlyCount {
     pub hour: u32,  // 0-23
@@ -289,6 +797,8 @@ pub struct TriageSummary {
     pub anomaly_count: usize,
     pub time_range: Option<String>,
     pub status: String, // "CRITICAL", "WARNING", "NORMAL"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_skew: Option<ClockSkewOut>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -315,13 +825,166 @@ pub struct TriageFieldAnomaly {
 use std::collections::HashSet;
 use ahash::AHashMap;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone)]
 pub struct SummarizeOpts {
     pub use_drain: bool,
     pub analyze_spikes: bool,
     pub verbose: bool,
     pub triage: bool,
     pub deep: bool,
+    /// Drain parse-tree depth
+    pub drain_depth: u16,
+    /// Drain minimum similarity (0..1); higher values produce finer-grained clusters
+    pub drain_similarity: f32,
+    /// Drain maximum children per tree node
+    pub drain_max_children: u16,
+    /// Suppress all stderr diagnostics (timing, Drain effectiveness, stream status)
+    pub quiet: bool,
+    /// Per-field cardinality-explosion overrides, first match wins
+    pub cardinality_rules: Vec<crate::field_anomaly::CardinalityRule>,
+    /// Mask these param types in examples and param values (None keeps raw values)
+    pub redact: Option<Vec<param_extractor::ParamType>>,
+    /// Baseline pattern frequencies (template -> share of lines); enables DisappearedPattern anomalies
+    pub baseline_frequencies: HashMap<String, f64>,
+    /// Extracted param values longer than this many chars are truncated (0 = unlimited)
+    pub max_value_length: usize,
+    /// Decode `<B64>` values that hold JSON and expose their fields as `B64_JSON_*` params
+    pub decode_b64_json: bool,
+    /// Split URL query strings into `QUERY_<key>` params (the whole URL stays a param too)
+    pub query_params: bool,
+    /// Report `pattern_clusters` grouping templates at this token similarity (None = off)
+    pub pattern_cluster_similarity: Option<f64>,
+    /// Run a lightweight co-occurrence pass and fill `correlations` on each pattern
+    pub correlate: bool,
+    /// Bursts with fewer events than this are not reported
+    pub burst_min_events: usize,
+    /// Bucket width bursts and spikes are measured in (default one minute)
+    pub burst_window: chrono::Duration,
+    /// Restricts which structured fields are tracked as params (default: all non-infrastructure fields)
+    pub field_filter: param_extractor::FieldFilter,
+    /// Parser path per line; `Auto` keeps the per-line JSON heuristic
+    pub input_format: parser::InputFormat,
+    /// Strip ANSI escape sequences from lines before templating (and from examples)
+    pub strip_ansi: bool,
+    /// Keep at most this many values per param in `param_stats` (None = all)
+    pub top_params: Option<usize>,
+    /// Known values per structured field from earlier windows; enables enum_drift field anomalies
+    pub baseline_field_values: HashMap<String, HashSet<String>>,
+    /// Chunked summarizer keeps at most this many patterns, evicting the rarest after each
+    /// chunk (None = unbounded)
+    pub max_tracked_patterns: Option<usize>,
+    /// Pattern pairs whose count ratio is checked for shifts (`ratio_anomalies`)
+    pub ratio_rules: Vec<anomaly::RatioRule>,
+    /// Cluster by template alone instead of template + level; levels show in `severity_breakdown`
+    pub merge_levels: bool,
+    /// Emit each pattern's raw Drain template next to the humanized one (debugging aid)
+    pub show_drain_template: bool,
+    /// Normalize SQL literals so queries cluster by shape (see `sql_masking`)
+    pub mask_sql: bool,
+    /// Attach the raw source lines behind each field anomaly as `evidence`
+    pub explain_anomalies: bool,
+    /// Words kept verbatim in templates instead of becoming params (see `param_extractor::restore_literals`)
+    pub keep_literals: Vec<String>,
+    /// User regex rules whose named groups are masked before the built-in maskers run
+    pub mask_rules: Vec<param_extractor::MaskRule>,
+    /// Collapse patterns seen fewer than this many times into `other_patterns` (None = list all)
+    pub min_count: Option<usize>,
+    /// Treatment of quoted phrases in free-text lines (see `param_extractor::QuotedStrings`)
+    pub quoted_strings: param_extractor::QuotedStrings,
+    /// Mask leftover tokens at or above this Shannon entropy (bits per character) as `<RANDOM>`
+    /// (see `param_extractor::mask_random_tokens`); None disables the pass
+    pub random_token_entropy: Option<f64>,
+    /// Firehose mode: only count lines per template. Skips param aggregation, examples, sources,
+    /// schema tracking, anomaly detection and the analyzer registry; patterns carry just
+    /// template, count, frequency and severity
+    pub counts_only: bool,
+    /// Per-pattern analyzers; add custom ones with `AnalyzerRegistry::with_analyzer`
+    pub analyzers: std::sync::Arc<analyzers::AnalyzerRegistry>,
+}
+
+impl Default for SummarizeOpts {
+    fn default() -> Self {
+        Self {
+            use_drain: false,
+            analyze_spikes: false,
+            verbose: false,
+            triage: false,
+            deep: false,
+            drain_depth: drain_adapter::DEFAULT_MAX_DEPTH,
+            drain_similarity: drain_adapter::DEFAULT_MIN_SIMILARITY,
+            drain_max_children: drain_adapter::DEFAULT_MAX_CHILDREN,
+            quiet: false,
+            cardinality_rules: crate::field_anomaly::default_cardinality_rules(),
+            redact: None,
+            baseline_frequencies: HashMap::new(),
+            max_value_length: param_extractor::DEFAULT_MAX_VALUE_LENGTH,
+            decode_b64_json: false,
+            query_params: false,
+            pattern_cluster_similarity: None,
+            correlate: false,
+            burst_min_events: temporal::DEFAULT_BURST_MIN_EVENTS,
+            burst_window: chrono::Duration::seconds(temporal::DEFAULT_BURST_WINDOW_SECS),
+            field_filter: param_extractor::FieldFilter::default(),
+            input_format: parser::InputFormat::Auto,
+            strip_ansi: true,
+            top_params: None,
+            baseline_field_values: HashMap::new(),
+            max_tracked_patterns: None,
+            ratio_rules: Vec::new(),
+            merge_levels: false,
+            show_drain_template: false,
+            mask_sql: false,
+            explain_anomalies: false,
+            keep_literals: Vec::new(),
+            mask_rules: Vec::new(),
+            random_token_entropy: None,
+            quoted_strings: param_extractor::QuotedStrings::Split,
+            min_count: None,
+            counts_only: false,
+            analyzers: std::sync::Arc::new(analyzers::AnalyzerRegistry::new()),
+        }
+    }
+}
+
+impl SummarizeOpts {
+    /// Build a Drain adapter using the tuning parameters from these options
+    pub fn new_drain(&self) -> drain_adapter::DrainAdapter {
+        drain_adapter::DrainAdapter::new_tuned_with_filters(self.drain_depth, self.drain_similarity, self.drain_max_children)
+    }
+
+    /// ` [LEVEL]` suffix that keeps a template's levels in separate patterns (empty with `merge_levels`)
+    fn level_suffix(&self, level: Option<&str>) -> String {
+        match level {
+            Some(level) if !self.merge_levels => format!(" [{level}]"),
+            _ => String::new(),
+        }
+    }
+
+    /// `param_extractor::canonicalize_for_drain`, with `mask_rules` applied first, SQL statements
+    /// normalized under `mask_sql` (non-JSON lines only), URL query strings split into params under `query_params`, random-looking
+    /// tokens masked under `random_token_entropy` and `keep_literals` put back into the masked text
+    pub fn canonicalize(&self, input: &str) -> param_extractor::MaskingResult {
+        let ruled = param_extractor::apply_mask_rules(input, &self.mask_rules);
+        let input = ruled.as_ref().map_or(input, |r| r.masked_text.as_str());
+        // JSON lines keep SQL inside field values, which are params already
+        let sql = |input: &str| (self.mask_sql && param_extractor::try_flatten_json(input).is_none())
+            .then(|| crate::sql_masking::mask_sql(input)).flatten();
+        let base = |input: &str| sql(input)
+            .unwrap_or_else(|| param_extractor::canonicalize_with_field_filter(input, &self.field_filter, self.quoted_strings));
+        let mut masked = if self.query_params { param_extractor::with_query_params(input, base) } else { base(input) };
+        for (param, values) in ruled.map(|r| r.extracted_params).unwrap_or_default() {
+            masked.extracted_params.entry(param).or_default().extend(values);
+        }
+        if let Some(min_entropy) = self.random_token_entropy {
+            masked = param_extractor::mask_random_tokens(masked, min_entropy);
+        }
+        param_extractor::restore_literals(masked, &self.keep_literals)
+    }
+
+    /// Per-line parser options derived from these options
+    pub fn parse_opts(&self) -> parser::ParseOpts {
+        parser::ParseOpts { input_format: self.input_format, strip_ansi: self.strip_ansi }
+    }
 }
 
 /// Calculate pattern importance for verbose mode ordering
@@ -329,8 +992,9 @@ pub struct SummarizeOpts {
 fn calculate_pattern_importance(pattern: &PatternOut) -> f64 {
     let mut importance = 0.0;
     
-    // 1. Severity level (highest weight: 1000-4000 range)
+    // 1. Severity level (highest weight: 1000-5000 range)
     let severity_score = match pattern.severity.as_deref() {
+        Some("fatal") | Some("FATAL") => 5000.0,
         Some("error") | Some("ERROR") | Some("err") | Some("ERR") => 4000.0,
         Some("warn") | Some("WARN") | Some("warning") | Some("WARNING") => 3000.0,  
         Some("info") | Some("INFO") => 2000.0,
@@ -366,15 +1030,15 @@ fn calculate_pattern_importance(pattern: &PatternOut) -> f64 {
 
 
 pub fn summarize_lines(lines: &[&str]) -> AiOutput {
-    summarize_impl(lines, &[], None, &SummarizeOpts::default())
+    summarize_impl(lines, &[], &[], None, &SummarizeOpts::default())
 }
 
 pub fn summarize_lines_with_hints<'a>(lines: &[&'a str], time_keys: &[&'a str]) -> AiOutput {
-    summarize_impl(lines, time_keys, None, &SummarizeOpts::default())
+    summarize_impl(lines, &[], time_keys, None, &SummarizeOpts::default())
 }
 
 pub fn summarize_lines_with_baseline(lines: &[&str], baseline_templates: &HashSet<String>) -> AiOutput {
-    summarize_impl(lines, &[], Some(baseline_templates), &SummarizeOpts::default())
+    summarize_impl(lines, &[], &[], Some(baseline_templates), &SummarizeOpts::default())
 }
 
 pub fn summarize_lines_with_opts<'a>(
@@ -383,7 +1047,225 @@ pub fn summarize_lines_with_opts<'a>(
     baseline_templates: Option<&HashSet<String>>,
     opts: &SummarizeOpts,
 ) -> AiOutput {
-    summarize_impl(lines, time_keys, baseline_templates, opts)
+    summarize_impl(lines, &[], time_keys, baseline_templates, opts)
+}
+
+/// `summarize_lines_with_opts` for lines concatenated from several files: `files` lists the
+/// files in input order with their line counts, and each pattern gets a `by_file` breakdown.
+/// Lines past the last span are not attributed to any file.
+pub fn summarize_lines_by_file<'a>(
+    lines: &[&'a str],
+    files: &[FileSpan],
+    time_keys: &[&'a str],
+    baseline_templates: Option<&HashSet<String>>,
+    opts: &SummarizeOpts,
+) -> AiOutput {
+    summarize_impl(lines, files, time_keys, baseline_templates, opts)
+}
+
+/// `files` re-counted after JSON array expansion, which turns one line into several records
+fn expanded_file_spans(lines: &[&str], files: &[FileSpan], format: parser::InputFormat) -> Vec<FileSpan> {
+    let mut start = 0;
+    files.iter().map(|span| {
+        let end = (start + span.lines).min(lines.len());
+        let slice = &lines[start.min(end)..end];
+        start = end;
+        let lines = parser::expand_json_arrays(slice, format).map_or(slice.len(), |e| e.len());
+        FileSpan { file: span.file.clone(), lines }
+    }).collect()
+}
+
+/// Per-window summaries from `summarize_windows`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowedOut {
+    pub window_seconds: i64,
+    /// Windows that hold at least one line, oldest first
+    pub windows: Vec<WindowSummaryOut>,
+    /// Lines left out because no line up to and including them had a timestamp
+    pub untimestamped_lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowSummaryOut {
+    pub start: String,
+    pub end: String,
+    pub total_lines: usize,
+    pub unique_patterns: usize,
+    /// Templates not seen in any earlier window
+    pub new_patterns: usize,
+    /// Templates of the previous non-empty window that are absent from this one
+    pub gone_patterns: usize,
+    pub patterns: Vec<WindowPatternOut>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other_patterns: Option<OtherPatternsOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowPatternOut {
+    pub template: String,
+    pub count: usize,
+    pub frequency: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+}
+
+/// Slice `lines` into fixed `window`-wide time windows and summarize each one in
+/// `counts_only` mode. Lines without a timestamp (continuations, untimed records) belong to the
+/// window of the closest timestamped line before them.
+pub fn summarize_windows<'a>(lines: &[&'a str], time_keys: &[&'a str], opts: &SummarizeOpts, window: chrono::Duration) -> WindowedOut {
+    // Batch lines hold records from different times; window each record on its own
+    if let Some(expanded) = parser::expand_json_arrays(lines, opts.input_format) {
+        let refs: Vec<&str> = expanded.iter().map(String::as_str).collect();
+        return summarize_windows(&refs, time_keys, opts, window);
+    }
+    let window_ms = window.num_milliseconds().max(1);
+    let parse_opts = opts.parse_opts();
+    // Detect once so every window reads the same field
+    let detected_time_key = if time_keys.is_empty() { parser::detect_time_key(lines, parse_opts) } else { None };
+    let detected_keys: Vec<&str> = detected_time_key.iter().map(String::as_str).collect();
+    let time_keys: &[&str] = if detected_keys.is_empty() { time_keys } else { &detected_keys };
+    let buckets: Vec<Option<i64>> = lines.par_iter().enumerate()
+        .map(|(i, l)| parser::parse_line_as(l, i + 1, time_keys, parse_opts).timestamp.map(|t| t.timestamp_millis().div_euclid(window_ms)))
+        .collect();
+    let mut by_window: BTreeMap<i64, Vec<&'a str>> = BTreeMap::new();
+    let mut untimestamped_lines = 0;
+    let mut current = None;
+    for (line, bucket) in lines.iter().zip(buckets) {
+        current = bucket.or(current);
+        match current {
+            Some(b) => by_window.entry(b).or_default().push(line),
+            None => untimestamped_lines += 1,
+        }
+    }
+
+    let window_opts = SummarizeOpts { counts_only: true, quiet: true, ..opts.clone() };
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut previous: HashSet<String> = HashSet::new();
+    let mut windows = Vec::with_capacity(by_window.len());
+    for (bucket, window_lines) in by_window {
+        let out = summarize_impl(&window_lines, &[], time_keys, None, &window_opts);
+        let current: HashSet<String> = out.patterns.iter().map(|p| p.template.clone()).collect();
+        windows.push(WindowSummaryOut {
+            start: temporal::bucket_start(bucket, window_ms).to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            end: temporal::bucket_start(bucket.saturating_add(1), window_ms).to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            total_lines: out.summary.total_lines,
+            unique_patterns: out.summary.unique_patterns,
+            new_patterns: current.iter().filter(|t| !seen.contains(*t)).count(),
+            gone_patterns: previous.iter().filter(|t| !current.contains(*t)).count(),
+            patterns: out.patterns.into_iter()
+                .map(|p| WindowPatternOut { template: p.template, count: p.total_count, frequency: p.frequency, severity: p.severity })
+                .collect(),
+            other_patterns: out.other_patterns,
+        });
+        seen.extend(current.iter().cloned());
+        previous = current;
+    }
+    WindowedOut { window_seconds: window.num_seconds(), windows, untimestamped_lines }
+}
+
+/// Pick up to `max` line indices whose values for `param` are distinct, in input order.
+/// Remaining slots are filled with the earliest unpicked lines.
+fn select_diverse_examples(
+    idxs: &[usize],
+    line_params: &[HashMap<String, Vec<String>>],
+    param: Option<&str>,
+    max: usize,
+) -> Vec<usize> {
+    let mut picked: Vec<usize> = Vec::with_capacity(max);
+    if let Some(param) = param {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for &i in idxs {
+            if picked.len() >= max { break; }
+            if let Some(v) = line_params[i].get(param).and_then(|vs| vs.first()) {
+                if seen.insert(v.as_str()) { picked.push(i); }
+            }
+        }
+    }
+    for &i in idxs {
+        if picked.len() >= max { break; }
+        if !picked.contains(&i) { picked.push(i); }
+    }
+    picked.sort_unstable();
+    picked
+}
+
+/// Pattern category for a parsed line: `json` for structured records, else the masking path
+fn line_category(structured: bool, base: &str) -> &'static str {
+    if structured { "json" } else { param_extractor::masking_category(base) }
+}
+
+/// Composite pattern key without its trailing ` [LEVEL]` suffix
+fn strip_level_suffix(key: &str) -> &str {
+    match key.rfind(" [") {
+        Some(pos) if key.ends_with(']') && !key[pos..].contains('<') && !key[pos..].contains('>') => &key[..pos],
+        _ => key,
+    }
+}
+
+/// Byte length of the logger prefix opening `message`: timestamp tokens (`2024-01-01T00:00:00Z`,
+/// `2024/01/01 00:00:01`), bracketed tags (`[worker-3]`) and source locations (`main.go:42:`)
+fn logger_prefix_len(message: &str) -> usize {
+    let mut rest = message.trim_start();
+    loop {
+        let token = rest.split_whitespace().next().unwrap_or("");
+        let timestamp = token.starts_with(|c: char| c.is_ascii_digit())
+            && token.contains(['-', '/', ':'])
+            && token.chars().all(|c| c.is_ascii_digit() || "-/:.,TZ+".contains(c));
+        let bracketed = token.starts_with('[') && token.ends_with(']');
+        let location = token.strip_suffix(':')
+            .and_then(|t| t.rsplit_once(':'))
+            .is_some_and(|(file, line)| file.contains('.') && !line.is_empty() && line.bytes().all(|b| b.is_ascii_digit()));
+        if token.is_empty() || !(timestamp || bracketed || location) {
+            return message.len() - rest.len();
+        }
+        rest = rest[token.len()..].trim_start();
+    }
+}
+
+/// Start of a crash header in `message`: Go `panic:`, `fatal error:` and `goroutine N [running]:`
+/// (at the start of the message or right after its logger prefix), Rust `thread '..' panicked at`,
+/// uncaught Java and Python exceptions
+fn crash_signature_pos(message: &str) -> Option<usize> {
+    let start = logger_prefix_len(message);
+    let trimmed = &message[start..];
+    let goroutine_header = trimmed.strip_prefix("goroutine ")
+        .and_then(|rest| rest.split_once(' '))
+        .is_some_and(|(id, state)| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) && state.starts_with('['));
+    if goroutine_header || trimmed.starts_with("panic: ") || trimmed.starts_with("fatal error: ") {
+        return Some(start);
+    }
+    let panicked = message.find("thread '")
+        .filter(|&pos| message[pos + 8..].contains("' panicked at"));
+    [panicked, message.find("Exception in thread \""), message.find("Traceback (most recent call last):")]
+        .into_iter()
+        .flatten()
+        .min()
+}
+
+/// Level for a plaintext message without a JSON or syslog level. Crash headers carry no level
+/// token, so they are classified as FATAL unless an explicit level token precedes them
+/// (`INFO handler recovered from panic: ...` stays INFO)
+fn detect_text_level(message: &str) -> Option<String> {
+    const LEVEL_TOKENS: [&str; 7] = ["TRACE", "DEBUG", "INFO", "WARN", "WARNING", "ERROR", "ERR"];
+    if let Some(pos) = crash_signature_pos(message) {
+        let leveled = message[..pos].split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|w| LEVEL_TOKENS.iter().any(|t| w.eq_ignore_ascii_case(t)));
+        if !leveled { return Some("FATAL".to_string()); }
+    }
+    let msg_upper = message.to_uppercase();
+    if msg_upper.contains(" ERROR") || msg_upper.contains(" ERR ") {
+        Some("ERROR".to_string())
+    } else if msg_upper.contains(" WARN") || msg_upper.contains(" WARNING") {
+        Some("WARN".to_string())
+    } else if msg_upper.contains(" INFO") {
+        Some("INFO".to_string())
+    } else if msg_upper.contains(" DEBUG") {
+        Some("DEBUG".to_string())
+    } else if msg_upper.contains(" TRACE") {
+        Some("TRACE".to_string())
+    } else {
+        None
+    }
 }
 
 /// Extract placeholder names from a template string efficiently
@@ -446,6 +1328,38 @@ pub fn optimize_template_with_stats(template: &str, param_stats: &HashMap<String
     optimized
 }
 
+/// CI gate conditions for `--fail-on`, evaluated against the triage summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailOn {
+    /// Any error-level lines
+    Error,
+    /// Triage status is CRITICAL
+    Critical,
+    /// Any pattern or field anomaly reported by triage
+    Anomaly,
+}
+
+impl FailOn {
+    /// Parse a user-supplied condition: error | critical | anomaly
+    pub fn parse(name: &str) -> Result<FailOn, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(FailOn::Error),
+            "critical" => Ok(FailOn::Critical),
+            "anomaly" => Ok(FailOn::Anomaly),
+            _ => Err(format!("unknown fail-on condition '{name}' (expected error, critical or anomaly)")),
+        }
+    }
+
+    /// Whether the condition holds for this triage result
+    pub fn triggered(&self, triage: &TriageOutput) -> bool {
+        match self {
+            FailOn::Error => triage.summary.error_lines > 0,
+            FailOn::Critical => triage.summary.status == "CRITICAL",
+            FailOn::Anomaly => triage.summary.anomaly_count > 0 || !triage.field_anomalies.is_empty(),
+        }
+    }
+}
+
 /// Converts full analysis output to compact triage format
 pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
     // Filter for critical patterns only (ERROR level + high anomaly/burst patterns)
@@ -455,7 +1369,7 @@ pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
     
     for pattern in &full_output.patterns {
         let is_error = matches!(pattern.severity.as_deref(), 
-            Some("error") | Some("ERROR") | Some("err") | Some("ERR"));
+            Some("error") | Some("ERROR") | Some("err") | Some("ERR") | Some("fatal") | Some("FATAL"));
         let has_bursts = pattern.temporal.as_ref().map(|t| t.bursts > 0).unwrap_or(false);
         let has_spikes = pattern.spike_analysis.is_some();
         let has_param_anomalies = pattern.parameter_anomalies.is_some();
@@ -531,8 +1445,8 @@ pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
     
     // Sort pattern anomalies by importance: ERROR first, then by count
     pattern_anomalies.sort_by(|a, b| {
-        let a_is_error = matches!(a.severity.as_str(), "error" | "ERROR" | "err" | "ERR");
-        let b_is_error = matches!(b.severity.as_str(), "error" | "ERROR" | "err" | "ERR");
+        let a_is_error = matches!(a.severity.as_str(), "error" | "ERROR" | "err" | "ERR" | "fatal" | "FATAL");
+        let b_is_error = matches!(b.severity.as_str(), "error" | "ERROR" | "err" | "ERR" | "fatal" | "FATAL");
         
         b_is_error.cmp(&a_is_error)
             .then_with(|| b.count.cmp(&a.count))
@@ -557,6 +1471,10 @@ pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
         insights.push(format!("Detected {} new/rare patterns - investigate for changes", 
             full_output.anomalies.pattern_anomalies.len()));
     }
+
+    for r in &full_output.anomalies.ratio_anomalies {
+        insights.push(format!("Ratio '{}' shifted from {:.3} to {:.3}", r.rule, r.baseline_ratio, r.current_ratio));
+    }
     
     // Build detailed field anomalies section
     let mut triage_field_anomalies = Vec::new();
@@ -631,9 +1549,9 @@ pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
     // Count patterns with anomalies for status determination
     let anomaly_pattern_count = pattern_anomalies.iter()
         .filter(|p| p.anomaly_type.is_some())
-        .count();
+        .count() + full_output.anomalies.ratio_anomalies.len();
     
-    if pattern_anomalies.is_empty() {
+    if pattern_anomalies.is_empty() && full_output.anomalies.ratio_anomalies.is_empty() {
         insights.push("No critical issues detected - system appears stable".to_string());
     }
     
@@ -662,6 +1580,7 @@ pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
             anomaly_count: anomaly_pattern_count,
             time_range,
             status: status.to_string(),
+            clock_skew: full_output.summary.clock_skew.clone(),
         },
         pattern_anomalies,
         field_anomalies: triage_field_anomalies,
@@ -669,10 +1588,59 @@ pub fn create_triage_output(full_output: &AiOutput) -> TriageOutput {
     }
 }
 
-fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Option<&HashSet<String>>, opts: &SummarizeOpts) -> AiOutput {
-    use std::time::Instant;
+/// Wall-clock time of one pipeline stage
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTime {
+    pub stage: &'static str,
+    pub seconds: f64,
+}
+
+/// Per-stage timings of one in-memory summarization run
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTimings {
+    pub lines: usize,
+    pub stages: Vec<StageTime>,
+    pub total_seconds: f64,
+}
+
+impl StageTimings {
+    fn eprint(&self) {
+        eprintln!("\n=== Performance Timing ===");
+        eprintln!("Total lines processed: {}", self.lines);
+        for st in &self.stages {
+            eprintln!("{}: {:.3}s", st.stage, st.seconds);
+        }
+        eprintln!("Total time: {:.3}s", self.total_seconds);
+        eprintln!("=======================\n");
+    }
+}
+
+/// Run the full in-memory pipeline and return its output with per-stage timings instead of
+/// printing them; for benchmarks and profiling (nothing is written to stdout or stderr
+/// unless `opts` enables verbose diagnostics)
+pub fn analyze_only<'a>(lines: &[&'a str], time_keys: &[&'a str], opts: &SummarizeOpts) -> (AiOutput, StageTimings) {
+    summarize_timed(lines, &[], time_keys, None, opts)
+}
+
+fn summarize_impl<'a>(lines: &[&'a str], files: &[FileSpan], time_keys: &[&'a str], baseline_opt: Option<&HashSet<String>>, opts: &SummarizeOpts) -> AiOutput {
+    let (out, timings) = summarize_timed(lines, files, time_keys, baseline_opt, opts);
+    if !opts.quiet { timings.eprint(); }
+    out
+}
+
+fn summarize_timed<'a>(lines: &[&'a str], files: &[FileSpan], time_keys: &[&'a str], baseline_opt: Option<&HashSet<String>>, opts: &SummarizeOpts) -> (AiOutput, StageTimings) {
+    // A line holding a JSON array of objects is a batch; each element is its own record
+    if let Some(expanded) = parser::expand_json_arrays(lines, opts.input_format) {
+        let refs: Vec<&str> = expanded.iter().map(String::as_str).collect();
+        let files = expanded_file_spans(lines, files, opts.input_format);
+        return summarize_timed(&refs, &files, time_keys, baseline_opt, opts);
+    }
     let start_time = Instant::now();
     let mut stage_times = Vec::new();
+
+    let detected_time_key = if time_keys.is_empty() { parser::detect_time_key(lines, opts.parse_opts()) } else { None };
+    let detected_keys: Vec<&str> = detected_time_key.iter().map(String::as_str).collect();
+    let time_keys: &[&str] = if detected_keys.is_empty() { time_keys } else { &detected_keys };
     
     let total = lines.len();
     let mut min_ts: Option<chrono::DateTime<chrono::Utc>> = None;
@@ -689,6 +1657,8 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         malformed_json: bool,
         fingerprint: Option<schema::Fingerprint>,
         flat_fields: Option<std::collections::BTreeMap<String,String>>,
+        format: &'static str,
+        facility: Option<&'static str>,
     }
 
 
@@ -741,9 +1711,8 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         .par_iter()
         .enumerate()
         .map(|(i, l)| {
-            let looks_json = l.trim_start().starts_with('{') || l.trim_start().starts_with('[');
-            let rec = if time_keys.is_empty() { parser::parse_line(l, i + 1) } else { parser::parse_line_with_hints(l, i + 1, time_keys) };
-            let malformed_json = looks_json && rec.flat_fields.is_none();
+            let rec = parser::parse_line_as(l, i + 1, time_keys, opts.parse_opts());
+            let malformed_json = opts.input_format.expects_json(l) && rec.flat_fields.is_none();
             // Build template base: for JSON, drop high-cardinality source keys
             let base = if let Some(ff) = rec.flat_fields.as_ref() {
                 let mut items: Vec<(String,String)> = ff.iter().map(|(k,v)| (k.clone(), v.clone())).collect();
@@ -763,25 +1732,12 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             // Extract level from JSON fields or detect in plain text
             let level = rec.flat_fields.as_ref()
                 .and_then(|f| f.get("level").cloned())
-                .or_else(|| {
-                    // For plain text logs, try to detect common log levels
-                    let msg_upper = rec.message.to_uppercase();
-                    if msg_upper.contains(" ERROR") || msg_upper.contains(" ERR ") {
-                        Some("ERROR".to_string())
-                    } else if msg_upper.contains(" WARN") || msg_upper.contains(" WARNING") {
-                        Some("WARN".to_string())
-                    } else if msg_upper.contains(" INFO") {
-                        Some("INFO".to_string())
-                    } else if msg_upper.contains(" DEBUG") {
-                        Some("DEBUG".to_string())
-                    } else if msg_upper.contains(" TRACE") {
-                        Some("TRACE".to_string())
-                    } else {
-                        None
-                    }
-                });
-            let (service_opt, host_opt) = extract_source(&rec, &rec.message);
-            let fingerprint = if rec.flat_fields.is_some() {
+                .or_else(|| rec.syslog.map(|s| s.level().to_string()))
+                .or_else(|| detect_text_level(&rec.message));
+            let (service_opt, host_opt) = if opts.counts_only { (None, None) } else { extract_source(&rec, &rec.message) };
+            let fingerprint = if opts.counts_only {
+                None
+            } else if rec.flat_fields.is_some() {
                 if let Some(rv) = rec.raw_json.as_ref() {
                     Some(schema::fingerprint_value(rv))
                 } else {
@@ -791,7 +1747,9 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
                 }
             } else { None };
 
-            LineDeriv { message: rec.message, timestamp: rec.timestamp, base, level, service: service_opt, host: host_opt, malformed_json, fingerprint, flat_fields: rec.flat_fields.clone() }
+            let format = line_format(&rec);
+            let facility = rec.syslog.map(|s| s.facility);
+            LineDeriv { message: rec.message, timestamp: rec.timestamp, base, level, service: service_opt, host: host_opt, malformed_json, fingerprint, flat_fields: rec.flat_fields.clone(), format, facility }
         })
         .collect();
     stage_times.push(("Stage 1: Parse lines", stage_start.elapsed()));
@@ -803,15 +1761,27 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     let mut templates: Vec<String> = Vec::with_capacity(total);
     let mut json_fps: Vec<(usize, schema::Fingerprint, Option<chrono::DateTime<chrono::Utc>>)> = Vec::new();
     let mut error_samples: Vec<ErrorSample> = Vec::new();
+    let mut malformed_total = 0usize;
     let mut service_by_tpl: HashMap<String, HashMap<String, usize>> = HashMap::new();
     let mut host_by_tpl: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    let mut file_by_tpl: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+    let line_files: Vec<&str> = files.iter().flat_map(|f| std::iter::repeat_n(f.file.as_str(), f.lines)).collect();
+    let mut skew = temporal::SkewTracker::default();
+    let mut format_counts: HashMap<&'static str, usize> = HashMap::new();
+    let mut facility_counts: HashMap<&'static str, usize> = HashMap::new();
     for (i, d) in derived.iter().enumerate() {
+        *format_counts.entry(d.format).or_insert(0) += 1;
+        if let Some(f) = d.facility { *facility_counts.entry(f).or_insert(0) += 1; }
         if let Some(ts) = d.timestamp {
+            skew.observe(ts);
             min_ts = Some(match min_ts { Some(m) => m.min(ts), None => ts });
             max_ts = Some(match max_ts { Some(m) => m.max(ts), None => ts });
         }
-        if d.malformed_json && error_samples.len() < 10 {
-            error_samples.push(ErrorSample { line_number: i + 1, kind: "malformed_json".into() });
+        if d.malformed_json {
+            malformed_total += 1;
+            if error_samples.len() < 10 {
+                error_samples.push(ErrorSample { line_number: i + 1, kind: "malformed_json".into() });
+            }
         }
         if let Some(fp) = d.fingerprint.as_ref() { json_fps.push((i, fp.clone(), d.timestamp)); }
         // service/host attribution computed after templates are assigned
@@ -827,7 +1797,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     
     // Always use Drain with masking for consistent pattern extraction
     // More aggressive clustering for structured logs
-    let mut drain = drain_adapter::DrainAdapter::new_tuned_with_filters(32, 0.1, 512);
+    let mut drain = opts.new_drain();
     
     // Store canonicalization results to avoid recomputing in Pass 2
     let mut canon_results: Vec<Option<param_extractor::MaskingResult>> = vec![None; messages.len()];
@@ -858,7 +1828,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     let unique_canon_keys: Vec<_> = canon_groups.keys().cloned().collect();
     let canon_results_unique: Vec<_> = unique_canon_keys
         .par_iter()
-        .map(|key| param_extractor::canonicalize_for_drain(key))
+        .map(|key| opts.canonicalize(key))
         .collect();
     
     // Create mapping from canonicalization key to result
@@ -869,14 +1839,17 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     for (canon_key, indices) in canon_groups.iter() {
         if let Some(canon_result) = key_to_canon.get(canon_key) {
             for &i in indices {
-                line_params[i] = canon_result.extracted_params.clone();
                 canon_results[i] = Some(canon_result.clone());
+                if opts.counts_only { continue; }
+                line_params[i] = canon_result.extracted_params.clone();
                 
                 // Also extract from structured fields if available
                 if let Some(ff) = derived[i].flat_fields.as_ref() {
-                    let kv_params = param_extractor::extract_kv_params(ff);
+                    let kv_params = param_extractor::extract_kv_params_filtered(ff, &opts.field_filter);
                     line_params[i] = param_extractor::merge_params(line_params[i].clone(), kv_params);
                 }
+                if opts.decode_b64_json { param_extractor::merge_b64_json_params(&mut line_params[i]); }
+                param_extractor::truncate_param_values(&mut line_params[i], opts.max_value_length);
             }
         }
     }
@@ -966,7 +1939,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
                 *template_slot = to_generic_template(&cached_canon.masked_text);
             } else {
                 // This should be rare as canonicalization was cached in Pass 1
-                let canon = param_extractor::canonicalize_for_drain(&derived[i].base);
+                let canon = opts.canonicalize(&derived[i].base);
                 *template_slot = to_generic_template(&canon.masked_text);
             }
         }
@@ -974,7 +1947,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     stage_times.push(("    Direct writes", write_start.elapsed()));
     
     // Track Drain effectiveness only if verbose mode is enabled (and limit sample size)
-    if opts.verbose {
+    if opts.verbose && !opts.quiet {
         let effectiveness_start = Instant::now();
         
         // Sample at most 5000 lines to compute effectiveness metrics
@@ -1000,6 +1973,16 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         
         eprintln!("DRAIN EFFECTIVENESS (sampled {} lines): Drain templates: {}, Pure masking templates: {}", 
                   sample_size, drain_unique_templates.len(), masking_unique_templates.len());
+
+        let over_merged = crate::drain_adapter::over_merged_templates(&masked_to_template);
+        for m in over_merged.iter().take(5) {
+            eprintln!("DRAIN OVER-MERGING: '{}' merges {} distinct messages ({:.0}% placeholders: '{}'), e.g. '{}'",
+                      m.template, m.variants, m.placeholder_share * 100.0, m.merged, m.examples.join("', '"));
+        }
+        if !over_merged.is_empty() {
+            eprintln!("DRAIN OVER-MERGING: {} template(s) may hide distinct messages; raise --drain-similarity (currently {}) to split them",
+                      over_merged.len(), opts.drain_similarity);
+        }
         
         stage_times.push(("    Effectiveness sampling", effectiveness_start.elapsed()));
     }
@@ -1009,11 +1992,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
 
     // Now that templates are computed, build source attribution maps using composite keys
     for i in 0..messages.len() {
-        let level_suffix = if let Some(level) = &levels[i] {
-            format!(" [{level}]")
-        } else {
-            String::new()
-        };
+        let level_suffix = opts.level_suffix(levels[i].as_deref());
         let composite_key = format!("{}{}", templates[i], level_suffix);
         
         if let Some(svc) = derived[i].service.clone() {
@@ -1022,6 +2001,9 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         if let Some(h) = derived[i].host.clone() {
             *host_by_tpl.entry(composite_key.clone()).or_default().entry(h).or_insert(0) += 1;
         }
+        if let Some(f) = line_files.get(i) {
+            *file_by_tpl.entry(composite_key.clone()).or_default().entry(f).or_insert(0) += 1;
+        }
     }
 
     // Stage 3: Cluster by template + log level (separate patterns for different log levels)
@@ -1031,11 +2013,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     let mut times_by_tpl: HashMap<String, Vec<chrono::DateTime<chrono::Utc>>> = HashMap::new();
     for (i, tpl) in templates.iter().enumerate() {
         // Create composite key: template + log level to separate different severities
-        let level_suffix = if let Some(level) = &levels[i] {
-            format!(" [{level}]")
-        } else {
-            String::new()
-        };
+        let level_suffix = opts.level_suffix(levels[i].as_deref());
         let composite_key = format!("{tpl}{level_suffix}");
         
         *counts.entry(composite_key.clone()).or_insert(0) += 1;
@@ -1052,6 +2030,25 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     };
     let start_date = min_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
     let end_date = max_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    if opts.counts_only {
+        let patterns = counts.iter().map(|(tpl, &cnt)| {
+            let mut lvl_counts: HashMap<&str, usize> = HashMap::new();
+            for &i in &idxs_by_tpl[tpl] {
+                if let Some(lv) = levels[i].as_deref() { *lvl_counts.entry(lv).or_insert(0) += 1; }
+            }
+            let severity = lvl_counts.into_iter().max_by_key(|(_, c)| *c).map(|(l, _)| l.to_string());
+            count_only_pattern(tpl.clone(), cnt, total, severity)
+        }).collect();
+        stage_times.push(("Stage 3: Clustering", stage_start.elapsed()));
+        let summary = Summary { total_lines: total, unique_patterns: unique, compression_ratio, start_date, end_date, timestamped_ratio: timestamped_ratio(skew.timestamped(), total), time_key: detected_time_key.clone(), diagnostic: input_diagnostic(total, malformed_total), clock_skew: skew.report().map(Into::into), detected_formats: count_items_out(&format_counts), by_facility: count_items_out(&facility_counts), deduped_lines: 0, evicted_patterns: 0, evicted_lines: 0, widest_span: None, narrowest_span: None };
+        let errors = ErrorsOut { total: malformed_total, samples: error_samples, invalid_utf8_lines: 0 };
+        let timings = StageTimings {
+            lines: total,
+            stages: stage_times.iter().map(|(stage, d)| StageTime { stage, seconds: d.as_secs_f64() }).collect(),
+            total_seconds: start_time.elapsed().as_secs_f64(),
+        };
+        return (counts_only_output(summary, patterns, errors, opts), timings);
+    }
     // Patterns output
     let mut patterns = Vec::new();
     let mut suggestions: Vec<SuggestionOut> = Vec::new();
@@ -1067,6 +2064,16 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     // Sampling limits: cap per-pattern analysis for performance
     let sample_limit = if opts.deep { 8192 } else { 2048 };
     
+    // level counts per template, before the level suffix splits it into separate patterns
+    let mut levels_by_template: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    for (tpl, level) in templates.iter().zip(levels.iter()) {
+        if let Some(lv) = level.as_deref() {
+            *levels_by_template.entry(tpl.as_str()).or_default().entry(lv).or_insert(0) += 1;
+        }
+    }
+
+    let correlated = if opts.correlate { correlate_patterns(&times_by_tpl) } else { HashMap::new() };
+
     // Parallel pattern building with optimizations
     let pattern_results: Vec<_> = counts_vec
         .par_iter()
@@ -1090,34 +2097,32 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         
         // severity = most frequent level (scan sampled indices only)
         let mut lvl_counts: HashMap<String, usize> = HashMap::new();
-        let mut exs: Vec<String> = Vec::new();
         for &i in sampled_idxs.iter() {
             if let Some(lv) = levels[i].as_ref() { *lvl_counts.entry(lv.clone()).or_insert(0) += 1; }
-            if exs.len() < max_examples { exs.push(messages[i].clone()); }
         }
         let severity = lvl_counts.into_iter().max_by_key(|(_, c)| *c).map(|(l, _)| l);
+        let severity_breakdown = severity_breakdown_out(levels_by_template.get(templates[idxs[0]].as_str()));
         
         // Extract start and end times for this pattern
         let start_time = ts_for_tpl.iter().min().map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
         let end_time = ts_for_tpl.iter().max().map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
         
-        let bursts = temporal::compute_bursts(&ts_for_tpl, chrono::Duration::minutes(1), 3.0);
+        let bursts = temporal::compute_bursts_min_events(&ts_for_tpl, opts.burst_window, 3.0, opts.burst_min_events);
         let largest_burst = bursts.iter().max_by_key(|b| b.peak_rate).map(|b| b.start_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
         let trend = trend_label(&ts_for_tpl);
-        // Skip correlations for simpler analysis
-        let related: Vec<CorrelatedOut> = Vec::new();
+        let related: Vec<CorrelatedOut> = correlated.get(*tpl).cloned().unwrap_or_default();
 
         // Pattern stability score (0..1): measures how stable/persistent this pattern is
         // Combines: temporal consistency (60% weight) + frequency (40% weight)
         // High score = appears frequently AND consistently over time
-        let temporal_consistency = if !ts_for_tpl.is_empty() && min_ts.is_some() && max_ts.is_some() {
-            let a = min_ts.unwrap(); let b = max_ts.unwrap();
+        let temporal_consistency = if let (false, Some(a), Some(b)) = (ts_for_tpl.is_empty(), min_ts, max_ts) {
             let span_secs = (b - a).num_seconds().abs().max(60) as f64;
-            let unique_minutes = ts_for_tpl.iter().map(|t| (t.timestamp()/60)).collect::<std::collections::BTreeSet<_>>().len() as f64;
+            let unique_minutes = ts_for_tpl.iter().map(|t| t.timestamp()/60).collect::<std::collections::BTreeSet<_>>().len() as f64;
             (unique_minutes * 60.0 / span_secs).min(1.0)
         } else { 0.0 };
         let freq_factor = ((cnt as f64) / (total as f64)).sqrt().min(1.0);
         let pattern_stability = (temporal_consistency * 0.6) + (freq_factor * 0.4);
+        let periodicity = temporal::compute_periodicity(&ts_for_tpl);
 
         // sources breakdown top3
         let mut svc_items: Vec<CountItem> = service_by_tpl.get(*tpl).map(|m| m.iter().map(|(k,v)| CountItem { name: k.clone(), count: *v }).collect()).unwrap_or_default();
@@ -1126,6 +2131,9 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         let mut host_items: Vec<CountItem> = host_by_tpl.get(*tpl).map(|m| m.iter().map(|(k,v)| CountItem { name: k.clone(), count: *v }).collect()).unwrap_or_default();
         host_items.sort_by(|a,b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
         if host_items.len() > 3 { host_items.truncate(3); }
+        let mut file_items: Vec<CountItem> = file_by_tpl.get(*tpl).map(|m| m.iter().map(|(k,v)| CountItem { name: k.to_string(), count: *v }).collect()).unwrap_or_default();
+        file_items.sort_by(|a,b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        if file_items.len() > 3 { file_items.truncate(3); }
 
         // OPTIMIZATION 3 & 4: Optimize parameter aggregation with precomputed placeholders
         let mut param_stats: std::collections::HashMap<String, ParamFieldStats> = std::collections::HashMap::new();
@@ -1164,11 +2172,11 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
                 };
                 
                 // OPTIMIZATION 4: Use O(1) HashSet membership check instead of contains()
-                let should_include = if fixed_param_type == "NESTED_PATTERN" {
-                    true  // Always include nested patterns as they're useful anomalies
-                } else {
-                    template_placeholders.contains(&fixed_param_type)
-                };
+                // Nested patterns are useful anomalies; decoded payload fields live under <B64>
+                let should_include = fixed_param_type == "NESTED_PATTERN"
+                    || fixed_param_type.starts_with(param_extractor::B64_JSON_PREFIX)
+                    || fixed_param_type.starts_with(param_extractor::QUERY_PARAM_PREFIX)
+                    || template_placeholders.contains(&fixed_param_type);
                 
                 if should_include {
                     for value in values {
@@ -1179,6 +2187,16 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             }
         }
         
+        // Examples: prefer lines spanning distinct values of the highest-cardinality param
+        let diversity_param = pattern_params.iter()
+            .filter(|(k, v)| v.len() > 1 && k.as_str() != "NESTED_PATTERN" && !param_extractor::is_time_param(k))
+            .max_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| b.0.cmp(a.0)))
+            .map(|(k, _)| k.clone());
+        let exs: Vec<String> = select_diverse_examples(&sampled_idxs, &line_params, diversity_param.as_deref(), max_examples)
+            .into_iter()
+            .map(|i| messages[i].clone())
+            .collect();
+
         // Compute statistics for each parameter type
         for (param_type, value_counts) in pattern_params.iter() {
             let total: usize = value_counts.values().sum();
@@ -1200,14 +2218,17 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             let base_stats = ParamFieldStats { 
                 total, 
                 cardinality, 
+                numeric_summary: compute_numeric_summary(&all_values),
                 values: all_values.clone(), 
                 top_ratio,
                 is_sequence: None,
                 sequence_info: None,
+                routes: None,
+                latency_buckets: None,
             };
             
             // Apply sequence detection and compaction (consistent with chunked mode)
-            let final_stats = apply_sequence_detection(base_stats, param_type);
+            let final_stats = apply_latency_buckets(apply_route_templating(apply_sequence_detection(base_stats, param_type), param_type), param_type);
             param_stats.insert(param_type.clone(), final_stats);
         }
 
@@ -1231,9 +2252,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
                     return false;
                 }
                 // Remove TIME-related params (they're now shown as start_time/end_time)
-                let name_upper = param_name.to_uppercase();
-                if name_upper == "TIME" || name_upper == "TIMESTAMP" || name_upper == "TS" || 
-                   name_upper == "DATETIME" || name_upper == "DATE" {
+                if param_extractor::is_time_param(param_name) {
                     return false;
                 }
                 // Remove high-cardinality numeric parameters (likely timestamps, IDs, etc.)
@@ -1279,6 +2298,25 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             None
         };
 
+        let (dominant_service, dominant_service_ratio) = dominant_source(&svc_items, cnt);
+        let (dominant_host, dominant_host_ratio) = dominant_source(&host_items, cnt);
+
+        // Custom analyzers get the same pattern-local context as in chunked mode
+        let registered = if opts.analyzers.has_registered() {
+            let context = analyzers::AnalysisContext {
+                template: tpl.to_string(),
+                clean_template: clean_template.clone(),
+                total_count: cnt,
+                timestamps: ts_for_tpl.clone(),
+                line_params: idxs.iter().map(|&i| line_params[i].clone()).collect(),
+                pattern_indices: (0..idxs.len()).collect(),
+                param_stats: if filtered_param_stats.is_empty() { None } else { Some(filtered_param_stats.clone()) },
+            };
+            opts.analyzers.analyze_registered(&context, opts)
+        } else {
+            analyzers::AnalysisResults::default()
+        };
+
         // Use original fast manual approach for non-chunked mode
         // Keep the template with placeholders (consistent with chunked mode)
         Some(PatternOut {
@@ -1286,6 +2324,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             frequency: (cnt as f64) / (total as f64),
             total_count: cnt,
             severity,
+            severity_breakdown,
             start_time,
             end_time,
             spike_analysis,
@@ -1293,8 +2332,15 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             examples: exs,
             correlations: related,
             pattern_stability,
+            periodicity,
+            dominant_service,
+            dominant_service_ratio,
+            dominant_host,
+            dominant_host_ratio,
+            by_file: file_items,
+            category: Some(line_category(derived[idxs[0]].flat_fields.is_some(), &derived[idxs[0]].base).to_string()),
             sources: SourceBreakdown { by_service: svc_items, by_host: host_items },
-            drain_template: idxs.first().and_then(|&i| drain_templates_raw[i].clone()),
+            drain_template: if opts.show_drain_template { idxs.first().and_then(|&i| drain_templates_raw[i].clone()) } else { None },
             param_stats: if filtered_param_stats.is_empty() { None } else { Some(filtered_param_stats.clone()) },
             parameter_anomalies: {
                 // Fast parameter anomaly detection
@@ -1304,11 +2350,8 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
                     if total_param == 0 { continue; }
                     
                     // Skip time-based parameters
-                    let is_time_param = param_type == "TIME" || param_type == "TIMESTAMP" || 
-                                       param_type == "DATE" || param_type == "DATETIME";
-                    let is_high_cardinality_numeric = param_type == "NS" || 
-                                                     (param_type == "NUM" && stats.cardinality as f64 / total_param as f64 > 0.9);
-                    if is_time_param || is_high_cardinality_numeric { continue; }
+                    if param_extractor::is_time_param(param_type)
+                        || param_extractor::is_high_cardinality_numeric(param_type, stats.cardinality, total_param) { continue; }
                     
                     // Value concentration anomaly
                     if stats.top_ratio >= 0.9 && cnt > 10 && stats.cardinality > 1 {
@@ -1366,14 +2409,29 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
                         });
                     }
                 }
+                // HTTP status surge; unfiltered stats so an all-5xx pattern still counts
+                for (param_type, stats) in param_stats.iter().filter(|(k, _)| param_extractor::is_status_param(k)) {
+                    let ordered: Vec<&str> = idxs.iter()
+                        .filter_map(|&i| line_params[i].get(param_type).and_then(|vs| vs.first()).map(|v| v.as_str()))
+                        .collect();
+                    param_anoms.extend(analyzers::detect_status_surge(param_type, stats, &ordered));
+                }
+                for param_type in param_stats.keys().filter(|k| param_extractor::is_latency_param(k)) {
+                    let ordered: Vec<&str> = idxs.iter()
+                        .filter_map(|&i| line_params[i].get(param_type).and_then(|vs| vs.first()).map(|v| v.as_str()))
+                        .collect();
+                    param_anoms.extend(analyzers::detect_slow_latency_rise(param_type, &ordered));
+                }
+                param_anoms.extend(registered.parameter_anomalies.unwrap_or_default());
                 if param_anoms.is_empty() { None } else { Some(param_anoms) }
             },
             deep_temporal: if opts.deep && !ts_for_tpl.is_empty() {
-                Some(compute_deep_temporal(&ts_for_tpl, &clean_template, &line_params, idxs))
+                Some(compute_deep_temporal_with_opts(&ts_for_tpl, &clean_template, &line_params, idxs, opts))
             } else { None },
             deep_correlations: if opts.deep {
                 Some(compute_deep_correlations(&times_by_tpl, tpl))
             } else { None },
+            extensions: registered.extensions,
         })
         })
         .collect();
@@ -1390,11 +2448,12 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         for &i in idxs.iter() {
             if let Some(ts) = timestamps[i] { ts_for_tpl.push(ts); }
         }
-        let bursts = temporal::compute_bursts(&ts_for_tpl, chrono::Duration::minutes(1), 3.0);
+        let bursts = temporal::compute_bursts_min_events(&ts_for_tpl, opts.burst_window, 3.0, opts.burst_min_events);
         // Suggestions from bursts
         if let Some(b) = bursts.iter().max_by_key(|b| b.peak_rate) {
             suggestions.push(SuggestionOut {
                 priority: "HIGH".into(),
+                reason_code: ReasonCode::Burst,
                 description: format!("Pattern burst for '{tpl}'"),
                 query: SuggestQuery {
                     command: "GET_LINES_BY_TIME".into(),
@@ -1432,29 +2491,29 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         let (_last_idx, last_fp, last_ts) = &json_fps[json_fps.len() - 1];
         let changes = schema::diff_fingerprints(first_fp, last_fp);
         for ch in changes {
-            match ch {
+            match &ch {
                 schema::SchemaChange::FieldAdded { field, .. } => {
-                    schema_changes.push(SchemaChangeOut { timestamp: last_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)), change_type: "field_added".into(), field: field.clone(), impact: None });
+                    schema_changes.push(SchemaChangeOut::from_change(&ch, last_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))));
                     if let Some(ts) = last_ts {
                         let start = (*ts - chrono::Duration::minutes(5)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
                         let end = (*ts + chrono::Duration::minutes(5)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
-                        suggestions.push(SuggestionOut { priority: "MEDIUM".into(), description: format!("Schema field added: {field}"), query: SuggestQuery { command: "GET_LINES_BY_TIME".into(), params: SuggestParams { start: Some(start), end: Some(end), pattern: None } } });
+                        suggestions.push(SuggestionOut { priority: "MEDIUM".into(), reason_code: ReasonCode::SchemaChange, description: format!("Schema field added: {field}"), query: SuggestQuery { command: "GET_LINES_BY_TIME".into(), params: SuggestParams { start: Some(start), end: Some(end), pattern: None } } });
                     }
                 }
                 schema::SchemaChange::FieldRemoved { field, .. } => {
-                    schema_changes.push(SchemaChangeOut { timestamp: last_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)), change_type: "field_removed".into(), field: field.clone(), impact: None });
+                    schema_changes.push(SchemaChangeOut::from_change(&ch, last_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))));
                     if let Some(ts) = last_ts {
                         let start = (*ts - chrono::Duration::minutes(5)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
                         let end = (*ts + chrono::Duration::minutes(5)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
-                        suggestions.push(SuggestionOut { priority: "MEDIUM".into(), description: format!("Schema field removed: {field}"), query: SuggestQuery { command: "GET_LINES_BY_TIME".into(), params: SuggestParams { start: Some(start), end: Some(end), pattern: None } } });
+                        suggestions.push(SuggestionOut { priority: "MEDIUM".into(), reason_code: ReasonCode::SchemaChange, description: format!("Schema field removed: {field}"), query: SuggestQuery { command: "GET_LINES_BY_TIME".into(), params: SuggestParams { start: Some(start), end: Some(end), pattern: None } } });
                     }
                 }
                 schema::SchemaChange::TypeChanged { field, .. } => {
-                    schema_changes.push(SchemaChangeOut { timestamp: last_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)), change_type: "type_changed".into(), field: field.clone(), impact: None });
+                    schema_changes.push(SchemaChangeOut::from_change(&ch, last_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))));
                     if let Some(ts) = last_ts {
                         let start = (*ts - chrono::Duration::minutes(5)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
                         let end = (*ts + chrono::Duration::minutes(5)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
-                        suggestions.push(SuggestionOut { priority: "MEDIUM".into(), description: format!("Schema type changed: {field}"), query: SuggestQuery { command: "GET_LINES_BY_TIME".into(), params: SuggestParams { start: Some(start), end: Some(end), pattern: None } } });
+                        suggestions.push(SuggestionOut { priority: "MEDIUM".into(), reason_code: ReasonCode::SchemaChange, description: format!("Schema type changed: {field}"), query: SuggestQuery { command: "GET_LINES_BY_TIME".into(), params: SuggestParams { start: Some(start), end: Some(end), pattern: None } } });
                     }
                 }
             }
@@ -1464,23 +2523,20 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     // NewPattern is only emitted when a non-empty baseline is provided (e.g., streaming mode).
     let empty_baseline = std::collections::HashSet::<String>::new();
     let baseline_ref = baseline_opt.unwrap_or(&empty_baseline);
-    let pattern_anoms = anomaly::detect_pattern_anomalies(&counts, total, baseline_ref, 0.1);
+    let mut pattern_anoms = anomaly::detect_pattern_anomalies(&counts, total, baseline_ref, 0.1);
+    pattern_anoms.extend(anomaly::detect_disappeared_patterns(&counts, total, &opts.baseline_frequencies, anomaly::DISAPPEARED_MIN_BASELINE_FREQ, anomaly::DISAPPEARED_DROP_RATIO));
     let pattern_anomalies: Vec<PatternAnomalyOut> = pattern_anoms
         .into_iter()
-        .map(|a| PatternAnomalyOut { 
-            kind: match a.kind { 
-                anomaly::AnomalyKind::NewPattern => "NewPattern".into(), 
-                anomaly::AnomalyKind::RarePattern => "RarePattern".into() 
-            }, 
-            template: a.template, 
-            frequency: a.frequency,
-            count: a.count
-        })
+        .map(|a| PatternAnomalyOut::new(a, &opts.baseline_frequencies))
         .collect();
     // Field anomalies using robust numeric stats and categorical explosions
     let lines_refs: Vec<&str> = lines.to_vec();
     let num_outliers = crate::field_anomaly::analyze_numeric_outliers(&lines_refs, 3.0);
-    let cat_explosions = crate::field_anomaly::analyze_categorical_explosions(&lines_refs, 0.8, 10);
+    let cat_explosions = crate::field_anomaly::analyze_categorical_explosions_with_rules(&lines_refs, 0.8, 10, &opts.cardinality_rules);
+    let evidence = |idxs: &[usize]| -> Vec<String> {
+        if !opts.explain_anomalies { return Vec::new(); }
+        idxs.iter().filter_map(|&i| lines_refs.get(i).map(|l| l.to_string())).collect()
+    };
     let mut field_anomalies = Vec::new();
     for o in num_outliers {
         field_anomalies.push(FieldAnomaly {
@@ -1492,6 +2548,9 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             unique_count: None,
             total: None,
             ratio: None,
+            new_values: Vec::new(),
+            evidence: evidence(&[o.line_index]),
+            score: anomaly::numeric_outlier_score(o.robust_z),
         });
     }
     for e in cat_explosions {
@@ -1504,24 +2563,64 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
             unique_count: Some(e.unique_count),
             total: Some(e.total),
             ratio: Some(e.ratio),
+            new_values: Vec::new(),
+            evidence: evidence(&e.line_indices),
+            score: anomaly::cardinality_explosion_score(e.ratio, e.total),
+        });
+    }
+    for d in crate::field_anomaly::detect_enum_drift(&lines_refs, &opts.baseline_field_values) {
+        field_anomalies.push(FieldAnomaly {
+            anomaly_type: "enum_drift".to_string(),
+            field: d.field,
+            template: d.template,
+            value: None,
+            z_score: None,
+            unique_count: Some(d.known_count),
+            total: Some(d.total),
+            ratio: None,
+            score: anomaly::enum_drift_score(d.new_values.len(), d.total),
+            new_values: d.new_values,
+            evidence: evidence(&d.line_indices),
         });
     }
 
     // Temporal anomalies: bursts only (gap analysis removed)
     let mut temporal_anomalies = Vec::new();
     for (tpl, ts_list) in times_by_tpl.iter() {
-        let bursts = temporal::compute_bursts(ts_list, chrono::Duration::minutes(1), 3.0);
+        let bursts = temporal::compute_bursts_min_events(ts_list, opts.burst_window, 3.0, opts.burst_min_events);
         for b in bursts {
-            temporal_anomalies.push(format!("burst template={} start={} end={} peak={}", tpl, b.start_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), b.end_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), b.peak_rate));
+            temporal_anomalies.push(TemporalAnomalyOut {
+                kind: "burst".into(),
+                template: tpl.to_string(),
+                start: b.start_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                end: b.end_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                peak_rate: b.peak_rate,
+                severity: b.severity,
+                score: anomaly::burst_score(b.severity, b.peak_rate),
+            });
         }
     }
 
-    let anomalies = AnomaliesOut { pattern_anomalies: pattern_anomalies.clone(), field_anomalies, temporal_anomalies };
+    // Ratio anomalies: against the previous window when given, else latest quarter vs earlier lines
+    let ratio_anomalies = if !opts.baseline_frequencies.is_empty() {
+        ratio_anomalies_vs_baseline(&opts.ratio_rules, &counts, &opts.baseline_frequencies)
+    } else {
+        let split = total - total / 4;
+        opts.ratio_rules.iter().filter_map(|rule| {
+            let earlier = rule.tally(idxs_by_tpl.iter().map(|(t, idxs)| (t.as_str(), idxs.iter().filter(|&&i| i < split).count() as f64)));
+            let (num, den) = rule.tally(idxs_by_tpl.iter().map(|(t, idxs)| (t.as_str(), idxs.iter().filter(|&&i| i >= split).count() as f64)));
+            anomaly::detect_ratio_shift(rule, (num as usize, den as usize), earlier, anomaly::RATIO_SHIFT_FACTOR, anomaly::RATIO_MIN_EVENTS)
+                .map(|s| RatioAnomalyOut::new(s, "earlier_lines"))
+        }).collect()
+    };
+
+    let anomalies = AnomaliesOut { pattern_anomalies: pattern_anomalies.clone(), field_anomalies, temporal_anomalies, ratio_anomalies };
     // Suggestions from anomalies
     for pa in pattern_anomalies.into_iter() {
-        let priority = if pa.kind == "NewPattern" { "HIGH" } else { "LOW" };
+        let priority = if pa.kind == "NewPattern" || pa.kind == "DisappearedPattern" { "HIGH" } else { "LOW" };
         suggestions.push(SuggestionOut {
             priority: priority.into(),
+            reason_code: ReasonCode::for_pattern_anomaly(&pa.kind),
             description: format!("{}: {}", pa.kind, pa.template),
             query: SuggestQuery { command: "GET_LINES_BY_PATTERN".into(), params: SuggestParams { start: None, end: None, pattern: Some(pa.template) } },
         });
@@ -1544,7 +2643,7 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
         best.insert(key, s);
     }
     let mut deduped: Vec<SuggestionOut> = best.into_values().collect();
-    deduped.sort_by(|a,b| prio_rank(&b.priority).cmp(&prio_rank(&a.priority)));
+    deduped.sort_by_key(|s| std::cmp::Reverse(prio_rank(&s.priority)));
 
     let query_interface = QueryInterfaceOut {
         available_commands: vec!["GET_LINES_BY_PATTERN".into(), "GET_LINES_BY_TIME".into(), "GET_CONTEXT".into()],
@@ -1552,24 +2651,103 @@ fn summarize_impl<'a>(lines: &[&'a str], time_keys: &[&'a str], baseline_opt: Op
     };
 
     stage_times.push(("Stage 4: Build patterns", stage_start.elapsed()));
-    
-    // Print timing information
-    let total_time = start_time.elapsed();
-    eprintln!("\n=== Performance Timing ===");
-    eprintln!("Total lines processed: {total}");
-    for (stage_name, duration) in &stage_times {
-        eprintln!("{}: {:.3}s", stage_name, duration.as_secs_f64());
-    }
-    eprintln!("Total time: {:.3}s", total_time.as_secs_f64());
-    eprintln!("=======================\n");
-    
-    AiOutput {
-        summary: Summary { total_lines: total, unique_patterns: unique, compression_ratio, start_date, end_date },
+    let timings = StageTimings {
+        lines: total,
+        stages: stage_times.iter().map(|(stage, d)| StageTime { stage, seconds: d.as_secs_f64() }).collect(),
+        total_seconds: start_time.elapsed().as_secs_f64(),
+    };
+
+    let mut out = AiOutput {
+        summary: Summary { total_lines: total, unique_patterns: unique, compression_ratio, start_date, end_date, timestamped_ratio: timestamped_ratio(skew.timestamped(), total), time_key: detected_time_key.clone(), diagnostic: input_diagnostic(total, malformed_total), clock_skew: skew.report().map(Into::into), detected_formats: count_items_out(&format_counts), by_facility: count_items_out(&facility_counts), deduped_lines: 0, evicted_patterns: 0, evicted_lines: 0, widest_span: None, narrowest_span: None },
         patterns,
         schema_changes,
         anomalies,
         query_interface,
-        errors: ErrorsOut { total: error_samples.len(), samples: error_samples },
+        errors: ErrorsOut { total: malformed_total, samples: error_samples, invalid_utf8_lines: 0 },
+        pattern_clusters: Vec::new(),
+        global_params: BTreeMap::new(),
+        other_patterns: None,
+    };
+    if let Some(threshold) = opts.pattern_cluster_similarity { out.pattern_clusters = build_pattern_clusters(&out.patterns, threshold); }
+    if let Some(types) = &opts.redact { redact_output(&mut out, types); }
+    out.global_params = build_global_params(&out.patterns);
+    if let Some(min) = opts.min_count { collapse_rare_patterns(&mut out, min); }
+    set_pattern_spans(&mut out);
+    if let Some(n) = opts.top_params { limit_param_values(&mut out, n); }
+    (out, timings)
+}
+
+/// Keep only the `n` most frequent values (and routes) per param; `total`, `cardinality`
+/// and `top_ratio` still describe the full distribution
+pub fn limit_param_values(out: &mut AiOutput, n: usize) {
+    for stats in out.patterns.iter_mut().filter_map(|p| p.param_stats.as_mut()) {
+        for st in stats.values_mut() {
+            st.values.truncate(n);
+            if let Some(routes) = st.routes.as_mut() { routes.truncate(n); }
+        }
+    }
+    for global in out.global_params.values_mut() {
+        global.values.truncate(n);
+    }
+}
+
+/// Mask sensitive values in examples and parameter statistics so output can leave the trusted zone.
+/// Param values that become identical after masking are merged; cardinality keeps the raw count.
+pub fn redact_output(out: &mut AiOutput, types: &[param_extractor::ParamType]) {
+    let redact = |s: &str| param_extractor::redact_line(s, types);
+    let merge = |values: &mut Vec<ParamValueCount>| {
+        let mut merged: Vec<ParamValueCount> = Vec::with_capacity(values.len());
+        for v in values.drain(..) {
+            let value = redact(&v.value);
+            match merged.iter_mut().find(|m| m.value == value) {
+                Some(m) => m.count += v.count,
+                None => merged.push(ParamValueCount { value, count: v.count }),
+            }
+        }
+        merged.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        *values = merged;
+    };
+    for p in &mut out.patterns {
+        for ex in &mut p.examples { *ex = redact(ex); }
+        if let Some(stats) = p.param_stats.as_mut() {
+            for st in stats.values_mut() {
+                merge(&mut st.values);
+                if let Some(routes) = st.routes.as_mut() { merge(routes); }
+                if let Some(seq) = st.sequence_info.as_mut() {
+                    seq.start_value = redact(&seq.start_value);
+                    seq.end_value = redact(&seq.end_value);
+                }
+            }
+        }
+        for a in p.parameter_anomalies.iter_mut().flatten() {
+            a.details = a.details.replace(&a.value, &redact(&a.value));
+            a.value = redact(&a.value);
+        }
+        if let Some(deep) = p.deep_temporal.as_mut() {
+            for shift in deep.pattern_evolution.iter_mut().flat_map(|e| e.parameter_shifts.iter_mut()) {
+                shift.old_dominant_value = redact(&shift.old_dominant_value);
+                shift.new_dominant_value = redact(&shift.new_dominant_value);
+            }
+            for b in &mut deep.burst_analysis {
+                for f in &mut b.contributing_factors { *f = redact(f); }
+            }
+        }
+    }
+}
+
+/// Fraction of `total` lines that carried a parsed timestamp (0 for empty input)
+fn timestamped_ratio(timestamped: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { timestamped as f64 / total as f64 }
+}
+
+/// Diagnostic for inputs with nothing to summarize, so pipelines can tell "no data" from "quiet logs"
+fn input_diagnostic(total: usize, malformed: usize) -> Option<String> {
+    if total == 0 {
+        Some("no input lines".to_string())
+    } else if malformed >= total {
+        Some(format!("all {total} input lines were malformed"))
+    } else {
+        None
     }
 }
 
@@ -1594,12 +2772,27 @@ fn trend_label(ts: &[chrono::DateTime<chrono::Utc>]) -> Option<String> {
 }
 
 // Deep analysis functions
+
+/// `compute_deep_temporal_with_opts` with the default one-minute burst window and burst minimum
 pub fn compute_deep_temporal(
     timestamps: &[chrono::DateTime<chrono::Utc>],
     template: &str,
     line_params: &[HashMap<String, Vec<String>>],
     pattern_indices: &[usize],
 ) -> DeepTemporalOut {
+    compute_deep_temporal_with_opts(timestamps, template, line_params, pattern_indices, &SummarizeOpts::default())
+}
+
+/// Deep temporal detail for one pattern, with bursts measured in `opts.burst_window` buckets
+/// and kept only from `opts.burst_min_events` events
+pub fn compute_deep_temporal_with_opts(
+    timestamps: &[chrono::DateTime<chrono::Utc>],
+    template: &str,
+    line_params: &[HashMap<String, Vec<String>>],
+    pattern_indices: &[usize],
+    opts: &SummarizeOpts,
+) -> DeepTemporalOut {
+    let (burst_window, burst_min_events) = (opts.burst_window, opts.burst_min_events);
     use chrono::Timelike;
     
     // Hourly distribution
@@ -1613,6 +2806,18 @@ pub fn compute_deep_temporal(
         }
     }
     
+    let weekday_hour_heatmap = match (timestamps.iter().min(), timestamps.iter().max()) {
+        (Some(first), Some(last)) if (*last - *first).num_hours() > HEATMAP_MIN_SPAN_HOURS => {
+            use chrono::Datelike;
+            let mut grid = vec![vec![0usize; 24]; 7];
+            for ts in timestamps {
+                grid[ts.weekday().num_days_from_monday() as usize][ts.hour() as usize] += 1;
+            }
+            Some(grid)
+        }
+        _ => None,
+    };
+
     let hourly_distribution: Vec<HourlyCount> = hourly_counts
         .iter()
         .enumerate()
@@ -1678,7 +2883,7 @@ pub fn compute_deep_temporal(
                         for &(idx, _) in window_indices {
                             if let Some(params) = line_params.get(idx) {
                                 for param_type in params.keys() {
-                                    let base_type = crate::analyzers::get_base_param_type(param_type);
+                                    let base_type = param_extractor::get_base_param_type(param_type);
                                     base_param_types.insert(base_type.to_string());
                                 }
                             }
@@ -1692,7 +2897,7 @@ pub fn compute_deep_temporal(
                             for &(idx, _) in window_indices {
                                 if let Some(params) = line_params.get(idx) {
                                     for (param_name, values) in params.iter() {
-                                        if crate::analyzers::get_base_param_type(param_name) == base_param_type {
+                                        if param_extractor::get_base_param_type(param_name) == base_param_type {
                                             for value in values {
                                                 *curr_param_counts.entry(value.clone()).or_insert(0) += 1;
                                             }
@@ -1705,7 +2910,7 @@ pub fn compute_deep_temporal(
                             for &(idx, _) in prev_window {
                                 if let Some(params) = line_params.get(idx) {
                                     for (param_name, values) in params.iter() {
-                                        if crate::analyzers::get_base_param_type(param_name) == base_param_type {
+                                        if param_extractor::get_base_param_type(param_name) == base_param_type {
                                             for value in values {
                                                 *prev_param_counts.entry(value.clone()).or_insert(0) += 1;
                                             }
@@ -1754,7 +2959,7 @@ pub fn compute_deep_temporal(
     }
     
     // Enhanced burst analysis with contributing factors
-    let bursts = temporal::compute_bursts(timestamps, chrono::Duration::minutes(1), 3.0);
+    let bursts = temporal::compute_bursts_min_events(timestamps, burst_window, 3.0, burst_min_events);
     let burst_analysis: Vec<BurstDetail> = bursts.iter().map(|b| {
         let mut contributing_factors = Vec::new();
         
@@ -1797,6 +3002,7 @@ pub fn compute_deep_temporal(
         time_clustering: time_clusters,
         pattern_evolution,
         burst_analysis,
+        weekday_hour_heatmap,
     }
 }
 
@@ -1914,11 +3120,32 @@ fn extract_source(rec: &parser::ParsedRecord, message: &str) -> (Option<String>,
         // host only
         return (None, pick_host(f, &host_keys));
     }
+    // Syslog with priority: host and app-name/tag from the header
+    if rec.syslog.is_some() {
+        if let Some(src) = syslog_source(message) { return src; }
+    }
     // Plaintext: try syslog-like host after timestamp
     if let Some(h) = extract_host_from_plaintext(message) { return (None, Some(h)); }
     (None, None)
 }
 
+/// (service, host) from a syslog header after its `<PRI>`:
+/// RFC5424 `1 <ts> host app ...` or RFC3164 `Oct 11 22:14:15 host tag[pid]: ...`
+fn syslog_source(line: &str) -> Option<(Option<String>, Option<String>)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let field = |s: &str| (s != "-").then(|| s.to_string());
+    if parts.len() >= 4 && parts[0] == "1" {
+        return Some((field(parts[3]), field(parts[2])));
+    }
+    let host = extract_host_from_plaintext(line)?;
+    let tag = parts.get(4)
+        .and_then(|t| t.strip_suffix(':'))
+        .map(|t| t.split('[').next().unwrap_or_default())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string);
+    Some((tag, Some(host)))
+}
+
 fn pick_host(f: &std::collections::BTreeMap<String,String>, keys: &[&str]) -> Option<String> { for k in keys { if let Some(v)=f.get(*k){ return Some(v.clone()); } } None }
 
 fn extract_host_from_plaintext(line: &str) -> Option<String> {
@@ -1949,19 +3176,32 @@ pub struct StreamingSummarizer {
     total_lines: usize,
     min_ts: Option<chrono::DateTime<chrono::Utc>>,
     max_ts: Option<chrono::DateTime<chrono::Utc>>,
+    skew: temporal::SkewTracker,
+    format_counts: HashMap<&'static str, usize>,
+    // lines per syslog facility (`<PRI>` prefixed lines only)
+    facility_counts: HashMap<&'static str, usize>,
 
     // Composite key = "{human_template}{level_suffix}"
     counts: std::collections::HashMap<String, usize>,
     examples: std::collections::HashMap<String, Vec<String>>,
     // severity votes per composite key
     severity_votes: std::collections::HashMap<String, std::collections::HashMap<String, usize>>,
+    // pattern category from its first line (see line_category)
+    category_by_tpl: std::collections::HashMap<String, &'static str>,
+    // level counts per template without the level suffix, shared by its per-level patterns
+    levels_by_template: std::collections::HashMap<String, std::collections::HashMap<String, usize>>,
     // source breakdowns
     service_by_tpl: std::collections::HashMap<String, std::collections::HashMap<String, usize>>,
     host_by_tpl: std::collections::HashMap<String, std::collections::HashMap<String, usize>>,
+    file_by_tpl: std::collections::HashMap<String, std::collections::HashMap<String, usize>>,
+    // file the next ingested chunks come from, see set_source_file
+    source_file: Option<String>,
     // param stats: tpl -> (param -> (value -> count))
     param_counts: std::collections::HashMap<String, std::collections::HashMap<String, std::collections::HashMap<String, usize>>>,
-    // temporal minute buckets: tpl -> (epoch_minute -> count)
-    minute_buckets: std::collections::HashMap<String, std::collections::BTreeMap<i64, usize>>,
+    // temporal buckets: tpl -> (epoch_ms / bucket_ms -> count)
+    time_buckets: std::collections::HashMap<String, std::collections::BTreeMap<i64, usize>>,
+    // bucket width in ms, see temporal::aggregation_bucket_ms
+    bucket_ms: i64,
     // for deep temporal analysis: store timestamps and params per template (limited to first 1000 to prevent memory issues)
     timestamps_by_tpl: std::collections::HashMap<String, Vec<chrono::DateTime<chrono::Utc>>>,
     line_params_by_tpl: std::collections::HashMap<String, Vec<std::collections::HashMap<String, Vec<String>>>>,
@@ -1974,6 +3214,14 @@ pub struct StreamingSummarizer {
 
     // error samples
     error_samples: Vec<ErrorSample>,
+    malformed_total: usize,
+
+    // bounded mode (`max_tracked_patterns`): patterns dropped and the lines they had counted
+    evicted_patterns: usize,
+    evicted_lines: usize,
+
+    // time key auto-detected from the first chunk when none was given (None = not yet checked)
+    detected_time_key: Option<Option<String>>,
 }
 
 impl Default for StreamingSummarizer {
@@ -1984,8 +3232,13 @@ impl Default for StreamingSummarizer {
 
 impl StreamingSummarizer {
     pub fn new() -> Self {
+        Self::with_opts(&SummarizeOpts::default())
+    }
+
+    /// Create a summarizer whose Drain tree is tuned by `opts`
+    pub fn with_opts(opts: &SummarizeOpts) -> Self {
         Self {
-            drain: drain_adapter::DrainAdapter::new_tuned_with_filters(32, 0.1, 512),
+            drain: opts.new_drain(),
             unique_masked: std::collections::BTreeSet::new(),
             masked_to_template: std::collections::BTreeMap::new(),
             base_cache: std::collections::HashMap::new(),
@@ -1993,13 +3246,21 @@ impl StreamingSummarizer {
             total_lines: 0,
             min_ts: None,
             max_ts: None,
+            skew: temporal::SkewTracker::default(),
+            format_counts: HashMap::new(),
+            facility_counts: HashMap::new(),
             counts: std::collections::HashMap::new(),
             examples: std::collections::HashMap::new(),
             severity_votes: std::collections::HashMap::new(),
+            category_by_tpl: std::collections::HashMap::new(),
+            levels_by_template: std::collections::HashMap::new(),
             service_by_tpl: std::collections::HashMap::new(),
             host_by_tpl: std::collections::HashMap::new(),
+            file_by_tpl: std::collections::HashMap::new(),
+            source_file: None,
             param_counts: std::collections::HashMap::new(),
-            minute_buckets: std::collections::HashMap::new(),
+            time_buckets: std::collections::HashMap::new(),
+            bucket_ms: temporal::aggregation_bucket_ms(opts.burst_window),
             timestamps_by_tpl: std::collections::HashMap::new(),
             line_params_by_tpl: std::collections::HashMap::new(),
             first_fp: None,
@@ -2007,7 +3268,53 @@ impl StreamingSummarizer {
             first_fp_ts: None,
             last_fp_ts: None,
             error_samples: Vec::new(),
+            malformed_total: 0,
+            evicted_patterns: 0,
+            evicted_lines: 0,
+            detected_time_key: None,
+        }
+    }
+
+    /// Keep only the `k` most frequent patterns, dropping everything tracked for the rest
+    /// (examples, sources, params, buckets) plus the template caches that only they used.
+    /// An evicted pattern that shows up again restarts from zero, so counts of rare or
+    /// late-rising patterns are underestimated and a returning pattern may look new.
+    fn evict_to(&mut self, k: usize) {
+        if self.counts.len() <= k { return; }
+        let mut ranked: Vec<(String, usize)> = self.counts.iter().map(|(t, c)| (t.clone(), *c)).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (tpl, cnt) in ranked.drain(k..) {
+            self.counts.remove(&tpl);
+            self.examples.remove(&tpl);
+            self.severity_votes.remove(&tpl);
+            self.category_by_tpl.remove(&tpl);
+            self.service_by_tpl.remove(&tpl);
+            self.host_by_tpl.remove(&tpl);
+            self.file_by_tpl.remove(&tpl);
+            self.param_counts.remove(&tpl);
+            self.time_buckets.remove(&tpl);
+            self.timestamps_by_tpl.remove(&tpl);
+            self.line_params_by_tpl.remove(&tpl);
+            self.evicted_patterns += 1;
+            self.evicted_lines += cnt;
         }
+        let kept: HashSet<&str> = self.counts.keys().map(|t| strip_level_suffix(t)).collect();
+        self.levels_by_template.retain(|t, _| kept.contains(t.as_str()));
+        self.human_template_cache.retain(|_, human| kept.contains(human.as_str()));
+        let cache = &self.human_template_cache;
+        self.masked_to_template.retain(|_, tpl| cache.contains_key(tpl));
+        let mapped = &self.masked_to_template;
+        self.unique_masked.retain(|m| mapped.contains_key(m));
+    }
+
+    /// Raw Drain template that humanizes to `key` (minus its level suffix); the smallest when several do
+    fn raw_drain_template(&self, key: &str) -> Option<String> {
+        let human = strip_level_suffix(key);
+        self.human_template_cache.iter()
+            .filter(|(_, h)| h.as_str() == human)
+            .map(|(raw, _)| raw)
+            .min()
+            .cloned()
     }
 
     /// Fast humanizer for Drain templates (copied from summarize_impl local fn)
@@ -2054,11 +3361,25 @@ impl StreamingSummarizer {
         result
     }
 
+    /// Attribute the lines of the following `ingest_chunk` calls to `file` in each pattern's
+    /// `by_file` breakdown (None stops tagging)
+    pub fn set_source_file(&mut self, file: Option<&str>) {
+        self.source_file = file.map(str::to_string);
+    }
+
     /// Ingest a chunk of aggregated log records.
     pub fn ingest_chunk(&mut self, lines: &[String], time_keys: &[&str], opts: &SummarizeOpts) {
-        use rayon::prelude::*;
         use std::collections::{BTreeMap, BTreeSet, HashMap};
 
+        if let Some(expanded) = parser::expand_json_arrays(lines, opts.input_format) {
+            return self.ingest_chunk(&expanded, time_keys, opts);
+        }
+        if time_keys.is_empty() && self.detected_time_key.is_none() {
+            self.detected_time_key = Some(parser::detect_time_key(lines, opts.parse_opts()));
+        }
+        let detected_keys: Vec<&str> = self.detected_time_key.iter().flatten().map(String::as_str).collect();
+        let time_keys: &[&str] = if time_keys.is_empty() { &detected_keys } else { time_keys };
+
         #[derive(Clone)]
         struct LineDeriv {
             message: String,
@@ -2070,6 +3391,8 @@ impl StreamingSummarizer {
             malformed_json: bool,
             fingerprint: Option<schema::Fingerprint>,
             flat_fields: Option<std::collections::BTreeMap<String,String>>,
+            format: &'static str,
+            facility: Option<&'static str>,
             // params extracted during canonicalization/KV merge
             extracted_params: HashMap<String, Vec<String>>,
             masked_text: String,
@@ -2080,13 +3403,8 @@ impl StreamingSummarizer {
             .par_iter()
             .enumerate()
             .map(|(i, l)| {
-                let looks_json = l.trim_start().starts_with('{') || l.trim_start().starts_with('[');
-                let rec = if time_keys.is_empty() {
-                    parser::parse_line(l, i + 1)
-                } else {
-                    parser::parse_line_with_hints(l, i + 1, time_keys)
-                };
-                let malformed_json = looks_json && rec.flat_fields.is_none();
+                let rec = parser::parse_line_as(l, i + 1, time_keys, opts.parse_opts());
+                let malformed_json = opts.input_format.expects_json(l) && rec.flat_fields.is_none();
                 let base = if let Some(ff) = rec.flat_fields.as_ref() {
                     let mut items: Vec<(String,String)> = ff.iter().map(|(k,v)| (k.clone(), v.clone())).collect();
                     items.sort_by(|a,b| a.0.cmp(&b.0));
@@ -2104,24 +3422,12 @@ impl StreamingSummarizer {
                 };
                 let level = rec.flat_fields.as_ref()
                     .and_then(|f| f.get("level").cloned())
-                    .or_else(|| {
-                        let msg_upper = rec.message.to_uppercase();
-                        if msg_upper.contains(" ERROR") || msg_upper.contains(" ERR ") {
-                            Some("ERROR".to_string())
-                        } else if msg_upper.contains(" WARN") || msg_upper.contains(" WARNING") {
-                            Some("WARN".to_string())
-                        } else if msg_upper.contains(" INFO") {
-                            Some("INFO".to_string())
-                        } else if msg_upper.contains(" DEBUG") {
-                            Some("DEBUG".to_string())
-                        } else if msg_upper.contains(" TRACE") {
-                            Some("TRACE".to_string())
-                        } else {
-                            None
-                        }
-                    });
-                let (service_opt, host_opt) = extract_source(&rec, &rec.message);
-                let fingerprint = if rec.flat_fields.is_some() {
+                    .or_else(|| rec.syslog.map(|s| s.level().to_string()))
+                    .or_else(|| detect_text_level(&rec.message));
+                let (service_opt, host_opt) = if opts.counts_only { (None, None) } else { extract_source(&rec, &rec.message) };
+                let fingerprint = if opts.counts_only {
+                    None
+                } else if rec.flat_fields.is_some() {
                     if let Some(rv) = rec.raw_json.as_ref() {
                         Some(schema::fingerprint_value(rv))
                     } else {
@@ -2130,6 +3436,8 @@ impl StreamingSummarizer {
                             .map(|v| schema::fingerprint_value(&v))
                     }
                 } else { None };
+                let format = line_format(&rec);
+                let facility = rec.syslog.map(|s| s.facility);
                 LineDeriv {
                     message: rec.message,
                     timestamp: rec.timestamp,
@@ -2140,6 +3448,8 @@ impl StreamingSummarizer {
                     malformed_json,
                     fingerprint,
                     flat_fields: rec.flat_fields.clone(),
+                    format,
+                    facility,
                     extracted_params: HashMap::new(),
                     masked_text: String::new(),
                 }
@@ -2148,12 +3458,18 @@ impl StreamingSummarizer {
 
         // Track min/max timestamps and errors (global)
         for (i, d) in derived.iter().enumerate() {
+            *self.format_counts.entry(d.format).or_insert(0) += 1;
+            if let Some(f) = d.facility { *self.facility_counts.entry(f).or_insert(0) += 1; }
             if let Some(ts) = d.timestamp {
+                self.skew.observe(ts);
                 self.min_ts = Some(self.min_ts.map(|m| m.min(ts)).unwrap_or(ts));
                 self.max_ts = Some(self.max_ts.map(|m| m.max(ts)).unwrap_or(ts));
             }
-            if d.malformed_json && self.error_samples.len() < 10 {
-                self.error_samples.push(ErrorSample { line_number: i + 1, kind: "malformed_json".into() });
+            if d.malformed_json {
+                self.malformed_total += 1;
+                if self.error_samples.len() < 10 {
+                    self.error_samples.push(ErrorSample { line_number: i + 1, kind: "malformed_json".into() });
+                }
             }
         }
 
@@ -2183,7 +3499,7 @@ impl StreamingSummarizer {
             .collect();
         let computed: Vec<(String, param_extractor::MaskingResult)> = to_compute
             .par_iter()
-            .map(|k| (k.clone(), param_extractor::canonicalize_for_drain(k)))
+            .map(|k| (k.clone(), opts.canonicalize(k)))
             .collect();
         for (k, res) in computed {
             self.base_cache.insert(k, res);
@@ -2222,8 +3538,7 @@ impl StreamingSummarizer {
         // Fan out: attach masked_text + extract params
         let line_templates_raw: Vec<String> = derived
             .par_iter()
-            .enumerate()
-            .map(|(_i, d)| {
+            .map(|d| {
                 // For JSON logs, look up by message; for others by base
                 let canon_key = if d.flat_fields.is_some() {
                     &d.message
@@ -2232,7 +3547,7 @@ impl StreamingSummarizer {
                 };
                 let canon = self.base_cache.get(canon_key)
                     .cloned()
-                    .unwrap_or_else(|| param_extractor::canonicalize_for_drain(canon_key));
+                    .unwrap_or_else(|| opts.canonicalize(canon_key));
                 // store
                 let masked = canon.masked_text.clone();
                 self.masked_to_template.get(&masked)
@@ -2245,13 +3560,20 @@ impl StreamingSummarizer {
         for (i, mut d) in derived.into_iter().enumerate() {
             let raw_tpl = &line_templates_raw[i];
             let human_tpl = self.humanize_drain_template(raw_tpl);
-            let level_suffix = if let Some(level) = &d.level {
-                format!(" [{level}]")
-            } else {
-                String::new()
-            };
+            let level_suffix = opts.level_suffix(d.level.as_deref());
             let composite_key = format!("{human_tpl}{level_suffix}");
 
+            *self.counts.entry(composite_key.clone()).or_insert(0) += 1;
+            self.total_lines += 1;
+            // severity votes
+            if let Some(lv) = d.level.clone() {
+                *self.levels_by_template.entry(human_tpl.clone()).or_default()
+                    .entry(lv.clone()).or_insert(0) += 1;
+                *self.severity_votes.entry(composite_key.clone()).or_default()
+                    .entry(lv).or_insert(0) += 1;
+            }
+            if opts.counts_only { continue; }
+
             // recompute params for this line (single-threaded merge; small cost)
             // For JSON logs, look up by message; for others by base
             let canon_key = if d.flat_fields.is_some() {
@@ -2261,12 +3583,17 @@ impl StreamingSummarizer {
             };
             let canon = self.base_cache.get(canon_key)
                 .cloned()
-                .unwrap_or_else(|| param_extractor::canonicalize_for_drain(canon_key));
+                .unwrap_or_else(|| opts.canonicalize(canon_key));
             let mut params = canon.extracted_params.clone();
             if let Some(ff) = d.flat_fields.as_ref() {
-                let kv = param_extractor::extract_kv_params(ff);
+                let kv = param_extractor::extract_kv_params_filtered(ff, &opts.field_filter);
                 params = param_extractor::merge_params(params, kv);
             }
+            if opts.decode_b64_json { param_extractor::merge_b64_json_params(&mut params); }
+            param_extractor::truncate_param_values(&mut params, opts.max_value_length);
+            if !self.category_by_tpl.contains_key(&composite_key) {
+                self.category_by_tpl.insert(composite_key.clone(), line_category(d.flat_fields.is_some(), &d.base));
+            }
             d.masked_text = canon.masked_text;
             
             // Store the params for later use in deep analysis
@@ -2274,16 +3601,9 @@ impl StreamingSummarizer {
             let extracted_params_for_deep = params.clone();
             d.extracted_params = params;
 
-            *self.counts.entry(composite_key.clone()).or_insert(0) += 1;
-            self.total_lines += 1;
             // keep up to 3 examples (like non-deep mode)
             let exs = self.examples.entry(composite_key.clone()).or_default();
             if exs.len() < 3 { exs.push(d.message.clone()); }
-            // severity votes
-            if let Some(lv) = d.level.clone() {
-                *self.severity_votes.entry(composite_key.clone()).or_default()
-                    .entry(lv).or_insert(0) += 1;
-            }
             // sources
             if let Some(svc) = d.service.clone() {
                 *self.service_by_tpl.entry(composite_key.clone()).or_default()
@@ -2293,6 +3613,10 @@ impl StreamingSummarizer {
                 *self.host_by_tpl.entry(composite_key.clone()).or_default()
                     .entry(h).or_insert(0) += 1;
             }
+            if let Some(f) = &self.source_file {
+                *self.file_by_tpl.entry(composite_key.clone()).or_default()
+                    .entry(f.clone()).or_insert(0) += 1;
+            }
             // params: include only ones that appear as placeholders in template
             let clean_template = if let Some(bracket_pos) = composite_key.rfind(" [") {
                 let suffix = &composite_key[bracket_pos..];
@@ -2305,16 +3629,17 @@ impl StreamingSummarizer {
             let placeholders = extract_placeholders(&clean_template);
             let pc = self.param_counts.entry(composite_key.clone()).or_default();
             for (k, vals) in extracted_params_for_processing.into_iter() {
-                let include = k == "NESTED_PATTERN" || placeholders.contains(&k);
+                let include = k == "NESTED_PATTERN" || k.starts_with(param_extractor::B64_JSON_PREFIX)
+                    || k.starts_with(param_extractor::QUERY_PARAM_PREFIX) || placeholders.contains(&k);
                 if !include { continue; }
                 let m = pc.entry(k).or_default();
                 for v in vals { *m.entry(v).or_insert(0) += 1; }
             }
-            // temporal minute bucket
+            // temporal bucket
             if let Some(ts) = d.timestamp {
-                let min_epoch = ts.timestamp() / 60;
-                *self.minute_buckets.entry(composite_key.clone()).or_default()
-                    .entry(min_epoch).or_insert(0) += 1;
+                let bucket = ts.timestamp_millis().div_euclid(self.bucket_ms);
+                *self.time_buckets.entry(composite_key.clone()).or_default()
+                    .entry(bucket).or_insert(0) += 1;
             }
             // schema fingerprints
             if let Some(fp) = d.fingerprint {
@@ -2325,8 +3650,8 @@ impl StreamingSummarizer {
                 self.last_fp = Some(fp);
                 self.last_fp_ts = d.timestamp;
             }
-            // collect timestamps and params for deep temporal analysis (limit to prevent memory issues)
-            if opts.deep {
+            // collect timestamps and params for deep temporal analysis and custom analyzers (limit to prevent memory issues)
+            if opts.deep || opts.analyzers.has_registered() {
                 let timestamps = self.timestamps_by_tpl.entry(composite_key.clone()).or_default();
                 let line_params = self.line_params_by_tpl.entry(composite_key.clone()).or_default();
                 
@@ -2339,6 +3664,11 @@ impl StreamingSummarizer {
                 }
             }
         }
+        if let Some(k) = opts.max_tracked_patterns {
+            // Canonicalizations are cached per distinct line and would grow with the stream
+            self.base_cache.clear();
+            self.evict_to(k);
+        }
     }
 
     /// Finalize aggregated data into AiOutput (no access to original lines).
@@ -2352,15 +3682,40 @@ impl StreamingSummarizer {
         let compression_ratio = if unique > 0 { (total as f64) / (unique as f64) } else { 0.0 };
         let start_date = self.min_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
         let end_date = self.max_ts.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+        let summary = Summary { total_lines: total, unique_patterns: unique, compression_ratio, start_date, end_date, timestamped_ratio: timestamped_ratio(self.skew.timestamped(), total), time_key: self.detected_time_key.clone().flatten(), diagnostic: input_diagnostic(total, self.malformed_total), clock_skew: self.skew.report().map(Into::into), detected_formats: count_items_out(&self.format_counts), by_facility: count_items_out(&self.facility_counts), deduped_lines: 0, evicted_patterns: self.evicted_patterns, evicted_lines: self.evicted_lines, widest_span: None, narrowest_span: None };
+        if opts.counts_only {
+            let patterns = self.counts.iter().map(|(tpl, &cnt)| {
+                let severity = self.severity_votes.get(tpl)
+                    .and_then(|m| m.iter().max_by_key(|(_, c)| *c).map(|(k, _)| k.clone()));
+                count_only_pattern(tpl.clone(), cnt, total, severity)
+            }).collect();
+            let errors = ErrorsOut { total: self.malformed_total, samples: self.error_samples, invalid_utf8_lines: 0 };
+            return counts_only_output(summary, patterns, errors, opts);
+        }
 
         let mut patterns = Vec::new();
         let mut suggestions: Vec<SuggestionOut> = Vec::new();
 
+        // --correlate over time buckets: one timestamp per occupied bucket keeps this bounded, so
+        // co-occurrence (and `count`) is measured in buckets rather than lines
+        let correlated = if opts.correlate {
+            let occupied: HashMap<String, Vec<chrono::DateTime<chrono::Utc>>> = self.time_buckets.iter()
+                .map(|(tpl, buckets)| (tpl.clone(), buckets.keys().map(|&b| temporal::bucket_start(b, self.bucket_ms)).collect()))
+                .collect();
+            correlate_patterns(&occupied)
+        } else {
+            HashMap::new()
+        };
+
         // Build patterns from aggregates
         for (tpl, cnt) in self.counts.iter() {
             // severity
             let severity = self.severity_votes.get(tpl)
                 .and_then(|m| m.iter().max_by_key(|(_,c)| *c).map(|(k,_)| k.clone()));
+            let base_tpl = severity.as_ref()
+                .and_then(|lv| tpl.strip_suffix(&format!(" [{lv}]")))
+                .unwrap_or(tpl);
+            let severity_breakdown = severity_breakdown_out(self.levels_by_template.get(base_tpl));
             // examples
             let examples = self.examples.get(tpl).cloned().unwrap_or_default();
             // sources (top 3)
@@ -2374,6 +3729,11 @@ impl StreamingSummarizer {
                 .unwrap_or_default();
             host_items.sort_by(|a,b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
             if host_items.len() > 3 { host_items.truncate(3); }
+            let mut file_items: Vec<CountItem> = self.file_by_tpl.get(tpl)
+                .map(|m| m.iter().map(|(k,v)| CountItem{ name: k.clone(), count: *v }).collect())
+                .unwrap_or_default();
+            file_items.sort_by(|a,b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+            if file_items.len() > 3 { file_items.truncate(3); }
             // param stats
             let param_stats = self.param_counts.get(tpl).map(|pc| {
                 let mut out = std::collections::HashMap::new();
@@ -2389,14 +3749,17 @@ impl StreamingSummarizer {
                     let base_stats = ParamFieldStats {
                         total,
                         cardinality,
+                        numeric_summary: compute_numeric_summary(&values_out),
                         values: values_out,
                         top_ratio,
                         is_sequence: None,
                         sequence_info: None,
+                        routes: None,
+                        latency_buckets: None,
                     };
                     
                     // Apply sequence detection and compaction
-                    let final_stats = apply_sequence_detection(base_stats, param);
+                    let final_stats = apply_latency_buckets(apply_route_templating(apply_sequence_detection(base_stats, param), param), param);
                     out.insert(param.clone(), final_stats);
                 }
                 out
@@ -2409,16 +3772,13 @@ impl StreamingSummarizer {
                 } else { tpl.clone() }
             } else { tpl.clone() };
 
-            // Convert minute buckets to DateTime timestamps for temporal analysis
-            let timestamps = if let Some(buckets) = self.minute_buckets.get(tpl) {
+            // Convert time buckets to DateTime timestamps for temporal analysis
+            let timestamps = if let Some(buckets) = self.time_buckets.get(tpl) {
                 let mut ts = Vec::new();
-                for (&minute, &count) in buckets.iter() {
-                    if let Some(dt) = chrono::Utc.timestamp_opt(minute * 60, 0).single() {
-                        // Add multiple timestamps for each count to represent the frequency
-                        for _ in 0..count {
-                            ts.push(dt);
-                        }
-                    }
+                for (&bucket, &count) in buckets.iter() {
+                    // Add multiple timestamps for each count to represent the frequency
+                    let dt = temporal::bucket_start(bucket, self.bucket_ms);
+                    ts.extend(std::iter::repeat_n(dt, count));
                 }
                 ts
             } else {
@@ -2430,7 +3790,7 @@ impl StreamingSummarizer {
             let end_time = timestamps.iter().max().map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
             
             // Compute temporal analysis
-            let bursts = temporal::compute_bursts(&timestamps, chrono::Duration::minutes(1), 3.0);
+            let bursts = temporal::compute_bursts_min_events(&timestamps, opts.burst_window, 3.0, opts.burst_min_events);
             let largest_burst = bursts.iter().max_by_key(|b| b.peak_rate)
                 .map(|b| b.start_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
             let trend = trend_label(&timestamps);
@@ -2441,9 +3801,7 @@ impl StreamingSummarizer {
             });
             
             // Compute pattern stability
-            let temporal_consistency = if !timestamps.is_empty() && self.min_ts.is_some() && self.max_ts.is_some() {
-                let a = self.min_ts.unwrap();
-                let b = self.max_ts.unwrap();
+            let temporal_consistency = if let (false, Some(a), Some(b)) = (timestamps.is_empty(), self.min_ts, self.max_ts) {
                 let span_secs = (b - a).num_seconds().abs().max(60) as f64;
                 let unique_minutes = timestamps.iter()
                     .map(|t| t.timestamp() / 60)
@@ -2455,6 +3813,11 @@ impl StreamingSummarizer {
             };
             let freq_factor = ((*cnt as f64) / (total as f64)).sqrt().min(1.0);
             let pattern_stability = (temporal_consistency * 0.6) + (freq_factor * 0.4);
+            // Prefer exact timestamps over minute buckets for inter-arrival regularity
+            let periodicity = match self.timestamps_by_tpl.get(tpl) {
+                Some(exact) if !exact.is_empty() => temporal::compute_periodicity(exact),
+                _ => temporal::compute_periodicity(&timestamps),
+            };
             
             // Use unified pattern builder - it will handle all analysis including temporal
             let pattern_data = analyzers::PatternData {
@@ -2463,15 +3826,19 @@ impl StreamingSummarizer {
                 frequency: (*cnt as f64) / (total as f64),
                 examples,
                 severity,
+                severity_breakdown,
                 start_time,
                 end_time,
                 spike_analysis: None, // Let build_pattern compute if opts.analyze_spikes
                 temporal,
-                correlations: Vec::new(),
+                correlations: correlated.get(tpl).cloned().unwrap_or_default(),
                 pattern_stability,
+                periodicity,
                 service_breakdown: svc_items,
                 host_breakdown: host_items,
-                drain_template: None,
+                file_breakdown: file_items,
+                category: self.category_by_tpl.get(tpl).map(|c| c.to_string()),
+                drain_template: opts.show_drain_template.then(|| self.raw_drain_template(tpl)).flatten(),
                 param_stats,
                 timestamps,
                 line_params: self.line_params_by_tpl.get(tpl).cloned().unwrap_or_default(),
@@ -2481,24 +3848,22 @@ impl StreamingSummarizer {
             patterns.push(analyzers::AnalyzerRegistry::build_pattern(pattern_data, opts, total, None));
 
             // Suggestion from largest burst if present
-            if let Some(buckets) = self.minute_buckets.get(tpl) {
+            if let Some(buckets) = self.time_buckets.get(tpl) {
                 if let Some((&m, &_c)) = buckets.iter().max_by_key(|(_,c)| *c) {
-                    let st = chrono::Utc.timestamp_opt(m * 60, 0).single()
-                        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
-                    if let Some(st) = st {
-                        suggestions.push(SuggestionOut {
-                            priority: "HIGH".into(),
-                            description: format!("Pattern burst for '{tpl}'"),
-                            query: SuggestQuery {
-                                command: "GET_LINES_BY_TIME".into(),
-                                params: SuggestParams {
-                                    start: Some(st.clone()),
-                                    end: Some(st), // single-minute window; UI can expand
-                                    pattern: Some(tpl.clone()),
-                                },
+                    let st = temporal::bucket_start(m, self.bucket_ms).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+                    suggestions.push(SuggestionOut {
+                        priority: "HIGH".into(),
+                        reason_code: ReasonCode::Burst,
+                        description: format!("Pattern burst for '{tpl}'"),
+                        query: SuggestQuery {
+                            command: "GET_LINES_BY_TIME".into(),
+                            params: SuggestParams {
+                                start: Some(st.clone()),
+                                end: Some(st), // single-bucket window; UI can expand
+                                pattern: Some(tpl.clone()),
                             },
-                        });
-                    }
+                        },
+                    });
                 }
             }
         }
@@ -2510,35 +3875,24 @@ impl StreamingSummarizer {
         let mut schema_changes = Vec::new();
         if let (Some(first_fp), Some(last_fp), Some(last_ts)) = (self.first_fp.as_ref(), self.last_fp.as_ref(), self.last_fp_ts) {
             for ch in schema::diff_fingerprints(first_fp, last_fp) {
-                match ch {
-                    schema::SchemaChange::FieldAdded { field, .. } => {
-                        schema_changes.push(SchemaChangeOut { timestamp: Some(last_ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)), change_type: "field_added".into(), field: field.clone(), impact: None });
-                    }
-                    schema::SchemaChange::FieldRemoved { field, .. } => {
-                        schema_changes.push(SchemaChangeOut { timestamp: Some(last_ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)), change_type: "field_removed".into(), field: field.clone(), impact: None });
-                    }
-                    schema::SchemaChange::TypeChanged { field, .. } => {
-                        schema_changes.push(SchemaChangeOut { timestamp: Some(last_ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)), change_type: "type_changed".into(), field: field.clone(), impact: None });
-                    }
-                }
+                schema_changes.push(SchemaChangeOut::from_change(&ch, Some(last_ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))));
             }
         }
 
         // Pattern anomalies (New/Rare) using the same helper
         let empty_baseline = std::collections::HashSet::<String>::new();
         let baseline_ref = baseline_opt.unwrap_or(&empty_baseline);
-        let pattern_anoms = anomaly::detect_pattern_anomalies(&self.counts, total, baseline_ref, 0.1);
-        let pattern_anomalies: Vec<PatternAnomalyOut> = pattern_anoms.into_iter().map(|a| PatternAnomalyOut {
-            kind: match a.kind { anomaly::AnomalyKind::NewPattern => "NewPattern".into(), anomaly::AnomalyKind::RarePattern => "RarePattern".into() },
-            template: a.template,
-            frequency: a.frequency,
-            count: a.count,
-        }).collect();
+        let mut pattern_anoms = anomaly::detect_pattern_anomalies(&self.counts, total, baseline_ref, 0.1);
+        pattern_anoms.extend(anomaly::detect_disappeared_patterns(&self.counts, total, &opts.baseline_frequencies, anomaly::DISAPPEARED_MIN_BASELINE_FREQ, anomaly::DISAPPEARED_DROP_RATIO));
+        let pattern_anomalies: Vec<PatternAnomalyOut> = pattern_anoms.into_iter()
+            .map(|a| PatternAnomalyOut::new(a, &opts.baseline_frequencies))
+            .collect();
         // also seed suggestions from anomalies
         for pa in &pattern_anomalies {
-            let priority = if pa.kind == "NewPattern" { "HIGH" } else { "LOW" };
+            let priority = if pa.kind == "NewPattern" || pa.kind == "DisappearedPattern" { "HIGH" } else { "LOW" };
             suggestions.push(SuggestionOut {
                 priority: priority.into(),
+                reason_code: ReasonCode::for_pattern_anomaly(&pa.kind),
                 description: format!("{}: {}", pa.kind, pa.template),
                 query: SuggestQuery {
                     command: "GET_LINES_BY_PATTERN".into(),
@@ -2555,7 +3909,7 @@ impl StreamingSummarizer {
             best.insert(key, s);
         }
         let mut deduped: Vec<SuggestionOut> = best.into_values().collect();
-        deduped.sort_by(|a,b| prio_rank(&b.priority).cmp(&prio_rank(&a.priority)));
+        deduped.sort_by_key(|s| std::cmp::Reverse(prio_rank(&s.priority)));
         let query_interface = QueryInterfaceOut {
             available_commands: vec!["GET_LINES_BY_PATTERN".into(), "GET_LINES_BY_TIME".into(), "GET_CONTEXT".into()],
             suggested_investigations: deduped,
@@ -2566,16 +3920,28 @@ impl StreamingSummarizer {
             pattern_anomalies: pattern_anomalies.clone(),
             field_anomalies: Vec::new(),
             temporal_anomalies: Vec::new(),
+            // Without per-line order only the previous-window baseline is available here
+            ratio_anomalies: ratio_anomalies_vs_baseline(&opts.ratio_rules, &self.counts, &opts.baseline_frequencies),
         };
 
-        AiOutput {
-            summary: Summary { total_lines: total, unique_patterns: unique, compression_ratio, start_date, end_date },
+        let mut out = AiOutput {
+            summary,
             patterns,
             schema_changes,
             anomalies,
             query_interface,
-            errors: ErrorsOut { total: self.error_samples.len(), samples: self.error_samples },
-        }
+            errors: ErrorsOut { total: self.malformed_total, samples: self.error_samples, invalid_utf8_lines: 0 },
+            pattern_clusters: Vec::new(),
+            global_params: BTreeMap::new(),
+            other_patterns: None,
+        };
+        if let Some(threshold) = opts.pattern_cluster_similarity { out.pattern_clusters = build_pattern_clusters(&out.patterns, threshold); }
+        if let Some(types) = &opts.redact { redact_output(&mut out, types); }
+        out.global_params = build_global_params(&out.patterns);
+        if let Some(min) = opts.min_count { collapse_rare_patterns(&mut out, min); }
+        set_pattern_spans(&mut out);
+        if let Some(n) = opts.top_params { limit_param_values(&mut out, n); }
+        out
     }
 }
 
@@ -2585,11 +3951,40 @@ pub fn prewarm_regexes() {
     let _ = &*TEMPLATE_FIELD_PATTERN;
 }
 
+/// Groups path-like parameter values (PATH, URL, REQUEST_PATH) by REST route shape.
+/// Original values stay in `values`; `routes` is only set when templating collapses something.
+pub fn apply_route_templating(mut stats: ParamFieldStats, param_type: &str) -> ParamFieldStats {
+    use param_extractor::ParamType;
+    let path_like = matches!(ParamType::of(param_type), Some(ParamType::Path | ParamType::Url))
+        || param_extractor::get_base_param_type(param_type) == "REQUEST_PATH";
+    if !path_like {
+        return stats;
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for v in &stats.values {
+        *counts.entry(param_extractor::templatize_path(&v.value)).or_insert(0) += v.count;
+    }
+    if counts.len() >= stats.values.len() {
+        return stats;
+    }
+    let mut routes: Vec<ParamValueCount> = counts.into_iter()
+        .map(|(value, count)| ParamValueCount { value, count })
+        .collect();
+    routes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    stats.routes = Some(routes);
+    stats
+}
+
+/// Fill `latency_buckets` for latency params (see `analyzers::latency_buckets`)
+pub fn apply_latency_buckets(mut stats: ParamFieldStats, param_type: &str) -> ParamFieldStats {
+    stats.latency_buckets = analyzers::latency_buckets(param_type, &stats);
+    stats
+}
+
 /// Applies sequence detection to parameter statistics and compacts sequences
 pub fn apply_sequence_detection(mut stats: ParamFieldStats, param_type: &str) -> ParamFieldStats {
     // Only apply sequence detection to numeric parameters
-    let base_param_type = get_base_param_type(param_type);
-    if base_param_type != "NUM" {
+    if param_extractor::get_base_param_type(param_type) != param_extractor::ParamType::Num.as_str() {
         stats.is_sequence = Some(false);
         return stats;
     }
@@ -2866,15 +4261,3 @@ fn generate_sequence_values(seq_info: &SequenceInfo) -> std::collections::HashSe
     values
 }
 
-/// Extracts base parameter type (e.g., "NUM_2" -> "NUM")
-fn get_base_param_type(param_type: &str) -> &str {
-    if let Some(underscore_pos) = param_type.rfind('_') {
-        if param_type[underscore_pos + 1..].chars().all(|c| c.is_ascii_digit()) {
-            &param_type[..underscore_pos]
-        } else {
-            param_type
-        }
-    } else {
-        param_type
-    }
-}
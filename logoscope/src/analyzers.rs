@@ -1,18 +1,29 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use chrono::{DateTime, Utc};
 
 // Re-export types from ai module that analyzers need
-use crate::ai::{ParameterAnomaly, DeepTemporalOut, DeepCorrelation, ParamFieldStats, CorrelatedOut};
+use crate::ai::{ParameterAnomaly, DeepTemporalOut, DeepCorrelation, ParamFieldStats, ParamValueCount, CorrelatedOut};
 
-/// Common data structure passed to all analyzers
+/// Common data structure passed to all analyzers, one pattern at a time.
+///
+/// Contract for analyzers registered with `AnalyzerRegistry::with_analyzer`: the context is
+/// built the same way in chunked and in-memory mode, and an analyzer sees only this pattern.
 #[derive(Debug, Clone)]
 pub struct AnalysisContext {
+    /// Pattern template including the ` [LEVEL]` suffix, as in `PatternOut::template`
     pub template: String,
+    /// `template` without the level suffix
     pub clean_template: String,
+    /// Lines matched by the pattern
     pub total_count: usize,
+    /// Parsed timestamps of the pattern's lines in input order (lines without one are skipped)
     pub timestamps: Vec<DateTime<Utc>>,
+    /// Extracted params per line, keyed by placeholder name (`STATUS`, `NUM_2`, ...)
     pub line_params: Vec<HashMap<String, Vec<String>>>,
+    /// Positions into `line_params` of the pattern's lines, in input order. In chunked mode only
+    /// the first 1000 timestamped lines are kept per pattern; use `total_count` for counts.
     pub pattern_indices: Vec<usize>,
+    /// Per-param value statistics, when any params were extracted
     pub param_stats: Option<HashMap<String, ParamFieldStats>>,
 }
 
@@ -24,14 +35,18 @@ pub struct PatternData {
     pub frequency: f64,
     pub examples: Vec<String>,
     pub severity: Option<String>,
+    pub severity_breakdown: Vec<crate::ai::CountItem>,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
     pub spike_analysis: Option<crate::ai::SpikeAnalysis>,
     pub temporal: Option<crate::ai::TemporalOut>,
     pub correlations: Vec<CorrelatedOut>,
     pub pattern_stability: f64,
+    pub periodicity: Option<f64>,
     pub service_breakdown: Vec<crate::ai::CountItem>,
     pub host_breakdown: Vec<crate::ai::CountItem>,
+    pub file_breakdown: Vec<crate::ai::CountItem>,
+    pub category: Option<String>,
     pub drain_template: Option<String>,
     pub param_stats: Option<HashMap<String, ParamFieldStats>>,
     pub timestamps: Vec<DateTime<Utc>>,
@@ -45,6 +60,9 @@ pub struct AnalysisResults {
     pub parameter_anomalies: Option<Vec<ParameterAnomaly>>,
     pub deep_temporal: Option<DeepTemporalOut>,
     pub deep_correlations: Option<Vec<DeepCorrelation>>,
+    /// Output of analyzers registered with `AnalyzerRegistry::with_analyzer`, keyed by analyzer
+    /// name; emitted as the pattern's `extensions`
+    pub extensions: BTreeMap<String, serde_json::Value>,
 }
 
 /// Trait that all analyzers must implement
@@ -61,24 +79,8 @@ pub trait AnalysisResult: Send + Sync {
 /// Parameter anomaly analyzer
 pub struct ParameterAnomalyAnalyzer;
 
-/// Helper function to get the base parameter type from potentially numbered parameters
-/// e.g., "NUM_2" -> "NUM", "IP_3" -> "IP", "NUM" -> "NUM"
-pub fn get_base_param_type(param_type: &str) -> &str {
-    if let Some(underscore_pos) = param_type.rfind('_') {
-        let suffix = &param_type[underscore_pos + 1..];
-        // Check if suffix is a number
-        if suffix.chars().all(|c| c.is_ascii_digit()) {
-            return &param_type[..underscore_pos];
-        }
-    }
-    param_type
-}
-
-/// Helper function to check if a parameter should be treated as high-cardinality numeric
-fn is_high_cardinality_numeric(param_type: &str, cardinality: usize, total: usize) -> bool {
-    let base_type = get_base_param_type(param_type);
-    base_type == "NS" || (base_type == "NUM" && cardinality as f64 / total as f64 > 0.9)
-}
+pub use crate::param_extractor::get_base_param_type;
+use crate::param_extractor::{is_high_cardinality_numeric, is_latency_param, is_status_param, is_time_param, latency_ms, ParamType};
 
 impl Analyzer for ParameterAnomalyAnalyzer {
     fn name(&self) -> &'static str {
@@ -97,9 +99,7 @@ impl Analyzer for ParameterAnomalyAnalyzer {
                 let top_ratio = stats.top_ratio;
                 
                 // Skip anomaly detection for time-based parameters (naturally unique)
-                let base_param_type = get_base_param_type(param_type);
-                let is_time_param = base_param_type == "TIME" || base_param_type == "TIMESTAMP" || 
-                                   base_param_type == "DATE" || base_param_type == "DATETIME";
+                let is_time = is_time_param(param_type);
                 
                 // Skip anomaly detection for high-cardinality numeric parameters (like nanoseconds)
                 let is_high_card_numeric = is_high_cardinality_numeric(param_type, cardinality, total);
@@ -141,7 +141,7 @@ impl Analyzer for ParameterAnomalyAnalyzer {
                 }
                 
                 // Skip anomaly detection for time-based or high-cardinality numeric parameters
-                if is_time_param || is_high_card_numeric {
+                if is_time || is_high_card_numeric {
                     continue;
                 }
                 
@@ -213,7 +213,7 @@ impl Analyzer for ParameterAnomalyAnalyzer {
                 }
                 
                 // Special alert for security-relevant parameters
-                if base_param_type == "IP" && cardinality == 1 && total >= 100 {
+                if ParamType::of(param_type) == Some(ParamType::Ip) && cardinality == 1 && total >= 100 {
                     param_anoms.push(ParameterAnomaly {
                         anomaly_type: "SECURITY_ALERT".to_string(),
                         param: param_type.clone(),
@@ -251,13 +251,14 @@ impl Analyzer for DeepTemporalAnalyzer {
         "deep_temporal"
     }
 
-    fn analyze(&self, context: &AnalysisContext, _opts: &crate::ai::SummarizeOpts) -> Box<dyn AnalysisResult> {
+    fn analyze(&self, context: &AnalysisContext, opts: &crate::ai::SummarizeOpts) -> Box<dyn AnalysisResult> {
         if !context.timestamps.is_empty() && context.timestamps.len() == context.line_params.len() {
-            let deep_temporal = crate::ai::compute_deep_temporal(
+            let deep_temporal = crate::ai::compute_deep_temporal_with_opts(
                 &context.timestamps, 
                 &context.clean_template, 
                 &context.line_params, 
-                &context.pattern_indices
+                &context.pattern_indices,
+                opts,
             );
             Box::new(DeepTemporalResult { analysis: Some(deep_temporal) })
         } else {
@@ -304,9 +305,205 @@ impl AnalysisResult for DeepCorrelationResult {
     }
 }
 
+/// Share of 5xx responses in a pattern at or above which `status_surge` is reported
+pub const STATUS_SURGE_5XX_RATIO: f64 = 0.05;
+/// The latest quarter of a pattern counts as a sharp rise when its 5xx share is at least
+/// this multiple of the earlier share (and above [`STATUS_SURGE_5XX_RATIO`])
+pub const STATUS_SURGE_RISE_FACTOR: f64 = 3.0;
+/// Patterns with fewer valid status codes than this are not checked
+pub const STATUS_SURGE_MIN_EVENTS: usize = 20;
+
+fn status_class(value: &str) -> Option<u16> {
+    value.trim().parse::<u16>().ok().filter(|c| (100..600).contains(c)).map(|c| c / 100)
+}
+
+fn share_5xx<'a>(values: impl Iterator<Item = &'a str>) -> (usize, f64) {
+    let (mut n, mut err) = (0usize, 0usize);
+    for class in values.filter_map(status_class) {
+        n += 1;
+        if class == 5 { err += 1; }
+    }
+    (n, if n == 0 { 0.0 } else { err as f64 / n as f64 })
+}
+
+/// HTTP status-class check for one status-code param: flags `status_surge` when 5xx
+/// responses reach [`STATUS_SURGE_5XX_RATIO`] of the pattern, or when the latest quarter of
+/// `ordered` (status values in arrival order, may be empty) rises sharply over the rest.
+/// The anomaly value is the most frequent failing status; details carry 4xx/5xx shares.
+pub fn detect_status_surge(param: &str, stats: &ParamFieldStats, ordered: &[&str]) -> Option<ParameterAnomaly> {
+    let mut total = 0usize;
+    let mut class_counts = [0usize; 6];
+    let mut failing: Option<(&str, usize)> = None;
+    for v in &stats.values {
+        let Some(class) = status_class(&v.value) else { continue };
+        total += v.count;
+        class_counts[class as usize] += v.count;
+        // values are sorted by count, so the first 5xx (else 4xx) is the dominant failure
+        let better = match failing {
+            None => class >= 4,
+            Some((cur, _)) => class == 5 && status_class(cur) == Some(4),
+        };
+        if better { failing = Some((v.value.as_str(), v.count)); }
+    }
+    if total < STATUS_SURGE_MIN_EVENTS { return None; }
+    let ratio_5xx = class_counts[5] as f64 / total as f64;
+    let ratio_4xx = class_counts[4] as f64 / total as f64;
+
+    let rise = if ordered.len() >= STATUS_SURGE_MIN_EVENTS {
+        let split = ordered.len() * 3 / 4;
+        let (_, before) = share_5xx(ordered[..split].iter().copied());
+        let (n_recent, recent) = share_5xx(ordered[split..].iter().copied());
+        (n_recent > 0 && recent >= STATUS_SURGE_5XX_RATIO && recent >= before * STATUS_SURGE_RISE_FACTOR && recent > before)
+            .then_some((before, recent))
+    } else {
+        None
+    };
+    if ratio_5xx < STATUS_SURGE_5XX_RATIO && rise.is_none() { return None; }
+
+    let (value, _) = failing?;
+    let pct = |r: f64| (r * 100.0).round() as i32;
+    let mut details = format!("5xx responses are {}% and 4xx {}% of {} '{}' values; most common failure is {}",
+        pct(ratio_5xx), pct(ratio_4xx), total, param, value);
+    if let Some((before, recent)) = rise {
+        details.push_str(&format!("; 5xx rose from {}% to {}% in the latest quarter", pct(before), pct(recent)));
+    }
+    Some(ParameterAnomaly {
+        anomaly_type: "status_surge".to_string(),
+        param: param.to_string(),
+        value: value.to_string(),
+        count: Some(class_counts[5]),
+        ratio: Some(ratio_5xx),
+        details,
+    })
+}
+
+/// HTTP status-class analyzer for access-log patterns
+pub struct StatusSurgeAnalyzer;
+
+impl Analyzer for StatusSurgeAnalyzer {
+    fn name(&self) -> &'static str {
+        "status_surge"
+    }
+
+    fn analyze(&self, context: &AnalysisContext, _opts: &crate::ai::SummarizeOpts) -> Box<dyn AnalysisResult> {
+        let mut anomalies = Vec::new();
+        for (param, stats) in context.param_stats.iter().flatten() {
+            if !is_status_param(param) { continue; }
+            let ordered: Vec<&str> = context.line_params.iter()
+                .filter_map(|p| p.get(param).and_then(|vs| vs.first()).map(|v| v.as_str()))
+                .collect();
+            anomalies.extend(detect_status_surge(param, stats, &ordered));
+        }
+        Box::new(StatusSurgeResult { anomalies })
+    }
+}
+
+pub struct StatusSurgeResult {
+    anomalies: Vec<ParameterAnomaly>,
+}
+
+impl AnalysisResult for StatusSurgeResult {
+    fn merge_into(self: Box<Self>, results: &mut AnalysisResults) {
+        if !self.anomalies.is_empty() {
+            results.parameter_anomalies.get_or_insert_with(Vec::new).extend(self.anomalies);
+        }
+    }
+}
+
+/// Latency buckets reported in `ParamFieldStats::latency_buckets`: label and exclusive upper
+/// bound in milliseconds. The last bucket is the "slow" one
+pub const LATENCY_BUCKETS: [(&str, f64); 4] = [("<10ms", 10.0), ("10-100ms", 100.0), ("100ms-1s", 1000.0), (">1s", f64::INFINITY)];
+/// Share of slow (>1s) requests in the latest quarter at or above which a rise is reported
+pub const SLOW_LATENCY_MIN_RATIO: f64 = 0.05;
+/// How many times the earlier slow share the latest quarter must reach
+pub const SLOW_LATENCY_RISE_FACTOR: f64 = 3.0;
+/// Minimum number of latency values before `slow_latency_rise` is considered
+pub const SLOW_LATENCY_MIN_EVENTS: usize = 20;
+
+fn latency_bucket(ms: f64) -> usize {
+    LATENCY_BUCKETS.iter().position(|(_, bound)| ms < *bound).unwrap_or(LATENCY_BUCKETS.len() - 1)
+}
+
+/// Bucket distribution of a latency param's values, all buckets in order (empty ones included).
+/// None unless the param is a latency param with at least one parseable value.
+pub fn latency_buckets(param: &str, stats: &ParamFieldStats) -> Option<Vec<ParamValueCount>> {
+    if !is_latency_param(param) { return None; }
+    let mut counts = [0usize; LATENCY_BUCKETS.len()];
+    for v in &stats.values {
+        if let Some(ms) = latency_ms(param, &v.value) {
+            counts[latency_bucket(ms)] += v.count;
+        }
+    }
+    if counts.iter().all(|&c| c == 0) { return None; }
+    Some(LATENCY_BUCKETS.iter().zip(counts).map(|((label, _), count)| ParamValueCount { value: label.to_string(), count }).collect())
+}
+
+/// Flags `slow_latency_rise` when the share of slow requests in the latest quarter of
+/// `ordered` (latency values in arrival order) reaches [`SLOW_LATENCY_MIN_RATIO`] and
+/// [`SLOW_LATENCY_RISE_FACTOR`] times the share before it
+pub fn detect_slow_latency_rise(param: &str, ordered: &[&str]) -> Option<ParameterAnomaly> {
+    let slow: Vec<bool> = ordered.iter()
+        .filter_map(|v| latency_ms(param, v))
+        .map(|ms| latency_bucket(ms) == LATENCY_BUCKETS.len() - 1)
+        .collect();
+    if slow.len() < SLOW_LATENCY_MIN_EVENTS { return None; }
+    let split = slow.len() * 3 / 4;
+    let share = |s: &[bool]| s.iter().filter(|&&x| x).count() as f64 / s.len() as f64;
+    let (before, recent) = (share(&slow[..split]), share(&slow[split..]));
+    if recent < SLOW_LATENCY_MIN_RATIO || recent < before * SLOW_LATENCY_RISE_FACTOR || recent <= before {
+        return None;
+    }
+    let recent_slow = slow[split..].iter().filter(|&&x| x).count();
+    let pct = |r: f64| (r * 100.0).round() as i32;
+    Some(ParameterAnomaly {
+        anomaly_type: "slow_latency_rise".to_string(),
+        param: param.to_string(),
+        value: LATENCY_BUCKETS[LATENCY_BUCKETS.len() - 1].0.to_string(),
+        count: Some(recent_slow),
+        ratio: Some(recent),
+        details: format!("Requests over 1s rose from {}% to {}% in the latest quarter of {} '{}' values",
+            pct(before), pct(recent), slow.len(), param),
+    })
+}
+
+/// Slow-request analyzer for latency params
+pub struct SlowLatencyAnalyzer;
+
+impl Analyzer for SlowLatencyAnalyzer {
+    fn name(&self) -> &'static str {
+        "slow_latency"
+    }
+
+    fn analyze(&self, context: &AnalysisContext, _opts: &crate::ai::SummarizeOpts) -> Box<dyn AnalysisResult> {
+        let mut anomalies = Vec::new();
+        for param in context.param_stats.iter().flat_map(|s| s.keys()) {
+            if !is_latency_param(param) { continue; }
+            let ordered: Vec<&str> = context.line_params.iter()
+                .filter_map(|p| p.get(param).and_then(|vs| vs.first()).map(|v| v.as_str()))
+                .collect();
+            anomalies.extend(detect_slow_latency_rise(param, &ordered));
+        }
+        Box::new(SlowLatencyResult { anomalies })
+    }
+}
+
+pub struct SlowLatencyResult {
+    anomalies: Vec<ParameterAnomaly>,
+}
+
+impl AnalysisResult for SlowLatencyResult {
+    fn merge_into(self: Box<Self>, results: &mut AnalysisResults) {
+        if !self.anomalies.is_empty() {
+            results.parameter_anomalies.get_or_insert_with(Vec::new).extend(self.anomalies);
+        }
+    }
+}
+
 /// Main analyzer registry that manages all analyzers
 pub struct AnalyzerRegistry {
     analyzers: Vec<Box<dyn Analyzer>>,
+    /// Analyzers added with `with_analyzer`, run after the built-in ones
+    registered: Vec<Box<dyn Analyzer>>,
 }
 
 impl Default for AnalyzerRegistry {
@@ -320,22 +517,48 @@ impl AnalyzerRegistry {
         Self {
             analyzers: vec![
                 Box::new(ParameterAnomalyAnalyzer),
+                Box::new(StatusSurgeAnalyzer),
+                Box::new(SlowLatencyAnalyzer),
                 Box::new(DeepTemporalAnalyzer),
                 Box::new(DeepCorrelationAnalyzer),
             ],
+            registered: Vec::new(),
         }
     }
 
+    /// Add a custom analyzer that runs on every pattern after the built-in ones. Pass the
+    /// registry to the pipeline through `SummarizeOpts::analyzers`; results usually go to
+    /// `AnalysisResults::extensions` or `parameter_anomalies`.
+    pub fn with_analyzer(mut self, analyzer: Box<dyn Analyzer>) -> Self {
+        self.registered.push(analyzer);
+        self
+    }
+
+    /// Whether any analyzers were added with `with_analyzer`
+    pub fn has_registered(&self) -> bool {
+        !self.registered.is_empty()
+    }
+
     pub fn analyze(&self, context: &AnalysisContext, opts: &crate::ai::SummarizeOpts) -> AnalysisResults {
         let mut results = AnalysisResults::default();
         
-        for analyzer in &self.analyzers {
+        for analyzer in self.analyzers.iter().chain(&self.registered) {
             let analysis_result = analyzer.analyze(context, opts);
             analysis_result.merge_into(&mut results);
         }
         
         results
     }
+
+    /// Run only the analyzers added with `with_analyzer` (the in-memory pipeline computes the
+    /// built-in analyses inline)
+    pub fn analyze_registered(&self, context: &AnalysisContext, opts: &crate::ai::SummarizeOpts) -> AnalysisResults {
+        let mut results = AnalysisResults::default();
+        for analyzer in &self.registered {
+            analyzer.analyze(context, opts).merge_into(&mut results);
+        }
+        results
+    }
     
     /// Unified pattern builder that both chunked and non-chunked modes can use
     pub fn build_pattern(
@@ -378,8 +601,7 @@ impl AnalyzerRegistry {
         final_context.clean_template = clean_template;
         
         // Run all analyzers
-        let registry = AnalyzerRegistry::new();
-        let analysis_results = registry.analyze(&final_context, opts);
+        let analysis_results = opts.analyzers.analyze(&final_context, opts);
         
         // Build correlations if we have times_by_template data
         let deep_correlations = if opts.deep {
@@ -392,11 +614,15 @@ impl AnalyzerRegistry {
             analysis_results.deep_correlations
         };
         
+        let (dominant_service, dominant_service_ratio) = crate::ai::dominant_source(&pattern_data.service_breakdown, pattern_data.total_count);
+        let (dominant_host, dominant_host_ratio) = crate::ai::dominant_source(&pattern_data.host_breakdown, pattern_data.total_count);
+
         crate::ai::PatternOut {
             template: pattern_data.template.clone(),  // Use original template with level suffix
             frequency: pattern_data.frequency,
             total_count: pattern_data.total_count,
             severity: pattern_data.severity,
+            severity_breakdown: pattern_data.severity_breakdown,
             start_time: pattern_data.start_time,
             end_time: pattern_data.end_time,
             spike_analysis: pattern_data.spike_analysis,
@@ -404,6 +630,13 @@ impl AnalyzerRegistry {
             examples: pattern_data.examples,
             correlations: pattern_data.correlations,
             pattern_stability: pattern_data.pattern_stability,
+            periodicity: pattern_data.periodicity,
+            dominant_service,
+            dominant_service_ratio,
+            dominant_host,
+            dominant_host_ratio,
+            by_file: pattern_data.file_breakdown,
+            category: pattern_data.category,
             sources: crate::ai::SourceBreakdown { 
                 by_service: pattern_data.service_breakdown, 
                 by_host: pattern_data.host_breakdown 
@@ -413,6 +646,7 @@ impl AnalyzerRegistry {
             parameter_anomalies: analysis_results.parameter_anomalies,
             deep_temporal: analysis_results.deep_temporal,
             deep_correlations,
+            extensions: analysis_results.extensions,
         }
     }
 }
\ No newline at end of file
@@ -4,8 +4,24 @@ use std::collections::{HashMap, HashSet};
 pub enum AnomalyKind {
     NewPattern,
     RarePattern,
+    DisappearedPattern,
 }
 
+impl AnomalyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyKind::NewPattern => "NewPattern",
+            AnomalyKind::RarePattern => "RarePattern",
+            AnomalyKind::DisappearedPattern => "DisappearedPattern",
+        }
+    }
+}
+
+/// Baseline frequency a pattern must have had to be reported when it disappears
+pub const DISAPPEARED_MIN_BASELINE_FREQ: f64 = 0.01;
+/// Current/baseline frequency ratio at or below which a pattern counts as disappeared
+pub const DISAPPEARED_DROP_RATIO: f64 = 0.1;
+
 #[derive(Debug, Clone)]
 pub struct PatternAnomaly {
     pub kind: AnomalyKind,
@@ -35,3 +51,161 @@ pub fn detect_pattern_anomalies(
     }
     out
 }
+
+/// Detect previously-frequent baseline patterns whose frequency dropped to (near) zero,
+/// e.g. a healthcheck that went silent. `frequency`/`count` report the current values.
+pub fn detect_disappeared_patterns(
+    counts: &HashMap<String, usize>,
+    total: usize,
+    baseline_frequencies: &HashMap<String, f64>,
+    min_baseline_freq: f64,
+    drop_ratio: f64,
+) -> Vec<PatternAnomaly> {
+    let mut out = Vec::new();
+    for (tpl, &baseline_freq) in baseline_frequencies.iter() {
+        if baseline_freq < min_baseline_freq { continue; }
+        let count = counts.get(tpl).copied().unwrap_or(0);
+        let freq = if total == 0 { 0.0 } else { (count as f64) / (total as f64) };
+        if freq <= baseline_freq * drop_ratio {
+            out.push(PatternAnomaly { kind: AnomalyKind::DisappearedPattern, template: tpl.clone(), frequency: freq, count });
+        }
+    }
+    out.sort_by(|a, b| a.template.cmp(&b.template));
+    out
+}
+
+/// Current/baseline change (either direction) at or above which a tracked ratio is reported
+pub const RATIO_SHIFT_FACTOR: f64 = 2.0;
+/// Numerator plus denominator lines needed in the current window before a ratio is judged
+pub const RATIO_MIN_EVENTS: usize = 10;
+
+/// Two pattern regexes whose count ratio is tracked, e.g. `login failed / login succeeded`
+#[derive(Debug, Clone)]
+pub struct RatioRule {
+    pub spec: String,
+    pub numerator: regex::Regex,
+    pub denominator: regex::Regex,
+}
+
+impl RatioRule {
+    /// Parse `NUMERATOR_REGEX / DENOMINATOR_REGEX`; the separator is a slash with spaces around it
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (num, den) = spec
+            .split_once(" / ")
+            .ok_or_else(|| format!("expected 'NUMERATOR_REGEX / DENOMINATOR_REGEX', got '{spec}'"))?;
+        let compile = |re: &str| {
+            let re = re.trim();
+            if re.is_empty() { return Err(format!("empty regex in '{spec}'")); }
+            regex::Regex::new(re).map_err(|e| format!("invalid regex '{re}' in '{spec}': {e}"))
+        };
+        Ok(Self { spec: spec.to_string(), numerator: compile(num)?, denominator: compile(den)? })
+    }
+
+    /// Summed weights of the templates matching the numerator and the denominator
+    pub fn tally<'a, I: IntoIterator<Item = (&'a str, f64)>>(&self, weights: I) -> (f64, f64) {
+        weights.into_iter().fold((0.0, 0.0), |(n, d), (tpl, w)| {
+            (
+                if self.numerator.is_match(tpl) { n + w } else { n },
+                if self.denominator.is_match(tpl) { d + w } else { d },
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RatioShift {
+    pub rule: String,
+    pub current_ratio: f64,
+    pub baseline_ratio: f64,
+    pub numerator_count: usize,
+    pub denominator_count: usize,
+}
+
+/// Report a ratio whose current value moved at least `factor` times away from the baseline
+/// ratio (or to/from zero). Skipped when either denominator is zero or the current window has
+/// fewer than `min_events` matching lines.
+pub fn detect_ratio_shift(
+    rule: &RatioRule,
+    current: (usize, usize),
+    baseline: (f64, f64),
+    factor: f64,
+    min_events: usize,
+) -> Option<RatioShift> {
+    let (num, den) = current;
+    if den == 0 || baseline.1 <= 0.0 || num + den < min_events { return None; }
+    let current_ratio = num as f64 / den as f64;
+    let baseline_ratio = baseline.0 / baseline.1;
+    let shifted = match (current_ratio > 0.0, baseline_ratio > 0.0) {
+        (true, true) => current_ratio / baseline_ratio >= factor || baseline_ratio / current_ratio >= factor,
+        (false, false) => false,
+        _ => true,
+    };
+    shifted.then(|| RatioShift {
+        rule: rule.spec.clone(),
+        current_ratio,
+        baseline_ratio,
+        numerator_count: num,
+        denominator_count: den,
+    })
+}
+
+// Severity scores: every anomaly kind gets a 0-1 `score` so consumers can rank and threshold
+// across kinds with one number. score = weight * (0.6 * deviation + 0.4 * volume), where
+// `deviation` (0-1) is how far the observation is from normal, `volume` (0-1) how many lines
+// it covers, and the kind weight reflects how actionable the kind usually is.
+
+/// `x / (x + half)`: maps an unbounded magnitude onto 0-1, reaching 0.5 at `half`
+fn saturate(x: f64, half: f64) -> f64 {
+    if x <= 0.0 { 0.0 } else { x / (x + half) }
+}
+
+fn score(weight: f64, deviation: f64, volume: f64) -> f64 {
+    (weight * (0.6 * deviation.clamp(0.0, 1.0) + 0.4 * volume.clamp(0.0, 1.0))).clamp(0.0, 1.0)
+}
+
+/// Share of all lines at which a pattern's volume scores 0.5
+const VOLUME_HALF_FREQUENCY: f64 = 0.01;
+/// Lines behind a field or burst anomaly at which its volume scores 0.5
+const VOLUME_HALF_LINES: f64 = 50.0;
+
+/// New patterns are fully unexpected; disappeared ones score by how far their frequency fell and
+/// how common they were in the baseline (`baseline_frequency`); rare ones by how few lines they
+/// have, with a low weight since most corpora have a long tail of them
+pub fn pattern_anomaly_score(a: &PatternAnomaly, baseline_frequency: Option<f64>) -> f64 {
+    match a.kind {
+        AnomalyKind::NewPattern => score(0.9, 1.0, saturate(a.frequency, VOLUME_HALF_FREQUENCY)),
+        AnomalyKind::DisappearedPattern => {
+            let baseline = baseline_frequency.unwrap_or(0.0);
+            let drop = if baseline > 0.0 { 1.0 - a.frequency / baseline } else { 1.0 };
+            score(0.8, drop, saturate(baseline, VOLUME_HALF_FREQUENCY))
+        }
+        AnomalyKind::RarePattern => score(0.4, 1.0 / a.count.max(1) as f64, 0.0),
+    }
+}
+
+/// Numeric outlier with robust z-score `z` (detected from |z| >= 3)
+pub fn numeric_outlier_score(z: f64) -> f64 {
+    score(0.6, saturate(z.abs(), 6.0), 0.0)
+}
+
+/// Field whose `unique` values over `total` lines exploded (`ratio` = unique / total)
+pub fn cardinality_explosion_score(ratio: f64, total: usize) -> f64 {
+    score(0.5, ratio, saturate(total as f64, VOLUME_HALF_LINES))
+}
+
+/// Categorical field with `new_values` never seen in the baseline, over `total` lines
+pub fn enum_drift_score(new_values: usize, total: usize) -> f64 {
+    score(0.7, saturate(new_values as f64, 1.0), saturate(total as f64, VOLUME_HALF_LINES))
+}
+
+/// Burst peaking at `multiple` times the quiet rate with `peak_rate` events in its busiest bucket
+pub fn burst_score(multiple: f64, peak_rate: usize) -> f64 {
+    score(0.7, saturate(multiple, 6.0), saturate(peak_rate as f64, VOLUME_HALF_LINES))
+}
+
+/// Ratio shift by the factor between current and baseline (to or from zero counts as maximal)
+pub fn ratio_shift_score(shift: &RatioShift) -> f64 {
+    let (c, b) = (shift.current_ratio, shift.baseline_ratio);
+    let deviation = if c > 0.0 && b > 0.0 { saturate((c / b).max(b / c).log2(), 2.0) } else { 1.0 };
+    score(0.7, deviation, saturate((shift.numerator_count + shift.denominator_count) as f64, VOLUME_HALF_LINES))
+}
@@ -1,6 +1,7 @@
 use clap::Parser;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use logoscope::multiline::MultiLineAggregator;
 use chrono::{DateTime, Utc, SecondsFormat};
 use regex::Regex;
@@ -32,20 +33,38 @@ Quick Start:
   kubectl logs -f deployment/api | logoscope --follow --triage"
 )]
 struct Cli {
-    /// Input files (`-` for stdin). May be repeated.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Input files or directories (`-` for stdin). May be repeated.
     #[arg(required = false)]
     input: Vec<String>,
 
-    /// Timestamp field hints for JSON logs (e.g., time, ts, timestamp)
+    /// Timestamp field hints for JSON logs (e.g., time, ts, meta.event.time); nested fields use dot-paths
     #[arg(long = "time-key")]
     time_key: Vec<String>,
 
-    /// Print only a specific section: patterns | logs | summary
+    /// Parser path for every line: json | text | auto (default: per-line JSON detection)
+    #[arg(long = "input-format", default_value = "auto", value_parser = logoscope::parser::InputFormat::parse)]
+    input_format: logoscope::parser::InputFormat,
+
+    /// Strip ANSI color/escape sequences before templating; `--strip-ansi=false` keeps them
+    #[arg(long = "strip-ansi", num_args = 0..=1, require_equals = true, default_value_t = true, default_missing_value = "true", action = clap::ArgAction::Set)]
+    strip_ansi: bool,
+
+    /// How stdin is split into records: lines (multiline-aggregated) | length-prefixed (decimal
+    /// byte count and a newline before each record) | null-delimited (records end with a NUL byte).
+    /// Framed records are taken whole, newlines included, without multiline heuristics
+    #[arg(long = "framing", default_value = "lines", value_parser = Framing::parse)]
+    framing: Framing,
+
+    /// Print only a specific section: patterns | logs | summary | anomalies (with suggested investigations)
     #[arg(long = "only")]
     only: Option<String>,
 
     // Pattern filters (when --only patterns)
     #[arg(long = "top")] top: Option<usize>,
+    /// Hide patterns seen fewer than N times; the full summary collapses them into `other_patterns`
     #[arg(long = "min-count")] min_count: Option<usize>,
     #[arg(long = "min-frequency")] min_frequency: Option<f64>,
     #[arg(long = "match")] match_re: Option<String>,
@@ -56,17 +75,28 @@ struct Cli {
     #[arg(long = "no-temporal", default_value_t = false)] no_temporal: bool,
     #[arg(long = "max-patterns")] max_patterns: Option<usize>,
     #[arg(long = "analyze-spikes", default_value_t = false)] analyze_spikes: bool,
+    /// Quoted phrases in free text: split (mask inside them as usual), keep (verbatim) or mask (one <QUOTED> param)
+    #[arg(long = "quoted", value_parser = logoscope::param_extractor::QuotedStrings::parse, default_value = "split")] quoted: logoscope::param_extractor::QuotedStrings,
+    /// Firehose mode: only count lines per template (no examples, params, anomalies or analyzers)
+    #[arg(long = "counts-only", default_value_t = false)] counts_only: bool,
     /// Verbose mode: reorder patterns by importance (errors > warnings > info > debug)
     #[arg(long = "verbose", short = 'v', default_value_t = false)] verbose: bool,
     /// Triage mode: show only critical patterns and anomalies for rapid problem identification
     #[arg(long = "triage", short = 't', default_value_t = false)] triage: bool,
+    /// Quiet mode: suppress all stderr diagnostics (timing, Drain effectiveness, stream status)
+    #[arg(long = "quiet", short = 'q', default_value_t = false)] quiet: bool,
     /// Deep investigation mode: maximum detail for thorough analysis (all patterns, 10 examples, full stats, temporal analysis)
     #[arg(long = "deep", short = 'd', default_value_t = false)] deep: bool,
+    /// With --deep: write the template correlations as a Graphviz DOT graph to FILE (edges point
+    /// from the leading to the following template)
+    #[arg(long = "correlation-graph", value_name = "FILE", requires = "deep")] correlation_graph: Option<std::path::PathBuf>,
 
     // Logs view flags (when --only logs)
     #[arg(long = "start")] start: Option<String>,
     #[arg(long = "end")] end: Option<String>,
     #[arg(long = "pattern")] pattern: Option<String>,
+    /// Print the timeline of one distributed trace (templates in order) by trace ID
+    #[arg(long = "trace")] trace: Option<String>,
     #[arg(long = "before", short = 'B', default_value_t = 0)] before: usize,
     #[arg(long = "after", short = 'A', default_value_t = 0)] after: usize,
 
@@ -80,11 +110,28 @@ struct Cli {
     #[arg(long = "max-lines", default_value_t = 10000)] max_lines: usize,
     /// Fail fast on parse errors
     #[arg(long = "fail-fast", default_value_t = false)] fail_fast: bool,
+    /// With --follow: emit only the JSONL deltas of new or changed patterns, without the full
+    /// summary after each interval
+    #[arg(long = "deltas-only", default_value_t = false, requires = "follow")] deltas_only: bool,
+    /// Run the analysis but print only per-stage timings as JSON (no summary output, no stderr)
+    #[arg(long = "bench", default_value_t = false)] bench: bool,
+    /// Re-summarize the input files whenever they change and print each updated summary
+    #[arg(long = "watch", default_value_t = false)] watch: bool,
+    /// Exit with status 2 when the input is empty or every line is malformed
+    #[arg(long = "error-on-empty", default_value_t = false)] error_on_empty: bool,
+    /// Exit with status 3 when the triage result meets a condition: error | critical | anomaly (comma-separated)
+    #[arg(long = "fail-on", value_delimiter = ',', value_parser = logoscope::ai::FailOn::parse)] fail_on: Vec<logoscope::ai::FailOn>,
 
-    /// Patterns output format: json | table (when --only patterns)
+    /// Output format: json | markdown (full summary) | table (when --only patterns)
     #[arg(long = "format", default_value = "json")] format: String,
-    /// Group patterns by: none | service | level (when --only patterns)
+    /// Print JSON output on a single line instead of pretty-printed (smaller and faster to pipe)
+    #[arg(long = "compact", default_value_t = false)] compact: bool,
+    /// Group patterns by: none | service | level | category (access/app/json) | file (when --only patterns)
     #[arg(long = "group-by", default_value = "none")] group_by: String,
+    /// Count column style in table output: plain | thousands (1,234,567) | si (1.2M); JSON stays numeric
+    #[arg(long = "count-format", default_value = "plain", value_parser = ["plain", "thousands", "si"])] count_format: String,
+    /// Disable severity colors in table output (also honors NO_COLOR; off when stdout is not a TTY)
+    #[arg(long = "no-color", default_value_t = false)] no_color: bool,
     /// Sort patterns by: count | freq | bursts | confidence (desc)
     #[arg(long = "sort", default_value = "count")] sort_by: String,
     
@@ -94,46 +141,568 @@ struct Cli {
     #[arg(long = "no-chunked", action = clap::ArgAction::SetTrue)] no_chunked: bool,
     /// Chunk size in MB for chunked processing
     #[arg(long = "chunk-size", default_value_t = 16)] chunk_size_mb: usize,
+    /// Bounded memory: process in chunks and keep only the K most frequent patterns, evicting rare
+    /// ones (their counts become approximate; see summary.evicted_patterns). Not available with
+    /// --follow, whose window is already capped by --max-lines
+    #[arg(long = "max-tracked-patterns", conflicts_with = "follow")] max_tracked_patterns: Option<usize>,
+    /// Memory budget in MB: analyze in memory while the input read so far fits, then switch to
+    /// chunked streaming aggregation (useful for stdin of unknown size)
+    #[arg(long = "limit-memory", value_name = "MB", value_parser = clap::value_parser!(u64).range(1..))] limit_memory_mb: Option<u64>,
+    /// In chunked mode (--max-tracked-patterns, or past the --limit-memory budget), report lines and chunks processed to stderr
+    /// every few seconds. Ignored with --quiet
+    #[arg(long = "progress", default_value_t = false)] progress: bool,
+    /// Cardinality-explosion override per field glob: GLOB=RATIO or GLOB=off (e.g. 'session*=off'). May be repeated.
+    #[arg(long = "cardinality-rule", value_parser = logoscope::field_anomaly::CardinalityRule::parse)] cardinality_rules: Vec<logoscope::field_anomaly::CardinalityRule>,
+    /// Descend into subdirectories when an input is a directory
+    #[arg(long = "recursive", short = 'r', default_value_t = false)] recursive: bool,
+    /// Reorder input files oldest first before reading: `time` by each file's first timestamp,
+    /// `rotation` by logrotate suffix (app.log.2, app.log.1, app.log)
+    #[arg(long = "order-files", value_parser = ["time", "rotation"])] order_files: Option<String>,
+    /// Drop lines before this RFC3339 time during ingestion (counts and anomalies reflect only the window)
+    #[arg(long = "since", value_parser = parse_rfc3339)] since: Option<DateTime<Utc>>,
+    /// Drop lines after this RFC3339 time during ingestion
+    #[arg(long = "until", value_parser = parse_rfc3339)] until: Option<DateTime<Utc>>,
+    /// With --since/--until, also drop lines without a parseable timestamp (kept by default)
+    #[arg(long = "drop-untimed", default_value_t = false)] drop_untimed: bool,
+    /// Drop a line repeating the previous line (ignoring timestamps) within this window, e.g. 50ms or 1s;
+    /// reported as summary.deduped_lines. Off by default; not applied with --follow
+    #[arg(long = "dedup-window", value_parser = parse_dedup_window)] dedup_window: Option<chrono::Duration>,
+    /// Analyze only the last N records (after multiline aggregation and the filters above),
+    /// buffering about 2N per input while reading
+    #[arg(long = "tail", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))] tail: Option<u64>,
+    /// Mask sensitive values in examples and param stats; optionally pick types, e.g. --redact=email,ip
+    #[arg(long = "redact", num_args = 0..=1, require_equals = true, value_delimiter = ',', value_parser = logoscope::param_extractor::ParamType::parse)] redact: Option<Vec<logoscope::param_extractor::ParamType>>,
+    /// Truncate extracted param values longer than N chars (0 = unlimited)
+    #[arg(long = "max-value-length", default_value_t = logoscope::param_extractor::DEFAULT_MAX_VALUE_LENGTH)] max_value_length: usize,
+    /// Keep only the N most frequent values per param in param_stats (cardinality and total are unchanged)
+    #[arg(long = "top-params")] top_params: Option<usize>,
+    /// Decode base64 blobs that contain JSON and report their fields as B64_JSON_* params
+    #[arg(long = "decode-b64-json", default_value_t = false)] decode_b64_json: bool,
+    /// Split URL query strings into QUERY_<key> params (e.g. QUERY_action for ?action=delete); the full URL is kept too
+    #[arg(long = "query-params", default_value_t = false)] query_params: bool,
+    /// Minimum events a burst must contain to be reported (suppresses trivial spikes in sparse patterns)
+    #[arg(long = "burst-min-events", default_value_t = logoscope::temporal::DEFAULT_BURST_MIN_EVENTS)] burst_min_events: usize,
+    /// Bucket width bursts and spikes are measured in, e.g. 500ms, 10s, 1m (default) or 1h
+    #[arg(long = "burst-window", value_parser = parse_burst_window)] burst_window: Option<chrono::Duration>,
+    /// Summarize each fixed time window separately, e.g. 15m, 1h or 1d; prints per-window
+    /// pattern counts (with new/gone template churn) instead of the full summary
+    #[arg(long = "window-by", value_parser = parse_window_by)] window_by: Option<chrono::Duration>,
+    /// Write the numeric params of each free-text pattern as time series: one CSV per pattern
+    /// (`timestamp,NUM,NUM_2,...`) plus index.csv mapping files to templates, into DIR
+    #[arg(long = "metrics-csv", value_name = "DIR")] metrics_csv: Option<std::path::PathBuf>,
+    /// Report time-correlated patterns on each pattern (lightweight; no --deep needed). In chunked
+    /// mode co-occurrence is counted per time bucket (at most a minute wide) instead of per line
+    #[arg(long = "correlate", default_value_t = false)] correlate: bool,
+    /// Cluster messages regardless of level: one pattern per template with a severity_breakdown
+    #[arg(long = "merge-levels", default_value_t = false)] merge_levels: bool,
+    /// Include each pattern's raw Drain template (`drain_template`) to compare against the humanized one
+    #[arg(long = "show-drain-template", default_value_t = false)] show_drain_template: bool,
+    /// Normalize literals in SQL statements (numbers, strings, IN-lists) so queries cluster by shape
+    #[arg(long = "mask-sql", default_value_t = false)] mask_sql: bool,
+    /// Mask random-looking tokens (session keys, nonces) the built-in maskers miss as <RANDOM>:
+    /// 16+ characters mixing letters and digits with Shannon entropy >= BITS per character
+    /// (default 3.5). Opt-in since it can catch unusual identifiers
+    #[arg(long = "mask-random", value_name = "BITS", num_args = 0..=1, require_equals = true)] mask_random: Option<Option<f64>>,
+    /// Word to keep literal in templates even where it would become a param (e.g. GET,POST,SUCCESS,FAILED).
+    /// Comma-separated or repeated
+    #[arg(long = "keep-literal", value_delimiter = ',')] keep_literals: Vec<String>,
+    /// Regex whose named groups become params, masked before the built-in maskers, e.g.
+    /// '^\[(?P<ts>[^\]]+)\] (?P<component>\w+):' yields '[<TS>] <COMPONENT>:'. May be repeated; applied in order
+    #[arg(long = "mask-rule", value_parser = logoscope::param_extractor::MaskRule::parse)] mask_rules: Vec<logoscope::param_extractor::MaskRule>,
+    /// Known-good log file whose templates form the baseline: patterns absent from it are
+    /// reported as NewPattern anomalies in the main input (in every --follow window)
+    #[arg(long = "baseline-from", value_name = "FILE")] baseline_from: Option<String>,
+    /// Attach up to a few raw source lines (`evidence`) to each field anomaly
+    #[arg(long = "explain-anomaly", default_value_t = false)] explain_anomaly: bool,
+    /// Track a pattern-pair ratio, 'NUMERATOR_REGEX / DENOMINATOR_REGEX', and report it in
+    /// anomalies.ratio_anomalies when it shifts 2x vs baseline (the earlier lines of the input). May be repeated.
+    #[arg(long = "ratio", value_parser = logoscope::anomaly::RatioRule::parse)] ratio_rules: Vec<logoscope::anomaly::RatioRule>,
+    /// Track only these structured fields as params (exact name or `*` glob). May be repeated.
+    #[arg(long = "track-field")] track_fields: Vec<String>,
+    /// Never track these structured fields as params (exact name or `*` glob). May be repeated.
+    #[arg(long = "ignore-field")] ignore_fields: Vec<String>,
+    /// Normalize JSON field names to snake_case (userId, UserID and user_id merge into user_id)
+    #[arg(long = "normalize-fields", default_value_t = false)] normalize_fields: bool,
+    /// Track a nested JSON field under a friendly alias, listed first in templates:
+    /// PATH=ALIAS (e.g. payload.data.attributes.status=status). May be repeated.
+    #[arg(long = "extract", value_parser = logoscope::param_extractor::ExtractRule::parse)] extract_rules: Vec<logoscope::param_extractor::ExtractRule>,
+    /// Group similar templates into a `pattern_clusters` section; optional token similarity 0..1 (default 0.6)
+    #[arg(long = "pattern-clusters", num_args = 0..=1, require_equals = true, default_missing_value = "0.6")] pattern_clusters: Option<f64>,
+    /// Append this run's pattern counts to a persistent store directory (see `logoscope trends`)
+    #[arg(long = "pattern-store")] pattern_store: Option<std::path::PathBuf>,
+
+    /// Drain parse-tree depth
+    #[arg(long = "drain-depth", default_value_t = logoscope::drain_adapter::DEFAULT_MAX_DEPTH, value_parser = clap::value_parser!(u16).range(1..))] drain_depth: u16,
+    /// Drain minimum similarity (0..1); raise it to split over-merged free-text messages
+    #[arg(long = "drain-similarity", default_value_t = logoscope::drain_adapter::DEFAULT_MIN_SIMILARITY, value_parser = parse_drain_similarity)] drain_similarity: f32,
+    /// Drain maximum children per tree node
+    #[arg(long = "drain-max-children", default_value_t = logoscope::drain_adapter::DEFAULT_MAX_CHILDREN, value_parser = clap::value_parser!(u16).range(1..))] drain_max_children: u16,
+}
+
+fn parse_drain_similarity(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(v) if (0.0..=1.0).contains(&v) => Ok(v),
+        _ => Err(format!("drain similarity '{s}' must be a number between 0 and 1")),
+    }
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|e| format!("invalid RFC3339 time '{s}': {e}"))
+}
+
+/// Ingestion-time window filter for --since/--until
+pub struct TimeFilter {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    drop_untimed: bool,
+    time_keys: Vec<String>,
+    parse_opts: logoscope::parser::ParseOpts,
+}
+
+impl TimeFilter {
+    fn from_cli(cli: &Cli) -> Option<Self> {
+        if cli.since.is_none() && cli.until.is_none() { return None; }
+        Some(Self { since: cli.since, until: cli.until, drop_untimed: cli.drop_untimed,
+            time_keys: cli.time_key.clone(),
+            parse_opts: cli_parse_opts(cli) })
+    }
+
+    /// Whether an aggregated record falls inside the window
+    fn keep(&self, entry: &str) -> bool {
+        let keys: Vec<&str> = self.time_keys.iter().map(|s| s.as_str()).collect();
+        match logoscope::parser::parse_line_as(entry, 0, &keys, self.parse_opts).timestamp {
+            Some(ts) => self.since.is_none_or(|s| ts >= s) && self.until.is_none_or(|u| ts <= u),
+            None => !self.drop_untimed,
+        }
+    }
+}
+
+fn cli_parse_opts(cli: &Cli) -> logoscope::parser::ParseOpts {
+    logoscope::parser::ParseOpts { input_format: cli.input_format, strip_ansi: cli.strip_ansi }
+}
+
+/// How inputs are read before summarization: stdin framing, the --since/--until window and the
+/// --dedup-window/--tail reductions
+struct IngestOpts {
+    framing: Framing,
+    filter: Option<TimeFilter>,
+    dedup_window: Option<chrono::Duration>,
+    tail: Option<usize>,
+    time_keys: Vec<String>,
+    parse_opts: logoscope::parser::ParseOpts,
+}
+
+impl IngestOpts {
+    fn from_cli(cli: &Cli) -> Self {
+        Self { framing: cli.framing, filter: TimeFilter::from_cli(cli), dedup_window: cli.dedup_window,
+            tail: cli.tail.map(|n| n as usize),
+            time_keys: cli.time_key.clone(),
+            parse_opts: cli_parse_opts(cli) }
+    }
+
+    /// Whether an aggregated record falls inside the --since/--until window
+    fn in_window(&self, entry: &str) -> bool {
+        self.filter.as_ref().is_none_or(|f| f.keep(entry))
+    }
+
+    /// Fresh per-source duplicate filter when --dedup-window is set
+    fn dedup(&self) -> Option<logoscope::parser::ConsecutiveDedup> {
+        self.dedup_window.map(|w| logoscope::parser::ConsecutiveDedup::new(w, &self.time_keys, self.parse_opts))
+    }
+
+    /// With --tail, drop all but the last N records once `records` holds 2N (amortized ring
+    /// buffer); `finish` trims exactly to N
+    fn trim_tail(&self, records: &mut Vec<String>, finish: bool) {
+        if let Some(n) = self.tail {
+            if records.len() > n && (finish || records.len() >= 2 * n) {
+                records.drain(..records.len() - n);
+            }
+        }
+    }
+
+    /// `trim_tail` over several inputs in order: the last N records overall survive
+    fn trim_tail_files(&self, files: &mut [(String, Vec<String>)]) {
+        if let Some(mut keep) = self.tail {
+            for (_, records) in files.iter_mut().rev() {
+                let kept = keep.min(records.len());
+                records.drain(..records.len() - kept);
+                keep -= kept;
+            }
+        }
+    }
+}
+
+/// Parse a --dedup-window such as `50ms`, `2s` or `1m` (bare numbers are milliseconds)
+fn parse_dedup_window(s: &str) -> Result<chrono::Duration, String> {
+    parse_window(s, "dedup window")
+}
+
+fn parse_burst_window(s: &str) -> Result<chrono::Duration, String> {
+    let w = parse_window(s, "burst window")?;
+    if w <= chrono::Duration::zero() {
+        return Err(format!("burst window '{s}' must be positive"));
+    }
+    Ok(w)
+}
+
+fn parse_window_by(s: &str) -> Result<chrono::Duration, String> {
+    let w = parse_window(s, "window")?;
+    if w <= chrono::Duration::zero() {
+        return Err(format!("window '{s}' must be positive"));
+    }
+    Ok(w)
+}
+
+/// `<N><unit>` with unit ms, s, m, h or d; a bare number is milliseconds
+fn parse_window(s: &str, what: &str) -> Result<chrono::Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: i64 = num.parse().map_err(|_| format!("invalid {what} '{s}' (expected e.g. 50ms, 2s, 1m, 1h)"))?;
+    let duration = match unit {
+        "" | "ms" => chrono::Duration::try_milliseconds(n),
+        "s" => chrono::Duration::try_seconds(n),
+        "m" => chrono::Duration::try_minutes(n),
+        "h" => chrono::Duration::try_hours(n),
+        "d" => chrono::Duration::try_days(n),
+        _ => return Err(format!("unknown unit '{unit}' in {what} '{s}' (use ms, s, m, h or d)")),
+    };
+    duration.ok_or_else(|| format!("{what} '{s}' is out of range"))
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Report pattern frequency changes between the last two windows of a pattern store
+    Trends {
+        /// Store directory written by --pattern-store
+        #[arg(long = "pattern-store")] pattern_store: std::path::PathBuf,
+        /// Window length in days (7 = week-over-week)
+        #[arg(long = "days", default_value_t = 7)] days: i64,
+        /// Show only the N largest movers
+        #[arg(long = "top")] top: Option<usize>,
+    },
+    /// Write each stdin line's canonical (masked) template to stdout, line for line
+    Mask {
+        /// Emit one JSON object per line with the template and its extracted params
+        #[arg(long = "with-params", default_value_t = false)] with_params: bool,
+    },
+    /// Show why two lines share a pattern or not: masked forms, templates and differing tokens
+    Explain {
+        line_a: String,
+        line_b: String,
+    },
+}
+
+/// Types to mask for --redact; a bare flag selects the default sensitive set
+fn redact_types(cli: &Cli) -> Option<Vec<logoscope::param_extractor::ParamType>> {
+    cli.redact.as_ref().map(|types| {
+        if types.is_empty() { logoscope::param_extractor::DEFAULT_REDACT_TYPES.to_vec() } else { types.clone() }
+    })
+}
+
+fn field_filter(cli: &Cli) -> logoscope::param_extractor::FieldFilter {
+    logoscope::param_extractor::FieldFilter {
+        track: cli.track_fields.clone(),
+        ignore: cli.ignore_fields.clone(),
+        extract: cli.extract_rules.clone(),
+        normalize_names: cli.normalize_fields,
+    }
+}
+
+/// Counters gathered while reading inputs, reported alongside the summary
+#[derive(Debug, Default, Clone, Copy)]
+struct ReadStats {
+    /// Records that needed lossy UTF-8 decoding
+    invalid_utf8_lines: usize,
+    /// Records dropped by --dedup-window
+    deduped_lines: usize,
+}
+
+impl std::ops::AddAssign for ReadStats {
+    fn add_assign(&mut self, other: ReadStats) {
+        self.invalid_utf8_lines += other.invalid_utf8_lines;
+        self.deduped_lines += other.deduped_lines;
+    }
+}
+
+impl ReadStats {
+    fn apply_to(self, out: &mut logoscope::ai::AiOutput) {
+        out.errors.invalid_utf8_lines = self.invalid_utf8_lines;
+        out.summary.deduped_lines = self.deduped_lines;
+    }
+}
+
+/// Record boundaries of an input (`--framing`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Framing {
+    /// One record per line; continuation lines are joined by the multiline aggregator
+    #[default]
+    Lines,
+    /// Each record follows a line holding its length in bytes
+    LengthPrefixed,
+    /// Each record ends with a NUL byte
+    NullDelimited,
+}
+
+impl Framing {
+    fn parse(name: &str) -> Result<Framing, String> {
+        match name {
+            "lines" => Ok(Framing::Lines),
+            "length-prefixed" => Ok(Framing::LengthPrefixed),
+            "null-delimited" => Ok(Framing::NullDelimited),
+            _ => Err(format!("unknown framing '{name}' (expected lines, length-prefixed or null-delimited)")),
+        }
+    }
+
+    /// Multiline heuristics only apply when the producer did not mark record boundaries
+    fn aggregator(self) -> Option<MultiLineAggregator> {
+        (self == Framing::Lines).then(MultiLineAggregator::default)
+    }
+}
+
+/// Largest record `--framing length-prefixed` accepts; bigger headers are treated as corrupt input
+const MAX_FRAMED_RECORD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Like `BufRead::lines`, but decodes invalid UTF-8 lossily instead of failing the read.
+/// Each replaced record is counted in `invalid_utf8_lines`. Other framings yield whole records.
+struct LossyLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    framing: Framing,
+    invalid_utf8_lines: usize,
+}
+
+fn lossy_lines<R: BufRead>(reader: R) -> LossyLines<R> {
+    lossy_records(reader, Framing::Lines)
+}
+
+fn lossy_records<R: BufRead>(reader: R, framing: Framing) -> LossyLines<R> {
+    LossyLines { reader, buf: Vec::new(), framing, invalid_utf8_lines: 0 }
+}
+
+impl<R: BufRead> LossyLines<R> {
+    /// Fill `buf` with the next record; false at end of input
+    fn read_record(&mut self) -> io::Result<bool> {
+        match self.framing {
+            Framing::Lines => {
+                if self.reader.read_until(b'\n', &mut self.buf)? == 0 { return Ok(false); }
+                if self.buf.ends_with(b"\n") { self.buf.pop(); }
+                if self.buf.ends_with(b"\r") { self.buf.pop(); }
+            }
+            Framing::NullDelimited => loop {
+                if self.reader.read_until(0, &mut self.buf)? == 0 { return Ok(false); }
+                if self.buf.ends_with(&[0]) { self.buf.pop(); }
+                // Tolerate a newline after each NUL (and a trailing one at end of input)
+                if self.buf.first() == Some(&b'\n') { self.buf.remove(0); }
+                if !self.buf.is_empty() { break; }
+            },
+            Framing::LengthPrefixed => {
+                let mut header = String::new();
+                while header.trim().is_empty() {
+                    header.clear();
+                    if self.reader.read_line(&mut header)? == 0 { return Ok(false); }
+                }
+                let len: u64 = header.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData,
+                    format!("--framing length-prefixed: expected a byte count, got {:?}", header.trim())))?;
+                if len > MAX_FRAMED_RECORD_BYTES {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+                        "--framing length-prefixed: record length {len} exceeds the {MAX_FRAMED_RECORD_BYTES}-byte limit")));
+                }
+                // Grow the buffer as bytes arrive rather than trusting the header up front
+                if (&mut self.reader).take(len).read_to_end(&mut self.buf)? as u64 != len {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!(
+                        "--framing length-prefixed: input ended inside a {len}-byte record")));
+                }
+            }
+        }
+        Ok(true)
+    }
 }
 
-fn read_all_lines(paths: &[String]) -> io::Result<Vec<String>> {
+impl<R: BufRead> Iterator for LossyLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.clear();
+        match self.read_record() {
+            Ok(false) => None,
+            Ok(true) => {
+                Some(Ok(match String::from_utf8_lossy(&self.buf) {
+                    std::borrow::Cow::Borrowed(s) => s.to_owned(),
+                    std::borrow::Cow::Owned(s) => {
+                        self.invalid_utf8_lines += 1;
+                        s
+                    }
+                }))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Read one source fully, flushing its multiline aggregator at end of file
+fn read_source_lines<R: BufRead>(reader: R, framing: Framing, ingest: &IngestOpts) -> io::Result<(Vec<String>, ReadStats)> {
     let mut out = Vec::new();
-    let mut agg = MultiLineAggregator::default();
-    
-    for p in paths {
-        if p == "-" {
-            let stdin = io::stdin();
-            let reader = stdin.lock();
-            for line in reader.lines() {
-                let l = line?;
-                if let Some(e) = agg.push(&l) { out.push(e); }
+    let mut agg = framing.aggregator();
+    let mut dedup = ingest.dedup();
+    let mut emit = |e: String| {
+        if ingest.in_window(&e) && dedup.as_mut().is_none_or(|d| d.keep(&e)) {
+            out.push(e);
+            ingest.trim_tail(&mut out, false);
+        }
+    };
+    let mut records = lossy_records(reader, framing);
+    for line in records.by_ref() {
+        let l = line?;
+        match agg.as_mut() {
+            Some(agg) => if let Some(e) = agg.push(&l) { emit(e); },
+            None => emit(l),
+        }
+    }
+    if let Some(e) = agg.and_then(|mut agg| agg.finish()) { emit(e); }
+    ingest.trim_tail(&mut out, true);
+    let deduped_lines = dedup.map_or(0, |d| d.dropped);
+    Ok((out, ReadStats { invalid_utf8_lines: records.invalid_utf8_lines, deduped_lines }))
+}
+
+fn read_all_lines(paths: &[String], ingest: &IngestOpts) -> io::Result<(Vec<String>, ReadStats)> {
+    let (files, stats) = read_lines_by_file(paths, ingest)?;
+    Ok((files.into_iter().flat_map(|(_, lines)| lines).collect(), stats))
+}
+
+/// One input's records, tagged with its path (`-` for stdin)
+type SourceLines = (String, Vec<String>);
+
+/// `read_all_lines` keeping each input's records apart, tagged with its path (`-` for stdin).
+/// The ingest framing applies to stdin; files are always read line by line.
+fn read_lines_by_file(paths: &[String], ingest: &IngestOpts) -> io::Result<(Vec<SourceLines>, ReadStats)> {
+    use rayon::prelude::*;
+
+    // Files are read in parallel (each with its own multiline aggregator);
+    // stdin is read on the calling thread. Output keeps the input order.
+    let per_source: Vec<io::Result<(Vec<String>, ReadStats)>> = paths
+        .par_iter()
+        .map(|p| {
+            if p == "-" {
+                Ok(Default::default())
+            } else {
+                read_source_lines(BufReader::new(File::open(p)?), Framing::Lines, ingest)
             }
-        } else {
-            let f = File::open(p)?;
-            let r = BufReader::new(f);
-            for line in r.lines() {
-                let l = line?;
-                if let Some(e) = agg.push(&l) { out.push(e); }
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    let mut stats = ReadStats::default();
+    for (p, source) in paths.iter().zip(per_source) {
+        let (lines, source_stats) = if p == "-" { read_source_lines(io::stdin().lock(), ingest.framing, ingest)? } else { source? };
+        stats += source_stats;
+        out.push((p.clone(), lines));
+    }
+    ingest.trim_tail_files(&mut out);
+    Ok((out, stats))
+}
+
+/// Per-pattern `by_file` breakdowns are only kept when several inputs are read together
+fn tags_files(input_files: &[String]) -> bool {
+    input_files.len() > 1
+}
+
+/// Expand directory inputs into their files, sorted by name.
+/// Subdirectories are only descended into when `recursive` is set.
+fn expand_inputs(paths: &[String], recursive: bool) -> io::Result<Vec<String>> {
+    fn collect_dir(dir: &std::path::Path, recursive: bool, out: &mut Vec<String>) -> io::Result<()> {
+        let mut entries: Vec<std::path::PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .collect();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                if recursive { collect_dir(&path, recursive, out)?; }
+            } else if path.is_file() {
+                out.push(path.to_string_lossy().into_owned());
             }
         }
+        Ok(())
+    }
+
+    let mut out = Vec::new();
+    for p in paths {
+        let path = std::path::Path::new(p);
+        if p != "-" && path.is_dir() {
+            collect_dir(path, recursive, &mut out)?;
+        } else {
+            out.push(p.clone());
+        }
     }
-    if let Some(e) = agg.finish() { out.push(e); }
     Ok(out)
 }
 
+/// Lines scanned per file for its first timestamp (`--order-files time`)
+const FIRST_TIMESTAMP_SCAN_LINES: usize = 1000;
+
+/// Reorder inputs oldest first. `time` sorts by each file's first parseable timestamp (files
+/// without one, and stdin, go last); `rotation` keeps each log's files together and puts higher
+/// rotation numbers first, ignoring a compression suffix (app.log.2.gz, app.log.1, app.log).
+/// The sort is stable, so ties keep the given order.
+fn order_inputs(mut paths: Vec<String>, order: &str) -> Vec<String> {
+    fn first_timestamp(path: &str) -> Option<DateTime<Utc>> {
+        if path == "-" { return None; }
+        let file = File::open(path).ok()?;
+        lossy_lines(BufReader::new(file))
+            .take(FIRST_TIMESTAMP_SCAN_LINES)
+            .map_while(Result::ok)
+            .find_map(|l| logoscope::parser::parse_line(&l, 1).timestamp)
+    }
+    fn rotation_key(path: &str) -> (String, std::cmp::Reverse<u32>) {
+        let name = path.strip_suffix(".gz").or_else(|| path.strip_suffix(".bz2"))
+            .or_else(|| path.strip_suffix(".xz")).or_else(|| path.strip_suffix(".zst"))
+            .unwrap_or(path);
+        match name.rsplit_once('.').and_then(|(base, n)| n.parse::<u32>().ok().map(|n| (base, n))) {
+            Some((base, n)) => (base.to_string(), std::cmp::Reverse(n)),
+            None => (name.to_string(), std::cmp::Reverse(0)),
+        }
+    }
+
+    if order == "time" {
+        let mut keyed: Vec<(Option<DateTime<Utc>>, String)> = paths.into_iter().map(|p| (first_timestamp(&p), p)).collect();
+        keyed.sort_by_key(|(ts, _)| (ts.is_none(), *ts));
+        keyed.into_iter().map(|(_, p)| p).collect()
+    } else {
+        paths.sort_by_cached_key(|p| rotation_key(p));
+        paths
+    }
+}
+
 /// Stream lines in bounded chunks while preserving multiline aggregation.
 /// - `target_bytes`: flush when approx this many bytes are accumulated
 /// - `max_lines`:    additionally cap per-chunk by line count
-/// Calls `on_chunk` with a Vec<String> (owned aggregated records).
-pub fn stream_lines_in_chunks<F>(
+/// - `ingest`:       stdin framing (files are read line by line), time window and reductions
+///
+/// Calls `on_chunk` with the input path (`-` for stdin) and a Vec<String> (owned aggregated
+/// records); a chunk never spans two inputs. Returns the counters gathered while reading.
+fn stream_lines_in_chunks<F>(
     paths: &[String],
     target_bytes: usize,
     max_lines: usize,
+    ingest: &IngestOpts,
     mut on_chunk: F,
-) -> io::Result<()>
+) -> io::Result<ReadStats>
 where
-    F: FnMut(Vec<String>),
+    F: FnMut(&str, Vec<String>),
 {
+    // The last N records are only known at the end of the input; at most N are held per input
+    if ingest.tail.is_some() {
+        let (files, stats) = read_lines_by_file(paths, ingest)?;
+        for (path, lines) in files {
+            for chunk in lines.chunks(max_lines.max(1)) {
+                on_chunk(&path, chunk.to_vec());
+            }
+        }
+        return Ok(stats);
+    }
+
     let mut buf: Vec<String> = Vec::with_capacity(max_lines.max(1024));
     let mut buf_bytes: usize = 0;
 
@@ -152,18 +721,26 @@ where
     }
 
     // Helper reading a single "source" (stdin or file path)
+    #[allow(clippy::too_many_arguments)]
     fn read_source<R: std::io::BufRead, Fw: FnMut(Vec<String>)>(
         reader: R,
+        framing: Framing,
         target_bytes: usize,
         max_lines: usize,
+        ingest: &IngestOpts,
         buf: &mut Vec<String>,
         buf_bytes: &mut usize,
         on_chunk: &mut Fw,
-    ) -> io::Result<()> {
-        let mut agg = MultiLineAggregator::default();
-        for line in reader.lines() {
+    ) -> io::Result<ReadStats> {
+        let mut agg = framing.aggregator();
+        let mut dedup = ingest.dedup();
+        let mut keep = |e: &str| ingest.in_window(e) && dedup.as_mut().is_none_or(|d| d.keep(e));
+        let mut records = lossy_records(reader, framing);
+        for line in records.by_ref() {
             let l = line?;
-            if let Some(e) = agg.push(&l) {
+            let record = match agg.as_mut() { Some(agg) => agg.push(&l), None => Some(l) };
+            if let Some(e) = record {
+                if !keep(&e) { continue; }
                 *buf_bytes += e.len() + 1; // approximate newline
                 buf.push(e);
                 if buf.len() >= max_lines || *buf_bytes >= target_bytes {
@@ -171,38 +748,40 @@ where
                 }
             }
         }
-        if let Some(e) = agg.finish() {
+        if let Some(e) = agg.and_then(|mut agg| agg.finish()).filter(|e| keep(e)) {
             *buf_bytes += e.len() + 1;
             buf.push(e);
         }
-        Ok(())
+        let deduped_lines = dedup.map_or(0, |d| d.dropped);
+        Ok(ReadStats { invalid_utf8_lines: records.invalid_utf8_lines, deduped_lines })
     }
 
     if paths.is_empty() {
         let stdin = std::io::stdin();
         let locked = stdin.lock();
-        read_source(locked, target_bytes, max_lines, &mut buf, &mut buf_bytes, &mut on_chunk)?;
-        flush(&mut buf, &mut buf_bytes, &mut on_chunk);
-        return Ok(());
+        let mut emit = |chunk| on_chunk("-", chunk);
+        let stats = read_source(locked, ingest.framing, target_bytes, max_lines, ingest, &mut buf, &mut buf_bytes, &mut emit)?;
+        flush(&mut buf, &mut buf_bytes, &mut emit);
+        return Ok(stats);
     }
 
+    let mut stats = ReadStats::default();
     for p in paths {
+        let mut emit = |chunk| on_chunk(p, chunk);
         if p == "-" {
             let stdin = std::io::stdin();
             let locked = stdin.lock();
-            read_source(locked, target_bytes, max_lines, &mut buf, &mut buf_bytes, &mut on_chunk)?;
+            stats += read_source(locked, ingest.framing, target_bytes, max_lines, ingest, &mut buf, &mut buf_bytes, &mut emit)?;
         } else {
             let f = File::open(p)?;
             // Larger buffer reduces syscalls on big files.
             let r = BufReader::with_capacity(1 << 20, f);
-            read_source(r, target_bytes, max_lines, &mut buf, &mut buf_bytes, &mut on_chunk)?;
+            stats += read_source(r, Framing::Lines, target_bytes, max_lines, ingest, &mut buf, &mut buf_bytes, &mut emit)?;
         }
         // Flush between files to avoid chunk mixing across files
-        flush(&mut buf, &mut buf_bytes, &mut on_chunk);
+        flush(&mut buf, &mut buf_bytes, &mut emit);
     }
-    // Final flush (no-op if already empty)
-    flush(&mut buf, &mut buf_bytes, &mut on_chunk);
-    Ok(())
+    Ok(stats)
 }
 
 fn print_help_and_exit() {
@@ -259,9 +838,67 @@ fn main() -> anyhow::Result<()> {
     // Pre-compile all regex patterns to avoid first-use contention in parallel processing
     logoscope::param_extractor::prewarm_regexes();
     
+    if let Some(Command::Trends { pattern_store, days, top }) = &cli.command {
+        let records = logoscope::pattern_store::load_records(pattern_store)?;
+        let mut trends = logoscope::pattern_store::compute_trends(&records, Utc::now().date_naive(), (*days).max(1));
+        if let Some(n) = top { trends.truncate(*n); }
+        print_json(&trends, cli.compact)?;
+        return Ok(());
+    }
+
+    if let Some(Command::Mask { with_params }) = &cli.command {
+        return run_mask(*with_params, cli.strip_ansi);
+    }
+
+    if let Some(Command::Explain { line_a, line_b }) = &cli.command {
+        let opts = logoscope::ai::SummarizeOpts {
+            drain_depth: cli.drain_depth,
+            drain_similarity: cli.drain_similarity,
+            drain_max_children: cli.drain_max_children,
+            input_format: cli.input_format,
+            strip_ansi: cli.strip_ansi,
+            ..Default::default()
+        };
+        return print_json(&logoscope::explain::explain_clustering(line_a, line_b, &opts), cli.compact);
+    }
+
     // Streaming mode (stdin only)
     if cli.follow {
-        run_streaming(cli.interval_secs, cli.window_secs, cli.max_lines, cli.fail_fast)?;
+        let opts = logoscope::ai::SummarizeOpts {
+            drain_depth: cli.drain_depth,
+            drain_similarity: cli.drain_similarity,
+            drain_max_children: cli.drain_max_children,
+            quiet: cli.quiet,
+            redact: redact_types(&cli),
+            max_value_length: cli.max_value_length,
+            decode_b64_json: cli.decode_b64_json,
+            query_params: cli.query_params,
+            pattern_cluster_similarity: cli.pattern_clusters,
+            burst_min_events: cli.burst_min_events,
+            burst_window: cli.burst_window.unwrap_or_else(|| chrono::Duration::seconds(logoscope::temporal::DEFAULT_BURST_WINDOW_SECS)),
+            field_filter: field_filter(&cli),
+            input_format: cli.input_format,
+            strip_ansi: cli.strip_ansi,
+            top_params: cli.top_params,
+            ratio_rules: cli.ratio_rules.clone(),
+            merge_levels: cli.merge_levels,
+            show_drain_template: cli.show_drain_template,
+            mask_sql: cli.mask_sql,
+            min_count: cli.min_count,
+            counts_only: cli.counts_only,
+            quoted_strings: cli.quoted,
+            random_token_entropy: cli.mask_random.map(|bits| bits.unwrap_or(logoscope::param_extractor::DEFAULT_RANDOM_TOKEN_ENTROPY)),
+            keep_literals: cli.keep_literals.clone(),
+            mask_rules: cli.mask_rules.clone(),
+            explain_anomalies: cli.explain_anomaly,
+            correlate: cli.correlate,
+            ..Default::default()
+        };
+        let known_good = cli.baseline_from.as_deref()
+            .map(|path| baseline_templates(&cli, path, &opts))
+            .transpose()?
+            .unwrap_or_default();
+        run_streaming(&cli, &opts, &known_good)?;
         return Ok(());
     }
     
@@ -269,40 +906,33 @@ fn main() -> anyhow::Result<()> {
     let input_files = if cli.input.is_empty() {
         vec!["-".to_string()]
     } else {
-        cli.input.clone()
+        let files = expand_inputs(&cli.input, cli.recursive)?;
+        match cli.order_files.as_deref() {
+            Some(order) => order_inputs(files, order),
+            None => files,
+        }
     };
     
-    // Determine processing mode
-    // Auto-select based on file size: use non-chunked for files < 50MB total
-    const AUTO_CHUNKED_THRESHOLD: u64 = 50 * 1024 * 1024; // 50MB
+    // The in-memory pipeline is the default: the chunked summarizer approximates some stats
+    // across chunk boundaries. It is only chosen up front for --max-tracked-patterns, whose
+    // top-K eviction exists only there (--limit-memory switches over by itself when needed).
+    let use_chunked = cli.max_tracked_patterns.is_some();
     
-    // TEMPORARY OVERRIDE: Always disable chunked processing
-    let use_chunked = false;
-    
-    // Original logic commented out for temporary override:
-    // let use_chunked = if cli.no_chunked { 
-    //     false 
-    // } else if cli.chunked {
-    //     true
-    // } else {
-    //     // Auto-detect based on total file size
-    //     let total_size = input_files.iter()
-    //         .filter(|p| *p != "-")
-    //         .filter_map(|p| std::fs::metadata(p).ok())
-    //         .map(|m| m.len())
-    //         .sum::<u64>();
-    //     
-    //     // If stdin or total size > threshold, use chunked mode
-    //     input_files.contains(&"-".to_string()) || total_size > AUTO_CHUNKED_THRESHOLD
-    // };
+    let ingest = IngestOpts::from_cli(&cli);
     let chunk_size_bytes = cli.chunk_size_mb * 1024 * 1024;
-    const MAX_LINES_PER_CHUNK: usize = 50_000;
     
     // For logs-only view, we need all lines in memory regardless of chunked mode
-    if matches!(cli.only.as_deref(), Some("logs")) {
-        let lines = read_all_lines(&input_files)?;
+    if matches!(cli.only.as_deref(), Some("logs")) || cli.trace.is_some() {
+        let (lines, _) = read_all_lines(&input_files, &ingest)?;
         let mut idx = logoscope::query::QueryIndex::new();
         for l in &lines { let _ = idx.push_line(l); }
+        if let Some(trace_id) = cli.trace.as_deref() {
+            for e in idx.get_trace_timeline(trace_id) {
+                let ts = e.timestamp.map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true));
+                println!("{}", serde_json::json!({"id": e.id, "timestamp": ts, "service": e.service, "template": e.template, "line": e.line}));
+            }
+            return Ok(());
+        }
         let mut results: Vec<&logoscope::query::Entry> = Vec::new();
         if cli.start.is_some() || cli.end.is_some() {
             let s: Option<DateTime<Utc>> = cli.start.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok().map(|d| d.with_timezone(&Utc)));
@@ -314,7 +944,7 @@ fn main() -> anyhow::Result<()> {
             results = idx.get_lines_by_pattern(p);
         } else {
             // default: all entries as-is
-            results = (0..lines.len()).filter_map(|i| idx.get_context(i, 0, 0).get(0).copied()).collect();
+            results = (0..lines.len()).filter_map(|i| idx.get_context(i, 0, 0).first().copied()).collect();
         }
         if (cli.before > 0 || cli.after > 0) && !results.is_empty() {
             let id = results[0].id;
@@ -333,35 +963,348 @@ fn main() -> anyhow::Result<()> {
         verbose: cli.verbose,
         triage: cli.triage,
         deep: cli.deep,
+        drain_depth: cli.drain_depth,
+        drain_similarity: cli.drain_similarity,
+        drain_max_children: cli.drain_max_children,
+        quiet: cli.quiet,
+        cardinality_rules: cli.cardinality_rules.iter().cloned()
+            .chain(logoscope::field_anomaly::default_cardinality_rules())
+            .collect(),
+        redact: redact_types(&cli),
+        max_value_length: cli.max_value_length,
+        decode_b64_json: cli.decode_b64_json,
+        query_params: cli.query_params,
+        pattern_cluster_similarity: cli.pattern_clusters,
+        burst_min_events: cli.burst_min_events,
+        burst_window: cli.burst_window.unwrap_or_else(|| chrono::Duration::seconds(logoscope::temporal::DEFAULT_BURST_WINDOW_SECS)),
+        field_filter: field_filter(&cli),
+        input_format: cli.input_format,
+        strip_ansi: cli.strip_ansi,
+        top_params: cli.top_params,
+        max_tracked_patterns: cli.max_tracked_patterns,
+        ratio_rules: cli.ratio_rules.clone(),
+        merge_levels: cli.merge_levels,
+        show_drain_template: cli.show_drain_template,
+        mask_sql: cli.mask_sql,
+        min_count: cli.min_count,
+        counts_only: cli.counts_only,
+        quoted_strings: cli.quoted,
+        random_token_entropy: cli.mask_random.map(|bits| bits.unwrap_or(logoscope::param_extractor::DEFAULT_RANDOM_TOKEN_ENTROPY)),
+        keep_literals: cli.keep_literals.clone(),
+        mask_rules: cli.mask_rules.clone(),
+        explain_anomalies: cli.explain_anomaly,
+        correlate: cli.correlate,
         ..Default::default()
     };
+
+    let baseline = cli.baseline_from.as_deref()
+        .map(|path| baseline_templates(&cli, path, &opts))
+        .transpose()?;
+    let baseline = baseline.as_ref();
+
+    if cli.bench {
+        return run_bench(&cli, &input_files, &ingest, &opts);
+    }
+
+    if cli.watch {
+        anyhow::ensure!(!input_files.iter().any(|p| p == "-"), "--watch needs file inputs; use --follow for stdin");
+        return run_watch(&cli, &input_files, &ingest, baseline, &opts);
+    }
+
+    if let Some(dir) = &cli.metrics_csv {
+        let (lines, _) = read_all_lines(&input_files, &ingest)?;
+        let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        let keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
+        return write_metrics_csv(dir, &logoscope::metrics::extract_metrics(&refs, &keys, &opts), cli.quiet);
+    }
+
+    if let Some(window) = cli.window_by {
+        let (lines, _) = read_all_lines(&input_files, &ingest)?;
+        let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        let keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
+        return print_json(&logoscope::ai::summarize_windows(&refs, &keys, &opts, window), cli.compact);
+    }
     
     let out = if use_chunked {
         // Chunked processing for constant memory usage
-        let mut engine = logoscope::ai::StreamingSummarizer::new();
+        let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
         let time_keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
         
-        stream_lines_in_chunks(&input_files, chunk_size_bytes, MAX_LINES_PER_CHUNK, |chunk| {
+        let mut progress = (cli.progress && !cli.quiet).then(ChunkProgress::new);
+        let tag_files = tags_files(&input_files);
+        let stats = stream_lines_in_chunks(&input_files, chunk_size_bytes, MAX_LINES_PER_CHUNK, &ingest, |path, chunk| {
+            engine.set_source_file(tag_files.then_some(path));
             engine.ingest_chunk(&chunk, &time_keys, &opts);
+            if let Some(p) = progress.as_mut() { p.chunk_done(chunk.len()); }
         })?;
+        if let Some(p) = progress { p.finish(); }
         
-        engine.finalize(None, &opts)
+        let mut out = engine.finalize(baseline, &opts);
+        stats.apply_to(&mut out);
+        out
+    } else if let Some(mb) = cli.limit_memory_mb {
+        summarize_within_budget(&cli, &input_files, &ingest, baseline, &opts, mb, chunk_size_bytes)?
     } else {
         // Original all-in-memory processing
-        let lines = read_all_lines(&input_files)?;
-        let refs: Vec<&str> = lines.iter().map(|s| s.as_ref()).collect();
-        if cli.time_key.is_empty() {
-            logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts)
-        } else {
-            let keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
-            logoscope::ai::summarize_lines_with_opts(&refs, &keys, None, &opts)
+        summarize_files(&cli, &input_files, &ingest, baseline, &opts)?
+    };
+
+    if let Some(path) = &cli.correlation_graph {
+        std::fs::write(path, logoscope::report::render_correlation_dot(&out))?;
+    }
+
+    if let Some(dir) = &cli.pattern_store {
+        let now = Utc::now();
+        logoscope::pattern_store::append_run(dir, &out, now.date_naive(), now)?;
+    }
+
+    let diagnostic = out.summary.diagnostic.clone();
+    let gate = (!cli.fail_on.is_empty()).then(|| logoscope::ai::create_triage_output(&out));
+    emit_output(&cli, out)?;
+    if let (true, Some(d)) = (cli.error_on_empty, diagnostic) {
+        if !cli.quiet { eprintln!("logoscope: {d}"); }
+        std::process::exit(2);
+    }
+    if let Some(triage) = gate {
+        if let Some(cond) = cli.fail_on.iter().find(|c| c.triggered(&triage)) {
+            if !cli.quiet { eprintln!("logoscope: --fail-on {cond:?} triggered (status {})", triage.summary.status); }
+            std::process::exit(FAIL_ON_EXIT_CODE);
+        }
+    }
+    Ok(())
+}
+
+/// `--metrics-csv`: `metrics-NNN.csv` per series and `index.csv` (file, template, rows)
+fn write_metrics_csv(dir: &std::path::Path, series: &[logoscope::metrics::MetricSeries], quiet: bool) -> anyhow::Result<()> {
+    use logoscope::metrics::csv_field;
+    std::fs::create_dir_all(dir)?;
+    let mut index = String::from("file,template,rows\n");
+    for (i, s) in series.iter().enumerate() {
+        let file = format!("metrics-{:03}.csv", i + 1);
+        std::fs::write(dir.join(&file), s.to_csv())?;
+        index.push_str(&format!("{file},{},{}\n", csv_field(&s.template), s.rows.len()));
+    }
+    std::fs::write(dir.join("index.csv"), index)?;
+    if !quiet { eprintln!("logoscope: wrote {} metric series to {}", series.len(), dir.display()); }
+    Ok(())
+}
+
+/// Exit status when a --fail-on condition holds (2 is taken by --error-on-empty)
+const FAIL_ON_EXIT_CODE: i32 = 3;
+
+/// Minimum time between two `--progress` reports
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// `--progress` reporter for the chunked path: lines, chunks and elapsed time on stderr
+struct ChunkProgress {
+    start: std::time::Instant,
+    last_report: std::time::Instant,
+    lines: usize,
+    chunks: usize,
+}
+
+impl ChunkProgress {
+    fn new() -> Self {
+        let now = std::time::Instant::now();
+        Self { start: now, last_report: now, lines: 0, chunks: 0 }
+    }
+
+    fn chunk_done(&mut self, lines: usize) {
+        self.lines += lines;
+        self.chunks += 1;
+        if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            self.last_report = std::time::Instant::now();
+            self.report("progress");
+        }
+    }
+
+    fn finish(self) {
+        self.report("done");
+    }
+
+    fn report(&self, label: &str) {
+        let secs = self.start.elapsed().as_secs_f64();
+        let rate = if secs > 0.0 { self.lines as f64 / secs } else { 0.0 };
+        eprintln!("logoscope: {label}: {} lines, {} chunks, {secs:.1}s elapsed ({rate:.0} lines/s)", self.lines, self.chunks);
+    }
+}
+
+/// Read every input fully and summarize it in memory
+fn summarize_files(
+    cli: &Cli,
+    input_files: &[String],
+    ingest: &IngestOpts,
+    baseline: Option<&HashSet<String>>,
+    opts: &logoscope::ai::SummarizeOpts,
+) -> io::Result<logoscope::ai::AiOutput> {
+    let (files, stats) = read_lines_by_file(input_files, ingest)?;
+    let keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
+    let mut out = summarize_file_lines(&files, tags_files(input_files), &keys, baseline, opts);
+    stats.apply_to(&mut out);
+    Ok(out)
+}
+
+/// In-memory summary of records read per input, with `by_file` breakdowns when `tag_files`
+fn summarize_file_lines(
+    files: &[(String, Vec<String>)],
+    tag_files: bool,
+    time_keys: &[&str],
+    baseline: Option<&HashSet<String>>,
+    opts: &logoscope::ai::SummarizeOpts,
+) -> logoscope::ai::AiOutput {
+    let refs: Vec<&str> = files.iter().flat_map(|(_, lines)| lines.iter().map(String::as_str)).collect();
+    if !tag_files {
+        return logoscope::ai::summarize_lines_with_opts(&refs, time_keys, baseline, opts);
+    }
+    let spans: Vec<logoscope::ai::FileSpan> = files.iter()
+        .map(|(file, lines)| logoscope::ai::FileSpan { file: file.clone(), lines: lines.len() })
+        .collect();
+    logoscope::ai::summarize_lines_by_file(&refs, &spans, time_keys, baseline, opts)
+}
+
+/// `--baseline-from`: summarize a known-good file with the same options and keep its template
+/// set (the time filter, --dedup-window and --tail only apply to the main input)
+fn baseline_templates(cli: &Cli, path: &str, opts: &logoscope::ai::SummarizeOpts) -> io::Result<HashSet<String>> {
+    let opts = logoscope::ai::SummarizeOpts { quiet: true, ..opts.clone() };
+    let ingest = IngestOpts { filter: None, dedup_window: None, tail: None, ..IngestOpts::from_cli(cli) };
+    let out = summarize_files(cli, &[path.to_string()], &ingest, None, &opts)?;
+    Ok(out.patterns.into_iter().map(|p| p.template).collect())
+}
+
+/// Line cap per chunk in the chunked and memory-budgeted paths
+const MAX_LINES_PER_CHUNK: usize = 50_000;
+
+/// Rough peak memory of the in-memory pipeline per byte of input (the lines themselves, their
+/// canonicalized text and per-line parse results)
+const MEMORY_PER_INPUT_BYTE: u64 = 4;
+
+/// `--limit-memory`: buffer chunks while the estimated in-memory footprint fits `budget_mb`, then
+/// hand everything buffered to a StreamingSummarizer and stream the rest of the input through it
+fn summarize_within_budget(
+    cli: &Cli,
+    input_files: &[String],
+    ingest: &IngestOpts,
+    baseline: Option<&HashSet<String>>,
+    opts: &logoscope::ai::SummarizeOpts,
+    budget_mb: u64,
+    chunk_size_bytes: usize,
+) -> io::Result<logoscope::ai::AiOutput> {
+    let budget_bytes = budget_mb * 1024 * 1024;
+    let input_budget = (budget_bytes / MEMORY_PER_INPUT_BYTE).max(1);
+    let chunk_bytes = chunk_size_bytes.min(input_budget as usize);
+    let time_keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
+
+    let tag_files = tags_files(input_files);
+    let mut pending: Vec<(String, Vec<String>)> = Vec::new();
+    let mut pending_bytes: u64 = 0;
+    let mut engine: Option<logoscope::ai::StreamingSummarizer> = None;
+    let mut progress = (cli.progress && !cli.quiet).then(ChunkProgress::new);
+    let stats = stream_lines_in_chunks(input_files, chunk_bytes, MAX_LINES_PER_CHUNK, ingest, |path, chunk| {
+        if let Some(engine) = engine.as_mut() {
+            engine.set_source_file(tag_files.then_some(path));
+            engine.ingest_chunk(&chunk, &time_keys, opts);
+            if let Some(p) = progress.as_mut() { p.chunk_done(chunk.len()); }
+            return;
+        }
+        pending_bytes += chunk.iter().map(|l| l.len() as u64 + 1).sum::<u64>();
+        pending.push((path.to_string(), chunk));
+        if pending_bytes > input_budget {
+            if !cli.quiet {
+                eprintln!("logoscope: input exceeded the --limit-memory budget ({budget_mb} MB); switching to streaming aggregation");
+            }
+            let mut streaming = logoscope::ai::StreamingSummarizer::with_opts(opts);
+            for (path, chunk) in pending.drain(..) {
+                streaming.set_source_file(tag_files.then_some(path.as_str()));
+                streaming.ingest_chunk(&chunk, &time_keys, opts);
+                if let Some(p) = progress.as_mut() { p.chunk_done(chunk.len()); }
+            }
+            engine = Some(streaming);
         }
+    })?;
+
+    let mut out = match engine {
+        Some(engine) => {
+            if let Some(p) = progress { p.finish(); }
+            engine.finalize(baseline, opts)
+        }
+        None => summarize_file_lines(&pending, tag_files, &time_keys, baseline, opts),
+    };
+    stats.apply_to(&mut out);
+    Ok(out)
+}
+
+/// Run the in-memory pipeline without serializing its output and print only stage timings as
+/// JSON (input reading is timed separately from analysis)
+fn run_bench(cli: &Cli, input_files: &[String], ingest: &IngestOpts, opts: &logoscope::ai::SummarizeOpts) -> anyhow::Result<()> {
+    let read_start = std::time::Instant::now();
+    let (lines, _) = read_all_lines(input_files, ingest)?;
+    let read_seconds = read_start.elapsed().as_secs_f64();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_ref()).collect();
+    let keys: Vec<&str> = cli.time_key.iter().map(|s| s.as_str()).collect();
+    let opts = logoscope::ai::SummarizeOpts { quiet: true, ..opts.clone() };
+    let (out, timings) = logoscope::ai::analyze_only(&refs, &keys, &opts);
+    drop(out);
+    print_json(&serde_json::json!({"read_seconds": read_seconds, "analysis": timings}), cli.compact)
+}
+
+/// Quiet period that must follow a change before --watch re-runs
+const WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Re-summarize `input_files` whenever one of them changes, printing a fresh summary each time.
+/// Parent directories are watched so rotated or recreated files are still picked up.
+fn run_watch(
+    cli: &Cli,
+    input_files: &[String],
+    ingest: &IngestOpts,
+    baseline: Option<&HashSet<String>>,
+    opts: &logoscope::ai::SummarizeOpts,
+) -> anyhow::Result<()> {
+    use notify::{RecursiveMode, Watcher};
+    use std::path::{Path, PathBuf};
+
+    let absolute = |p: &str| std::path::absolute(Path::new(p));
+    let targets: Vec<PathBuf> = input_files.iter().map(|p| absolute(p)).collect::<io::Result<_>>()?;
+    let mut dirs: Vec<PathBuf> = targets.iter().filter_map(|p| p.parent().map(Path::to_path_buf)).collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for dir in &dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+    let touches_input = |event: &notify::Event| {
+        !event.kind.is_access() && event.paths.iter().any(|p| targets.contains(p))
     };
 
+    loop {
+        match summarize_files(cli, input_files, ingest, baseline, opts) {
+            Ok(out) => emit_output(cli, out)?,
+            // An input being rotated can be briefly missing: no data yet, wait for it to reappear
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if !cli.quiet { eprintln!("logoscope: an input is missing ({e}); waiting for it to reappear"); }
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        // Block until an input changes, then wait out the burst of follow-up events
+        loop {
+            match rx.recv() {
+                Ok(Ok(event)) if touches_input(&event) => break,
+                Ok(_) => continue,
+                Err(_) => return Ok(()),
+            }
+        }
+        while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+    }
+}
+
+/// Print the summary in the format selected on the command line
+fn emit_output(cli: &Cli, out: logoscope::ai::AiOutput) -> anyhow::Result<()> {
     // Triage mode: output compact critical information only
     if cli.triage {
         let triage_output = logoscope::ai::create_triage_output(&out);
-        println!("{}", serde_json::to_string_pretty(&triage_output)?);
+        print_json(&triage_output, cli.compact)?;
         return Ok(());
     }
 
@@ -399,19 +1342,72 @@ fn main() -> anyhow::Result<()> {
             if p.examples.len() > max_examples { p.examples.truncate(max_examples); }
         }
         if cli.format == "table" {
-            print_patterns_table(&pats, &cli.group_by);
+            print_patterns_table(&pats, &cli.group_by, &cli.count_format, use_color(cli));
         } else {
-            println!("{}", serde_json::to_string_pretty(&pats)?);
+            print_json(&pats, cli.compact)?;
         }
         return Ok(());
     }
 
+    if matches!(cli.only.as_deref(), Some("anomalies")) {
+        // Alerting payload: anomalies plus the investigations they seed
+        print_json(&serde_json::json!({
+            "anomalies": out.anomalies,
+            "suggested_investigations": out.query_interface.suggested_investigations,
+        }), cli.compact)?;
+        return Ok(());
+    }
+
+    if cli.format == "markdown" {
+        print!("{}", logoscope::report::render_markdown(&out));
+        return Ok(());
+    }
+
     // Default: full JSON summary
-    println!("{}", serde_json::to_string_pretty(&out)?);
+    print_json(&out, cli.compact)?;
     Ok(())
 }
 
-fn run_streaming(interval_secs: u64, window_secs: i64, max_lines: usize, fail_fast: bool) -> anyhow::Result<()> {
+/// Serialize `value` as JSON (single-line when `compact`, from --compact, otherwise pretty)
+/// straight into buffered stdout. No intermediate string is built, so large outputs (tens of
+/// thousands of patterns) are written element by element.
+fn print_json<T: serde::Serialize + ?Sized>(value: &T, compact: bool) -> anyhow::Result<()> {
+    use std::io::Write;
+    let mut w = io::BufWriter::new(io::stdout().lock());
+    if compact {
+        serde_json::to_writer(&mut w, value)?;
+    } else {
+        serde_json::to_writer_pretty(&mut w, value)?;
+    }
+    writeln!(w)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Masking filter: canonicalize every stdin line without clustering, preserving order
+fn run_mask(with_params: bool, strip_ansi: bool) -> anyhow::Result<()> {
+    use std::io::Write;
+    let stdout = io::stdout();
+    let mut w = io::BufWriter::new(stdout.lock());
+    for line in lossy_lines(io::stdin().lock()) {
+        let line = line?;
+        let line = if strip_ansi { logoscope::parser::strip_ansi(&line) } else { std::borrow::Cow::Borrowed(line.as_str()) };
+        let masked = logoscope::param_extractor::canonicalize_for_drain(&line);
+        if with_params {
+            let params: std::collections::BTreeMap<_, _> = masked.extracted_params.into_iter().collect();
+            let rec = serde_json::json!({"template": masked.masked_text, "params": params});
+            writeln!(w, "{rec}")?;
+        } else {
+            writeln!(w, "{}", masked.masked_text)?;
+        }
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// `--follow`: summarize a sliding window of stdin every --interval seconds. Templates of
+/// `known_good` (from --baseline-from) count as already seen in every window.
+fn run_streaming(cli: &Cli, opts: &logoscope::ai::SummarizeOpts, known_good: &HashSet<String>) -> anyhow::Result<()> {
     use std::time::{Duration, Instant};
     use std::collections::{VecDeque, HashMap};
     use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
@@ -421,53 +1417,45 @@ fn run_streaming(interval_secs: u64, window_secs: i64, max_lines: usize, fail_fa
         let _ = ctrlc::set_handler(move || { r.store(false, Ordering::SeqCst); });
     }
     let stdin = io::stdin();
-    let mut reader = stdin.lock().lines();
-    let mut agg = MultiLineAggregator::default();
+    let mut reader = lossy_records(stdin.lock(), cli.framing);
+    let mut agg = cli.framing.aggregator();
     let mut buf: VecDeque<(String, Option<DateTime<Utc>>)> = VecDeque::new();
     let mut last_emit = Instant::now();
     let mut last_counts: HashMap<String, usize> = HashMap::new();
+    let mut known_values: HashMap<String, std::collections::HashSet<String>> = HashMap::new();
     loop {
         if !running.load(Ordering::SeqCst) {
-            emit_summary_with_deltas(&buf, &mut last_counts)?;
+            emit_summary_with_deltas(&buf, &mut last_counts, &mut known_values, reader.invalid_utf8_lines, known_good, cli, opts)?;
             break;
         }
-        match reader.next() {
-            Some(Ok(line)) => {
-                if let Some(entry) = agg.push(&line) {
-                    let rec = logoscope::parser::parse_line(&entry, buf.len() + 1);
-                    if fail_fast {
-                        let looks_json = entry.trim_start().starts_with('{') || entry.trim_start().starts_with('[');
-                        if looks_json && rec.flat_fields.is_none() && rec.synthetic_message.is_none() {
-                            eprintln!("[stream] parse error; aborting due to --fail-fast");
-                            break;
-                        }
-                    }
-                    buf.push_back((entry, rec.timestamp));
-                    trim_buffer(&mut buf, window_secs, max_lines);
-                    if last_emit.elapsed() >= Duration::from_secs(interval_secs) {
-                        emit_summary_with_deltas(&buf, &mut last_counts)?;
-                        last_emit = Instant::now();
-                    }
-                }
-            }
-            Some(Err(_e)) => {
-                // ignore read errors
-            }
-            None => {
-                std::thread::sleep(Duration::from_millis(200));
-                if last_emit.elapsed() >= Duration::from_secs(interval_secs) {
-                    emit_summary_with_deltas(&buf, &mut last_counts)?;
-                    last_emit = Instant::now();
-                }
+        let (record, at_eof) = match reader.next() {
+            Some(Ok(line)) => (match agg.as_mut() { Some(agg) => agg.push(&line), None => Some(line) }, false),
+            // ignore read errors
+            Some(Err(_e)) => continue,
+            // The producer closed stdin: flush the pending record, report what is left and stop
+            None => (agg.take().and_then(|mut agg| agg.finish()), true),
+        };
+        if let Some(entry) = record {
+            let rec = logoscope::parser::parse_line_as(&entry, buf.len() + 1, &[], opts.parse_opts());
+            if cli.fail_fast && opts.input_format.expects_json(&entry) && rec.flat_fields.is_none() && rec.synthetic_message.is_none() {
+                if !opts.quiet { eprintln!("[stream] parse error; aborting due to --fail-fast"); }
+                break;
             }
+            buf.push_back((entry, rec.timestamp));
+            trim_buffer(&mut buf, cli.window_secs, cli.max_lines);
+        }
+        if at_eof || last_emit.elapsed() >= Duration::from_secs(cli.interval_secs) {
+            emit_summary_with_deltas(&buf, &mut last_counts, &mut known_values, reader.invalid_utf8_lines, known_good, cli, opts)?;
+            last_emit = Instant::now();
         }
+        if at_eof { break; }
     }
     Ok(())
 }
 
 fn trim_buffer(buf: &mut std::collections::VecDeque<(String, Option<DateTime<Utc>>)>, window_secs: i64, max_lines: usize) {
     // trim by window using most recent timestamp if available
-    let most_recent_ts = buf.iter().rev().find_map(|(_,ts)| *ts).unwrap_or_else(|| Utc::now());
+    let most_recent_ts = buf.iter().rev().find_map(|(_,ts)| *ts).unwrap_or_else(Utc::now);
     let cutoff = most_recent_ts - chrono::Duration::seconds(window_secs);
     while let Some((_, ts)) = buf.front() {
         if let Some(t) = ts { if *t < cutoff { buf.pop_front(); continue; } }
@@ -476,14 +1464,32 @@ fn trim_buffer(buf: &mut std::collections::VecDeque<(String, Option<DateTime<Utc
     while buf.len() > max_lines { buf.pop_front(); }
 }
 
-fn emit_summary_with_deltas(buf: &std::collections::VecDeque<(String, Option<DateTime<Utc>>)>, last_counts: &mut std::collections::HashMap<String, usize>) -> anyhow::Result<()> {
+fn emit_summary_with_deltas(
+    buf: &std::collections::VecDeque<(String, Option<DateTime<Utc>>)>,
+    last_counts: &mut std::collections::HashMap<String, usize>,
+    known_values: &mut std::collections::HashMap<String, std::collections::HashSet<String>>,
+    invalid_utf8_lines: usize,
+    known_good: &HashSet<String>,
+    cli: &Cli,
+    opts: &logoscope::ai::SummarizeOpts,
+) -> anyhow::Result<()> {
     let lines: Vec<&str> = buf.iter().map(|(s, _)| s.as_str()).collect();
     // Build baseline templates from the last emitted counts (streaming semantics)
-    let baseline: std::collections::HashSet<String> = last_counts.keys().cloned().collect();
-    let opts = logoscope::ai::SummarizeOpts::default();
-    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], Some(&baseline), &opts);
+    let baseline: HashSet<String> = last_counts.keys().chain(known_good).cloned().collect();
+    let last_total: usize = last_counts.values().sum();
+    let opts = logoscope::ai::SummarizeOpts {
+        baseline_frequencies: last_counts.iter()
+            .map(|(tpl, c)| (tpl.clone(), *c as f64 / last_total.max(1) as f64))
+            .collect(),
+        // Lent to this window's summary and taken back below, instead of cloned per emit
+        baseline_field_values: std::mem::take(known_values),
+        ..opts.clone()
+    };
+    let mut out = logoscope::ai::summarize_lines_with_opts(&lines, &[], Some(&baseline), &opts);
+    // Lossy decodes since the stream started
+    out.errors.invalid_utf8_lines = invalid_utf8_lines;
     // Compact status to stderr
-    eprintln!("[stream] lines={} patterns={}", out.summary.total_lines, out.patterns.len());
+    if !opts.quiet { eprintln!("[stream] lines={} patterns={}", out.summary.total_lines, out.patterns.len()); }
     // Deltas JSONL on stdout
     let mut new_counts = std::collections::HashMap::new();
     for p in &out.patterns { new_counts.insert(p.template.clone(), p.total_count); }
@@ -494,14 +1500,26 @@ fn emit_summary_with_deltas(buf: &std::collections::VecDeque<(String, Option<Dat
         }
     }
     *last_counts = new_counts;
+    // Field values seen so far become the enum baseline for the next window
+    *known_values = opts.baseline_field_values;
+    logoscope::field_anomaly::remember_field_values(known_values, &lines);
     // Full summary after deltas
-    println!("{}", serde_json::to_string_pretty(&out)?);
+    if !cli.deltas_only { print_json(&out, cli.compact)?; }
     Ok(())
 }
 
-fn print_patterns_table(pats: &Vec<logoscope::ai::PatternOut>, group_by: &str) {
+/// Colors only for interactive terminals, never when --no-color or NO_COLOR (non-empty) is set
+fn use_color(cli: &Cli) -> bool {
+    use std::io::IsTerminal;
+    !cli.no_color
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+fn print_patterns_table(pats: &[logoscope::ai::PatternOut], group_by: &str, count_format: &str, color: bool) {
+    use owo_colors::OwoColorize;
     // Sort patterns by group first, then by count
-    let mut sorted_pats = pats.clone();
+    let mut sorted_pats = pats.to_vec();
     match group_by {
         "level" => {
             sorted_pats.sort_by(|a, b| {
@@ -514,13 +1532,29 @@ fn print_patterns_table(pats: &Vec<logoscope::ai::PatternOut>, group_by: &str) {
         },
         "service" => {
             sorted_pats.sort_by(|a, b| {
-                let a_service = a.sources.by_service.get(0).map(|c| c.name.clone()).unwrap_or_else(|| "".into());
-                let b_service = b.sources.by_service.get(0).map(|c| c.name.clone()).unwrap_or_else(|| "".into());
+                let a_service = a.sources.by_service.first().map(|c| c.name.clone()).unwrap_or_else(|| "".into());
+                let b_service = b.sources.by_service.first().map(|c| c.name.clone()).unwrap_or_else(|| "".into());
                 a_service.cmp(&b_service)
                     .then_with(|| b.total_count.cmp(&a.total_count))
                     .then_with(|| a.template.cmp(&b.template))
             });
         },
+        "file" => {
+            sorted_pats.sort_by(|a, b| {
+                let a_file = a.by_file.first().map(|c| c.name.as_str()).unwrap_or("");
+                let b_file = b.by_file.first().map(|c| c.name.as_str()).unwrap_or("");
+                a_file.cmp(b_file)
+                    .then_with(|| b.total_count.cmp(&a.total_count))
+                    .then_with(|| a.template.cmp(&b.template))
+            });
+        },
+        "category" => {
+            sorted_pats.sort_by(|a, b| {
+                a.category.cmp(&b.category)
+                    .then_with(|| b.total_count.cmp(&a.total_count))
+                    .then_with(|| a.template.cmp(&b.template))
+            });
+        },
         _ => {
             // Default sorting by count desc, then template asc
             sorted_pats.sort_by(|a, b| {
@@ -530,20 +1564,30 @@ fn print_patterns_table(pats: &Vec<logoscope::ai::PatternOut>, group_by: &str) {
         }
     }
     
-    println!("{:<6} {:<8} {:<8} {:<10} {:<10} {}", "Count", "Freq", "Bursts", "Confidence", "Level", "Template");
+    let counts: Vec<String> = sorted_pats.iter().map(|p| logoscope::report::format_count(p.total_count, count_format)).collect();
+    let cw = counts.iter().map(|c| c.len()).max().unwrap_or(0).max(6);
+    println!("{:<cw$} {:<8} {:<8} {:<10} {:<10} Template", "Count", "Freq", "Bursts", "Confidence", "Level");
     let mut current_group: Option<String> = None;
-    for p in &sorted_pats {
+    for (p, count) in sorted_pats.iter().zip(&counts) {
         let group_val = match group_by {
             "level" => p.severity.clone().unwrap_or_else(|| "".into()),
-            "service" => p.sources.by_service.get(0).map(|c| c.name.clone()).unwrap_or_else(|| "".into()),
+            "service" => p.sources.by_service.first().map(|c| c.name.clone()).unwrap_or_else(|| "".into()),
+            "category" => p.category.clone().unwrap_or_default(),
+            "file" => p.by_file.first().map(|c| c.name.clone()).unwrap_or_default(),
             _ => String::new(),
         };
         if !group_val.is_empty() && current_group.as_deref() != Some(group_val.as_str()) {
             current_group = Some(group_val.clone());
             println!("\n# {}", group_val);
-            println!("{:<6} {:<8} {:<8} {:<10} {:<10} {}", "Count", "Freq", "Bursts", "Confidence", "Level", "Template");
+            println!("{:<cw$} {:<8} {:<8} {:<10} {:<10} Template", "Count", "Freq", "Bursts", "Confidence", "Level");
+        }
+        let row = format!("{:<cw$} {:<8.4} {:<8} {:<10.3} {:<10} {}",
+            count, p.frequency, p.temporal.as_ref().map(|t| t.bursts).unwrap_or(0), p.pattern_stability, p.severity.clone().unwrap_or_else(|| "".into()), p.template);
+        let level = p.severity.as_deref().unwrap_or("").to_ascii_lowercase();
+        match level.as_str() {
+            "error" | "err" | "fatal" | "critical" | "crit" | "panic" if color => println!("{}", row.red()),
+            "warn" | "warning" if color => println!("{}", row.yellow()),
+            _ => println!("{row}"),
         }
-        println!("{:<6} {:<8.4} {:<8} {:<10.3} {:<10} {}",
-            p.total_count, p.frequency, p.temporal.as_ref().map(|t| t.bursts).unwrap_or(0), p.pattern_stability, p.severity.clone().unwrap_or_else(|| "".into()), p.template);
     }
 }
@@ -0,0 +1,42 @@
+//! Platform shims so the analysis core also builds single-threaded for wasm32 (`wasm` feature).
+//! Natively `par_iter`/`par_iter_mut` are rayon's; under `wasm` they are plain slice iterators
+//! with the same adapter chains (`enumerate`, `map`, `collect`, `for_each`).
+
+#[cfg(not(feature = "wasm"))]
+pub(crate) use rayon::prelude::*;
+
+#[cfg(not(feature = "wasm"))]
+pub(crate) use std::time::Instant;
+
+#[cfg(feature = "wasm")]
+pub(crate) trait SequentialSlice<T> {
+    fn par_iter(&self) -> std::slice::Iter<'_, T>;
+    fn par_iter_mut(&mut self) -> std::slice::IterMut<'_, T>;
+}
+
+#[cfg(feature = "wasm")]
+impl<T> SequentialSlice<T> for [T] {
+    fn par_iter(&self) -> std::slice::Iter<'_, T> {
+        self.iter()
+    }
+
+    fn par_iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.iter_mut()
+    }
+}
+
+/// `std::time::Instant::now` panics on wasm32-unknown-unknown; stage timings read zero instead
+#[cfg(feature = "wasm")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Instant;
+
+#[cfg(feature = "wasm")]
+impl Instant {
+    pub(crate) fn now() -> Self {
+        Instant
+    }
+
+    pub(crate) fn elapsed(&self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+}
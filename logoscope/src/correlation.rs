@@ -15,19 +15,22 @@ pub fn compute_correlations(
 ) -> Vec<Correlation> {
     let mut out = Vec::new();
     let keys: Vec<String> = times_by_template.keys().cloned().collect();
+    // Sort each series once rather than per pair
+    let sorted: Vec<Vec<DateTime<Utc>>> = keys.iter()
+        .map(|k| {
+            let mut t = times_by_template[k].clone();
+            t.sort_unstable();
+            t
+        })
+        .collect();
     for i in 0..keys.len() {
         for j in (i + 1)..keys.len() {
-            let a = &keys[i];
-            let b = &keys[j];
-            let mut ta = times_by_template.get(a).cloned().unwrap_or_default();
-            let mut tb = times_by_template.get(b).cloned().unwrap_or_default();
+            let (ta, tb) = (&sorted[i], &sorted[j]);
             if ta.is_empty() || tb.is_empty() { continue; }
-            ta.sort_unstable();
-            tb.sort_unstable();
-            let count = cooccurrence_count(&ta, &tb, window);
+            let count = cooccurrence_count(ta, tb, window);
             let union = ta.len() + tb.len() - count;
             let strength = if union > 0 { (count as f64) / (union as f64) } else { 0.0 };
-            out.push(Correlation { a: a.clone(), b: b.clone(), count, strength });
+            out.push(Correlation { a: keys[i].clone(), b: keys[j].clone(), count, strength });
         }
     }
     out
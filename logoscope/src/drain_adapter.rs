@@ -8,6 +8,17 @@ pub enum DrainError {
     Generic(String),
 }
 
+/// Default parse-tree depth used by the summarizers
+pub const DEFAULT_MAX_DEPTH: u16 = 32;
+/// Default minimum token similarity for a line to join an existing cluster
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.1;
+/// Default maximum number of children per internal tree node
+pub const DEFAULT_MAX_CHILDREN: u16 = 512;
+/// Placeholder share of a merged template from which it is suspected of over-merging
+pub const OVER_MERGE_PLACEHOLDER_SHARE: f64 = 0.3;
+/// Distinct messages a template must absorb before it is suspected of over-merging
+pub const OVER_MERGE_MIN_VARIANTS: usize = 3;
+
 pub struct DrainAdapter {
     tree: drain_rs::DrainTree,
 }
@@ -31,6 +42,7 @@ impl DrainAdapter {
         Self { tree }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn new_tuned_with_filters(max_depth: u16, min_similarity: f32, max_children: u16) -> Self {
         let patterns = vec![
             "%{IPV4:IPV4}",
@@ -51,6 +63,13 @@ impl DrainAdapter {
         Self { tree }
     }
 
+    /// wasm32 has no grok (it needs the Oniguruma C library), so the tree runs without token
+    /// filters; canonicalization has already masked these values in almost every line
+    #[cfg(target_arch = "wasm32")]
+    pub fn new_tuned_with_filters(max_depth: u16, min_similarity: f32, max_children: u16) -> Self {
+        Self::new_tuned(max_depth, min_similarity, max_children)
+    }
+
     pub fn from_tree(tree: drain_rs::DrainTree) -> Self {
         Self { tree }
     }
@@ -194,6 +213,52 @@ pub fn to_display_template(raw_template: &str, _original_line: &str) -> String {
     raw_template.to_string()
 }
 
+/// A Drain template that absorbed several distinct messages and is left mostly placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverMergedTemplate {
+    /// Template Drain reported for the merged messages
+    pub template: String,
+    /// What the messages actually share: `<*>` wherever they disagree
+    pub merged: String,
+    /// Distinct masked messages behind the template
+    pub variants: usize,
+    /// Fraction of `merged` tokens that are placeholders
+    pub placeholder_share: f64,
+    /// First few of the merged messages
+    pub examples: Vec<String>,
+}
+
+/// Templates that look over-merged, from `(masked message, Drain template)` pairs: at least
+/// `OVER_MERGE_MIN_VARIANTS` distinct messages whose common form is at least
+/// `OVER_MERGE_PLACEHOLDER_SHARE` placeholders (e.g. `user logged in` and `user logged out`
+/// behind one template). A higher Drain similarity splits them. Most variants first.
+pub fn over_merged_templates<'a>(assignments: impl IntoIterator<Item = (&'a String, &'a String)>) -> Vec<OverMergedTemplate> {
+    let mut by_template: std::collections::BTreeMap<&String, std::collections::BTreeSet<&String>> = Default::default();
+    for (masked, template) in assignments {
+        by_template.entry(template).or_default().insert(masked);
+    }
+    let mut out: Vec<OverMergedTemplate> = by_template.into_iter()
+        .filter(|(_, variants)| variants.len() >= OVER_MERGE_MIN_VARIANTS)
+        .filter_map(|(template, variants)| {
+            let variants: Vec<&String> = variants.into_iter().collect();
+            let merged = generalize_templates(variants.clone())?;
+            let tokens: Vec<&str> = merged.split_whitespace().collect();
+            if tokens.is_empty() { return None; }
+            let placeholders = tokens.iter().filter(|t| t.starts_with('<') && t.ends_with('>')).count();
+            let placeholder_share = placeholders as f64 / tokens.len() as f64;
+            (placeholder_share >= OVER_MERGE_PLACEHOLDER_SHARE).then(|| OverMergedTemplate {
+                template: template.clone(),
+                merged,
+                variants: variants.len(),
+                placeholder_share,
+                examples: variants.iter().take(3).map(|v| v.to_string()).collect(),
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| b.variants.cmp(&a.variants).then_with(|| a.template.cmp(&b.template)));
+    out
+}
+
 /// Computes clusters that can be merged by structural similarity
 /// This provides post-Drain structural merging based on field patterns
 pub fn clusters_merged_by_shape(clusters: &[DrainCluster]) -> Vec<DrainCluster> {
@@ -223,7 +288,7 @@ pub fn clusters_merged_by_shape(clusters: &[DrainCluster]) -> Vec<DrainCluster>
         }
     }
     
-    merged_clusters.sort_by(|a, b| b.size.cmp(&a.size));
+    merged_clusters.sort_by_key(|c| std::cmp::Reverse(c.size));
     merged_clusters
 }
 
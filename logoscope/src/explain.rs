@@ -0,0 +1,81 @@
+use crate::ai::{self, SummarizeOpts};
+use crate::parser;
+use serde::Serialize;
+
+/// How one line moves through the pipeline: parsed message, masked form, final pattern
+#[derive(Debug, Clone, Serialize)]
+pub struct LineClustering {
+    pub message: String,
+    pub masked: String,
+    pub template: Option<String>,
+    pub level: Option<String>,
+}
+
+/// Masked tokens that differ at the same position (an empty side means the line is shorter)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TokenDiff {
+    pub index: usize,
+    pub a: String,
+    pub b: String,
+}
+
+/// Why two lines ended up in the same pattern or in different ones
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusteringExplanation {
+    pub same_pattern: bool,
+    /// `masking` (identical masked forms), `drain_merged`, `level`, `token_count` or `drain_split`
+    pub cause: &'static str,
+    pub detail: String,
+    pub a: LineClustering,
+    pub b: LineClustering,
+    pub differing_tokens: Vec<TokenDiff>,
+}
+
+/// Run both lines through the summarize pipeline with `opts` and explain the outcome: whether
+/// masking already made them identical, Drain merged or split their masked forms, or the
+/// per-level pattern split kept them apart
+pub fn explain_clustering(line_a: &str, line_b: &str, opts: &SummarizeOpts) -> ClusteringExplanation {
+    let opts = SummarizeOpts { quiet: true, ..opts.clone() };
+    let out = ai::summarize_lines_with_opts(&[line_a, line_b], &[], None, &opts);
+    let describe = |line: &str| {
+        let rec = parser::parse_line_as(line, 1, &[], opts.parse_opts());
+        let masked = opts.canonicalize(&rec.message).masked_text;
+        let idx = out.patterns.iter().position(|p| p.examples.contains(&rec.message));
+        let pattern = idx.map(|i| &out.patterns[i]);
+        let line = LineClustering {
+            masked,
+            template: pattern.map(|p| p.template.clone()),
+            level: pattern.and_then(|p| p.severity.clone()),
+            message: rec.message,
+        };
+        (idx, line)
+    };
+    let ((idx_a, a), (idx_b, b)) = (describe(line_a), describe(line_b));
+    let same_pattern = idx_a.is_some() && idx_a == idx_b;
+
+    let tokens_a: Vec<&str> = a.masked.split_whitespace().collect();
+    let tokens_b: Vec<&str> = b.masked.split_whitespace().collect();
+    let differing_tokens: Vec<TokenDiff> = (0..tokens_a.len().max(tokens_b.len()))
+        .filter_map(|i| {
+            let (ta, tb) = (tokens_a.get(i).copied().unwrap_or(""), tokens_b.get(i).copied().unwrap_or(""));
+            (ta != tb).then(|| TokenDiff { index: i, a: ta.to_string(), b: tb.to_string() })
+        })
+        .collect();
+    let listed = differing_tokens.iter().map(|d| format!("#{} '{}' vs '{}'", d.index, d.a, d.b)).collect::<Vec<_>>().join(", ");
+
+    let (cause, detail) = if same_pattern && differing_tokens.is_empty() {
+        ("masking", "masking reduced both lines to the same text".to_string())
+    } else if same_pattern {
+        ("drain_merged", format!("masked forms differ at {listed}; Drain treated them as one template"))
+    } else if a.level != b.level {
+        ("level", format!("patterns are split per level ({} vs {})",
+            a.level.as_deref().unwrap_or("none"), b.level.as_deref().unwrap_or("none")))
+    } else if tokens_a.len() != tokens_b.len() {
+        ("token_count", format!("masked forms have {} vs {} tokens; Drain only merges lines of equal length",
+            tokens_a.len(), tokens_b.len()))
+    } else {
+        ("drain_split", format!("unmasked tokens differ at {listed}; mask them, or lower --drain-depth/--drain-similarity to merge"))
+    };
+
+    ClusteringExplanation { same_pattern, cause, detail, a, b, differing_tokens }
+}
@@ -30,6 +30,9 @@ pub struct NumericOutlier {
     pub line_index: usize,
 }
 
+/// Source lines kept per categorical anomaly as evidence (first line of each distinct value)
+pub const MAX_EVIDENCE_LINES: usize = 3;
+
 #[derive(Debug, Clone)]
 pub struct CategoricalExplosion {
     pub template: String,
@@ -37,6 +40,69 @@ pub struct CategoricalExplosion {
     pub unique_count: usize,
     pub total: usize,
     pub ratio: f64,
+    /// Indices of the first lines carrying distinct values (at most `MAX_EVIDENCE_LINES`)
+    pub line_indices: Vec<usize>,
+}
+
+/// Per-field override for the cardinality-explosion check
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardinalityRule {
+    /// Field-name glob; `*` matches any run of characters (e.g. `*_id`, `trace*`)
+    pub pattern: String,
+    /// Ratio threshold for matching fields; `None` suppresses the check entirely
+    pub ratio_threshold: Option<f64>,
+}
+
+impl CardinalityRule {
+    /// Parse `GLOB=RATIO` or `GLOB=off`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pattern, value) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("expected GLOB=RATIO or GLOB=off, got '{spec}'"))?;
+        if pattern.is_empty() {
+            return Err(format!("empty field pattern in '{spec}'"));
+        }
+        let ratio_threshold = if value.eq_ignore_ascii_case("off") {
+            None
+        } else {
+            let r: f64 = value.parse().map_err(|_| format!("invalid ratio '{value}' in '{spec}'"))?;
+            if !(0.0..=1.0).contains(&r) {
+                return Err(format!("ratio must be within 0..1 in '{spec}'"));
+            }
+            Some(r)
+        };
+        Ok(Self { pattern: pattern.to_string(), ratio_threshold })
+    }
+}
+
+/// Field names that are unique by design; cardinality explosions are not reported for them
+pub fn default_cardinality_rules() -> Vec<CardinalityRule> {
+    ["id", "*_id", "*.id", "*-id", "uuid", "*_uuid", "guid", "*_guid", "trace*", "span*",
+     "*_token", "token", "nonce", "*_hash", "hash", "*_key"]
+        .iter()
+        .map(|p| CardinalityRule { pattern: p.to_string(), ratio_threshold: None })
+        .collect()
+}
+
+/// Case-insensitive glob match supporting `*` wildcards
+pub fn field_glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let mut rest = name.as_str();
+    // First segment anchors at the start, last at the end
+    if !rest.starts_with(parts[0]) { return false; }
+    rest = &rest[parts[0].len()..];
+    for mid in &parts[1..parts.len() - 1] {
+        match rest.find(mid) {
+            Some(pos) => rest = &rest[pos + mid.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(parts[parts.len() - 1])
 }
 
 pub fn analyze_numeric_outliers(lines: &[&str], z_threshold: f64) -> Vec<NumericOutlier> {
@@ -92,10 +158,22 @@ pub fn analyze_categorical_explosions(
     lines: &[&str],
     ratio_threshold: f64,
     min_total: usize,
+) -> Vec<CategoricalExplosion> {
+    analyze_categorical_explosions_with_rules(lines, ratio_threshold, min_total, &[])
+}
+
+/// Like `analyze_categorical_explosions`, but the first rule whose pattern matches a field
+/// overrides `ratio_threshold` for that field (or suppresses it)
+pub fn analyze_categorical_explosions_with_rules(
+    lines: &[&str],
+    ratio_threshold: f64,
+    min_total: usize,
+    rules: &[CardinalityRule],
 ) -> Vec<CategoricalExplosion> {
     // Count unique categorical values per (template, field)
     let mut sets: HashMap<(String, String), HashSet<String>> = HashMap::new();
     let mut totals: HashMap<(String, String), usize> = HashMap::new();
+    let mut evidence: HashMap<(String, String), Vec<usize>> = HashMap::new();
     for (i, l) in lines.iter().enumerate() {
         let rec = parser::parse_line(l, i + 1);
         let base = if let Some(syn) = rec.synthetic_message { syn } else { rec.message };
@@ -109,22 +187,122 @@ pub fn analyze_categorical_explosions(
                 }
                 // Only categorical: strings that are not numbers
                 if parse_number(v).is_none() {
-                    sets.entry((template.clone(), k.clone())).or_default().insert(v.clone());
-                    *totals.entry((template.clone(), k.clone())).or_default() += 1;
+                    let key = (template.clone(), k.clone());
+                    if sets.entry(key.clone()).or_default().insert(v.clone()) {
+                        let idxs = evidence.entry(key.clone()).or_default();
+                        if idxs.len() < MAX_EVIDENCE_LINES { idxs.push(i); }
+                    }
+                    *totals.entry(key).or_default() += 1;
                 }
             }
         }
     }
     let mut out = Vec::new();
     for ((template, field), set) in sets.into_iter() {
+        let threshold = match rules.iter().find(|r| field_glob_match(&r.pattern, &field)) {
+            Some(rule) => match rule.ratio_threshold {
+                Some(r) => r,
+                None => continue,
+            },
+            None => ratio_threshold,
+        };
         let total = *totals.get(&(template.clone(), field.clone())).unwrap_or(&0);
         if total >= min_total {
             let ratio = (set.len() as f64) / (total as f64);
-            if ratio >= ratio_threshold {
-                out.push(CategoricalExplosion { template, field, unique_count: set.len(), total, ratio });
+            if ratio >= threshold {
+                let line_indices = evidence.remove(&(template.clone(), field.clone())).unwrap_or_default();
+                out.push(CategoricalExplosion { template, field, unique_count: set.len(), total, ratio, line_indices });
+            }
+        }
+    }
+    out
+}
+
+/// Fields with more distinct values than this in the baseline are not treated as enums
+pub const ENUM_MAX_CARDINALITY: usize = 12;
+
+#[derive(Debug, Clone)]
+pub struct EnumDrift {
+    pub template: String,
+    pub field: String,
+    /// Values absent from the baseline, sorted
+    pub new_values: Vec<String>,
+    /// Distinct values known for the field before this window
+    pub known_count: usize,
+    /// Lines carrying one of the new values
+    pub total: usize,
+    /// Indices of the first line of each new value (at most `MAX_EVIDENCE_LINES`)
+    pub line_indices: Vec<usize>,
+}
+
+/// Distinct categorical values per structured field (keyed by field name, across templates)
+pub fn categorical_field_values(lines: &[&str]) -> HashMap<String, HashSet<String>> {
+    let mut out: HashMap<String, HashSet<String>> = HashMap::new();
+    for (i, l) in lines.iter().enumerate() {
+        let rec = parser::parse_line(l, i + 1);
+        if let Some(fields) = rec.flat_fields {
+            for (k, v) in fields.into_iter() {
+                if should_exclude_from_anomaly_detection(&k) || parse_number(&v).is_some() {
+                    continue;
+                }
+                out.entry(k).or_default().insert(v);
+            }
+        }
+    }
+    out
+}
+
+/// Add the categorical values of `lines` to `known`, the enum baseline of a later window. A field
+/// stops growing once it holds more than `ENUM_MAX_CARDINALITY` values: it is no enum, and one
+/// value past the cap is enough to remember that.
+pub fn remember_field_values(known: &mut HashMap<String, HashSet<String>>, lines: &[&str]) {
+    for (field, values) in categorical_field_values(lines) {
+        let seen = known.entry(field).or_default();
+        for v in values {
+            if seen.len() > ENUM_MAX_CARDINALITY { break; }
+            seen.insert(v);
+        }
+    }
+}
+
+/// Report values of low-cardinality categorical fields that are not in `known`.
+/// Only fields present in `known` with at most `ENUM_MAX_CARDINALITY` values are checked,
+/// so unseen fields and ID-like fields never drift.
+pub fn detect_enum_drift(lines: &[&str], known: &HashMap<String, HashSet<String>>) -> Vec<EnumDrift> {
+    if known.is_empty() { return Vec::new(); }
+    #[derive(Default)]
+    struct Fresh { values: HashSet<String>, total: usize, line_indices: Vec<usize> }
+    let mut fresh: HashMap<(String, String), Fresh> = HashMap::new();
+    for (i, l) in lines.iter().enumerate() {
+        let rec = parser::parse_line(l, i + 1);
+        let Some(fields) = rec.flat_fields else { continue };
+        let mut template: Option<String> = None;
+        for (k, v) in fields.into_iter() {
+            let Some(values) = known.get(&k) else { continue };
+            if values.is_empty() || values.len() > ENUM_MAX_CARDINALITY || values.contains(&v) || parse_number(&v).is_some() {
+                continue;
+            }
+            let tpl = template.get_or_insert_with(|| {
+                let base = rec.synthetic_message.clone().unwrap_or_else(|| rec.message.clone());
+                to_generic_template(&masking::mask_text(&base))
+            });
+            let entry = fresh.entry((tpl.clone(), k)).or_default();
+            if entry.values.insert(v) && entry.line_indices.len() < MAX_EVIDENCE_LINES {
+                entry.line_indices.push(i);
             }
+            entry.total += 1;
         }
     }
+    let mut out: Vec<EnumDrift> = fresh
+        .into_iter()
+        .map(|((template, field), Fresh { values, total, line_indices })| {
+            let mut new_values: Vec<String> = values.into_iter().collect();
+            new_values.sort();
+            let known_count = known.get(&field).map_or(0, |s| s.len());
+            EnumDrift { template, field, new_values, known_count, total, line_indices }
+        })
+        .collect();
+    out.sort_by(|a, b| a.field.cmp(&b.field).then_with(|| a.template.cmp(&b.template)));
     out
 }
 
@@ -1,6 +1,7 @@
 pub mod parser;
 pub mod masking;
 pub mod smart_masking;
+pub mod sql_masking;
 pub mod schema;
 pub mod patterns;
 pub mod drain_adapter;
@@ -13,6 +14,15 @@ pub mod field_anomaly;
 pub mod correlation;
 pub mod multiline;
 pub mod analyzers;
+pub mod report;
+pub mod pattern_store;
+pub mod explain;
+pub mod metrics;
+mod compat;
+
+pub use param_extractor::{canonicalize, MaskingResult};
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[cfg(test)]
 mod timestamp_tests;
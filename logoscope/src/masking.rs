@@ -76,6 +76,10 @@ pub fn mask_text(input: &str) -> String {
     let s = RE_EMAIL.replace_all(&s, "<EMAIL>");
     let s = RE_UUID.replace_all(&s, "<UUID>");
     let s = RE_PATH.replace_all(&s, "<PATH>");
+    let s = crate::param_extractor::RE_HOSTNAME.replace_all(&s, |c: &regex::Captures| {
+        let m = c.get(0).expect("whole match");
+        if crate::param_extractor::is_hostname(&s, &m) { "<HOSTNAME>".to_string() } else { m.as_str().to_string() }
+    });
     let s = RE_HEX.replace_all(&s, "<HEX>");
     let s = RE_B64.replace_all(&s, "<B64>");
     // Replace number+unit tokens before generic float/int to avoid partial masking
@@ -0,0 +1,118 @@
+//! Numeric params of free-text patterns as time series, e.g. the three numbers in
+//! `processed 1523 records in 4.2s (362 rec/s)` charted over time
+use crate::ai::SummarizeOpts;
+use crate::param_extractor::ParamType;
+use crate::parser;
+use chrono::{DateTime, Utc};
+use crate::compat::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// One pattern's numeric params over time
+#[derive(Debug, Clone)]
+pub struct MetricSeries {
+    /// Canonicalized message the rows share (`processed <NUM> records in <NUM>s ...`)
+    pub template: String,
+    /// Numeric param names (`NUM`, `NUM_2`, ..., then unit params like `NUM_S`)
+    pub columns: Vec<String>,
+    /// Oldest first
+    pub rows: Vec<MetricRow>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MetricRow {
+    pub timestamp: DateTime<Utc>,
+    /// Per column; None when the line lacked that param
+    pub values: Vec<Option<f64>>,
+}
+
+impl MetricSeries {
+    /// `timestamp,<column>...` with one line per row; missing values are empty cells
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("timestamp,{}\n", self.columns.join(","));
+        for row in &self.rows {
+            csv.push_str(&row.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+            for v in &row.values {
+                csv.push(',');
+                if let Some(v) = v { let _ = write!(csv, "{v}"); }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Leading number of a masked value (`4.2s` -> 4.2, `87%` -> 87); compound durations
+/// (`DURATION`) are already extracted as milliseconds
+fn numeric_value(value: &str) -> Option<f64> {
+    let end = value.char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && c == '-')))
+        .map_or(value.len(), |(i, _)| i);
+    value[..end].parse().ok()
+}
+
+/// Positional params first (`NUM`, `NUM_2`, ..., `NUM_10`), then unit and duration params by name
+fn column_order(name: &str) -> (u8, usize, &str) {
+    match name.strip_prefix("NUM") {
+        Some("") => (0, 1, name),
+        Some(rest) => match rest.strip_prefix('_').and_then(|n| n.parse().ok()) {
+            Some(n) => (0, n, name),
+            None => (1, 0, name),
+        },
+        None => (1, 0, name),
+    }
+}
+
+/// Numeric params of one line, by param name
+type LineMetrics = HashMap<String, Vec<String>>;
+
+fn is_metric_param(name: &str) -> bool {
+    matches!(ParamType::of(name), Some(ParamType::Num | ParamType::Duration))
+}
+
+/// Numeric series per free-text pattern. Only timestamped plaintext lines contribute (JSON
+/// fields already have names); patterns without numeric params are left out. Series are
+/// ordered by row count, largest first.
+pub fn extract_metrics(lines: &[&str], time_keys: &[&str], opts: &SummarizeOpts) -> Vec<MetricSeries> {
+    let parse_opts = opts.parse_opts();
+    let samples: Vec<(String, DateTime<Utc>, LineMetrics)> = lines.par_iter().enumerate()
+        .filter_map(|(i, l)| {
+            let rec = parser::parse_line_as(l, i + 1, time_keys, parse_opts);
+            if rec.flat_fields.is_some() { return None; }
+            let timestamp = rec.timestamp?;
+            let masked = opts.canonicalize(&rec.message);
+            let mut params = masked.extracted_params;
+            params.retain(|name, _| is_metric_param(name));
+            (!params.is_empty()).then_some((masked.masked_text, timestamp, params))
+        })
+        .collect();
+
+    let mut by_template: HashMap<String, Vec<(DateTime<Utc>, LineMetrics)>> = HashMap::new();
+    for (template, timestamp, params) in samples {
+        by_template.entry(template).or_default().push((timestamp, params));
+    }
+    let mut series: Vec<MetricSeries> = by_template.into_iter().map(|(template, mut samples)| {
+        samples.sort_by_key(|(ts, _)| *ts);
+        let mut columns: Vec<String> = samples.iter().flat_map(|(_, p)| p.keys().cloned()).collect();
+        columns.sort_by(|a, b| column_order(a).cmp(&column_order(b)));
+        columns.dedup();
+        let rows = samples.into_iter().map(|(timestamp, params)| MetricRow {
+            timestamp,
+            values: columns.iter()
+                .map(|c| params.get(c).and_then(|vals| vals.first()).and_then(|v| numeric_value(v)))
+                .collect(),
+        }).collect();
+        MetricSeries { template, columns, rows }
+    }).collect();
+    series.sort_by(|a, b| b.rows.len().cmp(&a.rows.len()).then_with(|| a.template.cmp(&b.template)));
+    series
+}
+
+/// RFC 4180 quoting for a CSV cell
+pub fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
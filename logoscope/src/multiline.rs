@@ -7,6 +7,8 @@ pub struct MultiLineAggregator {
     buf: String,
     in_json: bool,
     brace_balance: i32,
+    // Pending CRI partial (`P`) fragments: (prefix rewritten to `F`, joined message)
+    cri_partial: Option<(String, String)>,
 }
 
 static RE_CONT: Lazy<Regex> = Lazy::new(|| {
@@ -32,6 +34,33 @@ fn json_balance_delta(line: &str) -> i32 {
 
 impl MultiLineAggregator {
     pub fn push(&mut self, line: &str) -> Option<String> {
+        // Docker json-file records: handle as the equivalent CRI line
+        if let Some(cri_line) = parser::docker_json_as_cri(line) {
+            return self.push(&cri_line);
+        }
+        // CRI partial lines: hold `P` fragments until the closing `F` line arrives
+        if let Some(cri) = parser::parse_cri_prefix(line) {
+            let head = line[..line.len() - cri.message.len()].trim_end();
+            if cri.partial {
+                // The first fragment starts a new record, so the buffered one is complete
+                let ready = if self.cri_partial.is_none() && !self.in_json && !self.buf.is_empty() {
+                    Some(std::mem::take(&mut self.buf))
+                } else {
+                    None
+                };
+                let pending = self.cri_partial.get_or_insert_with(|| (format!("{}F ", &head[..head.len() - 1]), String::new()));
+                pending.1.push_str(cri.message);
+                return ready;
+            }
+            if let Some((prefix, mut body)) = self.cri_partial.take() {
+                body.push_str(cri.message);
+                return self.push_entry(&format!("{prefix}{body}"));
+            }
+        }
+        self.push_entry(line)
+    }
+
+    fn push_entry(&mut self, line: &str) -> Option<String> {
         // JSON accumulation
         if self.in_json {
             if !self.buf.is_empty() { self.buf.push('\n'); }
@@ -60,9 +89,10 @@ impl MultiLineAggregator {
             return None;
         }
 
-        // Stack trace / continuation lines
+        // Stack trace / continuation lines (judged on the container message for CRI lines)
+        let cri_message = parser::parse_cri_prefix(line).map(|c| c.message);
         let is_new_entry = parser::detect_timestamp_in_text(line).is_some();
-        let is_cont = RE_CONT.is_match(line);
+        let is_cont = RE_CONT.is_match(cri_message.unwrap_or(line));
 
         if self.buf.is_empty() {
             self.buf.push_str(line);
@@ -77,11 +107,17 @@ impl MultiLineAggregator {
 
         // default continuation
         self.buf.push('\n');
-        self.buf.push_str(line);
+        self.buf.push_str(cri_message.unwrap_or(line));
         None
     }
 
     pub fn finish(&mut self) -> Option<String> {
+        // An unterminated CRI partial sequence is flushed as-is
+        if let Some((prefix, body)) = self.cri_partial.take() {
+            if self.buf.is_empty() { return Some(format!("{prefix}{body}")); }
+            self.buf.push('\n');
+            self.buf.push_str(&body);
+        }
         if self.buf.is_empty() { None } else { Some(std::mem::take(&mut self.buf)) }
     }
 }
@@ -3,20 +3,6 @@ use regex::Regex;
 use std::collections::HashMap;
 use std::collections::BTreeMap;
 
-// Re-use the same regexes from masking module for consistency
-static RE_TIMESTAMP: Lazy<Regex> = Lazy::new(|| {
-    // Enhanced ISO8601/RFC3339 with comprehensive timezone and fractional second support
-    // Supports: 2025-08-07T06:41:18Z, 2025-08-07T06:41:18.123456+01:00, 2025-08-07 06:41:18.999-0800
-    // Fractional seconds: 1-9 digits (.1 to .123456789)  
-    // Timezones: Z, ±HH:MM, ±HHMM, ±HH
-    Regex::new(r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d{1,9})?(?:Z|[+-](?:\d{2}(?::?\d{2})?|\d{4}))\b").unwrap()
-});
-
-static RE_SYSLOG_TIMESTAMP: Lazy<Regex> = Lazy::new(|| {
-    // Syslog format: "Aug 02 16:06:51" or variations
-    Regex::new(r"\b(Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec)\s+\d{1,2}\s+\d{2}:\d{2}:\d{2}\b").unwrap()
-});
-
 static RE_NUM_UNIT: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?i)\b-?\d+(?:\.\d+)?(?:\s*)(ms|us|µs|ns|s|m|h|kb|mb|gb|kib|mib|gib|b|%)\b").unwrap()
 });
@@ -25,6 +11,11 @@ static RE_NUM_PERCENT: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\b-?\d+(?:\.\d+)?%").unwrap()
 });
 
+// Compound Go-style durations (at least two number+unit parts): 1h30m, 2m15s, 1m30.5s
+static RE_DURATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b\d+(?:\.\d+)?(?:ns|us|µs|ms|h|m|s)(?:\d+(?:\.\d+)?(?:ns|us|µs|ms|h|m|s))+\b").unwrap()
+});
+
 static RE_URL: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"\b[a-zA-Z][a-zA-Z0-9+.-]*://[^\s"']+\b"#).unwrap()
 });
@@ -45,6 +36,83 @@ static RE_UUID: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b").unwrap()
 });
 
+// Hostname/FQDN candidates: three or more lowercase dotted labels ending in an alphabetic label
+// (e.g. web-prod-03.us-east-1.example.com); `is_hostname` decides which are hosts. URLs and email
+// domains start earlier and therefore win the overlap resolution below. Shared with `masking`.
+pub(crate) static RE_HOSTNAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.){2,}[a-z]{2,63}\b").unwrap()
+});
+
+/// Last labels accepted as a hostname's TLD besides two-letter country codes: common generic
+/// TLDs and private-zone suffixes
+const HOSTNAME_TLDS: &[&str] = &[
+    "com", "net", "org", "io", "dev", "app", "cloud", "info", "biz", "edu", "gov", "mil",
+    "local", "localdomain", "internal", "corp", "lan", "home", "intra", "svc", "cluster",
+];
+
+/// Extensions that make a dotted name a file rather than a host (archive.tar.gz) or an image
+/// (src/main.rs:10)
+const FILE_EXTENSIONS: &[&str] = &[
+    "gz", "tgz", "bz2", "xz", "zst", "zip", "tar", "jar", "war", "class", "log", "txt", "json",
+    "yaml", "yml", "xml", "csv", "conf", "cfg", "ini", "toml", "py", "rs", "go", "js", "ts", "rb",
+    "java", "kt", "so", "dll", "exe", "sh", "md", "html", "pdf", "png", "jpg", "bak", "tmp", "pid",
+    "sock", "lock", "db", "sql",
+];
+
+/// First labels of reverse-DNS package and logger names (org.apache.kafka.clients)
+const REVERSE_DNS_ROOTS: &[&str] = &["com", "org", "net", "io", "edu", "gov", "java", "javax", "android", "kotlin", "scala"];
+
+/// Whether a `RE_HOSTNAME` candidate in `text` names a host: not a file name or reverse-DNS
+/// package, and either ending in a known or country-code TLD or following a host key
+/// (`host=`, `hostname:`, `server ` ...)
+pub(crate) fn is_hostname(text: &str, m: &regex::Match<'_>) -> bool {
+    let name = m.as_str();
+    let first = name.split('.').next().unwrap_or_default();
+    let last = name.rsplit('.').next().unwrap_or_default();
+    if FILE_EXTENSIONS.contains(&last) || REVERSE_DNS_ROOTS.contains(&first) {
+        return false;
+    }
+    if HOSTNAME_TLDS.contains(&last) || last.len() == 2 {
+        return true;
+    }
+    let key = preceding_key(text, m.start());
+    ["host", "hostname", "server", "node", "fqdn"].iter().any(|k| key.ends_with(k))
+}
+
+/// The word or key right before `pos`, lowercased, skipping `=`, `:`, quotes and blanks
+fn preceding_key(text: &str, pos: usize) -> String {
+    text[..pos]
+        .trim_end_matches([' ', '=', ':', '"'])
+        .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+// Container image references: repository (optionally behind a registry host[:port]), then a
+// `:tag` and/or `@sha256:` digest. Only refs with a digest, or a tag on a `/` repository in an
+// image context, are masked (see `image_matches`) so `host:port`, `key:value` text and source
+// locations are left alone.
+static RE_IMAGE_REF: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b([a-z0-9][a-z0-9.-]*(?::\d+)?/)?([a-z0-9][a-z0-9._-]*(?:/[a-z0-9][a-z0-9._-]*)*)(?::(\w[\w.-]{0,127}))?(?:@(sha256:[0-9a-f]{64}))?").unwrap()
+});
+
+// Kubernetes pod names from Deployments: `<base>-<replicaset hash>-<pod hash>`, both hashes in
+// the Kubernetes random alphabet (no vowels, no 0/1/3)
+static RE_POD_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[a-z0-9](?:[a-z0-9-]*[a-z0-9])?-([bcdfghjklmnpqrstvwxz2456789]{5,10}-[bcdfghjklmnpqrstvwxz2456789]{5})\b").unwrap()
+});
+
+// Standalone bracketed thread/goroutine IDs: [thread-42], [pool-1-thread-3], (goroutine 17)
+static RE_BRACKET_THREAD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^|\s)[\[(]((?i:pool-\d+-thread|thread|worker|goroutine|exec|tid)[-_ #:]?\d+)[\])]").unwrap()
+});
+
+// Standalone bracketed request IDs: [req-abc123], (request_id=9f2c1)
+static RE_BRACKET_REQ_ID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^|\s)[\[(]((?i:request[-_]?id|req[-_]?id|request|req|rid)[-_:=]?[A-Za-z0-9-]*\d[A-Za-z0-9-]*)[\])]").unwrap()
+});
+
 static RE_PATH: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?x)
         (?:
@@ -67,6 +135,16 @@ static RE_B64: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\b[A-Za-z0-9+/]{20,}={0,2}\b").unwrap()
 });
 
+// W3C traceparent: version-traceid(32 hex)-spanid(16 hex)-flags
+static RE_TRACEPARENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[0-9a-f]{2}-([0-9a-f]{32})-([0-9a-f]{16})-[0-9a-f]{2}\b").unwrap()
+});
+
+// trace_id=..., traceId: "...", span.id ... in free text; group 1 names the kind
+static RE_TRACE_CONTEXT_KV: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b(trace|span)[._-]?id["']?(?:\s*[=:]\s*|\s+)["']?([0-9a-f]{16,32})\b"#).unwrap()
+});
+
 static RE_HEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\b[0-9a-fA-F]{16,}\b").unwrap()
 });
@@ -99,77 +177,506 @@ static RE_KV_EXTRACT: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"\b(\w+)\s*=\s*(?:"([^"]*)"|([^\s,]+))"#).unwrap()
 });
 
-#[derive(Debug, Clone)]
+// Regex for colon-separated pairs (`Status: 200`, `user: alice`), anchored more tightly than
+// the equals form: the key starts a token with a letter and the colon is followed by blanks,
+// so timestamps (`10:30:45`) and URLs (`http://`) never match.
+// Captures: (key): ("quoted value" | unquoted_value)
+static RE_KV_COLON_EXTRACT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:^|[\s,;{(\[|])([A-Za-z][\w.-]*):[ \t]+(?:"([^"]*)"|([^\s,;]+))"#).unwrap()
+});
+
+// The start of the next pair after a colon value (`user: alice status: 200`)
+static RE_NEXT_PAIR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z][\w.-]*(?::[ \t]|\s*=)").unwrap()
+});
+
+/// Leading level tokens (`ERROR: disk full`) that are not colon-pair keys
+const COLON_KEY_LEVELS: [&str; 10] = ["trace", "debug", "info", "notice", "warn", "warning", "error", "err", "fatal", "critical"];
+
+/// A canonicalized line: the template text Drain clusters on and the values masked out of it
+#[derive(Debug, Clone, PartialEq)]
 pub struct MaskingResult {
+    /// Line with variable parts replaced by placeholders (`<NUM>`, `<IP>`, `key = <KEY>`, ...)
     pub masked_text: String,
+    /// Original values keyed by placeholder name without brackets (`NUM`, `IP`, `KEY`), in line order
     pub extracted_params: HashMap<String, Vec<String>>,
 }
 
-/// Masks text while extracting the original values that were masked
-pub fn mask_and_extract(input: &str) -> MaskingResult {
-    let mut params: HashMap<String, Vec<String>> = HashMap::new();
-    let mut masked = input.to_string();
-    
-    // Collect all matches with positions, types, and replacements
-    let mut all_matches: Vec<(usize, usize, String, String, String)> = Vec::new();
+/// Built-in parameter categories produced by the text maskers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParamType {
+    Timestamp,
+    Url,
+    Ip,
+    Email,
+    Uuid,
+    Hostname,
+    Path,
+    Null,
+    Hex,
+    B64,
+    /// Distributed tracing IDs (W3C traceparent, OpenTelemetry `trace_id`/`span_id`)
+    TraceId,
+    SpanId,
+    /// Large integers outside the plausible epoch range (Snowflake-style IDs)
+    Id,
+    /// Compound durations (`1h30m`, `2m15s`); the extracted value is the total in milliseconds
+    Duration,
+    /// Tag and/or digest of a container image reference; the repository stays in the template
+    /// (`registry.io/team/app:<IMAGE_TAG>`)
+    ImageTag,
+    /// Random `-<replicaset>-<pod>` suffix of a Kubernetes pod name; the base name stays in the
+    /// template (`app-<POD_SUFFIX>`)
+    PodSuffix,
+    /// Standalone bracketed thread/goroutine IDs (`[thread-42]` -> `[<THREAD>]`)
+    Thread,
+    /// Standalone bracketed request IDs (`[req-abc123]` -> `[<REQ_ID>]`)
+    ReqId,
+    /// Quoted phrase in free text, masked whole under `QuotedStrings::Mask`
+    Quoted,
+    /// Plain numbers as well as numbers with units (`NUM_MS`, `NUM_%`, ...)
+    Num,
+}
+
+impl ParamType {
+    /// Canonical param name used in templates and `extracted_params`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParamType::Timestamp => "TIMESTAMP",
+            ParamType::Url => "URL",
+            ParamType::Ip => "IP",
+            ParamType::Email => "EMAIL",
+            ParamType::Uuid => "UUID",
+            ParamType::Hostname => "HOSTNAME",
+            ParamType::Path => "PATH",
+            ParamType::Null => "NULL",
+            ParamType::Hex => "HEX",
+            ParamType::B64 => "B64",
+            ParamType::TraceId => "TRACE_ID",
+            ParamType::SpanId => "SPAN_ID",
+            ParamType::Id => "ID",
+            ParamType::Duration => "DURATION",
+            ParamType::ImageTag => "IMAGE_TAG",
+            ParamType::PodSuffix => "POD_SUFFIX",
+            ParamType::Thread => "THREAD",
+            ParamType::ReqId => "REQ_ID",
+            ParamType::Quoted => "QUOTED",
+            ParamType::Num => "NUM",
+        }
+    }
+
+    /// Template placeholder for this type, e.g. `<IP>`
+    pub fn placeholder(&self) -> String {
+        format!("<{}>", self.as_str())
+    }
+
+    /// Category of a possibly positional param name (`IP_3` -> Ip); None for field-derived names
+    pub fn of(name: &str) -> Option<ParamType> {
+        ParamType::from_param_name(get_base_param_type(name))
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, ParamType::Num)
+    }
+
+    pub fn is_time(&self) -> bool {
+        matches!(self, ParamType::Timestamp)
+    }
+
+    /// Parse a user-supplied type name, case-insensitively (e.g. "email", "IP")
+    pub fn parse(name: &str) -> Result<ParamType, String> {
+        ParamType::from_param_name(&name.trim().to_ascii_uppercase())
+            .ok_or_else(|| format!("unknown param type '{name}'"))
+    }
+
+    /// Map a masker param name (e.g. "IP", "NUM_MS") back to its category
+    pub fn from_param_name(name: &str) -> Option<ParamType> {
+        match name {
+            "TIMESTAMP" => Some(ParamType::Timestamp),
+            "URL" => Some(ParamType::Url),
+            "IP" => Some(ParamType::Ip),
+            "EMAIL" => Some(ParamType::Email),
+            "UUID" => Some(ParamType::Uuid),
+            "HOSTNAME" => Some(ParamType::Hostname),
+            "PATH" => Some(ParamType::Path),
+            "NULL" => Some(ParamType::Null),
+            "HEX" => Some(ParamType::Hex),
+            "B64" => Some(ParamType::B64),
+            "TRACE_ID" => Some(ParamType::TraceId),
+            "SPAN_ID" => Some(ParamType::SpanId),
+            "ID" => Some(ParamType::Id),
+            "DURATION" => Some(ParamType::Duration),
+            "IMAGE_TAG" => Some(ParamType::ImageTag),
+            "POD_SUFFIX" => Some(ParamType::PodSuffix),
+            "THREAD" => Some(ParamType::Thread),
+            "REQ_ID" => Some(ParamType::ReqId),
+            "QUOTED" => Some(ParamType::Quoted),
+            n if n == "NUM" || n.starts_with("NUM_") => Some(ParamType::Num),
+            _ => None,
+        }
+    }
+}
+
+/// Helper function to get the base parameter type from potentially numbered parameters
+/// e.g., "NUM_2" -> "NUM", "IP_3" -> "IP", "NUM" -> "NUM"
+pub fn get_base_param_type(param_type: &str) -> &str {
+    if let Some(underscore_pos) = param_type.rfind('_') {
+        let suffix = &param_type[underscore_pos + 1..];
+        // Check if suffix is a number
+        if suffix.chars().all(|c| c.is_ascii_digit()) {
+            return &param_type[..underscore_pos];
+        }
+    }
+    param_type
+}
+
+/// Whether a param holds time values, either the built-in `TIMESTAMP` type or a
+/// time-like field name (`time`, `ts`, `date`, `datetime`); these are unique per line
+pub fn is_time_param(name: &str) -> bool {
+    let base = get_base_param_type(name);
+    ParamType::from_param_name(base).is_some_and(|t| t.is_time())
+        || ["TIME", "TS", "DATE", "DATETIME", "TIMESTAMP"].iter().any(|t| base.eq_ignore_ascii_case(t))
+}
+
+/// Whether a param holds HTTP status codes: `STATUS_CODE` from access-log masking or a
+/// status field such as `status`, `http.status_code` or `response_status`
+pub fn is_status_param(name: &str) -> bool {
+    let base = get_base_param_type(name);
+    ["STATUS_CODE", "STATUS", "HTTP_STATUS", "HTTP_STATUS_CODE", "RESPONSE_STATUS"].iter().any(|t| base.eq_ignore_ascii_case(t))
+}
+
+/// Request timing fields from access-log masking (ELB, nginx); bare values are seconds
+const ACCESS_LOG_TIMINGS: [&str; 6] = [
+    "REQUEST_TIME", "RESPONSE_TIME", "TARGET_TIME",
+    "UPSTREAM_RESPONSE_TIME", "UPSTREAM_CONNECT_TIME", "UPSTREAM_HEADER_TIME",
+];
+
+/// Whether a param holds request latencies: the access-log timing fields (`REQUEST_TIME`,
+/// `RESPONSE_TIME`, ...) or a field named like `latency`, `duration` or `elapsed`
+pub fn is_latency_param(name: &str) -> bool {
+    let base = get_base_param_type(name).to_ascii_uppercase();
+    ACCESS_LOG_TIMINGS.contains(&base.as_str())
+        || ["LATENCY", "DURATION", "ELAPSED"].iter().any(|t| base.contains(t))
+}
+
+/// Milliseconds per unit of a duration suffix (`ns`, `us`, `ms`, `s`, `m`/`min`, `h`)
+fn unit_scale_ms(unit: &str) -> Option<f64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "ns" => Some(1e-6),
+        "us" | "µs" => Some(1e-3),
+        "ms" => Some(1.0),
+        "s" => Some(1000.0),
+        "m" | "min" => Some(60_000.0),
+        "h" => Some(3_600_000.0),
+        _ => None,
+    }
+}
+
+/// Total milliseconds of a duration string made of number+unit parts (`150ms`, `1h30m`,
+/// `1m30.5s`); None when any part lacks a known unit
+pub fn duration_ms(text: &str) -> Option<f64> {
+    let mut rest = text.trim();
+    if rest.is_empty() { return None; }
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let num_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).filter(|&i| i > 0)?;
+        let n: f64 = rest[..num_end].parse().ok()?;
+        let unit = &rest[num_end..];
+        let unit_end = unit.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(unit.len());
+        total += n * unit_scale_ms(unit[..unit_end].trim())?;
+        rest = &unit[unit_end..];
+    }
+    total.is_finite().then_some(total)
+}
+
+/// A latency param value in milliseconds. Unit suffixes (`ns`, `us`, `ms`, `s`, `m`, `h`) and
+/// compound durations (`1m30.5s`) are honored; bare numbers are seconds for the access-log timing fields and `_s`/`_sec` names,
+/// micro/nanoseconds for `_us`/`_ns` names, and milliseconds otherwise. Negative values (ELB
+/// logs `-1` when a backend timed out) and non-numbers are None.
+pub fn latency_ms(name: &str, value: &str) -> Option<f64> {
+    let v = value.trim();
+    if !v.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return duration_ms(v);
+    }
+    let n: f64 = v.parse().ok().filter(|n: &f64| n.is_finite())?;
+    let base = get_base_param_type(name).to_ascii_uppercase();
+    let scale = if ACCESS_LOG_TIMINGS.contains(&base.as_str()) || ["_S", "_SEC", "_SECS", "_SECONDS"].iter().any(|s| base.ends_with(s)) {
+        1000.0
+    } else if base.ends_with("_US") {
+        1e-3
+    } else if base.ends_with("_NS") {
+        1e-6
+    } else {
+        1.0
+    };
+    Some(n * scale)
+}
+
+/// Whether a param is numeric and (nearly) unique per occurrence, like nanosecond
+/// fields or plain `NUM` with over 90% distinct values; these skip value anomaly checks
+pub fn is_high_cardinality_numeric(name: &str, cardinality: usize, total: usize) -> bool {
+    let base = get_base_param_type(name);
+    base == "NS" || (base == ParamType::Num.as_str() && cardinality as f64 / total as f64 > 0.9)
+}
+
+/// Lower bound of the epoch range bare integers are accepted in as timestamps: 2000-01-01T00:00:00Z
+pub const EPOCH_MIN_SECS: i64 = 946_684_800;
+/// Upper bound of the epoch range bare integers are accepted in as timestamps: 2100-01-01T00:00:00Z
+pub const EPOCH_MAX_SECS: i64 = 4_102_444_800;
+/// Integers with at least this many digits are a timestamp or an ID, never a plain `NUM`
+pub const LARGE_INT_MIN_DIGITS: usize = 10;
+
+/// Epoch seconds of an all-digit string read as epoch seconds (10 digits), millis (13) or
+/// micros (16), if that falls within [`EPOCH_MIN_SECS`, `EPOCH_MAX_SECS`].
+/// 19-digit nanoseconds are not accepted: they overlap Snowflake-style IDs.
+pub fn plausible_epoch_secs(digits: &str) -> Option<i64> {
+    if !digits.bytes().all(|b| b.is_ascii_digit()) { return None; }
+    let divisor = match digits.len() {
+        10 => 1,
+        13 => 1_000,
+        16 => 1_000_000,
+        _ => return None,
+    };
+    let secs = digits.parse::<i64>().ok()? / divisor;
+    (EPOCH_MIN_SECS..=EPOCH_MAX_SECS).contains(&secs).then_some(secs)
+}
+
+/// Type of an integer token: `TIMESTAMP` for plausible epochs, `ID` for other integers of
+/// at least [`LARGE_INT_MIN_DIGITS`] digits, `NUM` otherwise
+pub fn classify_integer(token: &str) -> ParamType {
+    if plausible_epoch_secs(token).is_some() {
+        ParamType::Timestamp
+    } else if token.len() >= LARGE_INT_MIN_DIGITS && token.bytes().all(|b| b.is_ascii_digit()) {
+        ParamType::Id
+    } else {
+        ParamType::Num
+    }
+}
+
+/// Param types treated as sensitive by default when redacting output:
+/// identifiers and addresses, but not numbers, timestamps or paths
+pub const DEFAULT_REDACT_TYPES: &[ParamType] = &[
+    ParamType::Email,
+    ParamType::Ip,
+    ParamType::Uuid,
+    ParamType::Hostname,
+    ParamType::Url,
+    ParamType::Hex,
+    ParamType::B64,
+];
+
+/// A masker match: (start, end, original value, param type, replacement)
+type RawMatch = (usize, usize, String, String, String);
+
+/// Non-overlapping timestamps in `text` in every format smart masking knows
+/// (ISO8601/RFC3339, Apache/Nginx bracket, syslog, time with zone), earliest and longest first.
+/// Bracket-format matches exclude the surrounding brackets.
+fn timestamp_matches(text: &str) -> Vec<regex::Match<'_>> {
+    let mut found: Vec<regex::Match<'_>> = crate::smart_masking::TIMESTAMP_PATTERNS
+        .iter()
+        .flat_map(|re| re.captures_iter(text).filter_map(|c| c.get(1)))
+        .collect();
+    found.sort_by(|a, b| a.start().cmp(&b.start()).then_with(|| b.end().cmp(&a.end())));
+    let mut last_end = 0;
+    found.retain(|m| {
+        let keep = m.start() >= last_end;
+        if keep { last_end = m.end(); }
+        keep
+    });
+    found
+}
+
+/// A tag-only ref is an image when its registry looks like a host (`registry.io/`, `host:5000/`,
+/// `localhost/`) or it follows an image keyword (`image`, `pulling` ...), and its repository
+/// does not end in a file name (`src/main.rs:10`)
+fn tagged_image_context(input: &str, cap: &regex::Captures<'_>) -> bool {
+    let repo = &cap[2];
+    let last = repo.rsplit('/').next().unwrap_or_default();
+    if last.rsplit_once('.').is_some_and(|(_, ext)| FILE_EXTENSIONS.contains(&ext)) {
+        return false;
+    }
+    let registry_host = cap.get(1).is_some_and(|r| r.as_str().contains(['.', ':']) || r.as_str() == "localhost/");
+    let key = preceding_key(input, cap.get(0).unwrap().start());
+    registry_host || ["image", "pull", "pulling", "pulled", "push", "pushing", "pushed"].iter().any(|k| key.ends_with(k))
+}
+
+/// Container image refs in `input`; the match spans the whole ref (so it outranks the path and
+/// hostname maskers) but only the tag/digest is replaced and extracted
+fn image_matches(input: &str) -> Vec<RawMatch> {
+    let mut out = Vec::new();
+    for cap in RE_IMAGE_REF.captures_iter(input) {
+        let full = cap.get(0).unwrap();
+        let has_slash = cap.get(1).is_some() || cap[2].contains('/');
+        let (tag, digest) = (cap.get(3), cap.get(4));
+        if digest.is_none() && !(tag.is_some() && has_slash && tagged_image_context(input, &cap)) { continue; }
+        // Starts mid-token (e.g. inside a path or URL): leave it to those maskers
+        if input[..full.start()].ends_with(['/', ':', '.', '@']) { continue; }
+        let name_end = cap.get(2).unwrap().end();
+        let sep = &input[name_end..name_end + 1];
+        let value = input[name_end + 1..full.end()].to_string();
+        let replacement = format!("{}{sep}{}", &input[full.start()..name_end], ParamType::ImageTag.placeholder());
+        out.push((full.start(), full.end(), value, ParamType::ImageTag.as_str().to_string(), replacement));
+    }
+    out
+}
+
+/// Contents of standalone bracketed ID tokens matched by `re`: the brackets must pair up and be
+/// followed by whitespace, `:` or the end of the line, so bracketed text inside words is skipped
+fn bracketed_id_matches(input: &str, re: &Regex, param_type: ParamType) -> Vec<RawMatch> {
+    let mut out = Vec::new();
+    for cap in re.captures_iter(input) {
+        let (full, inner) = (cap.get(0).unwrap(), cap.get(1).unwrap());
+        let open = input.as_bytes()[inner.start() - 1];
+        let close = input.as_bytes()[full.end() - 1];
+        let paired = matches!((open, close), (b'[', b']') | (b'(', b')'));
+        let standalone = input[full.end()..].chars().next().is_none_or(|c| c.is_whitespace() || c == ':');
+        if paired && standalone {
+            out.push(typed_match(inner, param_type));
+        }
+    }
+    out
+}
+
+/// How quoted phrases (`"..."`, `'...'`) in free text are treated during masking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotedStrings {
+    /// No special treatment: maskers apply inside quotes like anywhere else
+    #[default]
+    Split,
+    /// Quoted phrases stay verbatim; nothing inside them is masked
+    Keep,
+    /// Each quoted phrase becomes one `<QUOTED>` param holding its contents
+    Mask,
+}
+
+impl QuotedStrings {
+    /// Parse a user-supplied mode name: split | keep | mask
+    pub fn parse(name: &str) -> Result<QuotedStrings, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "split" => Ok(QuotedStrings::Split),
+            "keep" => Ok(QuotedStrings::Keep),
+            "mask" => Ok(QuotedStrings::Mask),
+            _ => Err(format!("unknown quoted-string mode '{name}' (expected split, keep or mask)")),
+        }
+    }
+}
+
+/// Quoted phrases in `input`, quotes included. A quote opens only at the start of the text or
+/// after a non-alphanumeric character and closes only before one, so apostrophes (`can't`) are
+/// not quotes; backslash-escaped quotes do not close. Unbalanced quotes are ignored.
+fn quoted_spans(input: &str) -> Vec<(usize, usize)> {
+    let bytes = input.as_bytes();
+    let boundary = |i: Option<usize>| i.and_then(|i| bytes.get(i)).is_none_or(|b| !b.is_ascii_alphanumeric());
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let q = bytes[i];
+        if (q == b'"' || q == b'\'') && boundary(i.checked_sub(1)) {
+            let mut j = i + 1;
+            let mut close = None;
+            while j < bytes.len() && bytes[j] != b'\n' {
+                if bytes[j] == b'\\' { j += 2; continue; }
+                if bytes[j] == q && boundary(Some(j + 1)) { close = Some(j); break; }
+                j += 1;
+            }
+            if let Some(j) = close {
+                spans.push((i, j + 1));
+                i = j + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    spans
+}
+
+/// A masker match for a built-in type, replaced by its `<TYPE>` placeholder
+fn typed_match(m: regex::Match<'_>, param_type: ParamType) -> RawMatch {
+    (m.start(), m.end(), m.as_str().to_string(), param_type.as_str().to_string(), param_type.placeholder())
+}
+
+/// Collects every candidate masker match in `input`, possibly overlapping
+fn collect_raw_matches(input: &str) -> Vec<RawMatch> {
+    let mut all_matches: Vec<RawMatch> = Vec::new();
     
     // Timestamps (highest priority)
-    for cap in RE_TIMESTAMP.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "TIMESTAMP".to_string(), "<TIMESTAMP>".to_string()));
+    for m in timestamp_matches(input) {
+        all_matches.push(typed_match(m, ParamType::Timestamp));
+    }
+
+    // Trace context (before hex so trace/span IDs win ties)
+    for cap in RE_TRACEPARENT.captures_iter(input) {
+        if let (Some(trace), Some(span)) = (cap.get(1), cap.get(2)) {
+            all_matches.push(typed_match(trace, ParamType::TraceId));
+            all_matches.push(typed_match(span, ParamType::SpanId));
+        }
+    }
+    for cap in RE_TRACE_CONTEXT_KV.captures_iter(input) {
+        let kind = if cap[1].eq_ignore_ascii_case("trace") { ParamType::TraceId } else { ParamType::SpanId };
+        all_matches.push(typed_match(cap.get(2).unwrap(), kind));
     }
     
     // URLs
     for cap in RE_URL.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "URL".to_string(), "<URL>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Url));
     }
+
+    // Container image tags/digests
+    all_matches.extend(image_matches(input));
+
+    // Bracketed thread and request IDs (before numbers so `thread-42` is one param)
+    all_matches.extend(bracketed_id_matches(input, &RE_BRACKET_THREAD, ParamType::Thread));
+    all_matches.extend(bracketed_id_matches(input, &RE_BRACKET_REQ_ID, ParamType::ReqId));
     
     // IP addresses (before numbers!)
     for cap in RE_IPV6.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "IP".to_string(), "<IP>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Ip));
     }
     
     for cap in RE_IPV4.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "IP".to_string(), "<IP>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Ip));
     }
     
     // Email addresses
     for cap in RE_EMAIL.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "EMAIL".to_string(), "<EMAIL>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Email));
     }
     
     // UUIDs
     for cap in RE_UUID.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "UUID".to_string(), "<UUID>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Uuid));
     }
     
+    // Hostnames (before paths and numbers so host labels are not fragmented)
+    for cap in RE_HOSTNAME.find_iter(input).filter(|m| is_hostname(input, m)) {
+        all_matches.push(typed_match(cap, ParamType::Hostname));
+    }
+    
+    // Kubernetes pod name suffixes (at least one digit, so hyphenated words are left alone)
+    for cap in RE_POD_NAME.captures_iter(input) {
+        if let Some(suffix) = cap.get(1).filter(|m| m.as_str().bytes().any(|b| b.is_ascii_digit())) {
+            all_matches.push(typed_match(suffix, ParamType::PodSuffix));
+        }
+    }
+
     // Paths (higher priority than Base64)
     for cap in RE_PATH.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "PATH".to_string(), "<PATH>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Path));
     }
     
     // Null values
     for cap in RE_NULL.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "NULL".to_string(), "<NULL>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Null));
     }
     
-    // Hex strings
-    for cap in RE_HEX.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "HEX".to_string(), "<HEX>".to_string()));
+    // Hex strings (all-digit runs are left to the integer classifier)
+    for cap in RE_HEX.find_iter(input).filter(|m| !m.as_str().bytes().all(|b| b.is_ascii_digit())) {
+        all_matches.push(typed_match(cap, ParamType::Hex));
     }
     
     // Base64
     for cap in RE_B64.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "B64".to_string(), "<B64>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::B64));
     }
     
     // Percentages (handle separately since % doesn't have word boundary)
@@ -178,6 +685,13 @@ pub fn mask_and_extract(input: &str) -> MaskingResult {
                          "NUM_%".to_string(), "<NUM>%".to_string()));
     }
     
+    // Compound durations, normalized to total milliseconds (before numbers with units)
+    for m in RE_DURATION.find_iter(input) {
+        if let Some(ms) = duration_ms(m.as_str()) {
+            all_matches.push((m.start(), m.end(), ms.to_string(), ParamType::Duration.as_str().to_string(), ParamType::Duration.placeholder()));
+        }
+    }
+
     // Numbers with units
     for cap in RE_NUM_UNIT.captures_iter(input) {
         let full_match = cap.get(0).unwrap();
@@ -190,33 +704,48 @@ pub fn mask_and_extract(input: &str) -> MaskingResult {
     
     // Generic floats
     for cap in RE_FLOAT.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "NUM".to_string(), "<NUM>".to_string()));
+        all_matches.push(typed_match(cap, ParamType::Num));
     }
     
-    // Generic integers (lowest priority)
+    // Generic integers (lowest priority); large ones are epochs or IDs
     for cap in RE_INT.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "NUM".to_string(), "<NUM>".to_string()));
+        all_matches.push(typed_match(cap, classify_integer(cap.as_str())));
     }
     
+    all_matches
+}
+
+/// Sorts matches and drops overlaps, keeping the earliest (and for ties, longest) match
+fn resolve_overlaps(mut all_matches: Vec<RawMatch>) -> Vec<RawMatch> {
     // Sort by start position, then by length (longer matches first for same position)
     all_matches.sort_by(|a, b| {
         a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1))
     });
     
     // Remove overlapping matches (keep first/longer match)
-    let mut filtered_matches: Vec<(usize, usize, String, String, String)> = Vec::new();
+    let mut filtered_matches: Vec<RawMatch> = Vec::new();
     let mut last_end = 0;
     
-    for (start, end, value, param_type, replacement) in all_matches {
-        if start >= last_end {
-            filtered_matches.push((start, end, value.clone(), param_type.clone(), replacement));
-            params.entry(param_type).or_default().push(value);
-            last_end = end;
+    for m in all_matches {
+        if m.0 >= last_end {
+            last_end = m.1;
+            filtered_matches.push(m);
         }
     }
     
+    filtered_matches
+}
+
+/// Masks text while extracting the original values that were masked
+pub fn mask_and_extract(input: &str) -> MaskingResult {
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    let mut masked = input.to_string();
+    
+    let filtered_matches = resolve_overlaps(collect_raw_matches(input));
+    for (_, _, value, param_type, _) in &filtered_matches {
+        params.entry(param_type.clone()).or_default().push(value.clone());
+    }
+    
     // Apply replacements from end to beginning
     for (start, end, _, _, replacement) in filtered_matches.iter().rev() {
         masked.replace_range(*start..*end, replacement);
@@ -228,116 +757,67 @@ pub fn mask_and_extract(input: &str) -> MaskingResult {
     }
 }
 
+/// Redacts only the requested parameter categories in a single line, leaving all other
+/// text intact. Intended for log scrubbing where no clustering is needed.
+pub fn redact_line(line: &str, types: &[ParamType]) -> String {
+    let requested: Vec<RawMatch> = collect_raw_matches(line)
+        .into_iter()
+        .filter(|m| ParamType::from_param_name(&m.3).is_some_and(|t| types.contains(&t)))
+        .collect();
+    
+    let mut redacted = line.to_string();
+    for (start, end, _, _, replacement) in resolve_overlaps(requested).iter().rev() {
+        redacted.replace_range(*start..*end, replacement);
+    }
+    redacted
+}
+
+/// Collapse ID-like path segments of a URL or path into `{id}` so REST routes group together,
+/// e.g. `/users/12345/orders/678` -> `/users/{id}/orders/{id}`. Numeric, UUID and long hex
+/// segments are replaced; scheme, host and query string are left untouched.
+pub fn templatize_path(value: &str) -> String {
+    fn is_id_segment(seg: &str) -> bool {
+        if seg.is_empty() { return false; }
+        seg.chars().all(|c| c.is_ascii_digit())
+            || RE_UUID.is_match(seg) && seg.len() == 36
+            || (seg.len() >= 16 && seg.chars().all(|c| c.is_ascii_hexdigit()))
+    }
+
+    let path_start = match value.find("://") {
+        Some(scheme_end) => value[scheme_end + 3..].find('/').map(|i| scheme_end + 3 + i).unwrap_or(value.len()),
+        None => 0,
+    };
+    let path_end = value[path_start..].find(['?', '#']).map(|i| path_start + i).unwrap_or(value.len());
+
+    let templated: Vec<&str> = value[path_start..path_end]
+        .split('/')
+        .map(|seg| if is_id_segment(seg) { "{id}" } else { seg })
+        .collect();
+    format!("{}{}{}", &value[..path_start], templated.join("/"), &value[path_end..])
+}
+
 /// Masks text while extracting parameters with positional disambiguation for repeated types
 /// This solves the problem where multiple <NUM> parameters get lumped together
 pub fn mask_and_extract_with_disambiguation(input: &str) -> MaskingResult {
+    mask_and_extract_quoted(input, QuotedStrings::Split)
+}
+
+/// `mask_and_extract_with_disambiguation` with quoted phrases kept verbatim or masked whole
+pub fn mask_and_extract_quoted(input: &str, quoted: QuotedStrings) -> MaskingResult {
     let mut params: HashMap<String, Vec<String>> = HashMap::new();
     let mut masked = input.to_string();
     
-    // Collect all matches with positions, types, and replacements
-    let mut all_matches: Vec<(usize, usize, String, String, String)> = Vec::new();
-    
-    // Timestamps (highest priority)
-    for cap in RE_TIMESTAMP.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "TIMESTAMP".to_string(), "<TIMESTAMP>".to_string()));
-    }
-    
-    // URLs
-    for cap in RE_URL.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "URL".to_string(), "<URL>".to_string()));
-    }
-    
-    // IP addresses (before numbers!)
-    for cap in RE_IPV6.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "IP".to_string(), "<IP>".to_string()));
-    }
-    
-    for cap in RE_IPV4.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "IP".to_string(), "<IP>".to_string()));
-    }
-    
-    // Email addresses
-    for cap in RE_EMAIL.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "EMAIL".to_string(), "<EMAIL>".to_string()));
-    }
-    
-    // UUIDs
-    for cap in RE_UUID.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "UUID".to_string(), "<UUID>".to_string()));
-    }
-    
-    // Paths (higher priority than Base64)
-    for cap in RE_PATH.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "PATH".to_string(), "<PATH>".to_string()));
-    }
-    
-    // Null values
-    for cap in RE_NULL.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "NULL".to_string(), "<NULL>".to_string()));
-    }
-    
-    // Hex strings
-    for cap in RE_HEX.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "HEX".to_string(), "<HEX>".to_string()));
-    }
-    
-    // Base64
-    for cap in RE_B64.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "B64".to_string(), "<B64>".to_string()));
-    }
-    
-    // Percentages (handle separately since % doesn't have word boundary)
-    for cap in RE_NUM_PERCENT.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(),
-                         "NUM_%".to_string(), "<NUM>%".to_string()));
-    }
-    
-    // Numbers with units
-    for cap in RE_NUM_UNIT.captures_iter(input) {
-        let full_match = cap.get(0).unwrap();
-        let unit = cap.get(1).map(|m| m.as_str()).unwrap_or("");
-        if unit != "%" {  // Skip % here since we handle it separately
-            all_matches.push((full_match.start(), full_match.end(), full_match.as_str().to_string(),
-                             format!("NUM_{}", unit.to_uppercase()), format!("<NUM>{unit}")));
+    let mut raw_matches = collect_raw_matches(input);
+    if quoted != QuotedStrings::Split {
+        // A phrase starts at its quote, so it outranks every match inside it
+        for (start, end) in quoted_spans(input) {
+            let value = input[start + 1..end - 1].to_string();
+            raw_matches.push((start, end, value, ParamType::Quoted.as_str().to_string(), ParamType::Quoted.placeholder()));
         }
     }
-    
-    // Generic floats
-    for cap in RE_FLOAT.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "NUM".to_string(), "<NUM>".to_string()));
-    }
-    
-    // Generic integers (lowest priority)
-    for cap in RE_INT.find_iter(input) {
-        all_matches.push((cap.start(), cap.end(), cap.as_str().to_string(), 
-                         "NUM".to_string(), "<NUM>".to_string()));
-    }
-    
-    // Sort by start position, then by length (longer matches first for same position)
-    all_matches.sort_by(|a, b| {
-        a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1))
-    });
-    
-    // Remove overlapping matches (keep first/longer match)
-    let mut filtered_matches: Vec<(usize, usize, String, String, String)> = Vec::new();
-    let mut last_end = 0;
-    
-    for (start, end, value, param_type, replacement) in all_matches {
-        if start >= last_end {
-            filtered_matches.push((start, end, value, param_type, replacement));
-            last_end = end;
-        }
+    let mut filtered_matches = resolve_overlaps(raw_matches);
+    if quoted == QuotedStrings::Keep {
+        filtered_matches.retain(|m| m.3 != ParamType::Quoted.as_str());
     }
     
     // Track position counts for disambiguation (process in forward order to get correct numbering)
@@ -387,22 +867,286 @@ pub fn mask_and_extract_with_disambiguation(input: &str) -> MaskingResult {
     }
 }
 
+/// Param name for a structured field: uppercase with `-`/`.` as `_`.
+/// Tracing ID aliases (`traceId`, `trace.id`, `otel.span_id`, ...) map to `TRACE_ID`/`SPAN_ID`.
+pub fn field_param_name(field_name: &str) -> String {
+    let last = field_name.rsplit('.').next().unwrap_or(field_name);
+    let squashed: String = last.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase();
+    let alias = match squashed.as_str() {
+        "traceid" => Some(ParamType::TraceId),
+        "spanid" => Some(ParamType::SpanId),
+        // `trace.id` / `span.id` (Elastic Common Schema)
+        "id" => match field_name.rsplit('.').nth(1).map(str::to_ascii_lowercase).as_deref() {
+            Some("trace") => Some(ParamType::TraceId),
+            Some("span") => Some(ParamType::SpanId),
+            _ => None,
+        },
+        _ => None,
+    };
+    match alias {
+        Some(t) => t.as_str().to_string(),
+        None => field_name.to_uppercase().replace("-", "_").replace(".", "_"),
+    }
+}
+
+/// Trace ID carried by a line: a tracing-ID field or `traceparent` field when structured,
+/// otherwise a W3C traceparent or `trace_id=` pair in the text. Lowercased for matching.
+pub fn find_trace_id(flat_fields: Option<&BTreeMap<String, String>>, text: &str) -> Option<String> {
+    if let Some(fields) = flat_fields {
+        for (name, value) in fields {
+            if field_param_name(name) == ParamType::TraceId.as_str() && !value.is_empty() {
+                return Some(value.to_ascii_lowercase());
+            }
+            if name.eq_ignore_ascii_case("traceparent") {
+                if let Some(c) = RE_TRACEPARENT.captures(&value.to_ascii_lowercase()) {
+                    return Some(c[1].to_string());
+                }
+            }
+        }
+    }
+    let lower = text.to_ascii_lowercase();
+    RE_TRACEPARENT.captures(&lower)
+        .map(|c| c[1].to_string())
+        .or_else(|| {
+            RE_TRACE_CONTEXT_KV.captures_iter(&lower)
+                .find(|c| &c[1] == "trace")
+                .map(|c| c[2].to_string())
+        })
+}
+
+/// Strips trailing `)`, `]` and `}` that have no opener inside `value`
+fn trim_unbalanced_closers(value: &str) -> &str {
+    let mut v = value;
+    while let Some(last) = v.chars().last() {
+        let open = match last {
+            ')' => '(',
+            ']' => '[',
+            '}' => '{',
+            _ => break,
+        };
+        if v.matches(open).count() >= v.matches(last).count() {
+            break;
+        }
+        v = &v[..v.len() - 1];
+    }
+    v
+}
+
+/// `trace.id=...` in free text: the pair regex only sees `id`, so look back for the namespace
+fn dotted_trace_param(before: &str, key: &str) -> Option<String> {
+    let prefix = before.strip_suffix('.')?;
+    let namespace = prefix.rsplit(|c: char| !c.is_ascii_alphanumeric() && c != '_').next()?;
+    let name = field_param_name(&format!("{namespace}.{key}"));
+    (name == ParamType::TraceId.as_str() || name == ParamType::SpanId.as_str()).then_some(name)
+}
+
+/// Which structured fields are tracked as params. Globs use `*` and match case-insensitively
+/// against the flattened field name (e.g. `http.*`, `*_id`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldFilter {
+    /// When non-empty, only matching fields are tracked
+    pub track: Vec<String>,
+    /// Matching fields are never tracked; takes precedence over `track`
+    pub ignore: Vec<String>,
+    /// Nested fields always tracked under a friendly alias and listed first in templates
+    pub extract: Vec<ExtractRule>,
+    /// Merge `userId`, `user_id` and `UserID` by naming fields with `normalize_field_name`
+    pub normalize_names: bool,
+}
+
+impl FieldFilter {
+    pub fn allows(&self, field_name: &str) -> bool {
+        let matches = |globs: &[String]| globs.iter().any(|g| crate::field_anomaly::field_glob_match(g, field_name));
+        !matches(&self.ignore) && (self.track.is_empty() || matches(&self.track))
+    }
+
+    /// Extraction rule for an exact flattened field name
+    pub fn extract_rule(&self, field_name: &str) -> Option<&ExtractRule> {
+        self.extract.iter().find(|r| r.path == field_name)
+    }
+
+    /// Name a field appears under in templates and params
+    pub fn field_name<'a>(&self, field_name: &'a str) -> std::borrow::Cow<'a, str> {
+        if self.normalize_names {
+            std::borrow::Cow::Owned(normalize_field_name(field_name))
+        } else {
+            std::borrow::Cow::Borrowed(field_name)
+        }
+    }
+}
+
+/// snake_case form of each dot-separated segment: `userId`, `UserID` and `user-id` all become
+/// `user_id`, and `HTTPStatus` becomes `http_status`
+pub fn normalize_field_name(field_name: &str) -> String {
+    let mut out = String::with_capacity(field_name.len() + 4);
+    for (i, segment) in field_name.split('.').enumerate() {
+        if i > 0 { out.push('.'); }
+        let chars: Vec<char> = segment.chars().collect();
+        for (j, &c) in chars.iter().enumerate() {
+            if c == '-' || c == '_' || c == ' ' {
+                if !out.is_empty() && !out.ends_with(['_', '.']) { out.push('_'); }
+                continue;
+            }
+            if c.is_uppercase() && j > 0 {
+                let prev = chars[j - 1];
+                let next_lower = chars.get(j + 1).is_some_and(|n| n.is_lowercase());
+                let boundary = prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower);
+                if boundary && !out.ends_with(['_', '.']) { out.push('_'); }
+            }
+            out.extend(c.to_lowercase());
+        }
+        while out.ends_with('_') { out.pop(); }
+    }
+    out
+}
+
+/// `PATH=ALIAS`: track the flattened JSON field `PATH` (dot path, optional leading `$.`) as the
+/// param `ALIAS`, e.g. `payload.data.attributes.status=status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractRule {
+    pub path: String,
+    pub alias: String,
+}
+
+impl ExtractRule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (path, alias) = spec.split_once('=')
+            .ok_or_else(|| format!("expected 'PATH=ALIAS', got '{spec}'"))?;
+        let path = path.trim();
+        let path = path.strip_prefix("$.").unwrap_or(path);
+        let alias = alias.trim();
+        if path.is_empty() || alias.is_empty() {
+            return Err(format!("empty path or alias in '{spec}'"));
+        }
+        Ok(Self { path: path.to_string(), alias: alias.to_string() })
+    }
+
+    /// Param name the field is tracked under
+    pub fn param_name(&self) -> String {
+        field_param_name(&self.alias)
+    }
+}
+
+/// User masking rule: a regex whose named groups become params, so one rule can mask several
+/// fields of a custom prefix, e.g. `^\[(?P<ts>[^\]]+)\] (?P<component>\w+):` turns
+/// `[2024-01-15 10:30] billing: ...` into `[<TS>] <COMPONENT>: ...` with params `TS` and
+/// `COMPONENT`. Text outside the groups stays literal; the rest of the line is masked as usual.
+#[derive(Debug, Clone)]
+pub struct MaskRule {
+    pub regex: Regex,
+    /// (group name, param name) for each named group
+    groups: Vec<(String, String)>,
+}
+
+impl MaskRule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let regex = Regex::new(spec).map_err(|e| format!("invalid regex '{spec}': {e}"))?;
+        let groups: Vec<(String, String)> = regex.capture_names().flatten()
+            .map(|name| (name.to_string(), field_param_name(name)))
+            .collect();
+        if groups.is_empty() {
+            return Err(format!("'{spec}' has no named groups; name each masked part like (?P<user>\\w+)"));
+        }
+        Ok(Self { regex, groups })
+    }
+}
+
+/// Apply `rules` in order, each to the text left by the previous one, replacing every named
+/// group match with its `<PARAM>` placeholder. None when no rule matched.
+pub fn apply_mask_rules(input: &str, rules: &[MaskRule]) -> Option<MaskingResult> {
+    let mut text = std::borrow::Cow::Borrowed(input);
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    for rule in rules {
+        let mut spans: Vec<(usize, usize, &str)> = Vec::new();
+        for caps in rule.regex.captures_iter(&text) {
+            for (group, param) in &rule.groups {
+                if let Some(m) = caps.name(group) {
+                    spans.push((m.start(), m.end(), param));
+                }
+            }
+        }
+        if spans.is_empty() { continue; }
+        spans.sort_by_key(|&(start, _, _)| start);
+        let mut masked = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end, param) in spans {
+            if start < last { continue; }
+            masked.push_str(&text[last..start]);
+            masked.push_str(&format!("<{param}>"));
+            params.entry(param.to_string()).or_default().push(text[start..end].to_string());
+            last = end;
+        }
+        masked.push_str(&text[last..]);
+        text = std::borrow::Cow::Owned(masked);
+    }
+    match text {
+        std::borrow::Cow::Owned(masked_text) => Some(MaskingResult { masked_text, extracted_params: params }),
+        std::borrow::Cow::Borrowed(_) => None,
+    }
+}
+
+/// `canonicalize_for_drain`, except that JSON fields matched by the filter's extract rules are
+/// renamed to their alias and moved to the front of the template (`status = <STATUS> ...`), and
+/// the other field names are normalized when the filter asks for it
+pub fn canonicalize_with_field_filter(input: &str, filter: &FieldFilter, quoted: QuotedStrings) -> MaskingResult {
+    if filter.extract.is_empty() && !filter.normalize_names {
+        return canonicalize_for_drain_quoted(input, quoted);
+    }
+    let Some(mut fields) = try_flatten_json(input) else { return canonicalize_for_drain_quoted(input, quoted) };
+    let extracted: Vec<(&ExtractRule, String)> = filter.extract.iter()
+        .filter_map(|r| fields.remove(&r.path).map(|v| (r, v)))
+        .collect();
+    if filter.normalize_names {
+        // On a collision within one record the first field in name order wins
+        let mut normalized = BTreeMap::new();
+        for (name, value) in fields {
+            normalized.entry(normalize_field_name(&name)).or_insert(value);
+        }
+        fields = normalized;
+    }
+    let mut result = canonicalize_json_structure(&fields);
+    if extracted.is_empty() {
+        return result;
+    }
+    let mut parts: Vec<String> = Vec::with_capacity(extracted.len() + 1);
+    for (rule, value) in extracted {
+        let name = rule.param_name();
+        parts.push(format!("{} = <{name}>", rule.alias));
+        result.extracted_params.entry(name).or_default().push(value);
+    }
+    if !result.masked_text.is_empty() {
+        parts.push(std::mem::take(&mut result.masked_text));
+    }
+    result.masked_text = parts.join(" ");
+    result
+}
+
 /// Extracts parameters from structured key-value pairs
 pub fn extract_kv_params(flat_fields: &std::collections::BTreeMap<String, String>) -> HashMap<String, Vec<String>> {
+    extract_kv_params_filtered(flat_fields, &FieldFilter::default())
+}
+
+/// Like `extract_kv_params`, tracking only fields the filter allows
+pub fn extract_kv_params_filtered(flat_fields: &std::collections::BTreeMap<String, String>, filter: &FieldFilter) -> HashMap<String, Vec<String>> {
     let mut params = HashMap::new();
     
     // Track ALL fields, using uppercase field name as the parameter type
     // This allows any application-specific fields to be tracked
     for (field_name, value) in flat_fields.iter() {
+        if let Some(rule) = filter.extract_rule(field_name) {
+            params.entry(rule.param_name()).or_insert_with(Vec::new).push(value.clone());
+            continue;
+        }
         // Skip fields we drop from templates
         if field_name == "host" || field_name == "hostname" || field_name == "service" ||
            field_name.starts_with("kubernetes.") || field_name == "pod" || 
            field_name == "namespace" || field_name == "container" || field_name == "container_id" {
             continue;
         }
+        if !filter.allows(field_name) { continue; }
         
         // Use uppercase field name as parameter type
-        let param_type = field_name.to_uppercase().replace("-", "_").replace(".", "_");
+        let param_type = field_param_name(&filter.field_name(field_name));
         params.entry(param_type).or_insert_with(Vec::new).push(value.clone());
     }
     
@@ -426,6 +1170,67 @@ pub fn merge_params(masked_params: HashMap<String, Vec<String>>, kv_params: Hash
     merged
 }
 
+/// Prefix for params decoded from base64-encoded JSON payloads
+pub const B64_JSON_PREFIX: &str = "B64_JSON_";
+
+/// Whether `name` is a `<B64>` param (including positional `B64_2`, `B64_3`, ...)
+fn is_b64_param(name: &str) -> bool {
+    name == "B64" || name.strip_prefix("B64_").is_some_and(|d| !d.is_empty() && d.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Decode a base64 blob (standard or URL-safe, padded or not) into a JSON object
+fn decode_b64_json(value: &str) -> Option<BTreeMap<String, String>> {
+    use base64::Engine;
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+    let bytes = [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(value).ok())?;
+    let text = std::str::from_utf8(&bytes).ok()?;
+    if !text.trim_start().starts_with('{') { return None; }
+    try_flatten_json(text)
+}
+
+/// For every `<B64>` value that decodes to a JSON object, add its flattened fields as
+/// params named `B64_JSON_<FIELD>`. Opt-in: decoding every blob has a cost.
+pub fn merge_b64_json_params(params: &mut HashMap<String, Vec<String>>) {
+    let decoded: Vec<BTreeMap<String, String>> = params.iter()
+        .filter(|(k, _)| is_b64_param(k))
+        .flat_map(|(_, vals)| vals.iter().filter_map(|v| decode_b64_json(v)))
+        .collect();
+    for fields in decoded {
+        for (key, values) in extract_kv_params(&fields) {
+            params.entry(format!("{B64_JSON_PREFIX}{key}")).or_default().extend(values);
+        }
+    }
+}
+
+/// Default cap on extracted param value length (chars) before truncation
+pub const DEFAULT_MAX_VALUE_LENGTH: usize = 256;
+
+/// Marker appended to values cut by `truncate_value`
+pub const TRUNCATION_MARKER: &str = "… (truncated)";
+
+/// Truncate `value` to at most `max_chars` characters plus a marker; `0` disables truncation
+pub fn truncate_value(value: &str, max_chars: usize) -> std::borrow::Cow<'_, str> {
+    if max_chars == 0 {
+        return std::borrow::Cow::Borrowed(value);
+    }
+    match value.char_indices().nth(max_chars) {
+        Some((cut, _)) => std::borrow::Cow::Owned(format!("{}{}", &value[..cut], TRUNCATION_MARKER)),
+        None => std::borrow::Cow::Borrowed(value),
+    }
+}
+
+/// Truncate every extracted value in place to bound memory and output size
+pub fn truncate_param_values(params: &mut HashMap<String, Vec<String>>, max_chars: usize) {
+    if max_chars == 0 { return; }
+    for values in params.values_mut() {
+        for v in values.iter_mut() {
+            if let std::borrow::Cow::Owned(t) = truncate_value(v, max_chars) { *v = t; }
+        }
+    }
+}
+
 /// Attempts to flatten JSON into sorted key-value pairs
 /// Returns None if the input is not valid JSON
 pub fn try_flatten_json(input: &str) -> Option<BTreeMap<String, String>> {
@@ -478,12 +1283,26 @@ fn value_to_simple_string(value: &serde_json::Value) -> String {
     }
 }
 
-/// Main canonicalization function that prepares text for Drain clustering
-/// This function applies structure-first canonicalization:
-/// - For JSON logs: converts to sorted "key = <KEY>" format
-/// - For inline KV logs: rewrites "key=value" to "key = <KEY>"
-/// - Then applies existing masking for any remaining free text
+/// Line in, template + params out: the canonicalization `summarize` applies to every message
+/// before clustering (with default options). Structure first, first match wins:
+/// - JSON objects: flattened, sorted `key = <KEY>` parts
+/// - inline `key=value` / `key: value` pairs: rewritten to `key = <KEY>`; timestamps, IPs and
+///   UUIDs in the text between them masked
+/// - known access-log formats (ELB, Nginx, Apache): the smart-masking template
+/// - anything else: typed masking (`<NUM>`, `<IP>`, `<UUID>`, ...)
+///
+/// `masking_category` tells JSON and access-log lines apart from the rest.
+pub fn canonicalize(line: &str) -> MaskingResult {
+    canonicalize_for_drain_quoted(line, QuotedStrings::Split)
+}
+
+/// Same as `canonicalize`
 pub fn canonicalize_for_drain(input: &str) -> MaskingResult {
+    canonicalize(input)
+}
+
+/// `canonicalize_for_drain` with quoted phrases in free text handled per `quoted`
+pub fn canonicalize_for_drain_quoted(input: &str, quoted: QuotedStrings) -> MaskingResult {
     // First, try to parse as JSON for structured canonicalization
     if let Some(json_fields) = try_flatten_json(input) {
         return canonicalize_json_structure(&json_fields);
@@ -506,7 +1325,163 @@ pub fn canonicalize_for_drain(input: &str) -> MaskingResult {
     }
     
     // Fallback to traditional masking for unstructured text with disambiguation
-    mask_and_extract_with_disambiguation(input)
+    mask_and_extract_quoted(input, quoted)
+}
+
+/// Default `mask_random_tokens` threshold in bits per character; random base62 tokens of 16+
+/// characters score about 3.7 and above, English words and identifiers mostly below
+pub const DEFAULT_RANDOM_TOKEN_ENTROPY: f64 = 3.5;
+
+/// Shortest token `mask_random_tokens` considers
+pub const RANDOM_TOKEN_MIN_LEN: usize = 16;
+
+/// Shannon entropy of `token` in bits per character
+pub fn shannon_entropy(token: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in token.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    let n = token.chars().count() as f64;
+    counts.values().map(|&c| { let p = c as f64 / n; -p * p.log2() }).sum()
+}
+
+/// Mask tokens the regex maskers left alone that look random (session keys, nonces): runs of
+/// `[A-Za-z0-9_+/-]` of at least `RANDOM_TOKEN_MIN_LEN` characters that mix letters and digits
+/// and whose Shannon entropy is at least `min_entropy` bits per character. They become
+/// `<RANDOM>`, `<RANDOM_2>`, ... in order of appearance.
+pub fn mask_random_tokens(mut result: MaskingResult, min_entropy: f64) -> MaskingResult {
+    let text = &result.masked_text;
+    let is_token_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '/' | '-');
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (is_token_char(c), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                let token = &text[s..i];
+                // Tokens touching a placeholder are part of an already-masked value
+                let touches_placeholder = text[..s].ends_with('<') || text[i..].starts_with('>');
+                if !touches_placeholder
+                    && token.len() >= RANDOM_TOKEN_MIN_LEN
+                    && token.bytes().any(|b| b.is_ascii_digit())
+                    && token.bytes().any(|b| b.is_ascii_alphabetic())
+                    && shannon_entropy(token) >= min_entropy
+                {
+                    spans.push((s, i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if spans.is_empty() {
+        return result;
+    }
+    let mut masked = String::with_capacity(text.len());
+    let mut last = 0;
+    for (n, &(s, e)) in spans.iter().enumerate() {
+        let name = if n == 0 { "RANDOM".to_string() } else { format!("RANDOM_{}", n + 1) };
+        masked.push_str(&text[last..s]);
+        masked.push_str(&format!("<{name}>"));
+        result.extracted_params.entry(name).or_default().push(text[s..e].to_string());
+        last = e;
+    }
+    masked.push_str(&text[last..]);
+    result.masked_text = masked;
+    result
+}
+
+/// Put known-constant words (`GET`, `SUCCESS`, ...) back in place of the placeholders that
+/// masked them, so they stay literal in templates. A placeholder is restored only when it occurs
+/// once in the masked text and captured a single value that is exactly one of `literals`.
+pub fn restore_literals(mut result: MaskingResult, literals: &[String]) -> MaskingResult {
+    if literals.is_empty() {
+        return result;
+    }
+    let restorable: Vec<String> = result
+        .extracted_params
+        .iter()
+        .filter(|(_, values)| matches!(values.as_slice(), [v] if literals.iter().any(|l| l == v)))
+        .map(|(name, _)| name.clone())
+        .collect();
+    for name in restorable {
+        let placeholder = format!("<{name}>");
+        if result.masked_text.matches(&placeholder).count() == 1 {
+            let value = result.extracted_params.remove(&name).and_then(|mut v| v.pop()).unwrap_or_default();
+            result.masked_text = result.masked_text.replace(&placeholder, &value);
+        }
+    }
+    result
+}
+
+/// Prefix for params parsed from URL query strings (`QUERY_action` for `?action=delete`)
+pub const QUERY_PARAM_PREFIX: &str = "QUERY_";
+
+/// The `key=value` pairs of a URL or absolute path's query string (fragment dropped), or None
+/// when `value` is not a URL/path with a query
+fn query_pairs(value: &str) -> Option<Vec<(&str, &str)>> {
+    let (base, query) = value.split_once('?')?;
+    let is_url = base.starts_with('/')
+        || base.split_once("://").is_some_and(|(scheme, _)| !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c)));
+    if !is_url || base.contains(char::is_whitespace) || base.contains('=') {
+        return None;
+    }
+    let query = query.split('#').next().unwrap_or_default();
+    let pairs: Vec<(&str, &str)> = query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(key, _)| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c)))
+        .collect();
+    (!pairs.is_empty()).then_some(pairs)
+}
+
+/// Run `canonicalize` with URL query strings split off first, then add each query pair as a
+/// `QUERY_<key>` param. Free-text URLs lose their `?...` before masking, so `action=delete` in a
+/// query is not mistaken for a key=value pair and the URL itself masks as one `<URL>`/`<PATH>`;
+/// the URL param keeps the full value. Field values holding a URL (`uri=/a?b=c`, JSON) are
+/// parsed as extracted.
+pub fn with_query_params(input: &str, canonicalize: impl FnOnce(&str) -> MaskingResult) -> MaskingResult {
+    // (URL without query, full URL) for every whitespace-separated token carrying a query
+    let mut split: Vec<(String, String)> = Vec::new();
+    let mut stripped = String::with_capacity(input.len());
+    let mut last = 0;
+    let mut start = 0;
+    for piece in input.split_inclusive(char::is_whitespace) {
+        let token = piece.trim_end_matches(char::is_whitespace);
+        let token_start = start;
+        start += piece.len();
+        let trimmed = token.trim_start_matches(['"', '\'', '(', '<', '[']).trim_end_matches(['"', '\'', ')', '>', ']', ',', ';']);
+        if query_pairs(trimmed).is_none() { continue; }
+        let url_start = token_start + (token.len() - token.trim_start_matches(['"', '\'', '(', '<', '[']).len());
+        let query_start = url_start + trimmed.find('?').unwrap_or_default();
+        let url_end = url_start + trimmed.len();
+        stripped.push_str(&input[last..query_start]);
+        last = url_end;
+        split.push((input[url_start..query_start].to_string(), trimmed.to_string()));
+    }
+    stripped.push_str(&input[last..]);
+
+    let mut result = canonicalize(if split.is_empty() { input } else { &stripped });
+    for values in result.extracted_params.values_mut() {
+        for value in values.iter_mut() {
+            if let Some(i) = split.iter().position(|(base, _)| base == value) {
+                *value = split.remove(i).1;
+            }
+        }
+    }
+    let mut query: Vec<(String, String)> = Vec::new();
+    let full_urls = result.extracted_params.iter()
+        .filter(|(name, _)| !name.starts_with(QUERY_PARAM_PREFIX))
+        .flat_map(|(_, values)| values.iter())
+        .chain(split.iter().map(|(_, full)| full));
+    for url in full_urls {
+        for (key, value) in query_pairs(url).unwrap_or_default() {
+            query.push((format!("{QUERY_PARAM_PREFIX}{key}"), value.to_string()));
+        }
+    }
+    for (name, value) in query {
+        result.extracted_params.entry(name).or_default().push(value);
+    }
+    result
 }
 
 /// Canonicalizes JSON structure into sorted key=<KEY> format
@@ -522,7 +1497,7 @@ fn canonicalize_json_structure(fields: &BTreeMap<String, String>) -> MaskingResu
         }
         
         // Create field-specific placeholder
-        let field_upper = field_name.to_uppercase().replace("-", "_").replace(".", "_");
+        let field_upper = field_param_name(field_name);
         let placeholder = format!("<{field_upper}>");
         
         // Add to canonicalized format
@@ -554,15 +1529,16 @@ fn canonicalize_kv_structure(input: &str) -> MaskingResult {
     fn mask_text_segment(text: &str, extracted_params: &mut HashMap<String, Vec<String>>) -> String {
         let mut masked = text.to_string();
         
-        // Mask timestamps first (highest priority)
-        for cap in RE_TIMESTAMP.find_iter(text) {
-            let timestamp_val = cap.as_str();
-            masked = masked.replace(timestamp_val, "<TIMESTAMP>");
-            extracted_params.entry("TIMESTAMP".to_string()).or_default().push(timestamp_val.to_string());
+        // Inline trace context (`traceId: <hex>`) the pair extractors left behind
+        for cap in RE_TRACE_CONTEXT_KV.captures_iter(text) {
+            let id = &cap[2];
+            let name = if cap[1].eq_ignore_ascii_case("trace") { "TRACE_ID" } else { "SPAN_ID" };
+            masked = masked.replace(id, &format!("<{name}>"));
+            extracted_params.entry(name.to_string()).or_default().push(id.to_string());
         }
         
-        // Mask syslog timestamps
-        for cap in RE_SYSLOG_TIMESTAMP.find_iter(text) {
+        // Mask timestamps (highest priority among the rest)
+        for cap in timestamp_matches(text) {
             let timestamp_val = cap.as_str();
             masked = masked.replace(timestamp_val, "<TIMESTAMP>");
             extracted_params.entry("TIMESTAMP".to_string()).or_default().push(timestamp_val.to_string());
@@ -591,25 +1567,48 @@ fn canonicalize_kv_structure(input: &str) -> MaskingResult {
     }
     
     // Use captures_iter for single-pass processing (avoids double regex execution)
-    for captures in RE_KV_EXTRACT.captures_iter(input) {
-        let mat = captures.get(0).unwrap();
-        let key = captures.get(1).unwrap().as_str();
-        // Handle quoted vs unquoted values (group 2 = quoted, group 3 = unquoted)
-        let value = captures.get(2).map(|m| m.as_str()).unwrap_or_else(|| captures.get(3).unwrap().as_str());
+    let mut pairs: Vec<(std::ops::Range<usize>, &str, &str)> = RE_KV_EXTRACT
+        .captures_iter(input)
+        .map(|captures| {
+            let mat = captures.get(0).unwrap();
+            let key = captures.get(1).unwrap().as_str();
+            // Handle quoted vs unquoted values (group 2 = quoted, group 3 = unquoted)
+            match captures.get(2) {
+                Some(quoted) => (mat.range(), key, quoted.as_str()),
+                None => {
+                    // `(trace_id=abc)`: the closing bracket belongs to the text, not the value
+                    let value = trim_unbalanced_closers(captures.get(3).unwrap().as_str());
+                    let end = mat.end() - (captures.get(3).unwrap().len() - value.len());
+                    (mat.start()..end, key, value)
+                }
+            }
+        })
+        .collect();
+    // Colon pairs fill the gaps between `key=value` pairs
+    for colon in colon_kv_pairs(input) {
+        if !pairs.iter().any(|(r, _, _)| r.start < colon.0.end && colon.0.start < r.end) {
+            pairs.push(colon);
+        }
+    }
+    pairs.sort_by_key(|(r, _, _)| r.start);
+
+    for (range, key, value) in pairs {
+        let matched = &input[range.clone()];
         
         // Add any text before this match (with masking)
-        if mat.start() > last_end {
-            let text_segment = &input[last_end..mat.start()];
+        if range.start > last_end {
+            let text_segment = &input[last_end..range.start];
             let masked_segment = mask_text_segment(text_segment, &mut extracted_params);
             result.push_str(&masked_segment);
         }
         
         // Skip infrastructure fields
         if should_skip_field(key) {
-            result.push_str(mat.as_str());
+            result.push_str(matched);
         } else {
             // Replace with placeholder
-            let key_upper = key.to_uppercase().replace("-", "_").replace(".", "_");
+            let key_upper = dotted_trace_param(&input[..range.start], key)
+                .unwrap_or_else(|| field_param_name(key));
             let placeholder = format!("{key} = <{key_upper}>");
             result.push_str(&placeholder);
             
@@ -628,7 +1627,7 @@ fn canonicalize_kv_structure(input: &str) -> MaskingResult {
             extracted_params.entry(key_upper).or_insert_with(Vec::new).push(final_value);
         }
         
-        last_end = mat.end();
+        last_end = range.end;
     }
     
     // Add any remaining text after the last match (with masking)
@@ -644,12 +1643,24 @@ fn canonicalize_kv_structure(input: &str) -> MaskingResult {
     }
 }
 
+/// Coarse kind of a line by the canonicalization path that handles it: `json` for JSON objects,
+/// `access` for known access-log formats (ELB, Nginx, Apache via smart masking), `app` otherwise
+pub fn masking_category(input: &str) -> &'static str {
+    if try_flatten_json(input).is_some() {
+        "json"
+    } else if !has_kv_pairs(input) && crate::smart_masking::smart_mask_line(input).confidence > 0.8 {
+        "access"
+    } else {
+        "app"
+    }
+}
+
 /// Checks if input contains key-value pairs
 fn has_kv_pairs(input: &str) -> bool {
     // Simple check: does it contain '=' and look like key=value?
     // Avoid regex for detection to prevent any catastrophic backtracking
     if !input.contains('=') {
-        return false;
+        return !colon_kv_pairs(input).is_empty();
     }
     
     // Quick heuristic: check if there's at least one word followed by '='
@@ -662,7 +1673,57 @@ fn has_kv_pairs(input: &str) -> bool {
             }
         }
     }
-    false
+    !colon_kv_pairs(input).is_empty()
+}
+
+/// `key: value` pairs in `input` as (match range, key, value), or none at all unless the line is
+/// clearly pair-structured: two or more pairs, or a single pair with a numeric value ending the
+/// line (`Status: 200`). This keeps prose such as `Error: connection refused` as free text.
+///
+/// A colon only forms a pair when its value looks like one: numeric, quoted, or followed by
+/// another pair, a delimiter or the end of the line. A logger prefix (`main: Starting worker`)
+/// fails that test, and a line with such a prose colon needs two real pairs to count.
+fn colon_kv_pairs(input: &str) -> Vec<(std::ops::Range<usize>, &str, &str)> {
+    if !input.contains(": ") && !input.contains(":\t") {
+        return Vec::new();
+    }
+    let mut saw_prose = false;
+    let pairs: Vec<_> = RE_KV_COLON_EXTRACT
+        .captures_iter(input)
+        .filter_map(|c| {
+            let key = c.get(1)?;
+            if COLON_KEY_LEVELS.iter().any(|l| key.as_str().eq_ignore_ascii_case(l)) {
+                return None;
+            }
+            let (value, quoted) = match c.get(2) {
+                Some(v) => (v, true),
+                None => (c.get(3)?, false),
+            };
+            let end = c.get(0)?.end();
+            let numeric = value.as_str().starts_with(|c: char| c.is_ascii_digit());
+            if !quoted && !numeric && !ends_colon_value(&input[end..]) {
+                saw_prose = true;
+                return None;
+            }
+            Some((key.start()..end, key.as_str(), value.as_str()))
+        })
+        .collect();
+    let single_numeric = |(range, _, value): &(std::ops::Range<usize>, &str, &str)| {
+        input[range.end..].trim().is_empty() && value.starts_with(|c: char| c.is_ascii_digit())
+    };
+    match pairs.as_slice() {
+        [only] if saw_prose || !single_numeric(only) => Vec::new(),
+        _ => pairs,
+    }
+}
+
+/// Whether the text after an unquoted colon value closes it: end of line, a delimiter, or the
+/// next `key:` / `key=` pair
+fn ends_colon_value(rest: &str) -> bool {
+    let rest = rest.trim_start_matches([' ', '\t']);
+    rest.is_empty()
+        || rest.starts_with([',', ';', ')', ']', '}', '|'])
+        || RE_NEXT_PAIR.is_match(rest)
 }
 
 /// Determines if a field should be skipped during canonicalization
@@ -676,15 +1737,22 @@ fn should_skip_field(field_name: &str) -> bool {
 /// Call this once at startup before any parallel work begins
 pub fn prewarm_regexes() {
     // Force initialization of all lazy regex patterns
-    let _ = &*RE_TIMESTAMP;
-    let _ = &*RE_SYSLOG_TIMESTAMP;
+    let _ = &*crate::smart_masking::TIMESTAMP_PATTERNS;
     let _ = &*RE_NUM_UNIT;
+    let _ = &*RE_DURATION;
+    let _ = &*RE_TRACEPARENT;
+    let _ = &*RE_TRACE_CONTEXT_KV;
     let _ = &*RE_NUM_PERCENT;
     let _ = &*RE_URL;
     let _ = &*RE_IPV6;
     let _ = &*RE_IPV4;
     let _ = &*RE_EMAIL;
     let _ = &*RE_UUID;
+    let _ = &*RE_IMAGE_REF;
+    let _ = &*RE_POD_NAME;
+    let _ = &*RE_BRACKET_THREAD;
+    let _ = &*RE_BRACKET_REQ_ID;
+    let _ = &*RE_HOSTNAME;
     let _ = &*RE_PATH;
     let _ = &*RE_NULL;
     let _ = &*RE_B64;
@@ -693,6 +1761,8 @@ pub fn prewarm_regexes() {
     let _ = &*RE_INT;
     let _ = &*RE_KV_PAIR;
     let _ = &*RE_KV_EXTRACT;
+    let _ = &*RE_KV_COLON_EXTRACT;
+    let _ = &*RE_NEXT_PAIR;
     
     // Also prewarm smart masking regexes
     crate::smart_masking::prewarm_regexes();
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Utc};
 use serde_json::Value;
 use std::collections::BTreeMap;
+use crate::param_extractor::plausible_epoch_secs;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LogFormat {
@@ -17,6 +18,229 @@ pub struct ParsedRecord {
     pub flat_fields: Option<BTreeMap<String, String>>, // for JSON
     pub synthetic_message: Option<String>,             // for JSON derived message
     pub raw_json: Option<Value>,                       // original JSON value when format==Json
+    pub cri: Option<CriMeta>,                          // container runtime prefix, when present
+    pub syslog: Option<SyslogMeta>,                    // RFC3164/5424 `<PRI>` prefix, when present
+}
+
+/// Facility and severity decoded from a syslog `<PRI>` prefix (PRI = facility * 8 + severity)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyslogMeta {
+    pub facility: &'static str,
+    pub severity: &'static str,
+}
+
+const SYSLOG_FACILITIES: [&str; 24] = [
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news",
+    "uucp", "cron", "authpriv", "ftp", "ntp", "security", "console", "clock",
+    "local0", "local1", "local2", "local3", "local4", "local5", "local6", "local7",
+];
+const SYSLOG_SEVERITIES: [&str; 8] = ["emerg", "alert", "crit", "err", "warning", "notice", "info", "debug"];
+
+impl SyslogMeta {
+    /// Log level in the vocabulary used for plaintext detection (ERROR, WARN, INFO, DEBUG)
+    pub fn level(&self) -> &'static str {
+        match self.severity {
+            "emerg" | "alert" | "crit" | "err" => "ERROR",
+            "warning" => "WARN",
+            "debug" => "DEBUG",
+            _ => "INFO",
+        }
+    }
+}
+
+/// Split a leading syslog priority (`<34>Oct 11 22:14:15 host su: ...`, `<165>1 2003-10-11T22:14:15Z ...`)
+/// into its decoded facility/severity and the rest of the line
+pub fn parse_syslog_pri(line: &str) -> Option<(SyslogMeta, &str)> {
+    let rest = line.strip_prefix('<')?;
+    let end = rest.find('>')?;
+    let digits = &rest[..end];
+    if digits.is_empty() || digits.len() > 3 || !digits.bytes().all(|b| b.is_ascii_digit()) || (digits.len() > 1 && digits.starts_with('0')) {
+        return None;
+    }
+    let pri: usize = digits.parse().ok()?;
+    let facility = *SYSLOG_FACILITIES.get(pri / 8)?;
+    Some((SyslogMeta { facility, severity: SYSLOG_SEVERITIES[pri % 8] }, &rest[end + 1..]))
+}
+
+/// Fields from a Kubernetes CRI / containerd log prefix (`<ts> stdout|stderr P|F <msg>`) or a
+/// Docker json-file record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriMeta {
+    pub stream: String,
+    pub partial: bool,
+}
+
+/// A CRI log line split into its prefix fields and the container's own message
+#[derive(Debug, Clone)]
+pub struct CriLine<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub stream: &'a str,
+    pub partial: bool,
+    pub message: &'a str,
+}
+
+static RE_CRI_PREFIX: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d{1,9})?(?:Z|[+-]\d{2}:\d{2})) (stdout|stderr) ([PF]) ?").unwrap()
+});
+
+/// Split a CRI-formatted line (`2024-01-15T10:30:45.123456789Z stdout F msg`) into prefix and message
+pub fn parse_cri_prefix(line: &str) -> Option<CriLine<'_>> {
+    let caps = RE_CRI_PREFIX.captures(line)?;
+    let timestamp = DateTime::parse_from_rfc3339(&caps[1]).ok()?.with_timezone(&Utc);
+    Some(CriLine {
+        timestamp,
+        stream: caps.get(2)?.as_str(),
+        partial: &caps[3] == "P",
+        message: &line[caps.get(0)?.end()..],
+    })
+}
+
+/// A Docker json-file driver record (`{"log":"msg\n","stream":"stdout","time":"..."}`) split into
+/// the container's message and the driver fields
+#[derive(Debug, Clone)]
+pub struct DockerJsonLine {
+    /// `time` as written by the driver (RFC 3339 with nanoseconds)
+    pub time: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub stream: String,
+    /// The driver splits long lines; every chunk but the last lacks the trailing newline
+    pub partial: bool,
+    pub message: String,
+}
+
+/// Unwrap a Docker json-file record. Only objects whose keys are `log`, `stream`, `time` and
+/// optionally `attrs` qualify, so application JSON that happens to have a `log` field is untouched.
+pub fn parse_docker_json(line: &str) -> Option<DockerJsonLine> {
+    let t = line.trim();
+    if !t.starts_with("{\"log\":") { return None; }
+    let Ok(Value::Object(obj)) = serde_json::from_str::<Value>(t) else { return None };
+    if !obj.keys().all(|k| matches!(k.as_str(), "log" | "stream" | "time" | "attrs")) { return None; }
+    let log = obj.get("log")?.as_str()?;
+    let time = obj.get("time")?.as_str()?;
+    let message = log.strip_suffix('\n').map(|m| m.strip_suffix('\r').unwrap_or(m));
+    Some(DockerJsonLine {
+        time: time.to_string(),
+        timestamp: DateTime::parse_from_rfc3339(time).ok().map(|t| t.with_timezone(&Utc)),
+        stream: obj.get("stream")?.as_str()?.to_string(),
+        partial: message.is_none(),
+        message: message.unwrap_or(log).to_string(),
+    })
+}
+
+/// A Docker json-file record rewritten as the equivalent CRI line (`<time> <stream> P|F <msg>`),
+/// so partial-line joining and continuation handling are shared with CRI logs. None when the
+/// record has a multi-line message or a timestamp the CRI prefix would not accept.
+pub fn docker_json_as_cri(line: &str) -> Option<String> {
+    let rec = parse_docker_json(line)?;
+    if rec.message.contains('\n') { return None; }
+    let cri = format!("{} {} {} {}", rec.time, rec.stream, if rec.partial { "P" } else { "F" }, rec.message);
+    parse_cri_prefix(&cri).is_some().then_some(cri)
+}
+
+/// Which parser path to use per line: `Auto` tries JSON on every line; `Json` also accepts
+/// an object after a non-JSON prefix (e.g. a timestamp) and treats every other line as
+/// malformed; `Text` never parses JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    #[default]
+    Auto,
+    Json,
+    Text,
+}
+
+impl InputFormat {
+    /// Parse a user-supplied format name: json | text | auto
+    pub fn parse(name: &str) -> Result<InputFormat, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(InputFormat::Auto),
+            "json" => Ok(InputFormat::Json),
+            "text" => Ok(InputFormat::Text),
+            _ => Err(format!("unknown input format '{name}' (expected json, text or auto)")),
+        }
+    }
+
+    /// Whether a line that did not parse as JSON counts as malformed JSON
+    pub fn expects_json(self, line: &str) -> bool {
+        match self {
+            InputFormat::Auto => {
+                let t = line.trim_start();
+                t.starts_with('{') || t.starts_with('[')
+            }
+            InputFormat::Json => true,
+            InputFormat::Text => false,
+        }
+    }
+
+    /// The JSON object in `line` and the byte offset it starts at; a single-object array
+    /// (`[{...}]`) yields its object
+    fn json_object(self, line: &str) -> Option<(Value, usize)> {
+        if self == InputFormat::Text { return None; }
+        match serde_json::from_str::<Value>(line) {
+            Ok(v @ Value::Object(_)) => Some((v, 0)),
+            Ok(Value::Array(mut items)) if items.len() == 1 && items[0].is_object() => Some((items.remove(0), 0)),
+            _ if self == InputFormat::Json => {
+                let start = line.find('{')?;
+                serde_json::from_str::<Value>(&line[start..]).ok().filter(Value::is_object).map(|v| (v, start))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Records of a line holding a top-level JSON array of objects (a batch), each serialized as
+/// its own JSON line. None for anything else, including empty arrays and arrays with
+/// non-object elements, which keep being reported as malformed JSON.
+pub fn split_json_array(line: &str) -> Option<Vec<String>> {
+    let t = line.trim();
+    if !t.starts_with('[') { return None; }
+    match serde_json::from_str::<Value>(t) {
+        Ok(Value::Array(items)) if !items.is_empty() && items.iter().all(Value::is_object) => {
+            Some(items.iter().map(Value::to_string).collect())
+        }
+        _ => None,
+    }
+}
+
+/// `lines` with every batch line (see `split_json_array`) replaced by its records, in order;
+/// None when there is nothing to expand or `format` is `Text`
+pub fn expand_json_arrays<S: AsRef<str>>(lines: &[S], format: InputFormat) -> Option<Vec<String>> {
+    if format == InputFormat::Text || !lines.iter().any(|l| l.as_ref().trim_start().starts_with('[')) {
+        return None;
+    }
+    let mut expanded = false;
+    let mut out = Vec::with_capacity(lines.len());
+    for l in lines {
+        match split_json_array(l.as_ref()) {
+            Some(records) => { expanded = true; out.extend(records); }
+            None => out.push(l.as_ref().to_string()),
+        }
+    }
+    expanded.then_some(out)
+}
+
+/// Per-line parsing options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOpts {
+    pub input_format: InputFormat,
+    /// Remove ANSI escape sequences (colors, cursor moves) before parsing
+    pub strip_ansi: bool,
+}
+
+impl Default for ParseOpts {
+    fn default() -> Self {
+        Self { input_format: InputFormat::Auto, strip_ansi: true }
+    }
+}
+
+// CSI (`ESC [ ... m`), OSC (`ESC ] ... BEL`) and two-byte escape sequences
+static RE_ANSI: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)|[@-Z\\-_])").unwrap()
+});
+
+/// `line` without ANSI escape sequences; borrows when there are none
+pub fn strip_ansi(line: &str) -> std::borrow::Cow<'_, str> {
+    if !line.contains('\x1b') { return std::borrow::Cow::Borrowed(line); }
+    RE_ANSI.replace_all(line, "")
 }
 
 pub fn parse_line(line: &str, line_number: usize) -> ParsedRecord {
@@ -24,17 +248,48 @@ pub fn parse_line(line: &str, line_number: usize) -> ParsedRecord {
 }
 
 pub fn parse_line_with_hints(line: &str, line_number: usize, time_keys: &[&str]) -> ParsedRecord {
-    match serde_json::from_str::<Value>(line) {
-        Ok(v @ Value::Object(_)) => {
+    parse_line_as(line, line_number, time_keys, ParseOpts::default())
+}
+
+/// Parse one line with explicit options (parser path, ANSI stripping)
+pub fn parse_line_as(line: &str, line_number: usize, time_keys: &[&str], opts: ParseOpts) -> ParsedRecord {
+    let line = if opts.strip_ansi { strip_ansi(line) } else { std::borrow::Cow::Borrowed(line) };
+    let line = line.as_ref();
+    let format = opts.input_format;
+    // Syslog priority: parse the rest of the line, keep facility/severity alongside
+    if let Some((pri, rest)) = parse_syslog_pri(line) {
+        let mut rec = parse_line_as(rest, line_number, time_keys, ParseOpts { strip_ansi: false, ..opts });
+        rec.syslog = Some(pri);
+        return rec;
+    }
+    // CRI prefix: parse the container's message, keep the runtime timestamp as fallback
+    if let Some(cri) = parse_cri_prefix(line) {
+        let mut rec = parse_line_as(cri.message, line_number, time_keys, ParseOpts { strip_ansi: false, ..opts });
+        rec.timestamp = rec.timestamp.or(Some(cri.timestamp));
+        rec.cri = Some(CriMeta { stream: cri.stream.to_string(), partial: cri.partial });
+        return rec;
+    }
+    // Docker json-file driver: parse the wrapped message, keep the driver timestamp as fallback
+    if format != InputFormat::Text {
+        if let Some(docker) = parse_docker_json(line) {
+            // JSON escapes (`\u001b[31m`) only become ANSI sequences once unwrapped, so strip again
+            let mut rec = parse_line_as(&docker.message, line_number, time_keys, opts);
+            rec.timestamp = rec.timestamp.or(docker.timestamp);
+            rec.cri = Some(CriMeta { stream: docker.stream, partial: docker.partial });
+            return rec;
+        }
+    }
+    match format.json_object(line) {
+        Some((v, json_start)) => {
             let mut flat = BTreeMap::new();
             flatten_json("", &v, &mut flat);
 
             let message = line.trim_end().to_string();
 
-            // timestamp extraction: prioritized by hints, then scan all
+            // timestamp extraction: prioritized by hints, then scan all; unresolved hints are skipped
             let mut ts: Option<DateTime<Utc>> = None;
             for key in time_keys {
-                if let Some(val) = flat.get(*key) {
+                if let Some(val) = flat.get(flat_key_path(key).as_ref()) {
                     if let Some(t) = parse_ts_candidate(val) { ts = Some(t); break; }
                 }
             }
@@ -43,6 +298,10 @@ pub fn parse_line_with_hints(line: &str, line_number: usize, time_keys: &[&str])
                     if let Some(t) = parse_ts_candidate(v) { ts = Some(t); break; }
                 }
             }
+            // prefix before the object (`--input-format json`), e.g. `2024-01-01T00:00:00Z {...}`
+            if ts.is_none() && json_start > 0 {
+                ts = detect_timestamp_in_text(&line[..json_start]);
+            }
 
             // Build synthetic message from sorted flat fields
             let synthetic_message = if !flat.is_empty() {
@@ -63,9 +322,11 @@ pub fn parse_line_with_hints(line: &str, line_number: usize, time_keys: &[&str])
                 flat_fields: Some(flat),
                 synthetic_message,
                 raw_json: Some(v),
+                cri: None,
+                syslog: None,
             }
         }
-        _ => {
+        None => {
             let message = line.trim_end().to_string();
             let timestamp = detect_timestamp_in_text(&message);
             ParsedRecord {
@@ -76,11 +337,110 @@ pub fn parse_line_with_hints(line: &str, line_number: usize, time_keys: &[&str])
                 flat_fields: None,
                 synthetic_message: None,
                 raw_json: None,
+                cri: None,
+                syslog: None,
             }
         }
     }
 }
 
+/// JSON records `detect_time_key` samples from the start of the input
+pub const TIME_KEY_SAMPLE_RECORDS: usize = 200;
+
+/// Field names (last path segment, lowercased) that usually hold the record time; earlier
+/// names win ties
+const TIME_KEY_NAMES: [&str; 8] = ["@timestamp", "timestamp", "time", "ts", "eventtime", "event_time", "datetime", "date"];
+
+/// The JSON field that most consistently holds a parseable timestamp across the first
+/// `TIME_KEY_SAMPLE_RECORDS` JSON records, to use as a time-key hint. Fields named like a
+/// timestamp (`time`, `ts`, `@timestamp`, `eventTime`, ...) win ties; other fields only count
+/// with non-numeric timestamps, so counters are not mistaken for epochs. None unless some field
+/// parses in at least half of the sampled records.
+pub fn detect_time_key<S: AsRef<str>>(lines: &[S], opts: ParseOpts) -> Option<String> {
+    let mut hits: BTreeMap<String, usize> = BTreeMap::new();
+    let mut sampled = 0usize;
+    for line in lines {
+        if sampled == TIME_KEY_SAMPLE_RECORDS { break; }
+        let line = line.as_ref();
+        if !line.trim_start().starts_with('{') || parse_docker_json(line).is_some() { continue; }
+        let Some((v, _)) = opts.input_format.json_object(line) else { continue };
+        sampled += 1;
+        let mut flat = BTreeMap::new();
+        flatten_json("", &v, &mut flat);
+        for (key, value) in flat {
+            let named = time_key_rank(&key).is_some();
+            let numeric = value.bytes().all(|b| b.is_ascii_digit() || b == b'.');
+            if (named || !numeric) && parse_ts_candidate(&value).is_some() {
+                *hits.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+    hits.into_iter()
+        .filter(|(_, n)| n * 2 >= sampled)
+        .max_by_key(|(key, n)| (*n, std::cmp::Reverse(time_key_rank(key).unwrap_or(TIME_KEY_NAMES.len()))))
+        .map(|(key, _)| key)
+}
+
+fn time_key_rank(key: &str) -> Option<usize> {
+    let last = key.rsplit('.').next().unwrap_or(key).to_ascii_lowercase();
+    TIME_KEY_NAMES.iter().position(|n| *n == last)
+}
+
+/// Ingestion-time filter for agent-level double logging: drops a record that repeats the record
+/// right before it within `window`. Records repeat when they are byte-identical once timestamps
+/// are removed, so copies stamped a few milliseconds apart collapse; the window is measured
+/// between their parsed timestamps (copies without timestamps always collapse).
+#[derive(Debug, Clone)]
+pub struct ConsecutiveDedup {
+    window: chrono::Duration,
+    time_keys: Vec<String>,
+    opts: ParseOpts,
+    prev: Option<(String, Option<DateTime<Utc>>)>,
+    /// Records dropped so far
+    pub dropped: usize,
+}
+
+impl ConsecutiveDedup {
+    pub fn new(window: chrono::Duration, time_keys: &[String], opts: ParseOpts) -> Self {
+        Self { window, time_keys: time_keys.to_vec(), opts, prev: None, dropped: 0 }
+    }
+
+    /// Whether `record` should be kept; a dropped record still anchors the next comparison
+    pub fn keep(&mut self, record: &str) -> bool {
+        let key = crate::smart_masking::TIMESTAMP_PATTERNS
+            .iter()
+            .fold(record.trim_end().to_string(), |acc, re| re.replace_all(&acc, "").into_owned());
+        let keys: Vec<&str> = self.time_keys.iter().map(|s| s.as_str()).collect();
+        let ts = parse_line_as(record, 0, &keys, self.opts).timestamp;
+        let repeat = match &self.prev {
+            Some((prev_key, prev_ts)) if *prev_key == key => match (prev_ts, ts) {
+                (Some(a), Some(b)) => (b - *a).abs() <= self.window,
+                _ => true,
+            },
+            _ => false,
+        };
+        self.prev = Some((key, ts));
+        if repeat { self.dropped += 1; }
+        !repeat
+    }
+}
+
+/// Flattened-field key for a time-key hint: dot-paths pass through (`meta.event.time`),
+/// JSONPath-style hints are normalized (`$.events[0].ts` -> `events.0.ts`)
+fn flat_key_path(hint: &str) -> std::borrow::Cow<'_, str> {
+    let hint = hint.strip_prefix("$.").unwrap_or(hint);
+    if !hint.contains('[') { return std::borrow::Cow::Borrowed(hint); }
+    let mut out = String::with_capacity(hint.len());
+    for c in hint.chars() {
+        match c {
+            '[' => out.push('.'),
+            ']' => {}
+            _ => out.push(c),
+        }
+    }
+    std::borrow::Cow::Owned(out)
+}
+
 fn flatten_json(prefix: &str, v: &Value, out: &mut BTreeMap<String, String>) {
     match v {
         Value::Object(map) => {
@@ -158,8 +518,8 @@ fn parse_ts_string(s: &str) -> Option<DateTime<Utc>> {
 }
 
 fn parse_ts_number_string(s: &str) -> Option<DateTime<Utc>> {
-    let digits_only = s.chars().all(|c| c.is_ascii_digit());
-    if !digits_only { return None; }
+    // Only values in the plausible epoch range; anything else is more likely an ID
+    plausible_epoch_secs(s)?;
     match s.len() {
         10 => s.parse::<i64>().ok().and_then(epoch_secs_to_dt),
         13 => s.parse::<i64>().ok().and_then(epoch_millis_to_dt),
@@ -256,7 +616,7 @@ pub fn detect_timestamp_in_text(s: &str) -> Option<DateTime<Utc>> {
             return Some(Utc.from_utc_datetime(&naive));
         }
     }
-    // Try epoch (10s/13ms/16us)
+    // Try epoch (10s/13ms/16us), skipping integers outside the plausible epoch range
     static RE_EPOCH10: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
         regex::Regex::new(r"\b\d{10}\b").unwrap()
     });
@@ -266,13 +626,13 @@ pub fn detect_timestamp_in_text(s: &str) -> Option<DateTime<Utc>> {
     static RE_EPOCH16: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
         regex::Regex::new(r"\b\d{16}\b").unwrap()
     });
-    if let Some(m) = RE_EPOCH16.find(s) {
+    if let Some(m) = RE_EPOCH16.find_iter(s).find(|m| plausible_epoch_secs(m.as_str()).is_some()) {
         if let Ok(us) = m.as_str().parse::<i64>() { return epoch_micros_to_dt(us); }
     }
-    if let Some(m) = RE_EPOCH13.find(s) {
+    if let Some(m) = RE_EPOCH13.find_iter(s).find(|m| plausible_epoch_secs(m.as_str()).is_some()) {
         if let Ok(ms) = m.as_str().parse::<i64>() { return epoch_millis_to_dt(ms); }
     }
-    if let Some(m) = RE_EPOCH10.find(s) {
+    if let Some(m) = RE_EPOCH10.find_iter(s).find(|m| plausible_epoch_secs(m.as_str()).is_some()) {
         if let Ok(sec) = m.as_str().parse::<i64>() { return epoch_secs_to_dt(sec); }
     }
     None
@@ -0,0 +1,133 @@
+use crate::ai::AiOutput;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+/// File inside the store directory holding one JSON record per pattern per run
+pub const STORE_FILE: &str = "patterns.jsonl";
+
+/// One pattern's count from a single run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreRecord {
+    pub pattern_id: String,
+    pub template: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub severity: Option<String>,
+    pub count: usize,
+    pub total_lines: usize,
+    pub date: NaiveDate,
+    pub run_at: DateTime<Utc>,
+}
+
+/// Week-over-week (or any window) change for one pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternTrend {
+    pub pattern_id: String,
+    pub template: String,
+    pub current_count: usize,
+    pub previous_count: usize,
+    pub current_frequency: f64,
+    pub previous_frequency: f64,
+    /// Change in frequency (current - previous); positive means trending up
+    pub frequency_delta: f64,
+}
+
+/// Deterministic pattern identifier: FNV-1a 64 over template and severity.
+/// Stable across runs, platforms and releases so store records can be joined.
+pub fn pattern_id(template: &str, severity: Option<&str>) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let bytes = template.bytes().chain(std::iter::once(0x1f)).chain(severity.unwrap_or("").bytes());
+    for b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Append every pattern of `out` to the store under `dir`, creating it if needed.
+/// Returns the number of records written.
+pub fn append_run(dir: &Path, out: &AiOutput, date: NaiveDate, run_at: DateTime<Utc>) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(STORE_FILE))?;
+    let mut buf = String::new();
+    for p in &out.patterns {
+        let rec = StoreRecord {
+            pattern_id: pattern_id(&p.template, p.severity.as_deref()),
+            template: p.template.clone(),
+            severity: p.severity.clone(),
+            count: p.total_count,
+            total_lines: out.summary.total_lines,
+            date,
+            run_at,
+        };
+        buf.push_str(&serde_json::to_string(&rec).map_err(io::Error::other)?);
+        buf.push('\n');
+    }
+    file.write_all(buf.as_bytes())?;
+    Ok(out.patterns.len())
+}
+
+/// Load all records from the store; unreadable lines are skipped
+pub fn load_records(dir: &Path) -> io::Result<Vec<StoreRecord>> {
+    let file = fs::File::open(dir.join(STORE_FILE))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|l| serde_json::from_str(&l).ok())
+        .collect())
+}
+
+/// Compare pattern frequency in the `days` ending at `as_of` (inclusive) against the
+/// preceding `days`. Frequency uses the same count/total math as pattern anomalies,
+/// with totals summed over the distinct runs in each window. Sorted by delta, largest rise first.
+pub fn compute_trends(records: &[StoreRecord], as_of: NaiveDate, days: i64) -> Vec<PatternTrend> {
+    let current_start = as_of - Duration::days(days - 1);
+    let previous_start = current_start - Duration::days(days);
+
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    let mut templates: HashMap<&str, &str> = HashMap::new();
+    let mut runs: [HashMap<DateTime<Utc>, usize>; 2] = [HashMap::new(), HashMap::new()];
+    for r in records {
+        let window = if r.date >= current_start && r.date <= as_of {
+            0
+        } else if r.date >= previous_start && r.date < current_start {
+            1
+        } else {
+            continue;
+        };
+        let entry = counts.entry(r.pattern_id.as_str()).or_insert((0, 0));
+        if window == 0 { entry.0 += r.count; } else { entry.1 += r.count; }
+        templates.insert(r.pattern_id.as_str(), r.template.as_str());
+        runs[window].insert(r.run_at, r.total_lines);
+    }
+    let current_total: usize = runs[0].values().sum();
+    let previous_total: usize = runs[1].values().sum();
+    let freq = |c: usize, t: usize| if t == 0 { 0.0 } else { c as f64 / t as f64 };
+
+    let mut trends: Vec<PatternTrend> = counts
+        .into_iter()
+        .map(|(id, (cur, prev))| {
+            let current_frequency = freq(cur, current_total);
+            let previous_frequency = freq(prev, previous_total);
+            PatternTrend {
+                pattern_id: id.to_string(),
+                template: templates[id].to_string(),
+                current_count: cur,
+                previous_count: prev,
+                current_frequency,
+                previous_frequency,
+                frequency_delta: current_frequency - previous_frequency,
+            }
+        })
+        .collect();
+    trends.sort_by(|a, b| {
+        b.frequency_delta
+            .partial_cmp(&a.frequency_delta)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.pattern_id.cmp(&b.pattern_id))
+    });
+    trends
+}
@@ -26,3 +26,65 @@ fn to_template(masked: &str) -> String {
         .replace("<TIMESTAMP>", "<*>")
 }
 
+
+/// Default token similarity at which two templates are grouped into one family
+pub const DEFAULT_TEMPLATE_SIMILARITY: f64 = 0.6;
+
+fn is_placeholder(token: &str) -> bool {
+    token.len() >= 3 && token.starts_with('<') && token.ends_with('>')
+}
+
+/// Token-level similarity in 0.0..=1.0: one minus the normalized token edit distance.
+/// Any two placeholders (`<*>`, `<NUM>`, ...) count as equal tokens.
+pub fn template_similarity(a: &str, b: &str) -> f64 {
+    let ta: Vec<&str> = a.split_whitespace().collect();
+    let tb: Vec<&str> = b.split_whitespace().collect();
+    let longest = ta.len().max(tb.len());
+    if longest == 0 { return 1.0; }
+    let same = |x: &str, y: &str| x == y || (is_placeholder(x) && is_placeholder(y));
+    let mut prev: Vec<usize> = (0..=tb.len()).collect();
+    let mut cur = vec![0; tb.len() + 1];
+    for (i, x) in ta.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, y) in tb.iter().enumerate() {
+            let sub = prev[j] + usize::from(!same(x, y));
+            cur[j + 1] = sub.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    1.0 - prev[tb.len()] as f64 / longest as f64
+}
+
+/// Group templates whose pairwise similarity reaches `threshold` (single linkage).
+/// Returns index groups with at least two members, each sorted, ordered by first index.
+pub fn group_similar_templates(templates: &[&str], threshold: f64) -> Vec<Vec<usize>> {
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root { root = parent[root]; }
+        let mut node = i;
+        while parent[node] != root { let next = parent[node]; parent[node] = root; node = next; }
+        root
+    }
+    let lens: Vec<usize> = templates.iter().map(|t| t.split_whitespace().count()).collect();
+    let mut parent: Vec<usize> = (0..templates.len()).collect();
+    for i in 0..templates.len() {
+        for j in (i + 1)..templates.len() {
+            // Edit distance is at least the length difference, so skip pairs that cannot qualify
+            let (lo, hi) = (lens[i].min(lens[j]), lens[i].max(lens[j]));
+            if hi > 0 && (lo as f64 / hi as f64) < threshold { continue; }
+            if find(&mut parent, i) == find(&mut parent, j) { continue; }
+            if template_similarity(templates[i], templates[j]) >= threshold {
+                let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+                parent[rj] = ri;
+            }
+        }
+    }
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..templates.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+    let mut out: Vec<Vec<usize>> = groups.into_values().filter(|g| g.len() >= 2).collect();
+    out.sort_by_key(|g| g[0]);
+    out
+}
@@ -1,4 +1,4 @@
-use crate::{masking, parser, patterns};
+use crate::{masking, param_extractor, parser, patterns};
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone)]
@@ -9,6 +9,7 @@ pub struct Entry {
     pub template: String,
     pub service: Option<String>,
     pub host: Option<String>,
+    pub trace_id: Option<String>,
 }
 
 #[derive(Default)]
@@ -45,10 +46,11 @@ impl QueryIndex {
             }
         };
         let masked = masking::mask_text(&base);
-        let clusters = patterns::cluster_masked(&[masked.clone()]);
+        let clusters = patterns::cluster_masked(std::slice::from_ref(&masked));
         let template = clusters.first().map(|c| c.template.clone()).unwrap_or(masked);
         let (service, host) = extract_source(&rec, line);
-        self.entries.push(Entry { id, line: line.to_string(), timestamp: rec.timestamp, template, service, host });
+        let trace_id = param_extractor::find_trace_id(rec.flat_fields.as_ref(), line);
+        self.entries.push(Entry { id, line: line.to_string(), timestamp: rec.timestamp, template, service, host, trace_id });
         id
     }
 
@@ -82,6 +84,14 @@ impl QueryIndex {
         self.entries.iter().filter(|e| e.service.as_deref() == Some(service)).collect()
     }
 
+    /// Every line of one distributed trace in time order (input order for ties or missing timestamps)
+    pub fn get_trace_timeline(&self, trace_id: &str) -> Vec<&Entry> {
+        let trace_id = trace_id.to_ascii_lowercase();
+        let mut out: Vec<&Entry> = self.entries.iter().filter(|e| e.trace_id.as_deref() == Some(trace_id.as_str())).collect();
+        out.sort_by_key(|e| (e.timestamp.is_none(), e.timestamp, e.id));
+        out
+    }
+
     pub fn get_lines_by_host(&self, host: &str) -> Vec<&Entry> {
         self.entries.iter().filter(|e| e.host.as_deref() == Some(host)).collect()
     }
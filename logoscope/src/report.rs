@@ -0,0 +1,165 @@
+use crate::ai::{self, AiOutput};
+use std::fmt::Write;
+
+/// Escape characters that would break a Markdown table cell
+fn escape_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Render the full summary as a Markdown report suitable for tickets and chat.
+/// Sections: summary header, top insights (shared with triage), patterns table, anomalies.
+pub fn render_markdown(out: &AiOutput) -> String {
+    let triage = ai::create_triage_output(out);
+    let mut md = String::new();
+
+    let _ = writeln!(md, "# Logoscope Report");
+    let _ = writeln!(md);
+    let _ = writeln!(md, "**Status:** {}", triage.summary.status);
+    let _ = writeln!(md);
+    let _ = writeln!(md, "| Metric | Value |");
+    let _ = writeln!(md, "|---|---|");
+    let _ = writeln!(md, "| Total lines | {} |", out.summary.total_lines);
+    let _ = writeln!(md, "| Unique patterns | {} |", out.summary.unique_patterns);
+    let _ = writeln!(md, "| Compression ratio | {:.1}x |", out.summary.compression_ratio);
+    let _ = writeln!(md, "| Timestamped lines | {:.0}% |", out.summary.timestamped_ratio * 100.0);
+    let _ = writeln!(md, "| Error lines | {} |", triage.summary.error_lines);
+    if let Some(range) = &triage.summary.time_range {
+        let _ = writeln!(md, "| Time range | {} |", escape_cell(range));
+    }
+    let _ = writeln!(md);
+
+    let _ = writeln!(md, "## Top Insights");
+    let _ = writeln!(md);
+    for insight in &triage.insights {
+        let _ = writeln!(md, "- {insight}");
+    }
+    let _ = writeln!(md);
+
+    let _ = writeln!(md, "## Patterns");
+    let _ = writeln!(md);
+    if out.patterns.is_empty() {
+        let _ = writeln!(md, "_No patterns found._");
+    } else {
+        let _ = writeln!(md, "| Count | Freq | Level | Bursts | Template |");
+        let _ = writeln!(md, "|---:|---:|---|---:|---|");
+        for p in &out.patterns {
+            let _ = writeln!(
+                md,
+                "| {} | {:.4} | {} | {} | `{}` |",
+                p.total_count,
+                p.frequency,
+                p.severity.as_deref().unwrap_or("-"),
+                p.temporal.as_ref().map(|t| t.bursts).unwrap_or(0),
+                escape_cell(&p.template).replace('`', "'"),
+            );
+        }
+    }
+    let _ = writeln!(md);
+
+    let _ = writeln!(md, "## Anomalies");
+    let _ = writeln!(md);
+    let anomalies = &out.anomalies;
+    let has_param_anomalies = out.patterns.iter().any(|p| p.parameter_anomalies.is_some());
+    if anomalies.pattern_anomalies.is_empty()
+        && anomalies.field_anomalies.is_empty()
+        && anomalies.temporal_anomalies.is_empty()
+        && !has_param_anomalies
+    {
+        let _ = writeln!(md, "_No anomalies detected._");
+    }
+    for a in &anomalies.pattern_anomalies {
+        let _ = writeln!(md, "- **{}** pattern `{}` ({} occurrences)", a.kind, a.template.replace('`', "'"), a.count);
+    }
+    for fa in &triage.field_anomalies {
+        let _ = writeln!(md, "- **{}** {}", fa.impact, fa.description);
+    }
+    for t in &anomalies.temporal_anomalies {
+        let _ = writeln!(md, "- **{}** pattern `{}` {} to {} (peak {}, {:.1}x)", t.kind, t.template.replace('`', "'"), t.start, t.end, t.peak_rate, t.severity);
+    }
+    for p in &out.patterns {
+        if let Some(param_anoms) = &p.parameter_anomalies {
+            for a in param_anoms {
+                let _ = writeln!(md, "- **{}** in `{}`: {}", a.anomaly_type, p.template.replace('`', "'"), a.details);
+            }
+        }
+    }
+
+    if !out.schema_changes.is_empty() {
+        let _ = writeln!(md);
+        let _ = writeln!(md, "## Schema Changes");
+        let _ = writeln!(md);
+        for c in &out.schema_changes {
+            let _ = writeln!(md, "- {} `{}`{}{}", c.change_type, c.field,
+                c.timestamp.as_deref().map(|t| format!(" at {t}")).unwrap_or_default(),
+                c.impact.as_deref().map(|i| format!(": {i}")).unwrap_or_default());
+        }
+    }
+
+    md
+}
+
+/// Count for the table's Count column: `thousands` adds separators, `si` abbreviates (1.2K, 3.4M)
+pub fn format_count(n: usize, style: &str) -> String {
+    match style {
+        "thousands" => {
+            let digits = n.to_string();
+            let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+            for (i, c) in digits.chars().enumerate() {
+                if i > 0 && (digits.len() - i).is_multiple_of(3) { out.push(','); }
+                out.push(c);
+            }
+            out
+        }
+        "si" => {
+            // A count moves up a unit once it rounds to 1000.0 in the unit below: 999_950 is
+            // 1.0M, not 1000.0K, while 999_949 stays 999.9K
+            let rounded = |f: f64| (n as f64 / f * 10.0).round() / 10.0;
+            let scaled = [(1e9, "G"), (1e6, "M"), (1e3, "K")].into_iter()
+                .find(|&(f, _)| n as f64 >= f || (f > 1e3 && rounded(f / 1e3) >= 1000.0));
+            match scaled {
+                Some((f, suffix)) => format!("{:.1}{suffix}", n as f64 / f),
+                None => n.to_string(),
+            }
+        }
+        _ => n.to_string(),
+    }
+}
+
+/// Escape a string for a double-quoted Graphviz ID
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Render `deep_correlations` (only computed with `deep`) as a Graphviz DOT digraph: one node
+/// per correlated template, one edge per template pair carrying its strongest correlation.
+/// Edges point from the leading to the following template by the sign of `time_lag_seconds`;
+/// simultaneous (zero-lag) correlations are undirected.
+pub fn render_correlation_dot(out: &AiOutput) -> String {
+    let mut edges: std::collections::BTreeMap<(&str, &str), &ai::DeepCorrelation> = std::collections::BTreeMap::new();
+    for c in out.patterns.iter().flat_map(|p| p.deep_correlations.iter().flatten()) {
+        let key = if c.template_a <= c.template_b { (c.template_a.as_str(), c.template_b.as_str()) } else { (c.template_b.as_str(), c.template_a.as_str()) };
+        let stronger = edges.get(&key).is_none_or(|prev| c.correlation_strength > prev.correlation_strength);
+        if stronger { edges.insert(key, c); }
+    }
+    let mut nodes: Vec<&str> = edges.keys().flat_map(|&(a, b)| [a, b]).collect();
+    nodes.sort_unstable();
+    nodes.dedup();
+    let id = |tpl: &str| nodes.binary_search(&tpl).unwrap_or_default();
+
+    let mut dot = String::new();
+    let _ = writeln!(dot, "digraph correlations {{");
+    let _ = writeln!(dot, "  node [shape=box];");
+    for (i, tpl) in nodes.iter().enumerate() {
+        let count = out.patterns.iter().find(|p| p.template == *tpl).map_or(0, |p| p.total_count);
+        let _ = writeln!(dot, "  n{i} [label=\"{}\\n({count})\"];", escape_dot(tpl));
+    }
+    for c in edges.values() {
+        let (a, b) = (id(&c.template_a), id(&c.template_b));
+        let (from, to) = if c.time_lag_seconds < 0 { (b, a) } else { (a, b) };
+        let dir = if c.time_lag_seconds == 0 { ", dir=none" } else { "" };
+        let _ = writeln!(dot, "  n{from} -> n{to} [label=\"{:.2} ({}s)\", weight={:.2}{dir}];",
+            c.correlation_strength, c.time_lag_seconds.abs(), c.correlation_strength);
+    }
+    let _ = writeln!(dot, "}}");
+    dot
+}
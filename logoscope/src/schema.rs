@@ -78,6 +78,37 @@ pub enum SchemaChange {
     TypeChanged { field: String, from_type: String, to_type: String },
 }
 
+impl SchemaChange {
+    pub fn field(&self) -> &str {
+        match self {
+            SchemaChange::FieldAdded { field, .. }
+            | SchemaChange::FieldRemoved { field, .. }
+            | SchemaChange::TypeChanged { field, .. } => field,
+        }
+    }
+
+    /// Rough consequence for downstream consumers, e.g. `string→int may break parsers expecting text`
+    pub fn impact(&self) -> String {
+        let numeric = |t: &str| t == "int" || t == "float";
+        match self {
+            SchemaChange::FieldAdded { new_type, .. } => format!("new {new_type} field; additive, strict schemas may reject it"),
+            SchemaChange::FieldRemoved { old_type, .. } => format!("{old_type} field missing; consumers reading it get no value"),
+            SchemaChange::TypeChanged { from_type: from, to_type: to, .. } => {
+                let consequence = match (from.as_str(), to.as_str()) {
+                    ("int", "float") => "fractional values may break integer parsers",
+                    ("float", "int") => "narrowing; numeric consumers are usually unaffected",
+                    (_, "null") => "value became null; consumers must handle missing values",
+                    ("null", _) => "value now populated; previously always null",
+                    ("string", t) if numeric(t) => "may break parsers expecting text",
+                    (f, "string") if numeric(f) => "may break numeric aggregations and parsers",
+                    _ => "incompatible type; parsers and aggregations may break",
+                };
+                format!("{from}→{to} {consequence}")
+            }
+        }
+    }
+}
+
 pub fn diff_fingerprints(before: &Fingerprint, after: &Fingerprint) -> Vec<SchemaChange> {
     let mut changes = Vec::new();
 
@@ -1,12 +1,13 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashMap;
-use std::sync::Mutex;
 use std::cell::RefCell;
 
-// Global cache for smart masking results - used as fallback when thread-local cache misses
-static SMART_MASK_CACHE: Lazy<Mutex<lru::LruCache<String, SmartMaskingResult>>> = Lazy::new(|| {
-    Mutex::new(lru::LruCache::new(std::num::NonZeroUsize::new(1000).unwrap()))
+// Global cache for smart masking results - used as fallback when thread-local cache misses.
+// The `wasm` build is single-threaded, so the thread-local cache alone suffices there
+#[cfg(not(feature = "wasm"))]
+static SMART_MASK_CACHE: Lazy<std::sync::Mutex<lru::LruCache<String, SmartMaskingResult>>> = Lazy::new(|| {
+    std::sync::Mutex::new(lru::LruCache::new(std::num::NonZeroUsize::new(1000).unwrap()))
 });
 
 // Thread-local LRU cache (8K entries) for better performance in parallel processing
@@ -66,10 +67,11 @@ static USER_AGENT_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#""([^"]*(?:Mozilla|curl|wget|bot|Bot|spider|HealthCheck|Monitor|Apache-HttpClient|python-requests|Go-http-client|Java|Ruby|PHP|Node\.js)[^"]*)"#).unwrap()
 });
 
-static TIMESTAMP_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+/// Timestamp formats (first capture group is the timestamp); shared with `param_extractor`
+pub(crate) static TIMESTAMP_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
     vec![
         // Enhanced ISO8601/RFC3339 with comprehensive timezone and fractional second support
-        Regex::new(r"(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d{1,9})?(?:Z|[+-](?:\d{2}(?::?\d{2})?|\d{4})))").unwrap(),
+        Regex::new(r"\b(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d{1,9})?(?:Z|[+-](?:\d{2}(?::?\d{2})?|\d{4})))\b").unwrap(),
         // Apache/Nginx bracket format with timezone
         Regex::new(r"\[(\d{2}/\w{3}/\d{4}:\d{2}:\d{2}:\d{2}\s+[+-]\d{4})\]").unwrap(),
         // Syslog format (month day time)
@@ -89,6 +91,34 @@ pub enum LogFormat {
     Unknown,
 }
 
+impl LogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::ElasticLoadBalancer => "ELB",
+            LogFormat::NginxAccess => "NGINX",
+            LogFormat::ApacheAccess => "APACHE",
+            LogFormat::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Which known access-log format a line matches, without masking it.
+/// Cheap: every known format starts with a digit, so other lines are rejected up front.
+pub fn detect_format(line: &str) -> LogFormat {
+    if !line.starts_with(|c: char| c.is_ascii_digit()) || !line.contains('"') {
+        return LogFormat::Unknown;
+    }
+    if ELB_PATTERN.is_match(line) {
+        LogFormat::ElasticLoadBalancer
+    } else if NGINX_PATTERN.is_match(line) {
+        LogFormat::NginxAccess
+    } else if APACHE_PATTERN.is_match(line) {
+        LogFormat::ApacheAccess
+    } else {
+        LogFormat::Unknown
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SmartMaskingResult {
     pub template: String,
@@ -108,6 +138,7 @@ pub fn smart_mask_line(line: &str) -> SmartMaskingResult {
     }
     
     // Fallback to global cache via try_lock (non-blocking) to avoid contention
+    #[cfg(not(feature = "wasm"))]
     if let Ok(mut global_cache) = SMART_MASK_CACHE.try_lock() {
         if let Some(cached_result) = global_cache.get(line) {
             let result = cached_result.clone();
@@ -127,6 +158,7 @@ pub fn smart_mask_line(line: &str) -> SmartMaskingResult {
     });
     
     // Only try to fill global cache if we can get the lock without blocking
+    #[cfg(not(feature = "wasm"))]
     if let Ok(mut global_cache) = SMART_MASK_CACHE.try_lock() {
         global_cache.put(line.to_string(), result.clone());
     }
@@ -459,6 +491,25 @@ fn parse_http_request(request: &str) -> (String, String, String) {
 }
 
 
+/// Pre-compile all regex patterns to avoid first-use contention in parallel processing
+pub fn prewarm_regexes() {
+    // Force initialization of all lazy regex patterns
+    let _ = &*QUICK_REJECT_PATTERNS;
+    let _ = &*ELB_PATTERN;
+    let _ = &*NGINX_PATTERN;
+    let _ = &*APACHE_PATTERN;
+    let _ = &*IP_PORT_PATTERN;
+    let _ = &*IP_PATTERN;
+    let _ = &*HTTP_REQUEST_PATTERN;
+    let _ = &*STATUS_CODE_PATTERN;
+    let _ = &*USER_AGENT_QUICK_CHECK;
+    let _ = &*USER_AGENT_PATTERN;
+    let _ = &*TIMESTAMP_PATTERNS;
+    
+    // Note: SMART_MASK_CACHE is not a regex, it's an LRU cache
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -734,21 +785,3 @@ mod tests {
         assert!(result.confidence <= 0.8, "Unknown format should not bypass Drain");
     }
 }
-
-/// Pre-compile all regex patterns to avoid first-use contention in parallel processing
-pub fn prewarm_regexes() {
-    // Force initialization of all lazy regex patterns
-    let _ = &*QUICK_REJECT_PATTERNS;
-    let _ = &*ELB_PATTERN;
-    let _ = &*NGINX_PATTERN;
-    let _ = &*APACHE_PATTERN;
-    let _ = &*IP_PORT_PATTERN;
-    let _ = &*IP_PATTERN;
-    let _ = &*HTTP_REQUEST_PATTERN;
-    let _ = &*STATUS_CODE_PATTERN;
-    let _ = &*USER_AGENT_QUICK_CHECK;
-    let _ = &*USER_AGENT_PATTERN;
-    let _ = &*TIMESTAMP_PATTERNS;
-    
-    // Note: SMART_MASK_CACHE is not a regex, it's an LRU cache
-}
\ No newline at end of file
@@ -0,0 +1,223 @@
+use crate::param_extractor::{self, MaskingResult};
+use std::collections::HashMap;
+
+/// Statement keywords that start a SQL query, each with a keyword the statement must also contain
+const STATEMENTS: [(&str, &str); 4] = [("SELECT", "FROM"), ("INSERT", "INTO"), ("UPDATE", "SET"), ("DELETE", "FROM")];
+
+/// Byte offset where a SQL statement starts in `text`, if any. The statement keyword must be a
+/// whole word, in a consistent case, with its companion keyword in statement position:
+/// `INSERT INTO` and `DELETE FROM` adjacent, `UPDATE <table> SET`, and `SELECT <columns> FROM`
+/// where the columns are a comma-separated list of short expressions. Prose such as
+/// "user selected 3 items" or "failed to delete file from disk" is not treated as SQL.
+pub fn find_sql_start(text: &str) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (keyword, companion) in STATEMENTS {
+        for kw in [keyword.to_string(), keyword.to_lowercase()] {
+            let companion = if kw.starts_with(|c: char| c.is_lowercase()) { companion.to_lowercase() } else { companion.to_string() };
+            let mut from = 0;
+            while let Some(pos) = text[from..].find(&kw).map(|p| p + from) {
+                from = pos + kw.len();
+                if is_word_at(text, pos, kw.len()) && has_statement_shape(keyword, &text[from..], &companion) {
+                    best = Some(best.map_or(pos, |b| b.min(pos)));
+                    break;
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Normalize the SQL statement in `text` so queries cluster by shape: numbers become
+/// `<SQL_NUM>`, quoted strings `<SQL_STR>` and literal IN-lists `IN (<SQL_LIST>)` (the list
+/// length is the param value), with positional names (`SQL_NUM_2`, ...) for repeats.
+/// Text before the statement goes through the regular masker. None when no SQL is found.
+pub fn mask_sql(text: &str) -> Option<MaskingResult> {
+    let start = find_sql_start(text)?;
+    let (prefix, sql) = text.split_at(start);
+    let mut result = if prefix.trim().is_empty() {
+        MaskingResult { masked_text: String::new(), extracted_params: HashMap::new() }
+    } else {
+        param_extractor::mask_and_extract_with_disambiguation(prefix)
+    };
+    let mut masker = SqlMasker::default();
+    let masked_sql = masker.mask(sql);
+    result.masked_text.push_str(&masked_sql);
+    for (k, vals) in masker.params {
+        result.extracted_params.entry(k).or_default().extend(vals);
+    }
+    Some(result)
+}
+
+#[derive(Default)]
+struct SqlMasker {
+    params: HashMap<String, Vec<String>>,
+    positions: HashMap<&'static str, usize>,
+}
+
+impl SqlMasker {
+    /// Record `value` under the next positional name for `kind` and return its placeholder
+    fn placeholder(&mut self, kind: &'static str, value: String) -> String {
+        let n = self.positions.entry(kind).or_insert(0);
+        *n += 1;
+        let name = if *n == 1 { kind.to_string() } else { format!("{kind}_{n}") };
+        let placeholder = format!("<{name}>");
+        self.params.entry(name).or_default().push(value);
+        placeholder
+    }
+
+    fn mask(&mut self, sql: &str) -> String {
+        let mut out = String::with_capacity(sql.len());
+        let mut i = 0;
+        while i < sql.len() {
+            let rest = &sql[i..];
+            let c = rest.chars().next().unwrap();
+            if c == '\'' {
+                let (value, len) = quoted(rest, '\'');
+                out.push_str(&self.placeholder("SQL_STR", value));
+                i += len;
+            } else if c == '"' || c == '`' {
+                // Quoted identifiers are part of the query shape
+                let (_, len) = quoted(rest, c);
+                out.push_str(&rest[..len]);
+                i += len;
+            } else if c.is_whitespace() {
+                let len = rest.find(|ch: char| !ch.is_whitespace()).unwrap_or(rest.len());
+                out.push(' ');
+                i += len;
+            } else if c.is_ascii_digit() && !sql[..i].ends_with(is_ident_char) {
+                let len = rest.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.')).unwrap_or(rest.len());
+                let token = &rest[..len];
+                if is_number(token) {
+                    out.push_str(&self.placeholder("SQL_NUM", token.to_string()));
+                } else {
+                    out.push_str(token);
+                }
+                i += len;
+            } else if rest.get(..2).is_some_and(|w| w.eq_ignore_ascii_case("IN")) && is_word_at(sql, i, 2) {
+                match literal_list(&rest[2..]) {
+                    Some((count, len)) => {
+                        out.push_str(&rest[..2]);
+                        out.push_str(" (");
+                        out.push_str(&self.placeholder("SQL_LIST", count.to_string()));
+                        out.push(')');
+                        i += 2 + len;
+                    }
+                    None => {
+                        out.push_str(&rest[..2]);
+                        i += 2;
+                    }
+                }
+            } else if is_ident_char(c) {
+                // Copy whole identifiers so digits inside them (t1, col_2) stay untouched
+                let len = rest.find(|ch: char| !is_ident_char(ch)).unwrap_or(rest.len());
+                out.push_str(&rest[..len]);
+                i += len;
+            } else {
+                out.push(c);
+                i += c.len_utf8();
+            }
+        }
+        out.trim_end().to_string()
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn is_word_at(text: &str, pos: usize, len: usize) -> bool {
+    let before = text[..pos].chars().next_back().is_none_or(|c| !is_ident_char(c));
+    let after = text[pos + len..].chars().next().is_none_or(|c| !is_ident_char(c));
+    before && after
+}
+
+/// Byte offset of the first whole-word `word` in `text`
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(pos) = text[from..].find(word).map(|p| p + from) {
+        if is_word_at(text, pos, word.len()) {
+            return Some(pos);
+        }
+        from = pos + word.len();
+    }
+    None
+}
+
+/// Whether `after` (the text following `keyword`) continues it as a statement with `companion`
+fn has_statement_shape(keyword: &str, after: &str, companion: &str) -> bool {
+    let Some(pos) = find_word(after, companion) else { return false };
+    let between = &after[..pos];
+    match keyword {
+        "INSERT" | "DELETE" => between.trim().is_empty(),
+        "UPDATE" => between.split_whitespace().count() == 1,
+        // Each column is an expression, optionally aliased (`count(*) n`, `a.id AS id`)
+        _ => !between.trim().is_empty() && between.split(',').all(|column| {
+            let words: Vec<&str> = column.split_whitespace().collect();
+            match words.as_slice() {
+                [_] | [_, _] => true,
+                [_, alias_kw, _] => alias_kw.eq_ignore_ascii_case("AS"),
+                _ => false,
+            }
+        }),
+    }
+}
+
+/// Contents and byte length of a literal starting with `quote`; a doubled quote is an escape.
+/// An unterminated literal runs to the end of the text.
+fn quoted(text: &str, quote: char) -> (String, usize) {
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c == quote {
+            if chars.peek().map(|(_, n)| *n) == Some(quote) {
+                value.push(quote);
+                chars.next();
+                continue;
+            }
+            return (value, idx + c.len_utf8());
+        }
+        value.push(c);
+    }
+    (value, text.len())
+}
+
+fn is_number(token: &str) -> bool {
+    token.parse::<f64>().is_ok()
+        || token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).is_some_and(|h| !h.is_empty() && h.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// `(count, byte length)` of a parenthesized list of number, string or NULL literals at the
+/// start of `text` (leading whitespace allowed); None for subqueries and expressions
+fn literal_list(text: &str) -> Option<(usize, usize)> {
+    let open = text.len() - text.trim_start().len();
+    let body = text[open..].strip_prefix('(')?;
+    let mut pos = 0;
+    let mut count = 0;
+    loop {
+        let item = &body[pos..];
+        let skipped = item.len() - item.trim_start().len();
+        pos += skipped;
+        let item = &body[pos..];
+        let len = if item.starts_with('\'') {
+            let (_, len) = quoted(item, '\'');
+            len
+        } else {
+            let len = item.find(|c: char| c == ',' || c == ')' || c.is_whitespace()).unwrap_or(item.len());
+            let token = &item[..len];
+            let literal = is_number(token.trim_start_matches('-')) || token.eq_ignore_ascii_case("NULL");
+            if !literal {
+                return None;
+            }
+            len
+        };
+        pos += len;
+        count += 1;
+        let item = &body[pos..];
+        pos += item.len() - item.trim_start().len();
+        match body[pos..].chars().next() {
+            Some(',') => pos += 1,
+            Some(')') => return Some((count, open + 1 + pos + 1)),
+            _ => return None,
+        }
+    }
+}
@@ -23,10 +23,47 @@ pub struct SpikeBucket {
     pub zscore: f64,
 }
 
+/// Default minimum number of events a burst must contain to be reported
+pub const DEFAULT_BURST_MIN_EVENTS: usize = 5;
+
+/// Default width of the buckets bursts are measured in
+pub const DEFAULT_BURST_WINDOW_SECS: i64 = 60;
+
+/// Width (ms) of the pre-aggregated buckets kept for a burst `window` when exact timestamps are
+/// not retained: the largest common divisor of the window and one minute, so the buckets re-floor
+/// exactly into burst windows while staying minute-precise for start/end times
+pub fn aggregation_bucket_ms(window: Duration) -> i64 {
+    let (mut a, mut b) = (window.num_milliseconds().max(1), 60_000);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Start of pre-aggregated bucket `bucket` (epoch ms / `bucket_ms`). Buckets floored from the
+/// first representable instants can start before chrono's range; those clamp to the nearest
+/// representable time so their counts are never dropped.
+pub fn bucket_start(bucket: i64, bucket_ms: i64) -> DateTime<Utc> {
+    let ms = bucket.saturating_mul(bucket_ms);
+    Utc.timestamp_millis_opt(ms).single()
+        .unwrap_or(if ms < 0 { DateTime::<Utc>::MIN_UTC } else { DateTime::<Utc>::MAX_UTC })
+}
+
 pub fn compute_bursts(
     times: &[DateTime<Utc>],
     bucket: Duration,
     burst_multiplier: f64,
+) -> Vec<BurstPeriod> {
+    compute_bursts_min_events(times, bucket, burst_multiplier, 1)
+}
+
+/// Like `compute_bursts`, but drops bursts whose buckets hold fewer than `min_events` events in total.
+/// Filters statistically significant but trivial spikes in sparse patterns (e.g. 3 events over a 0.5 baseline).
+pub fn compute_bursts_min_events(
+    times: &[DateTime<Utc>],
+    bucket: Duration,
+    burst_multiplier: f64,
+    min_events: usize,
 ) -> Vec<BurstPeriod> {
     if times.is_empty() {
         return vec![];
@@ -41,36 +78,45 @@ pub fn compute_bursts(
     v.sort_by_key(|(t, _)| *t);
     let median = median_count(&v);
     let threshold = (median as f64 * burst_multiplier).max(1.0);
+    // Severity is measured against the typical bucket outside any burst, so earlier bursts
+    // cannot inflate the baseline and understate a later one
+    let quiet: Vec<(DateTime<Utc>, usize)> = v.iter().filter(|(_, c)| (*c as f64) < threshold).cloned().collect();
+    let baseline = if quiet.is_empty() { median } else { median_count(&quiet) } as f64;
 
     let mut bursts = Vec::new();
     let mut current_start: Option<DateTime<Utc>> = None;
     let mut current_peak: usize = 0;
     let mut current_severity: f64 = 0.0;
+    let mut current_events: usize = 0;
     for (i, (t, c)) in v.iter().enumerate() {
         if (*c as f64) >= threshold {
+            current_events += *c;
             if current_start.is_none() {
                 current_start = Some(*t);
                 current_peak = *c;
-                current_severity = (*c as f64) / (median.max(1) as f64);
+                current_severity = (*c as f64) / baseline;
             } else {
                 current_peak = current_peak.max(*c);
-                current_severity = current_severity.max((*c as f64) / (median.max(1) as f64));
+                current_severity = current_severity.max((*c as f64) / baseline);
             }
         } else if let Some(start) = current_start {
             // close burst at previous bucket
             let prev_t = v[i - 1].0;
-            bursts.push(BurstPeriod {
-                start_time: start,
-                end_time: prev_t,
-                peak_rate: current_peak,
-                severity: current_severity,
-            });
+            if current_events >= min_events {
+                bursts.push(BurstPeriod {
+                    start_time: start,
+                    end_time: prev_t,
+                    peak_rate: current_peak,
+                    severity: current_severity,
+                });
+            }
             current_start = None;
+            current_events = 0;
             current_peak = 0;
             current_severity = 0.0;
         }
     }
-    if let Some(start) = current_start {
+    if let Some(start) = current_start.filter(|_| current_events >= min_events) {
         if let Some((last_t, _)) = v.last() {
             bursts.push(BurstPeriod {
                 start_time: start,
@@ -85,11 +131,11 @@ pub fn compute_bursts(
 }
 
 fn floor_time(t: DateTime<Utc>, bucket: Duration) -> DateTime<Utc> {
-    let secs = bucket.num_seconds();
-    if secs <= 0 { return t; }
-    let ts = t.timestamp();
-    let floored = ts - (ts.rem_euclid(secs));
-    Utc.timestamp_opt(floored, 0).unwrap()
+    let ms = bucket.num_milliseconds();
+    if ms <= 0 { return t; }
+    let ts = t.timestamp_millis();
+    let floored = ts - (ts.rem_euclid(ms));
+    Utc.timestamp_millis_opt(floored).unwrap()
 }
 
 fn median_count(v: &[(DateTime<Utc>, usize)]) -> usize {
@@ -97,13 +143,92 @@ fn median_count(v: &[(DateTime<Utc>, usize)]) -> usize {
     let mut counts: Vec<usize> = v.iter().map(|(_, c)| *c).collect();
     counts.sort_unstable();
     let mid = counts.len() / 2;
-    if counts.len() % 2 == 0 {
+    if counts.len().is_multiple_of(2) {
         ((counts[mid - 1] + counts[mid]) / 2).max(1)
     } else {
         counts[mid].max(1)
     }
 }
 
+/// Share of out-of-order lines at which clock skew is reported
+pub const CLOCK_SKEW_MIN_RATIO: f64 = 0.01;
+/// Backward jump (seconds) at which clock skew is reported regardless of ratio
+pub const CLOCK_SKEW_MIN_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockSkew {
+    /// Lines whose timestamp is earlier than one seen before them
+    pub out_of_order_lines: usize,
+    pub timestamped_lines: usize,
+    /// Largest distance behind the latest timestamp seen so far
+    pub max_backward_seconds: i64,
+}
+
+/// Tracks timestamps in input order to detect clock skew (timestamps going backwards).
+/// Works incrementally so chunked ingestion can share it.
+#[derive(Debug, Clone, Default)]
+pub struct SkewTracker {
+    latest: Option<DateTime<Utc>>,
+    timestamped: usize,
+    out_of_order: usize,
+    max_backward_seconds: i64,
+}
+
+impl SkewTracker {
+    pub fn observe(&mut self, ts: DateTime<Utc>) {
+        self.timestamped += 1;
+        match self.latest {
+            Some(latest) if ts < latest => {
+                self.out_of_order += 1;
+                self.max_backward_seconds = self.max_backward_seconds.max((latest - ts).num_seconds());
+            }
+            _ => self.latest = Some(ts),
+        }
+    }
+
+    /// Number of timestamps observed so far
+    pub fn timestamped(&self) -> usize {
+        self.timestamped
+    }
+
+    /// Skew summary when significant: at least `CLOCK_SKEW_MIN_RATIO` of lines out of order,
+    /// or any jump back of `CLOCK_SKEW_MIN_SECONDS` or more
+    pub fn report(&self) -> Option<ClockSkew> {
+        if self.out_of_order == 0 { return None; }
+        let ratio = self.out_of_order as f64 / self.timestamped as f64;
+        if ratio < CLOCK_SKEW_MIN_RATIO && self.max_backward_seconds < CLOCK_SKEW_MIN_SECONDS { return None; }
+        Some(ClockSkew {
+            out_of_order_lines: self.out_of_order,
+            timestamped_lines: self.timestamped,
+            max_backward_seconds: self.max_backward_seconds,
+        })
+    }
+}
+
+/// Minimum number of timestamps required before periodicity is meaningful
+pub const MIN_PERIODICITY_SAMPLES: usize = 5;
+
+/// Regularity of inter-arrival times in (0, 1], based on their coefficient of variation.
+/// 1.0 = perfectly periodic (cron-like), ~0.5 = random arrivals, lower = clustered bursts.
+/// Returns None when there are fewer than `MIN_PERIODICITY_SAMPLES` timestamps.
+pub fn compute_periodicity(times: &[DateTime<Utc>]) -> Option<f64> {
+    if times.len() < MIN_PERIODICITY_SAMPLES { return None; }
+    let mut s = times.to_vec();
+    s.sort_unstable();
+    let intervals: Vec<f64> = s.windows(2)
+        .map(|w| (w[1] - w[0]).num_milliseconds() as f64)
+        .collect();
+    let n = intervals.len() as f64;
+    let mean = intervals.iter().sum::<f64>() / n;
+    if mean <= 0.0 {
+        // Every event at the same instant: maximally bursty
+        return Some(0.0);
+    }
+    let var = intervals.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+    let cv = var.sqrt() / mean;
+    Some(1.0 / (1.0 + cv))
+}
+
 pub fn compute_gaps(times: &[DateTime<Utc>], gap_multiplier: f64) -> Vec<GapPeriod> {
     if times.len() < 2 { return vec![]; }
     let mut s = times.to_vec();
@@ -115,7 +240,7 @@ pub fn compute_gaps(times: &[DateTime<Utc>], gap_multiplier: f64) -> Vec<GapPeri
     }
     let mut sorted = gaps_durations.clone();
     sorted.sort_unstable();
-    let med = if sorted.len() % 2 == 0 {
+    let med = if sorted.len().is_multiple_of(2) {
         (sorted[sorted.len()/2 - 1] + sorted[sorted.len()/2]) / 2
     } else { sorted[sorted.len()/2] };
     let threshold = (med as f64 * gap_multiplier).max(1.0);
@@ -0,0 +1,10 @@
+//! wasm-bindgen entry points for running the analysis in a browser (`wasm` feature)
+use wasm_bindgen::prelude::*;
+
+/// Summarize pasted log text (one record per line) and return the `AiOutput` as JSON
+#[wasm_bindgen]
+pub fn summarize_lines(text: &str) -> Result<String, JsError> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let out = crate::ai::summarize_lines(&lines);
+    serde_json::to_string(&out).map_err(|e| JsError::new(&e.to_string()))
+}
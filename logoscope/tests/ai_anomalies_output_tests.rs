@@ -1,12 +1,14 @@
 #[test]
 fn ai_output_includes_field_and_temporal_anomalies() {
     // Numeric outlier and a burst for the same pattern
-    let lines = vec![
+    let lines = [
         r#"{"level":"info","time":"2024-01-01T00:00:00Z","op":"query","latency_ms":10}"#,
         r#"{"level":"info","time":"2024-01-01T00:01:00Z","op":"query","latency_ms":12}"#,
         r#"{"level":"info","time":"2024-01-01T00:02:00Z","op":"query","latency_ms":1000}"#,
         r#"{"level":"info","time":"2024-01-01T00:02:05Z","op":"query","latency_ms":1100}"#,
         r#"{"level":"info","time":"2024-01-01T00:02:10Z","op":"query","latency_ms":900}"#,
+        r#"{"level":"info","time":"2024-01-01T00:02:15Z","op":"query","latency_ms":950}"#,
+        r#"{"level":"info","time":"2024-01-01T00:02:20Z","op":"query","latency_ms":1050}"#,
     ];
     let refs: Vec<&str> = lines.iter().map(|s| s.as_ref()).collect();
     let out = logoscope::ai::summarize_lines(&refs);
@@ -14,4 +16,176 @@ fn ai_output_includes_field_and_temporal_anomalies() {
     assert!(!out.anomalies.field_anomalies.is_empty());
     // temporal anomalies should include a burst/gap entry
     assert!(!out.anomalies.temporal_anomalies.is_empty());
+    let burst = &out.anomalies.temporal_anomalies[0];
+    assert_eq!(burst.kind, "burst");
+    assert!(burst.peak_rate > 0 && burst.severity > 1.0);
+
+    // Every anomaly carries a 0-1 score on the same scale
+    let scores: Vec<f64> = out.anomalies.pattern_anomalies.iter().map(|a| a.score)
+        .chain(out.anomalies.field_anomalies.iter().map(|a| a.score))
+        .chain(out.anomalies.temporal_anomalies.iter().map(|a| a.score))
+        .collect();
+    assert!(scores.iter().all(|s| *s > 0.0 && *s <= 1.0), "{scores:?}");
+    let json = serde_json::to_value(&out.anomalies).unwrap();
+    assert!(json["temporal_anomalies"][0]["score"].is_f64());
+    assert!(json["field_anomalies"][0]["score"].is_f64());
+}
+
+#[test]
+fn disappeared_baseline_pattern_gets_high_priority_suggestion() {
+    let lines = [
+        "2024-01-01T00:00:00Z request served in 5 ms",
+        "2024-01-01T00:00:01Z request served in 7 ms",
+    ];
+    let mut opts = logoscope::ai::SummarizeOpts::default();
+    opts.baseline_frequencies.insert("<TIMESTAMP> healthcheck ok".to_string(), 0.5);
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert!(out.anomalies.pattern_anomalies.iter().any(|a| a.kind == "DisappearedPattern" && a.template == "<TIMESTAMP> healthcheck ok"));
+    assert!(out.query_interface.suggested_investigations.iter().any(|s| s.priority == "HIGH" && s.description.contains("DisappearedPattern")));
+}
+
+#[test]
+fn status_surge_flags_5xx_share_and_dominant_failure() {
+    let lines: Vec<String> = (0..40)
+        .map(|i| {
+            let status = if i >= 30 { 503 } else { 200 };
+            format!(r#"10.0.0.{} - - [01/Jan/2024:00:00:{:02} +0000] "GET /api/orders HTTP/1.1" {} 512 "-" "curl/8.0""#, i % 5, i, status)
+        })
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let out = logoscope::ai::summarize_lines(&refs);
+    let surge = out.patterns.iter()
+        .flat_map(|p| p.parameter_anomalies.iter().flatten())
+        .find(|a| a.anomaly_type == "status_surge")
+        .expect("status_surge anomaly");
+    assert_eq!(surge.param, "STATUS_CODE");
+    assert_eq!(surge.value, "503");
+    assert_eq!(surge.count, Some(10));
+    assert!((surge.ratio.unwrap() - 0.25).abs() < 1e-9);
+    assert!(surge.details.contains("latest quarter"));
+}
+
+#[test]
+fn status_surge_ignores_healthy_patterns_and_detects_recent_rise() {
+    use logoscope::ai::{ParamFieldStats, ParamValueCount};
+    use logoscope::analyzers::detect_status_surge;
+    let stats = |values: &[(&str, usize)]| ParamFieldStats {
+        total: values.iter().map(|v| v.1).sum(),
+        cardinality: values.len(),
+        values: values.iter().map(|(v, c)| ParamValueCount { value: v.to_string(), count: *c }).collect(),
+        top_ratio: 0.0,
+        is_sequence: None,
+        sequence_info: None,
+        routes: None,
+        latency_buckets: None,
+        numeric_summary: None,
+    };
+    let healthy = stats(&[("200", 95), ("404", 4), ("500", 1)]);
+    assert!(detect_status_surge("STATUS", &healthy, &[]).is_none());
+
+    // 4% 5xx overall (under STATUS_SURGE_5XX_RATIO), but all of it at the end
+    let mut ordered = vec!["200"; 96];
+    ordered.extend(["502"; 4]);
+    let late = stats(&[("200", 96), ("502", 4)]);
+    let surge = detect_status_surge("STATUS", &late, &ordered).unwrap();
+    assert_eq!(surge.value, "502");
+    assert!(surge.details.contains("rose from 0% to 16%"));
+}
+
+#[test]
+fn latency_params_are_bucketed_and_slow_rise_flagged() {
+    let lines: Vec<String> = (0..40)
+        .map(|i| {
+            let latency = match i { 0..=29 => format!("{}ms", 5 + i * 10), _ if i % 2 == 0 => "2.5s".to_string(), _ => "40ms".to_string() };
+            format!(r#"{{"level":"info","time":"2024-01-01T00:00:{i:02}Z","msg":"request served","latency":"{latency}"}}"#)
+        })
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let out = logoscope::ai::summarize_lines(&refs);
+    let stats = &out.patterns[0].param_stats.as_ref().unwrap()["LATENCY"];
+    let buckets: Vec<(&str, usize)> = stats.latency_buckets.as_ref().unwrap().iter().map(|b| (b.value.as_str(), b.count)).collect();
+    assert_eq!(buckets, [("<10ms", 1), ("10-100ms", 14), ("100ms-1s", 20), (">1s", 5)]);
+
+    let rise = out.patterns[0].parameter_anomalies.iter().flatten()
+        .find(|a| a.anomaly_type == "slow_latency_rise")
+        .expect("slow_latency_rise anomaly");
+    assert_eq!(rise.param, "LATENCY");
+    assert_eq!(rise.count, Some(5));
+    assert!(rise.details.contains("rose from 0% to 50%"), "{}", rise.details);
+}
+
+#[test]
+fn latency_values_are_read_in_the_right_unit() {
+    use logoscope::param_extractor::{is_latency_param, latency_ms};
+    assert!(is_latency_param("RESPONSE_TIME") && is_latency_param("DURATION_MS") && !is_latency_param("TIMESTAMP"));
+    assert_eq!(latency_ms("RESPONSE_TIME", "0.25"), Some(250.0)); // ELB logs seconds
+    assert_eq!(latency_ms("RESPONSE_TIME", "-1"), None);
+    assert_eq!(latency_ms("DURATION_MS", "12"), Some(12.0));
+    assert_eq!(latency_ms("LATENCY", "1.5s"), Some(1500.0));
+    assert_eq!(latency_ms("ELAPSED_US", "500"), Some(0.5));
+    assert_eq!(latency_ms("ELAPSED", "1m30.5s"), Some(90_500.0));
+}
+
+#[test]
+fn enum_drift_reported_for_new_values_of_known_field() {
+    let lines = [
+        r#"{"level":"info","time":"2024-01-01T00:00:00Z","msg":"payment","method":"card"}"#,
+        r#"{"level":"info","time":"2024-01-01T00:00:01Z","msg":"payment","method":"crypto"}"#,
+    ];
+    let mut opts = logoscope::ai::SummarizeOpts::default();
+    opts.baseline_field_values.insert("method".into(), ["card", "paypal"].iter().map(|s| s.to_string()).collect());
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    let drift: Vec<_> = out.anomalies.field_anomalies.iter().filter(|a| a.anomaly_type == "enum_drift").collect();
+    assert_eq!(drift.len(), 1);
+    assert_eq!(drift[0].field, "method");
+    assert_eq!(drift[0].new_values, vec!["crypto".to_string()]);
+    assert_eq!(drift[0].unique_count, Some(2));
+
+    // Without a baseline no enum_drift is emitted
+    let plain = logoscope::ai::summarize_lines(&lines);
+    assert!(plain.anomalies.field_anomalies.iter().all(|a| a.anomaly_type != "enum_drift"));
+}
+
+#[test]
+fn ratio_anomaly_compares_latest_quarter_with_earlier_lines() {
+    // 30 lines at 1 failure per 9 successes, then 10 lines at 1:1
+    let mut lines: Vec<String> = Vec::new();
+    for i in 0..40 {
+        let failed = if i < 30 { i % 10 == 0 } else { i % 2 == 0 };
+        let outcome = if failed { "failed" } else { "succeeded" };
+        lines.push(format!("2024-01-01T00:00:{i:02}Z INFO login {outcome} for user {i}"));
+    }
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = logoscope::ai::SummarizeOpts {
+        ratio_rules: vec![logoscope::anomaly::RatioRule::parse("failed / succeeded").unwrap()],
+        ..Default::default()
+    };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let ratios = &out.anomalies.ratio_anomalies;
+    assert_eq!(ratios.len(), 1, "{ratios:?}");
+    assert_eq!(ratios[0].baseline, "earlier_lines");
+    assert!((ratios[0].baseline_ratio - 3.0 / 27.0).abs() < 1e-9);
+    assert!((ratios[0].current_ratio - 1.0).abs() < 1e-9);
+
+    let triage = logoscope::ai::create_triage_output(&out);
+    assert!(triage.summary.anomaly_count >= 1);
+}
+
+#[test]
+fn explain_anomalies_attaches_source_lines_as_evidence() {
+    let mut lines: Vec<String> = (0..8)
+        .map(|i| format!(r#"{{"level":"info","time":"2024-01-01T00:00:0{i}Z","msg":"query done","latency_ms":{}}}"#, 10 + i % 3))
+        .collect();
+    lines.push(r#"{"level":"info","time":"2024-01-01T00:00:09Z","msg":"query done","latency_ms":5000}"#.to_string());
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+
+    let plain = logoscope::ai::summarize_lines(&refs);
+    assert!(plain.anomalies.field_anomalies.iter().all(|a| a.evidence.is_empty()));
+
+    let opts = logoscope::ai::SummarizeOpts { explain_anomalies: true, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let outlier = out.anomalies.field_anomalies.iter()
+        .find(|a| a.anomaly_type == "numeric_outlier" && a.field == "latency_ms")
+        .expect("latency outlier");
+    assert_eq!(outlier.evidence, vec![lines[8].clone()]);
 }
@@ -6,7 +6,7 @@ fn ai_output_contains_correlated_patterns() {
     let fmt_a = |t: &str| format!(r#"{{"level":"info","time":"{}","op":"A","status":"success"}}"#, t);
     let fmt_b = |t: &str| format!(r#"{{"level":"info","time":"{}","action":"B","result":"ok"}}"#, t);
     let ts = |dt: chrono::DateTime<chrono::Utc>| dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
-    let lines = vec![
+    let lines = [
         fmt_a(&ts(start)),
         fmt_b(&ts(start + Duration::seconds(4))),
         fmt_a(&ts(start + Duration::seconds(5))),
@@ -27,3 +27,27 @@ fn ai_output_contains_correlated_patterns() {
     // The compression ratio should be good (> 1.0 means clustering happened)
     assert!(out.summary.compression_ratio >= 1.0, "Should achieve compression through clustering");
 }
+
+#[test]
+fn correlate_flag_populates_pattern_correlations() {
+    let mut lines = Vec::new();
+    for m in 0..6 {
+        lines.push(format!("2024-01-01T00:{:02}:00Z db connection lost to primary", m * 5));
+        lines.push(format!("2024-01-01T00:{:02}:02Z failover started for replica set", m * 5));
+        lines.push(format!("2024-01-01T00:{:02}:30Z heartbeat ok", m * 5 + 2));
+    }
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+
+    let plain = logoscope::ai::summarize_lines(&refs);
+    assert!(plain.patterns.iter().all(|p| p.correlations.is_empty()));
+    assert!(!serde_json::to_string(&plain).unwrap().contains("\"correlations\""));
+
+    let opts = logoscope::ai::SummarizeOpts { correlate: true, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let lost = out.patterns.iter().find(|p| p.template.contains("connection lost")).expect("db pattern");
+    assert_eq!(lost.correlations.len(), 1);
+    assert!(lost.correlations[0].template.contains("failover"));
+    assert_eq!(lost.correlations[0].count, 6);
+    let json = serde_json::to_value(lost).unwrap();
+    assert!(json["correlations"][0]["strength"].as_f64().unwrap() > 0.9);
+}
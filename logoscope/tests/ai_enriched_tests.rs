@@ -23,3 +23,274 @@ fn ai_enriched_output_includes_patterns_and_schema_changes() {
     // This is expected behavior - schema tracking requires comparing against a baseline
 }
 
+
+#[test]
+fn ai_examples_span_distinct_param_values() {
+    let mut lines: Vec<String> = (0..8)
+        .map(|i| format!(r#"{{"level":"error","time":"2024-01-01T00:0{i}:00Z","msg":"request failed","status":500}}"#))
+        .collect();
+    lines.push(r#"{"level":"error","time":"2024-01-01T00:08:00Z","msg":"request failed","status":404}"#.to_string());
+    lines.push(r#"{"level":"error","time":"2024-01-01T00:09:00Z","msg":"request failed","status":503}"#.to_string());
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let out = logoscope::ai::summarize_lines(&refs);
+    let p = out.patterns.iter().max_by_key(|p| p.total_count).unwrap();
+    assert_eq!(p.total_count, 10);
+    assert!(p.examples.len() <= 3);
+    assert!(p.examples.iter().any(|e| e.contains("404")), "examples: {:?}", p.examples);
+    assert!(p.examples.iter().any(|e| e.contains("503")), "examples: {:?}", p.examples);
+}
+
+#[test]
+fn ai_path_params_grouped_by_route() {
+    let lines = [
+        "2024-01-01T00:00:01Z GET /users/123/orders/45 200",
+        "2024-01-01T00:00:02Z GET /users/999/orders/7 200",
+        "2024-01-01T00:00:03Z GET /users/5/profile 200",
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let p = out.patterns.iter().find(|p| p.template.contains("<PATH>")).unwrap();
+    let stats = &p.param_stats.as_ref().unwrap()["PATH"];
+    assert_eq!(stats.cardinality, 3, "original values are kept");
+    let routes = stats.routes.as_ref().expect("routes");
+    assert_eq!(routes[0].value, "/users/{id}/orders/{id}");
+    assert_eq!(routes[0].count, 2);
+}
+
+#[test]
+fn ai_redact_masks_examples_and_param_values() {
+    use logoscope::param_extractor::ParamType;
+    let lines = [
+        "2024-01-01T00:00:01Z login ok for alice@example.com from 10.0.0.1",
+        "2024-01-01T00:00:02Z login ok for bob@example.com from 10.0.0.2",
+    ];
+    let opts = logoscope::ai::SummarizeOpts { redact: Some(vec![ParamType::Email]), ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    let p = &out.patterns[0];
+    assert!(p.examples.iter().all(|e| e.contains("<EMAIL>") && !e.contains("example.com")));
+    assert!(p.examples[0].contains("10.0.0.1"), "unselected types stay raw");
+    let email = &p.param_stats.as_ref().unwrap()["EMAIL"];
+    assert_eq!(email.cardinality, 2);
+    assert_eq!(email.values.len(), 1);
+    assert_eq!(email.values[0].value, "<EMAIL>");
+    assert_eq!(email.values[0].count, 2);
+
+    let raw = logoscope::ai::summarize_lines(&lines);
+    assert!(raw.patterns[0].examples[0].contains("alice@example.com"));
+}
+
+#[test]
+fn ai_long_param_values_are_truncated() {
+    let body = "x".repeat(5000);
+    let lines = [
+        format!(r#"{{"time":"2024-01-01T00:00:00Z","msg":"bad request","body":"a{body}"}}"#),
+        format!(r#"{{"time":"2024-01-01T00:00:01Z","msg":"bad request","body":"b{body}"}}"#),
+    ];
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = logoscope::ai::SummarizeOpts { max_value_length: 32, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let stats = out.patterns[0].param_stats.as_ref().expect("param stats");
+    let body_stats = stats.get("BODY").expect("BODY param");
+    assert!(body_stats.values.iter().all(|v| v.value.chars().count() < 64 && v.value.ends_with("(truncated)")));
+}
+
+#[test]
+fn ai_numeric_params_include_range_summary() {
+    use logoscope::ai::{compute_numeric_summary, ParamValueCount};
+    let vals = |v: &[(&str, usize)]| v.iter().map(|(s, c)| ParamValueCount { value: s.to_string(), count: *c }).collect::<Vec<_>>();
+    let s = compute_numeric_summary(&vals(&[("10", 2), ("20", 1), ("40ms", 1)])).unwrap();
+    assert_eq!(s.min, 10.0);
+    assert_eq!(s.max, 40.0);
+    assert_eq!(s.mean, 20.0);
+    assert_eq!(s.p50, 10.0);
+    assert_eq!(s.p99, 40.0);
+    assert!((s.stddev - 150f64.sqrt()).abs() < 1e-9);
+    assert!(compute_numeric_summary(&vals(&[("10", 1), ("timeout", 1)])).is_none());
+    assert!(compute_numeric_summary(&vals(&[("2024-01-01", 1)])).is_none());
+
+    let lines: Vec<String> = [12, 15, 250, 18]
+        .iter()
+        .enumerate()
+        .map(|(i, ms)| format!(r#"{{"time":"2024-01-01T00:00:0{i}Z","msg":"query done","latency_ms":{ms}}}"#))
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let out = logoscope::ai::summarize_lines(&refs);
+    let latency = &out.patterns[0].param_stats.as_ref().unwrap()["LATENCY_MS"];
+    let summary = latency.numeric_summary.as_ref().expect("numeric summary");
+    assert_eq!(summary.max, 250.0);
+    assert_eq!(summary.min, 12.0);
+}
+
+#[test]
+fn ai_decode_b64_json_is_opt_in() {
+    let lines = [
+        "2024-01-01T00:00:00Z INFO event envelope eyJ0eXBlIjoib3JkZXIuY3JlYXRlZCIsInVzZXIiOiJ1MSJ9 accepted",
+        "2024-01-01T00:00:01Z INFO event envelope eyJ0eXBlIjoib3JkZXIucGFpZCIsInVzZXIiOiJ1MiJ9 accepted",
+    ];
+    let plain = logoscope::ai::summarize_lines(&lines);
+    let has_decoded = |out: &logoscope::ai::AiOutput| out.patterns.iter()
+        .any(|p| p.param_stats.as_ref().is_some_and(|s| s.contains_key("B64_JSON_TYPE")));
+    assert!(!has_decoded(&plain));
+
+    let opts = logoscope::ai::SummarizeOpts { decode_b64_json: true, ..Default::default() };
+    let decoded = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert!(has_decoded(&decoded));
+}
+
+#[test]
+fn ai_query_params_reach_param_stats_in_both_modes() {
+    let lines: Vec<String> = ["view", "view", "view", "delete"].iter().enumerate()
+        .map(|(i, action)| format!("2024-01-01T00:00:0{i}Z INFO GET /v1/items?action={action}&id={i} 200"))
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let opts = logoscope::ai::SummarizeOpts { query_params: true, ..Default::default() };
+    let direct = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    engine.ingest_chunk(&lines, &[], &opts);
+    let chunked = engine.finalize(None, &opts);
+    for out in [&direct, &chunked] {
+        assert_eq!(out.patterns.len(), 1);
+        let stats = out.patterns[0].param_stats.as_ref().unwrap();
+        let action: Vec<(&str, usize)> = stats["QUERY_action"].values.iter().map(|v| (v.value.as_str(), v.count)).collect();
+        assert_eq!(action, [("view", 3), ("delete", 1)]);
+        assert_eq!(stats["QUERY_id"].cardinality, 4);
+    }
+}
+
+#[test]
+fn ai_pattern_clusters_are_opt_in() {
+    let lines = [
+        "2024-01-01T00:00:00Z connection timeout to 10.0.0.1 after 30 ms",
+        "2024-01-01T00:00:01Z connection timeout to 10.0.0.2 after 31 ms",
+        "2024-01-01T00:00:02Z connection refused by 10.0.0.3 after 2 ms",
+        "2024-01-01T00:00:03Z cache warmed in 120 ms",
+    ];
+    let plain = logoscope::ai::summarize_lines(&lines);
+    assert!(plain.pattern_clusters.is_empty());
+    assert!(serde_json::to_value(&plain).unwrap().get("pattern_clusters").is_none());
+
+    let opts = logoscope::ai::SummarizeOpts { pattern_cluster_similarity: Some(0.6), ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), plain.patterns.len(), "per-pattern output is unchanged");
+    assert_eq!(out.pattern_clusters.len(), 1);
+    let family = &out.pattern_clusters[0];
+    assert_eq!(family.members.len(), 2);
+    assert_eq!(family.total_count, 3);
+    assert!(family.representative.contains("timeout"));
+}
+
+#[test]
+fn severity_breakdown_exposes_rare_levels_of_the_same_message() {
+    let mut lines: Vec<String> = (0..19)
+        .map(|i| format!(r#"{{"level":"info","time":"2024-01-01T00:00:{i:02}Z","msg":"payment processed"}}"#))
+        .collect();
+    lines.push(r#"{"level":"error","time":"2024-01-01T00:00:30Z","msg":"payment processed"}"#.to_string());
+    lines.push(r#"{"level":"info","time":"2024-01-01T00:00:31Z","msg":"cache warmed","cache":"redis"}"#.to_string());
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let out = logoscope::ai::summarize_lines(&refs);
+
+    let payment: Vec<_> = out.patterns.iter().filter(|p| p.examples[0].contains("payment processed")).collect();
+    assert_eq!(payment.len(), 2);
+    for p in payment {
+        let breakdown: Vec<(&str, usize)> = p.severity_breakdown.iter().map(|c| (c.name.as_str(), c.count)).collect();
+        assert_eq!(breakdown, vec![("info", 19), ("error", 1)]);
+    }
+    let cache = out.patterns.iter().find(|p| p.examples[0].contains("cache warmed")).unwrap();
+    assert!(cache.severity_breakdown.is_empty());
+}
+
+#[test]
+fn top_params_caps_value_lists_but_keeps_cardinality() {
+    let lines: Vec<String> = (0..60)
+        .map(|i| format!(r#"{{"level":"info","time":"2024-01-01T00:00:{:02}Z","msg":"login","user":"u{}"}}"#, i, i % 12))
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = logoscope::ai::SummarizeOpts { deep: true, top_params: Some(3), ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let user = &out.patterns[0].param_stats.as_ref().unwrap()["USER"];
+    assert_eq!(user.values.len(), 3);
+    assert_eq!(user.cardinality, 12);
+    assert_eq!(user.total, 60);
+}
+
+#[test]
+fn schema_changes_carry_old_and_new_types_with_impact() {
+    let lines = [
+        r#"{"level":"info","time":"2024-01-01T00:00:00Z","status":"ok","region":"eu"}"#,
+        r#"{"level":"info","time":"2024-01-01T00:01:00Z","status":200,"retries":1}"#,
+    ];
+    let baseline = std::collections::HashSet::new();
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], Some(&baseline), &Default::default());
+    let change = |field: &str| out.schema_changes.iter().find(|c| c.field == field).unwrap_or_else(|| panic!("no change for {field}"));
+
+    let status = change("status");
+    assert_eq!(status.change_type, "type_changed");
+    assert_eq!(status.old_type.as_deref(), Some("string"));
+    assert_eq!(status.new_type.as_deref(), Some("int"));
+    assert_eq!(status.impact.as_deref(), Some("string→int may break parsers expecting text"));
+
+    let region = change("region");
+    assert_eq!(region.change_type, "field_removed");
+    assert_eq!(region.old_type.as_deref(), Some("string"));
+    assert!(region.new_type.is_none());
+
+    let retries = change("retries");
+    assert_eq!(retries.new_type.as_deref(), Some("int"));
+    assert!(retries.impact.is_some());
+}
+
+#[test]
+fn merge_levels_clusters_a_message_across_levels() {
+    let mut lines: Vec<String> = (0..6)
+        .map(|i| format!(r#"{{"level":"info","time":"2024-01-01T00:00:{i:02}Z","msg":"queue depth high"}}"#))
+        .collect();
+    lines.extend((6..9).map(|i| format!(r#"{{"level":"warn","time":"2024-01-01T00:00:{i:02}Z","msg":"queue depth high"}}"#)));
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+
+    assert_eq!(logoscope::ai::summarize_lines(&refs).patterns.len(), 2);
+
+    let opts = logoscope::ai::SummarizeOpts { merge_levels: true, ..Default::default() };
+    for out in [
+        logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts),
+        {
+            let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+            engine.ingest_chunk(&lines, &[], &opts);
+            engine.finalize(None, &opts)
+        },
+    ] {
+        assert_eq!(out.patterns.len(), 1);
+        let p = &out.patterns[0];
+        assert_eq!(p.total_count, 9);
+        assert_eq!(p.severity.as_deref(), Some("info"));
+        assert!(!p.template.ends_with("[info]"));
+        let breakdown: Vec<(&str, usize)> = p.severity_breakdown.iter().map(|c| (c.name.as_str(), c.count)).collect();
+        assert_eq!(breakdown, vec![("info", 6), ("warn", 3)]);
+    }
+}
+
+#[test]
+fn show_drain_template_exposes_the_raw_template() {
+    let lines: Vec<String> = (0..4)
+        .map(|i| format!("2024-01-01T00:00:0{i}Z INFO worker {} finished job in {}ms", i + 10, i * 7 + 3))
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+
+    let hidden = logoscope::ai::summarize_lines(&refs);
+    assert!(hidden.patterns.iter().all(|p| p.drain_template.is_none()));
+    assert!(!serde_json::to_string(&hidden).unwrap().contains("drain_template"));
+
+    let opts = logoscope::ai::SummarizeOpts { show_drain_template: true, ..Default::default() };
+    for out in [
+        logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts),
+        {
+            let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+            engine.ingest_chunk(&lines, &[], &opts);
+            engine.finalize(None, &opts)
+        },
+    ] {
+        assert_eq!(out.patterns.len(), 1);
+        let raw = out.patterns[0].drain_template.as_deref().expect("raw template");
+        assert!(raw.contains("<NUM>"), "{raw}");
+        let json = serde_json::to_value(&out).unwrap();
+        assert_eq!(json["patterns"][0]["drain_template"], raw);
+    }
+}
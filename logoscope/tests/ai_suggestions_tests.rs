@@ -1,18 +1,44 @@
 #[test]
 fn suggestions_include_burst_time_query() {
     // Construct lines with a clear burst for a single pattern
-    let mut lines: Vec<String> = Vec::new();
-    lines.push("{\"level\":\"info\",\"time\":\"2024-01-01T00:00:00Z\",\"msg\":\"ok\"}".into());
-    lines.push("{\"level\":\"info\",\"time\":\"2024-01-01T00:01:00Z\",\"msg\":\"ok\"}".into());
-    // burst window
-    lines.push("{\"level\":\"info\",\"time\":\"2024-01-01T00:02:00Z\",\"msg\":\"ok\"}".into());
-    lines.push("{\"level\":\"info\",\"time\":\"2024-01-01T00:02:10Z\",\"msg\":\"ok\"}".into());
-    lines.push("{\"level\":\"info\",\"time\":\"2024-01-01T00:02:20Z\",\"msg\":\"ok\"}".into());
+    let lines: Vec<String> = vec![
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:00:00Z\",\"msg\":\"ok\"}".into(),
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:01:00Z\",\"msg\":\"ok\"}".into(),
+        // burst window
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:02:00Z\",\"msg\":\"ok\"}".into(),
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:02:10Z\",\"msg\":\"ok\"}".into(),
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:02:20Z\",\"msg\":\"ok\"}".into(),
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:02:30Z\",\"msg\":\"ok\"}".into(),
+        "{\"level\":\"info\",\"time\":\"2024-01-01T00:02:40Z\",\"msg\":\"ok\"}".into(),
+    ];
     let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
     let out = logoscope::ai::summarize_lines(&refs);
     let qi = out.query_interface;
     assert!(qi.available_commands.contains(&"GET_LINES_BY_TIME".to_string()));
     let any_time = qi.suggested_investigations.iter().any(|s| s.query.command == "GET_LINES_BY_TIME" && s.query.params.start.is_some() && s.query.params.end.is_some());
     assert!(any_time);
+    let burst = qi.suggested_investigations.iter().find(|s| s.description.starts_with("Pattern burst")).unwrap();
+    assert_eq!(burst.reason_code, logoscope::ai::ReasonCode::Burst);
+    assert_eq!(serde_json::to_value(burst).unwrap()["reason_code"], "BURST");
 }
 
+
+#[test]
+fn pattern_anomaly_suggestions_carry_reason_codes() {
+    use logoscope::ai::ReasonCode;
+    let lines = ["2024-01-01T00:00:00Z request served in 5 ms", "2024-01-01T00:00:01Z request served in 7 ms"];
+    let mut opts = logoscope::ai::SummarizeOpts::default();
+    opts.baseline_frequencies.insert("<TIMESTAMP> healthcheck ok".to_string(), 0.5);
+    let baseline = std::collections::HashSet::from(["<TIMESTAMP> healthcheck ok".to_string()]);
+    let owned: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+
+    let direct = logoscope::ai::summarize_lines_with_opts(&lines, &[], Some(&baseline), &opts);
+    let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    engine.ingest_chunk(&owned, &[], &opts);
+    let chunked = engine.finalize(Some(&baseline), &opts);
+    for out in [direct, chunked] {
+        let codes: Vec<ReasonCode> = out.query_interface.suggested_investigations.iter().map(|s| s.reason_code).collect();
+        assert!(codes.contains(&ReasonCode::DisappearedPattern), "{codes:?}");
+        assert!(codes.contains(&ReasonCode::NewPattern), "{codes:?}");
+    }
+}
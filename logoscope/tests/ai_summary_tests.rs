@@ -20,3 +20,393 @@ fn builds_basic_ai_summary() {
         Some("2024-01-01T00:02:00Z")
     );
 }
+
+#[test]
+fn summary_reports_clock_skew_for_out_of_order_input() {
+    let lines = [
+        "2024-01-01T00:10:00Z host-a request served",
+        "2024-01-01T00:11:00Z host-a request served",
+        "2024-01-01T00:02:00Z host-b request served",
+        "2024-01-01T00:12:00Z host-a request served",
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let skew = out.summary.clock_skew.expect("clock skew reported");
+    assert_eq!(skew.out_of_order_lines, 1);
+    assert_eq!(skew.max_backward_seconds, 540);
+
+    let ordered = logoscope::ai::summarize_lines(&[lines[0], lines[1], lines[3]]);
+    assert!(ordered.summary.clock_skew.is_none());
+}
+
+#[test]
+fn summary_reports_detected_log_formats() {
+    let lines = [
+        r#"192.168.1.10 - - [10/Oct/2023:13:55:36 +0000] "GET /index.html HTTP/1.1" 200 1024 "-" "Mozilla/5.0""#,
+        r#"192.168.1.11 - - [10/Oct/2023:13:55:37 +0000] "GET /about HTTP/1.1" 200 512 "-" "curl/8.0""#,
+        r#"{"time":"2023-10-10T13:55:38Z","level":"info","msg":"ready"}"#,
+        "2023-10-10T13:55:39Z worker started",
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let counts: std::collections::HashMap<&str, usize> = out.summary.detected_formats.iter().map(|c| (c.name.as_str(), c.count)).collect();
+    assert_eq!(counts.get("NGINX"), Some(&2));
+    assert_eq!(counts.get("JSON"), Some(&1));
+    assert_eq!(counts.get("UNKNOWN"), Some(&1));
+    assert_eq!(out.summary.detected_formats[0].name, "NGINX");
+}
+
+#[test]
+fn syslog_facilities_are_counted_in_summary() {
+    let lines = [
+        "<38>Oct 11 22:14:15 web01 sshd[811]: Accepted password for bob",
+        "<38>Oct 11 22:14:16 web01 sshd[812]: Accepted password for amy",
+        "<86>Oct 11 22:14:17 web01 sudo: pam_unix(sudo:session): session opened",
+        "<78>Oct 11 22:15:00 web01 CRON[900]: (root) CMD (run-parts /etc/cron.hourly)",
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let facilities: Vec<(&str, usize)> = out.summary.by_facility.iter().map(|c| (c.name.as_str(), c.count)).collect();
+    assert_eq!(facilities, vec![("auth", 2), ("authpriv", 1), ("cron", 1)]);
+    let sshd = out.patterns.iter().find(|p| p.examples[0].contains("sshd")).unwrap();
+    assert_eq!(sshd.dominant_service.as_deref(), Some("sshd"));
+    assert_eq!(sshd.severity.as_deref(), Some("INFO"));
+}
+
+#[test]
+fn analyze_only_returns_output_with_stage_timings() {
+    let lines = ["2024-01-01T00:00:00Z User 1 logged in", "2024-01-01T00:00:01Z User 2 logged in"];
+    let (out, timings) = logoscope::ai::analyze_only(&lines, &[], &logoscope::ai::SummarizeOpts::default());
+    assert_eq!(out.summary.total_lines, 2);
+    assert_eq!(timings.lines, 2);
+    assert!(!timings.stages.is_empty());
+    assert!(timings.total_seconds >= timings.stages[0].seconds);
+}
+
+#[test]
+fn json_array_batches_count_each_element_as_a_record() {
+    let lines = [
+        r#"[{"level":"info","time":"2024-01-01T00:00:00Z","msg":"ok"},{"level":"info","time":"2024-01-01T00:00:01Z","msg":"ok"},{"level":"error","time":"2024-01-01T00:00:02Z","msg":"boom"}]"#,
+        r#"{"level":"info","time":"2024-01-01T00:00:03Z","msg":"ok"}"#,
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    assert_eq!(out.summary.total_lines, 4);
+    assert_eq!(out.errors.total, 0);
+
+    let opts = logoscope::ai::SummarizeOpts::default();
+    let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    let owned: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+    engine.ingest_chunk(&owned, &[], &opts);
+    let chunked = engine.finalize(None, &opts);
+    assert_eq!(chunked.summary.total_lines, 4);
+    assert_eq!(chunked.errors.total, 0);
+}
+
+#[test]
+fn crash_headers_without_level_tokens_are_fatal() {
+    let lines = [
+        "2024-01-01T00:00:00Z request served in 12ms",
+        "panic: runtime error: invalid memory address or nil pointer dereference",
+        "thread 'main' panicked at src/main.rs:10:5",
+        "goroutine 17 [running]:",
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    for needle in ["panic:", "panicked at", "goroutine"] {
+        let p = out.patterns.iter().find(|p| p.examples[0].contains(needle)).unwrap();
+        assert_eq!(p.severity.as_deref(), Some("FATAL"), "{needle}");
+    }
+    let served = out.patterns.iter().find(|p| p.examples[0].contains("served")).unwrap();
+    assert_eq!(served.severity, None);
+
+    let triage = logoscope::ai::create_triage_output(&out);
+    assert_eq!(triage.summary.error_lines, 3);
+
+    // An explicit level token before the crash wording wins
+    let out = logoscope::ai::summarize_lines(&[
+        "2024-01-01T00:00:00Z INFO handler recovered from panic: nil map write",
+        "2024-01-01T00:00:01Z main.go:42: panic: nil map write",
+    ]);
+    let severity = |needle: &str| out.patterns.iter().find(|p| p.examples[0].contains(needle)).unwrap().severity.clone();
+    assert_eq!(severity("recovered").as_deref(), Some("INFO"));
+    assert_eq!(severity("main.go").as_deref(), Some("FATAL"));
+
+    // `panic:` mid-message is prose, not a crash header
+    for line in ["2024-01-01T00:00:02Z worker recovered from panic: nil map", "2024-01-01T00:00:03Z handled panic: retrying"] {
+        let out = logoscope::ai::summarize_lines(&[line]);
+        assert_ne!(out.patterns[0].severity.as_deref(), Some("FATAL"), "{line}");
+    }
+    let out = logoscope::ai::summarize_lines(&["2024-01-01T00:00:04Z [worker-3] panic: boom"]);
+    assert_eq!(out.patterns[0].severity.as_deref(), Some("FATAL"));
+}
+
+#[test]
+fn mask_sql_clusters_queries_by_shape() {
+    let lines = [
+        "2024-01-01T00:00:00Z statement: SELECT id FROM orders WHERE status IN (1, 2, 3) AND note = 'rush'",
+        "2024-01-01T00:00:01Z statement: SELECT id FROM orders WHERE status IN (4) AND note = 'gift wrap'",
+    ];
+    assert_eq!(logoscope::ai::summarize_lines(&lines).patterns.len(), 2);
+
+    let opts = logoscope::ai::SummarizeOpts { mask_sql: true, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1);
+    assert!(out.patterns[0].template.contains("IN (<SQL_LIST>)"), "{}", out.patterns[0].template);
+}
+
+#[test]
+fn mask_sql_leaves_json_lines_and_prose_alone() {
+    let opts = logoscope::ai::SummarizeOpts { mask_sql: true, ..Default::default() };
+    let lines = [
+        r#"{"level":"info","msg":"query done","sql":"SELECT * FROM users WHERE id = 5","ms":3}"#,
+        r#"{"level":"info","msg":"query done","sql":"DELETE FROM sessions WHERE user_id = 77","ms":12}"#,
+    ];
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1);
+    assert!(out.patterns[0].template.contains("sql = <SQL>"), "{}", out.patterns[0].template);
+
+    let lines = ["failed to delete file from disk after 3 attempts"];
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert!(!out.patterns[0].template.contains("SQL_"), "{}", out.patterns[0].template);
+}
+
+#[test]
+fn keep_literals_stay_verbatim_in_templates() {
+    let lines = [
+        "2024-01-01T00:00:00Z INFO job status=SUCCESS method=GET",
+        "2024-01-01T00:00:01Z INFO job status=FAILED method=GET",
+        "2024-01-01T00:00:02Z INFO job status=FAILED method=GET",
+        "2024-01-01T00:00:03Z INFO job status=SKIPPED method=GET",
+    ];
+    let opts = logoscope::ai::SummarizeOpts {
+        keep_literals: vec!["SUCCESS".into(), "FAILED".into()],
+        ..Default::default()
+    };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    let mut templates: Vec<&str> = out.patterns.iter().map(|p| p.template.as_str()).collect();
+    templates.sort_unstable();
+    assert_eq!(templates.len(), 3, "{templates:?}");
+    assert!(templates.iter().any(|t| t.contains("status = FAILED method = <METHOD>")), "{templates:?}");
+    assert!(templates.iter().any(|t| t.contains("status = SUCCESS method = <METHOD>")), "{templates:?}");
+    // Words outside the list are still params
+    assert!(templates.iter().any(|t| t.contains("status = <STATUS>")), "{templates:?}");
+}
+
+#[test]
+fn summary_reports_timestamp_coverage() {
+    let lines: Vec<String> = vec![
+        "2024-01-01T00:00:00Z worker started".into(),
+        "worker heartbeat".into(),
+        "worker heartbeat".into(),
+        "worker heartbeat".into(),
+    ];
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = logoscope::ai::SummarizeOpts::default();
+    let direct = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    engine.ingest_chunk(&lines, &[], &opts);
+    let chunked = engine.finalize(None, &opts);
+    assert_eq!(direct.summary.timestamped_ratio, 0.25);
+    assert_eq!(chunked.summary.timestamped_ratio, 0.25);
+
+    assert_eq!(logoscope::ai::summarize_lines(&[]).summary.timestamped_ratio, 0.0);
+}
+
+#[test]
+fn random_token_masking_is_opt_in() {
+    let lines = [
+        "2024-01-01T00:00:00Z INFO session k8Jd92hQpL0zXw3R opened",
+        "2024-01-01T00:00:01Z INFO session aZ3kQ9xP2mL7vB4n opened",
+        "2024-01-01T00:00:02Z INFO session Xy7pQ2rT9vK4mZw8 opened",
+    ];
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &logoscope::ai::SummarizeOpts::default());
+    assert!(out.patterns.iter().all(|p| !p.template.contains("<RANDOM>")), "{:?}", out.patterns.iter().map(|p| &p.template).collect::<Vec<_>>());
+
+    let opts = logoscope::ai::SummarizeOpts { random_token_entropy: Some(3.5), ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1);
+    assert!(out.patterns[0].template.contains("session <RANDOM> opened"), "{}", out.patterns[0].template);
+    assert_eq!(out.patterns[0].total_count, 3);
+}
+
+#[test]
+fn global_params_roll_up_values_across_patterns() {
+    let lines = [
+        "2024-01-01T00:00:00Z INFO login from 10.0.0.1 ok",
+        "2024-01-01T00:00:01Z INFO login from 10.0.0.2 ok",
+        "2024-01-01T00:00:02Z INFO login from 10.0.0.1 ok",
+        "2024-01-01T00:00:03Z ERROR blocked request from 10.0.0.1 by firewall",
+        "2024-01-01T00:00:04Z ERROR blocked request from 10.0.0.3 by firewall",
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let ip = &out.global_params["IP"];
+    assert_eq!(ip.patterns, 2);
+    assert_eq!(ip.total, 5);
+    assert_eq!(ip.cardinality, 3);
+    assert_eq!((ip.values[0].value.as_str(), ip.values[0].count), ("10.0.0.1", 3));
+    assert!(!out.global_params.contains_key("TIMESTAMP"));
+}
+
+#[test]
+fn min_count_collapses_rare_patterns_into_other() {
+    let mut lines: Vec<String> = (0..6).map(|i| format!("2024-01-01T00:00:0{i}Z INFO request served")).collect();
+    lines.push("2024-01-01T00:00:07Z WARN disk almost full".into());
+    lines.push("2024-01-01T00:00:08Z ERROR cache node lost".into());
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let full = logoscope::ai::summarize_lines(&refs);
+    assert!(full.other_patterns.is_none());
+
+    let opts = logoscope::ai::SummarizeOpts { min_count: Some(2), ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1);
+    assert_eq!(out.patterns[0].total_count, 6);
+    let other = out.other_patterns.as_ref().expect("collapsed bucket");
+    assert_eq!((other.patterns, other.total_count), (2, 2));
+    assert!((other.frequency - 0.25).abs() < 1e-9);
+    // Totals still cover the collapsed patterns
+    assert_eq!(out.summary.total_lines, 8);
+    assert_eq!(out.summary.unique_patterns, full.summary.unique_patterns);
+    assert_eq!(out.summary.compression_ratio, full.summary.compression_ratio);
+}
+
+#[test]
+fn extract_rules_surface_nested_fields_under_alias() {
+    let lines = [
+        r#"{"ts":"2024-01-01T00:00:00Z","level":"info","payload":{"data":{"attributes":{"status":"active"}}},"msg":"sync"}"#,
+        r#"{"ts":"2024-01-01T00:00:01Z","level":"info","payload":{"data":{"attributes":{"status":"paused"}}},"msg":"sync"}"#,
+        r#"{"ts":"2024-01-01T00:00:02Z","level":"info","payload":{"data":{"attributes":{"status":"active"}}},"msg":"sync"}"#,
+    ];
+    let rule = logoscope::param_extractor::ExtractRule::parse("$.payload.data.attributes.status=status").unwrap();
+    assert_eq!(rule.path, "payload.data.attributes.status");
+    assert!(logoscope::param_extractor::ExtractRule::parse("no-alias").is_err());
+    let field_filter = logoscope::param_extractor::FieldFilter { extract: vec![rule], ..Default::default() };
+    let opts = logoscope::ai::SummarizeOpts { field_filter, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1);
+    let p = &out.patterns[0];
+    assert!(p.template.starts_with("status = <STATUS>"), "{}", p.template);
+    assert!(!p.template.contains("payload"), "{}", p.template);
+    let stats = p.param_stats.as_ref().expect("param stats");
+    assert!(stats.contains_key("STATUS"), "{:?}", stats.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn summary_reports_widest_and_narrowest_span_patterns() {
+    let mut lines: Vec<String> = (0..6).map(|i| format!("2024-01-01T00:{i:02}:00Z INFO heartbeat ok")).collect();
+    lines.push("2024-01-01T00:02:00Z ERROR upstream timeout".into());
+    lines.push("2024-01-01T00:02:05Z ERROR upstream timeout".into());
+    lines.push("2024-01-01T00:03:00Z WARN cache miss".into());
+    lines.push("2024-01-01T00:04:00Z WARN cache miss".into());
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let out = logoscope::ai::summarize_lines(&refs);
+    let widest = out.summary.widest_span.as_ref().expect("widest span");
+    assert!(widest.template.contains("heartbeat"), "{}", widest.template);
+    assert_eq!(widest.span_seconds, 300);
+    let narrowest = out.summary.narrowest_span.as_ref().expect("narrowest span");
+    assert!(narrowest.template.contains("timeout"), "{}", narrowest.template);
+    assert_eq!(narrowest.span_seconds, 5);
+
+    let single = logoscope::ai::summarize_lines(&["2024-01-01T00:00:00Z INFO only one"]);
+    assert!(single.summary.widest_span.is_none() && single.summary.narrowest_span.is_none());
+}
+
+#[test]
+fn docker_json_file_messages_are_clustered() {
+    let lines = [
+        r#"{"log":"user 1001 logged in\n","stream":"stdout","time":"2024-01-01T00:00:00.000000001Z"}"#,
+        r#"{"log":"user 1002 logged in\n","stream":"stdout","time":"2024-01-01T00:00:01.000000001Z"}"#,
+        r#"{"log":"cache warmed\n","stream":"stderr","time":"2024-01-01T00:00:02.000000001Z"}"#,
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    assert_eq!(out.patterns.len(), 2, "{:?}", out.patterns.iter().map(|p| &p.template).collect::<Vec<_>>());
+    let login = out.patterns.iter().find(|p| p.template.contains("logged in")).expect("login pattern");
+    assert_eq!(login.total_count, 2);
+    assert!(out.patterns.iter().all(|p| !p.template.contains("stream")));
+    assert!(out.summary.start_date.is_some());
+}
+
+#[test]
+fn normalized_field_names_merge_patterns_across_services() {
+    let lines = [
+        r#"{"ts":"2024-01-01T00:00:00Z","level":"info","userId":"u1","msg":"login"}"#,
+        r#"{"ts":"2024-01-01T00:00:01Z","level":"info","user_id":"u2","msg":"login"}"#,
+        r#"{"ts":"2024-01-01T00:00:02Z","level":"info","UserID":"u3","msg":"login"}"#,
+    ];
+    assert_eq!(logoscope::ai::summarize_lines(&lines).patterns.len(), 3);
+
+    let field_filter = logoscope::param_extractor::FieldFilter { normalize_names: true, ..Default::default() };
+    let opts = logoscope::ai::SummarizeOpts { field_filter, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1, "{:?}", out.patterns.iter().map(|p| &p.template).collect::<Vec<_>>());
+    assert!(out.patterns[0].template.contains("user_id = <USER_ID>"), "{}", out.patterns[0].template);
+    assert_eq!(out.patterns[0].param_stats.as_ref().expect("param stats")["USER_ID"].cardinality, 3);
+}
+
+#[test]
+fn counts_only_keeps_templates_and_counts_only() {
+    let mut lines: Vec<String> = (0..4).map(|i| format!("2024-01-01T00:00:0{i}Z INFO user {} logged in from 10.0.0.{i}", 100 + i)).collect();
+    lines.push("2024-01-01T00:00:05Z ERROR payment failed".into());
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let full = logoscope::ai::summarize_lines(&refs);
+
+    let opts = logoscope::ai::SummarizeOpts { counts_only: true, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    assert_eq!(out.summary.total_lines, 5);
+    assert_eq!(out.summary.unique_patterns, full.summary.unique_patterns);
+    let counts = |o: &logoscope::ai::AiOutput| o.patterns.iter().map(|p| (p.template.clone(), p.total_count)).collect::<Vec<_>>();
+    assert_eq!(counts(&out), counts(&full));
+    assert_eq!(out.patterns[1].severity.as_deref(), Some("ERROR"));
+    for p in &out.patterns {
+        assert!(p.examples.is_empty() && p.param_stats.is_none() && p.temporal.is_none());
+    }
+    assert!(out.anomalies.pattern_anomalies.is_empty());
+    assert!(out.query_interface.suggested_investigations.is_empty());
+
+    // Chunked summarizer gives the same counts
+    let mut ss = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    ss.ingest_chunk(&lines, &[], &opts);
+    let chunked = ss.finalize(None, &opts);
+    assert_eq!(counts(&chunked), counts(&full));
+    assert!(chunked.patterns.iter().all(|p| p.examples.is_empty()));
+}
+
+#[test]
+fn masked_quoted_phrases_cluster_as_one_token() {
+    let lines = [
+        r#"2024-01-01T00:00:00Z ERROR job failed: "disk quota exceeded on volume 3""#,
+        r#"2024-01-01T00:00:01Z ERROR job failed: "upstream closed connection""#,
+        r#"2024-01-01T00:00:02Z ERROR job failed: "disk quota exceeded on volume 7""#,
+    ];
+    let opts = logoscope::ai::SummarizeOpts { quoted_strings: logoscope::param_extractor::QuotedStrings::Mask, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &opts);
+    assert_eq!(out.patterns.len(), 1, "{:?}", out.patterns.iter().map(|p| &p.template).collect::<Vec<_>>());
+    assert!(out.patterns[0].template.contains("job failed: <QUOTED>"), "{}", out.patterns[0].template);
+    assert_eq!(out.patterns[0].param_stats.as_ref().expect("param stats")["QUOTED"].cardinality, 3);
+}
+
+#[test]
+fn windows_carry_untimestamped_lines_forward() {
+    let lines = [
+        "no timestamp yet",
+        "2024-01-01T00:10:00Z INFO job started",
+        "2024-01-01T00:50:00Z WARN job slow",
+        "continuation without timestamp",
+        "2024-01-01T02:00:00Z INFO job started",
+    ];
+    let out = logoscope::ai::summarize_windows(&lines, &[], &logoscope::ai::SummarizeOpts::default(), chrono::Duration::hours(1));
+    assert_eq!(out.untimestamped_lines, 1);
+    assert_eq!(out.windows.len(), 2);
+    assert_eq!(out.windows[0].total_lines, 3);
+    assert_eq!(out.windows[1].start, "2024-01-01T02:00:00Z");
+    assert_eq!((out.windows[1].new_patterns, out.windows[1].gone_patterns), (0, 2));
+}
+
+#[test]
+fn summary_reports_auto_detected_time_key() {
+    let lines: Vec<&str> = vec![
+        r#"{"created_at":"2023-05-01T00:00:00Z","eventTime":"2024-01-15T10:30:45Z","msg":"job done"}"#,
+        r#"{"created_at":"2023-05-02T00:00:00Z","eventTime":"2024-01-15T10:31:45Z","msg":"job done"}"#,
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    assert_eq!(out.summary.time_key.as_deref(), Some("eventTime"));
+    assert_eq!(out.summary.start_date.as_deref(), Some("2024-01-15T10:30:45Z"));
+
+    let hinted = logoscope::ai::summarize_lines_with_hints(&lines, &["created_at"]);
+    assert_eq!(hinted.summary.time_key, None);
+}
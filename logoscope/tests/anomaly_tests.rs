@@ -19,3 +19,75 @@ fn detects_new_and_rare_patterns() {
     assert!(!anomalies.iter().any(|a| a.template == "A"));
 }
 
+
+#[test]
+fn detects_disappeared_patterns() {
+    use std::collections::HashMap;
+    use logoscope::anomaly::{detect_disappeared_patterns, AnomalyKind};
+
+    let mut counts = HashMap::new();
+    counts.insert("request served".to_string(), 1000);
+    counts.insert("cache miss".to_string(), 50);
+    let total = 1050usize;
+
+    let mut baseline = HashMap::new();
+    baseline.insert("request served".to_string(), 0.8);
+    baseline.insert("healthcheck ok".to_string(), 0.15);
+    baseline.insert("cache miss".to_string(), 0.05);
+    baseline.insert("rare debug".to_string(), 0.001);
+
+    let anomalies = detect_disappeared_patterns(&counts, total, &baseline, 0.01, 0.1);
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].template, "healthcheck ok");
+    assert_eq!(anomalies[0].kind, AnomalyKind::DisappearedPattern);
+    assert_eq!(anomalies[0].count, 0);
+}
+
+#[test]
+fn ratio_rule_parses_and_detects_shift() {
+    use logoscope::anomaly::{detect_ratio_shift, RatioRule};
+    let rule = RatioRule::parse("login failed / login succeeded").unwrap();
+    assert!(RatioRule::parse("no separator").is_err());
+    assert!(RatioRule::parse("( / ok").is_err());
+
+    let (n, d) = rule.tally([("login failed for <*>", 5.0), ("login succeeded for <*>", 20.0), ("other", 7.0)]);
+    assert_eq!((n, d), (5.0, 20.0));
+
+    // 1:10 baseline to 1:4 now is a 2.5x shift
+    let s = detect_ratio_shift(&rule, (5, 20), (0.01, 0.1), 2.0, 10).expect("shift");
+    assert!((s.current_ratio - 0.25).abs() < 1e-9);
+    assert!((s.baseline_ratio - 0.1).abs() < 1e-9);
+    // Within the factor, too few events, or no denominator: nothing
+    assert!(detect_ratio_shift(&rule, (3, 20), (0.01, 0.1), 2.0, 10).is_none());
+    assert!(detect_ratio_shift(&rule, (2, 3), (0.01, 0.1), 2.0, 10).is_none());
+    assert!(detect_ratio_shift(&rule, (20, 0), (0.01, 0.1), 2.0, 10).is_none());
+    // Failures appearing where the baseline had none
+    assert!(detect_ratio_shift(&rule, (4, 20), (0.0, 0.1), 2.0, 10).is_some());
+}
+
+#[test]
+fn anomaly_scores_are_normalized_and_rank_by_severity() {
+    use logoscope::anomaly::*;
+
+    let pattern = |kind, frequency, count| PatternAnomaly { kind, template: "T".into(), frequency, count };
+    let new_common = pattern_anomaly_score(&pattern(AnomalyKind::NewPattern, 0.2, 200), None);
+    let new_single = pattern_anomaly_score(&pattern(AnomalyKind::NewPattern, 0.001, 1), None);
+    let rare = pattern_anomaly_score(&pattern(AnomalyKind::RarePattern, 0.001, 1), None);
+    let gone = pattern_anomaly_score(&pattern(AnomalyKind::DisappearedPattern, 0.0, 0), Some(0.3));
+    assert!(new_common > new_single && new_single > rare, "{new_common} {new_single} {rare}");
+    assert!(gone > rare);
+
+    assert!(numeric_outlier_score(50.0) > numeric_outlier_score(3.5));
+    assert_eq!(numeric_outlier_score(-8.0), numeric_outlier_score(8.0));
+    assert!(burst_score(20.0, 300) > burst_score(3.0, 4));
+    assert!(enum_drift_score(5, 100) > enum_drift_score(1, 100));
+    assert!(cardinality_explosion_score(1.0, 1000) > cardinality_explosion_score(0.8, 10));
+
+    let shift = |current_ratio, baseline_ratio| RatioShift { rule: "a / b".into(), current_ratio, baseline_ratio, numerator_count: 40, denominator_count: 60 };
+    assert!(ratio_shift_score(&shift(0.8, 0.1)) > ratio_shift_score(&shift(0.2, 0.1)));
+    assert!(ratio_shift_score(&shift(0.05, 0.1)) > 0.0);
+
+    for s in [new_common, new_single, rare, gone, numeric_outlier_score(1e9), burst_score(1e9, usize::MAX), ratio_shift_score(&shift(0.0, 0.1))] {
+        assert!((0.0..=1.0).contains(&s), "{s}");
+    }
+}
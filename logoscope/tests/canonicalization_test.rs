@@ -48,6 +48,39 @@ mod tests {
         assert_eq!(result2.extracted_params.get("API_ID").unwrap(), &vec!["def789"]);
     }
 
+    #[test]
+    fn test_colon_kv_canonicalization() {
+        let log1 = "2024-01-01T10:30:45Z request done Status: 200 user: alice url: http://a.example/x";
+        let log2 = "2024-01-01T10:31:02Z request done Status: 404 user: bob url: http://b.example/y";
+        let result1 = param_extractor::canonicalize_for_drain(log1);
+        let result2 = param_extractor::canonicalize_for_drain(log2);
+        assert_eq!(result1.masked_text, result2.masked_text);
+        assert!(result1.masked_text.contains("Status = <STATUS> user = <USER> url = <URL>"), "{}", result1.masked_text);
+        assert_eq!(result1.extracted_params.get("STATUS").unwrap(), &vec!["200"]);
+        assert_eq!(result2.extracted_params.get("USER").unwrap(), &vec!["bob"]);
+
+        // A lone numeric pair at the end of the line counts; prose with a colon does not
+        let single = param_extractor::canonicalize_for_drain("upstream responded Status: 503");
+        assert!(single.masked_text.ends_with("Status = <STATUS>"), "{}", single.masked_text);
+        for prose in ["ERROR: connection refused by peer", "Error: connection refused", "see http://example.com:8080/health"] {
+            let r = param_extractor::canonicalize_for_drain(prose);
+            assert!(!r.masked_text.contains(" = <"), "{prose} -> {}", r.masked_text);
+        }
+
+        // Logger/component prefixes and prose before a colon are never keys
+        for prose in [
+            "INFO main: Starting worker pool: default",
+            "ERROR db: connection lost, retrying: 5",
+            "WARN Note: disk almost full: 91%",
+        ] {
+            let r = param_extractor::canonicalize_for_drain(prose);
+            assert!(!r.masked_text.contains(" = <"), "{prose} -> {}", r.masked_text);
+            assert!(r.masked_text.contains(": "), "{prose} -> {}", r.masked_text);
+        }
+        let r = param_extractor::canonicalize_for_drain("payment declined code: 51 reason: \"insufficient funds\"");
+        assert_eq!(r.masked_text, "payment declined code = <CODE> reason = <REASON>");
+    }
+
     #[test]
     fn test_unstructured_fallback() {
         let unstructured = "Error connecting to database at 192.168.1.1:5432 with timeout 30s";
@@ -60,6 +93,40 @@ mod tests {
         assert!(result.extracted_params.contains_key("NUM_S"));
     }
 
+    #[test]
+    fn test_canonicalize_entry_point_dispatch() {
+        // JSON: sorted field placeholders, values kept per field
+        let json = logoscope::canonicalize(r#"{"user":"alice","level":"info","msg":"login"}"#);
+        assert_eq!(json.masked_text, "level = <LEVEL> msg = <MSG> user = <USER>");
+        assert_eq!(json.extracted_params["USER"], vec!["alice"]);
+
+        // Inline key=value pairs, with the free text around them masked
+        let kv = logoscope::canonicalize("2024-01-15T10:30:45Z request done status=200 client=10.0.0.7");
+        assert_eq!(kv.masked_text, "<TIMESTAMP> request done status = <STATUS> client = <CLIENT>");
+        assert_eq!(kv.extracted_params["STATUS"], vec!["200"]);
+        assert_eq!(kv.extracted_params["TIMESTAMP"], vec!["2024-01-15T10:30:45Z"]);
+
+        // Known access-log format goes through smart masking
+        let nginx = r#"192.168.1.1 - - [10/Oct/2023:13:55:36 +0000] "GET /api/users HTTP/1.1" 200 1234 "-" "Mozilla/5.0 (X11; Linux x86_64)""#;
+        let access = logoscope::canonicalize(nginx);
+        assert_eq!(param_extractor::masking_category(nginx), "access");
+        let smart = logoscope::smart_masking::smart_mask_line(nginx);
+        assert_eq!(access.masked_text, smart.template);
+        assert_eq!(access.extracted_params, smart.parameters);
+
+        // Free text falls back to typed masking
+        let text = logoscope::canonicalize("connection to 10.0.0.7 failed after 3 retries");
+        assert_eq!(text.masked_text, "connection to <IP> failed after <NUM> retries");
+        assert_eq!(text.extracted_params["IP"], vec!["10.0.0.7"]);
+
+        // Same result the summarizer clusters on with default options
+        for line in ["connection to 10.0.0.7 failed after 3 retries", "status=200 client=10.0.0.7", nginx] {
+            let opts = logoscope::ai::SummarizeOpts::default();
+            assert_eq!(logoscope::canonicalize(line), opts.canonicalize(line));
+            assert_eq!(param_extractor::canonicalize_for_drain(line), logoscope::canonicalize(line));
+        }
+    }
+
     #[test]
     fn test_json_flattening() {
         let nested_json = r#"{"level": "info", "service": {"name": "api", "version": "1.0"}, "metrics": {"cpu": 75.5}}"#;
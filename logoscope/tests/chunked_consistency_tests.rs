@@ -295,4 +295,91 @@ mod chunked_consistency_tests {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn bounded_mode_keeps_only_the_most_frequent_patterns() {
+    let opts = SummarizeOpts { max_tracked_patterns: Some(2), ..Default::default() };
+    let mut engine = StreamingSummarizer::with_opts(&opts);
+    let words = ["alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel"];
+    for chunk in 0..4 {
+        let mut lines: Vec<String> = (0..10).map(|_| "INFO cache warmed".to_string()).collect();
+        lines.extend((0..5).map(|_| "ERROR disk quota exceeded on volume".to_string()));
+        // One rare pattern per chunk with a distinct token count
+        lines.push(format!("WARN {}", words[..chunk + 3].join(" ")));
+        engine.ingest_chunk(&lines, &[], &opts);
+    }
+    let out = engine.finalize(None, &opts);
+    assert_eq!(out.patterns.len(), 2);
+    assert_eq!(out.patterns[0].total_count, 40);
+    assert_eq!(out.patterns[1].total_count, 20);
+    assert_eq!(out.summary.total_lines, 64);
+    assert_eq!(out.summary.evicted_patterns, 4);
+    assert_eq!(out.summary.evicted_lines, 4);
+}
+
+#[test]
+fn burst_window_applies_to_both_paths() {
+    let mut lines: Vec<String> = (0..30)
+        .flat_map(|s| [format!("2024-01-01T00:00:{s:02}.000Z INFO heartbeat ok"), format!("2024-01-01T00:00:{s:02}.500Z INFO heartbeat ok")])
+        .collect();
+    lines.extend((0..20).map(|i| format!("2024-01-01T00:00:12.{:03}Z INFO heartbeat ok", 10 + i * 10)));
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+
+    let bursts = |opts: &SummarizeOpts| {
+        let direct = summarize_lines_with_opts(&refs, &[], None, opts);
+        let mut engine = StreamingSummarizer::with_opts(opts);
+        engine.ingest_chunk(&lines, &[], opts);
+        let chunked = engine.finalize(None, opts);
+        [direct, chunked].map(|out| out.patterns[0].temporal.as_ref().map_or(0, |t| t.bursts))
+    };
+    assert_eq!(bursts(&SummarizeOpts::default()), [0, 0]);
+    let per_second = SummarizeOpts { burst_window: chrono::Duration::seconds(1), ..Default::default() };
+    assert_eq!(bursts(&per_second), [1, 1]);
+}
+
+#[test]
+fn patterns_are_tagged_with_a_log_category() {
+    let lines: Vec<String> = vec![
+        r#"192.168.1.10 - - [10/Oct/2024:13:55:36 +0000] "GET /api/users HTTP/1.1" 200 2326"#.into(),
+        r#"192.168.1.11 - - [10/Oct/2024:13:55:37 +0000] "GET /api/users HTTP/1.1" 200 1187"#.into(),
+        "worker started processing queue jobs".into(),
+        r#"{"level":"info","msg":"cache warmed","entries":42}"#.into(),
+    ];
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = SummarizeOpts::default();
+    let direct = summarize_lines_with_opts(&refs, &[], None, &opts);
+    let mut engine = StreamingSummarizer::with_opts(&opts);
+    engine.ingest_chunk(&lines, &[], &opts);
+    let chunked = engine.finalize(None, &opts);
+    for out in [direct, chunked] {
+        let mut categories: Vec<&str> = out.patterns.iter().filter_map(|p| p.category.as_deref()).collect();
+        categories.sort_unstable();
+        assert_eq!(categories, ["access", "app", "json"]);
+    }
+}
+
+#[test]
+fn correlate_applies_to_both_paths() {
+    let lines: Vec<String> = (0..20)
+        .flat_map(|m| {
+            if m % 2 == 0 {
+                vec![format!("2024-01-01T00:{m:02}:00Z ERROR db timeout"), format!("2024-01-01T00:{m:02}:02Z WARN retrying request")]
+            } else {
+                vec![format!("2024-01-01T00:{m:02}:30Z INFO heartbeat ok")]
+            }
+        })
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = SummarizeOpts { correlate: true, ..Default::default() };
+    let direct = summarize_lines_with_opts(&refs, &[], None, &opts);
+    let mut engine = StreamingSummarizer::with_opts(&opts);
+    engine.ingest_chunk(&lines, &[], &opts);
+    let chunked = engine.finalize(None, &opts);
+    for out in [direct, chunked] {
+        let timeout = out.patterns.iter().find(|p| p.template.contains("db timeout")).unwrap();
+        let related: Vec<&str> = timeout.correlations.iter().map(|c| c.template.as_str()).collect();
+        assert_eq!(related.len(), 1, "{related:?}");
+        assert!(related[0].contains("retrying request"), "{related:?}");
+    }
+}
@@ -0,0 +1,607 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run_cli(args: &[&str], stdin: impl AsRef<[u8]>) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_logoscope"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn logoscope");
+    child.stdin.take().unwrap().write_all(stdin.as_ref()).unwrap();
+    child.wait_with_output().expect("wait for logoscope")
+}
+
+#[test]
+fn quiet_suppresses_stderr_diagnostics() {
+    let input = "User 1 logged in\nUser 2 logged in\n";
+    let loud = run_cli(&["--verbose", "-"], input);
+    assert!(String::from_utf8_lossy(&loud.stderr).contains("Performance Timing"));
+
+    let quiet = run_cli(&["--verbose", "--quiet", "-"], input);
+    assert!(quiet.status.success());
+    assert!(quiet.stderr.is_empty());
+    assert!(!quiet.stdout.is_empty());
+}
+
+#[test]
+fn directory_inputs_are_expanded() {
+    let dir = std::env::temp_dir().join(format!("logoscope_dir_test_{}", std::process::id()));
+    let nested = dir.join("nested");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(dir.join("a.log"), "2024-01-01T00:00:00Z User 1 logged in\n2024-01-01T00:00:01Z User 2 logged in\n").unwrap();
+    std::fs::write(dir.join("b.log"), "2024-01-01T00:00:02Z User 3 logged in\n").unwrap();
+    std::fs::write(nested.join("c.log"), "2024-01-01T00:00:03Z User 4 logged in\n").unwrap();
+
+    let dir_arg = dir.to_string_lossy().into_owned();
+    let flat = run_cli(&["--quiet", &dir_arg], "");
+    let flat: serde_json::Value = serde_json::from_slice(&flat.stdout).unwrap();
+    assert_eq!(flat["summary"]["total_lines"], 3);
+
+    let recursive = run_cli(&["--quiet", "--recursive", &dir_arg], "");
+    let recursive: serde_json::Value = serde_json::from_slice(&recursive.stdout).unwrap();
+    assert_eq!(recursive["summary"]["total_lines"], 4);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn since_until_filter_lines_before_summarization() {
+    let input = "\
+{\"time\":\"2024-01-01T00:00:00Z\",\"msg\":\"early\"}
+{\"time\":\"2024-01-01T01:00:00Z\",\"msg\":\"inside\"}
+{\"time\":\"2024-01-01T01:30:00Z\",\"msg\":\"inside\"}
+{\"time\":\"2024-01-01T03:00:00Z\",\"msg\":\"late\"}
+{\"msg\":\"no timestamp\"}
+";
+    let window = ["--quiet", "--since", "2024-01-01T00:30:00Z", "--until", "2024-01-01T02:00:00Z", "-"];
+    let kept: serde_json::Value = serde_json::from_slice(&run_cli(&window, input).stdout).unwrap();
+    assert_eq!(kept["summary"]["total_lines"], 3);
+
+    let mut strict = window.to_vec();
+    strict.insert(1, "--drop-untimed");
+    let dropped: serde_json::Value = serde_json::from_slice(&run_cli(&strict, input).stdout).unwrap();
+    assert_eq!(dropped["summary"]["total_lines"], 2);
+
+    let bad = run_cli(&["--since", "yesterday", "-"], input);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn empty_input_reports_diagnostic_and_error_on_empty_fails() {
+    let out = run_cli(&["--quiet", "-"], "");
+    assert!(out.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(v["summary"]["total_lines"], 0);
+    assert_eq!(v["summary"]["compression_ratio"], 0.0);
+    assert!(v["summary"]["diagnostic"].is_string());
+
+    let strict = run_cli(&["--quiet", "--error-on-empty", "-"], "");
+    assert_eq!(strict.status.code(), Some(2));
+    assert!(!strict.stdout.is_empty(), "summary is still printed");
+
+    let malformed = run_cli(&["--quiet", "--error-on-empty", "-"], "{\"broken\": \n{not json}\n");
+    assert_eq!(malformed.status.code(), Some(2));
+
+    let ok = run_cli(&["--quiet", "--error-on-empty", "-"], "2024-01-01T00:00:00Z hello\n");
+    assert!(ok.status.success());
+}
+
+#[test]
+fn table_output_is_uncolored_when_not_a_tty() {
+    let input = "{\"level\":\"error\",\"time\":\"2024-01-01T00:00:00Z\",\"msg\":\"db down\"}\n{\"level\":\"warn\",\"time\":\"2024-01-01T00:00:01Z\",\"msg\":\"slow\"}\n";
+    let out = run_cli(&["--quiet", "--only", "patterns", "--format", "table", "-"], input);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Template"));
+    assert!(!stdout.contains('\u{1b}'), "no ANSI escapes when piped");
+}
+
+#[test]
+fn invalid_utf8_lines_are_decoded_lossily_and_counted() {
+    let path = std::env::temp_dir().join(format!("logoscope_utf8_test_{}.log", std::process::id()));
+    let mut bytes = b"2024-01-01T00:00:00Z User 1 logged in\n".to_vec();
+    bytes.extend_from_slice(b"2024-01-01T00:00:01Z User 2 logged in \xff\xfe\n");
+    bytes.extend_from_slice(b"2024-01-01T00:00:02Z User 3 logged in\n");
+    std::fs::write(&path, &bytes).unwrap();
+
+    let out = run_cli(&["--quiet", &path.to_string_lossy()], "");
+    let chunked = run_cli(&["--quiet", "--max-tracked-patterns", "100", &path.to_string_lossy()], "");
+    std::fs::remove_file(&path).unwrap();
+    assert!(out.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(v["summary"]["total_lines"], 3);
+    assert_eq!(v["errors"]["invalid_utf8_lines"], 1);
+    let v: serde_json::Value = serde_json::from_slice(&chunked.stdout).unwrap();
+    assert_eq!(v["errors"]["invalid_utf8_lines"], 1);
+
+    // --follow reports the count in its summaries too (the last compact line is the final one)
+    let follow = run_cli(&["--quiet", "--follow", "--compact"], &bytes);
+    let last = String::from_utf8(follow.stdout).unwrap().lines().last().unwrap().to_string();
+    let v: serde_json::Value = serde_json::from_str(&last).unwrap();
+    assert_eq!(v["errors"]["invalid_utf8_lines"], 1);
+
+    let clean = run_cli(&["--quiet", "-"], "2024-01-01T00:00:00Z hello\n");
+    let v: serde_json::Value = serde_json::from_slice(&clean.stdout).unwrap();
+    assert!(v["errors"].get("invalid_utf8_lines").is_none());
+}
+
+#[test]
+fn mask_subcommand_emits_one_template_per_line() {
+    let input = "user 42 logged in from 10.0.0.1\n{\"msg\":\"hi\",\"user\":\"bob\"}\nuser 43 logged in from 10.0.0.2\n";
+    let out = run_cli(&["mask"], input);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], lines[2], "same shape masks to the same template");
+    assert!(!lines[0].contains("10.0.0.1") && !lines[0].contains("42"));
+
+    let out = run_cli(&["mask", "--with-params"], input);
+    let recs: Vec<serde_json::Value> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(recs.len(), 3);
+    assert_eq!(recs[0]["template"], lines[0]);
+    assert!(recs[0]["params"].to_string().contains("10.0.0.1"));
+}
+
+#[test]
+fn watch_reruns_when_the_file_changes() {
+    use std::io::{BufRead, BufReader};
+    use std::time::Duration;
+
+    let path = std::env::temp_dir().join(format!("logoscope_watch_test_{}.log", std::process::id()));
+    std::fs::write(&path, "2024-01-01T00:00:00Z User 1 logged in\n").unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_logoscope"))
+        .args(["--watch", &path.to_string_lossy()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn logoscope");
+
+    // Forward the "input is missing" notices from stderr
+    let stderr = child.stderr.take().unwrap();
+    let (missing_tx, missing_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if line.contains("waiting for it to reappear") {
+                let _ = missing_tx.send(());
+            }
+        }
+    });
+
+    // Each summary is a pretty-printed JSON object; forward complete documents
+    let stdout = child.stdout.take().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut doc = String::new();
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            doc.push_str(&line);
+            if line == "}" {
+                let _ = tx.send(std::mem::take(&mut doc));
+            }
+        }
+    });
+    let next_total = || {
+        let doc = rx.recv_timeout(Duration::from_secs(20)).expect("summary printed");
+        serde_json::from_str::<serde_json::Value>(&doc).unwrap()["summary"]["total_lines"].clone()
+    };
+
+    // The watcher is registered before the first summary is printed
+    assert_eq!(next_total(), 1);
+    let mut f = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    f.write_all(b"2024-01-01T00:00:01Z User 2 logged in\n").unwrap();
+    drop(f);
+    assert_eq!(next_total(), 2);
+
+    // Rotation: while the file is gone the watcher waits instead of exiting
+    std::fs::remove_file(&path).unwrap();
+    missing_rx.recv_timeout(Duration::from_secs(20)).expect("missing input reported");
+    std::fs::write(&path, "2024-01-02T00:00:00Z User 3 logged in\n").unwrap();
+    assert_eq!(next_total(), 1);
+
+    child.kill().unwrap();
+    let _ = child.wait();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn trace_flag_prints_the_trace_timeline() {
+    let input = "\
+{\"time\":\"2024-01-01T00:00:02Z\",\"service\":\"billing\",\"msg\":\"charge ok\",\"trace_id\":\"abcdabcdabcdabcd\"}
+{\"time\":\"2024-01-01T00:00:01Z\",\"service\":\"api\",\"msg\":\"request received\",\"trace_id\":\"abcdabcdabcdabcd\"}
+{\"time\":\"2024-01-01T00:00:01Z\",\"service\":\"api\",\"msg\":\"request received\",\"trace_id\":\"ffffffffffffffff\"}
+";
+    let out = run_cli(&["--trace", "abcdabcdabcdabcd", "-"], input);
+    assert!(out.status.success());
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["service"], "api");
+    assert_eq!(events[1]["service"], "billing");
+    assert!(events[0]["template"].is_string());
+}
+
+#[test]
+fn large_outputs_stream_as_a_single_json_document() {
+    let input: String = (0..2000).map(|i| format!("2024-01-01T00:{:02}:{:02}Z user {} logged in\n", i / 60 % 60, i % 60, i)).collect();
+    let out = run_cli(&["--quiet", "-"], &input);
+    assert!(out.status.success());
+    assert!(out.stdout.ends_with(b"}\n"));
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(v["summary"]["total_lines"], 2000);
+    assert!(!v["patterns"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn input_format_text_avoids_malformed_json_errors() {
+    let input = "{cache} 2024-01-01T00:00:00Z warmed\n{cache} 2024-01-01T00:00:01Z warmed\n";
+    let auto: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "-"], input).stdout).unwrap();
+    assert!(auto["errors"]["total"].as_u64().unwrap() > 0);
+
+    let text: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "--input-format", "text", "-"], input).stdout).unwrap();
+    assert_eq!(text["errors"]["total"], 0);
+}
+
+#[test]
+fn fail_on_sets_exit_code_from_triage_result() {
+    let clean = "2024-01-01T00:00:00Z INFO cache warmed\n2024-01-01T00:00:01Z INFO cache warmed\n";
+    let errors = "2024-01-01T00:00:00Z INFO cache warmed\n2024-01-01T00:00:01Z ERROR payment failed\n";
+
+    assert!(run_cli(&["--quiet", "--fail-on", "error", "-"], clean).status.success());
+    let failed = run_cli(&["--fail-on", "error", "-"], errors);
+    assert_eq!(failed.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&failed.stderr).contains("fail-on"));
+    // Output is still printed before the gate trips
+    assert!(serde_json::from_slice::<serde_json::Value>(&failed.stdout).is_ok());
+    // A single error line is WARNING, not CRITICAL
+    assert!(run_cli(&["--quiet", "--fail-on", "critical", "-"], errors).status.success());
+}
+
+#[test]
+fn colored_lines_share_a_template_with_plain_ones() {
+    let input = "2024-01-01T00:00:00Z \x1b[32mINFO\x1b[0m build started\n2024-01-01T00:00:01Z INFO build started\n";
+    let out: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "-"], input).stdout).unwrap();
+    assert_eq!(out["summary"]["unique_patterns"], 1);
+    assert!(!out["patterns"][0]["examples"][0].as_str().unwrap().contains('\x1b'));
+
+    let kept: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "--strip-ansi=false", "-"], input).stdout).unwrap();
+    assert_eq!(kept["summary"]["unique_patterns"], 2);
+}
+
+#[test]
+fn bench_prints_only_stage_timings() {
+    let input = "2024-01-01T00:00:00Z User 1 logged in\n2024-01-01T00:00:01Z User 2 logged in\n";
+    let out = run_cli(&["--bench", "-"], input);
+    assert!(out.status.success());
+    assert!(out.stderr.is_empty());
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(v.get("patterns").is_none());
+    assert_eq!(v["analysis"]["lines"], 2);
+    assert!(v["analysis"]["stages"].as_array().unwrap().iter().any(|s| s["stage"] == "Stage 4: Build patterns"));
+}
+
+#[test]
+fn dedup_window_drops_repeats_of_the_previous_line() {
+    let input = "2024-01-01T00:00:00.001Z INFO job started\n\
+                 2024-01-01T00:00:00.004Z INFO job started\n\
+                 2024-01-01T00:00:05.000Z INFO job started\n\
+                 2024-01-01T00:00:06.000Z INFO job done\n";
+    let plain: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "-"], input).stdout).unwrap();
+    assert_eq!(plain["summary"]["total_lines"], 4);
+    assert!(plain["summary"].get("deduped_lines").is_none());
+
+    let deduped: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "--dedup-window", "50ms", "-"], input).stdout).unwrap();
+    // Only the copy 3ms later is dropped; the one 5s later is a real repeat
+    assert_eq!(deduped["summary"]["total_lines"], 3);
+    assert_eq!(deduped["summary"]["deduped_lines"], 1);
+
+    let chunked: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "--dedup-window", "50ms", "--max-tracked-patterns", "10", "-"], input).stdout).unwrap();
+    assert_eq!(chunked["summary"]["deduped_lines"], 1);
+}
+
+#[test]
+fn count_format_only_changes_the_table_count_column() {
+    let input: String = (0..1500).map(|i| format!("2024-01-01T00:{:02}:{:02}Z INFO cache warmed\n", i / 60 % 60, i % 60)).collect();
+    let table = |style: &str| {
+        let out = run_cli(&["--quiet", "--only", "patterns", "--format", "table", "--count-format", style, "-"], &input);
+        assert!(out.status.success());
+        String::from_utf8_lossy(&out.stdout).into_owned()
+    };
+    assert!(table("plain").lines().nth(1).unwrap().starts_with("1500 "));
+    assert!(table("thousands").lines().nth(1).unwrap().starts_with("1,500 "));
+    assert!(table("si").lines().nth(1).unwrap().starts_with("1.5K "));
+    use logoscope::report::format_count;
+    for (n, si) in [(950_000, "950.0K"), (999_949, "999.9K"), (999_950, "1.0M"), (999_949_999, "999.9M"), (999_950_000, "1.0G")] {
+        assert_eq!(format_count(n, "si"), si, "{n}");
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&run_cli(&["--quiet", "--only", "patterns", "--count-format", "si", "-"], &input).stdout).unwrap();
+    assert_eq!(json[0]["total_count"], 1500);
+}
+
+#[test]
+fn progress_reports_chunked_totals_on_stderr_unless_quiet() {
+    let input = "2024-01-01T00:00:00Z INFO a 1\n2024-01-01T00:00:01Z INFO a 2\n2024-01-01T00:00:02Z INFO a 3\n";
+    let out = run_cli(&["--max-tracked-patterns", "10", "--progress", "-"], input);
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("logoscope: done: 3 lines, 1 chunks"), "{stderr}");
+
+    let quiet = run_cli(&["--quiet", "--max-tracked-patterns", "10", "--progress", "-"], input);
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("logoscope: done"));
+}
+
+#[test]
+fn limit_memory_switches_to_streaming_past_the_budget() {
+    let line = |i: usize| format!("2024-01-01T00:00:{:02}Z INFO request {i} served {}\n", i % 60, "cached ".repeat(150));
+    let small: String = (0..100).map(line).collect();
+    let out = run_cli(&["--limit-memory", "1", "-"], &small);
+    assert!(out.status.success());
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("--limit-memory"));
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["summary"]["total_lines"], 100);
+
+    // ~330KB of input against 1MB of estimated in-memory footprint
+    let large: String = (0..300).map(line).collect();
+    let out = run_cli(&["--limit-memory", "1", "-"], &large);
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("switching to streaming aggregation"), "{stderr}");
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["summary"]["total_lines"], 300);
+}
+
+#[test]
+fn compact_prints_single_line_json() {
+    let input = "2024-01-01T00:00:00Z INFO a 1\n2024-01-01T00:00:01Z ERROR b 2\n";
+    for args in [&["--compact", "-"][..], &["--compact", "--triage", "-"], &["--compact", "--only", "patterns", "-"]] {
+        let out = run_cli(args, input);
+        assert!(out.status.success());
+        let stdout = String::from_utf8(out.stdout).unwrap();
+        assert_eq!(stdout.lines().count(), 1, "{args:?}: {stdout}");
+        serde_json::from_str::<serde_json::Value>(&stdout).unwrap();
+    }
+    let pretty = run_cli(&["-"], input);
+    assert!(String::from_utf8_lossy(&pretty.stdout).lines().count() > 1);
+}
+
+#[test]
+fn baseline_from_flags_patterns_missing_in_known_good_file() {
+    let path = std::env::temp_dir().join(format!("logoscope_baseline_test_{}.log", std::process::id()));
+    std::fs::write(&path, "2024-01-01T00:00:00Z INFO user 1 logged in\n2024-01-01T00:00:01Z INFO user 2 logged in\n2024-01-01T00:00:02Z INFO cache warmed in 5ms\n").unwrap();
+    let input = "2024-01-02T00:00:00Z INFO user 7 logged in\n2024-01-02T00:00:01Z INFO user 8 logged in\n2024-01-02T00:00:02Z ERROR disk full on /dev/sda1\n";
+    let out = run_cli(&["--baseline-from", path.to_str().unwrap(), "-"], input);
+    // --follow applies the baseline to its windows too (the last compact line is the final summary)
+    let follow = run_cli(&["--quiet", "--follow", "--compact", "--baseline-from", path.to_str().unwrap()], input);
+    let _ = std::fs::remove_file(&path);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let followed = String::from_utf8(follow.stdout).unwrap();
+    for v in [serde_json::from_slice::<serde_json::Value>(&out.stdout).unwrap(), serde_json::from_str(followed.lines().last().unwrap()).unwrap()] {
+        let new: Vec<&str> = v["anomalies"]["pattern_anomalies"].as_array().unwrap().iter()
+            .filter(|a| a["kind"] == "NewPattern")
+            .map(|a| a["template"].as_str().unwrap())
+            .collect();
+        assert_eq!(new.len(), 1, "{new:?}");
+        assert!(new[0].contains("disk full"), "{new:?}");
+    }
+}
+
+#[test]
+fn only_anomalies_prints_anomalies_and_suggestions() {
+    let input = "2024-01-01T00:00:00Z INFO a 1\n2024-01-01T00:00:01Z ERROR b 2\n";
+    let out = run_cli(&["--only", "anomalies", "-"], input);
+    assert!(out.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let keys: Vec<&str> = v.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+    assert_eq!(keys, ["anomalies", "suggested_investigations"]);
+    assert!(v["anomalies"]["pattern_anomalies"].is_array());
+}
+
+#[test]
+fn deltas_only_follow_prints_just_changed_patterns() {
+    let input: String = (0..4).map(|i| format!("2024-01-01T00:00:0{i}Z INFO request {i} served\n")).collect();
+    // --follow stops at end of stdin, so the run ends on its own
+    let out = run_cli(&["--follow", "--deltas-only", "--interval", "0", "--quiet"], &input);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(!stdout.is_empty());
+    for line in stdout.lines() {
+        let v: serde_json::Value = serde_json::from_str(line).unwrap_or_else(|e| panic!("{e}: {line}"));
+        assert!(v["template"].is_string() && v["delta"].is_i64(), "{line}");
+    }
+
+    // The record still held by the multiline aggregator at end of input is counted too
+    let full = run_cli(&["--follow", "--compact", "--quiet"], &input);
+    let last = String::from_utf8(full.stdout).unwrap().lines().last().unwrap().to_string();
+    let v: serde_json::Value = serde_json::from_str(&last).unwrap();
+    assert_eq!(v["summary"]["total_lines"], 4);
+}
+
+#[test]
+fn order_files_reads_rotated_logs_oldest_first() {
+    let dir = std::env::temp_dir().join(format!("logoscope_rotated_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let files = [
+        ("app.log", "2024-01-03T00:00:00Z INFO newest"),
+        ("app.log.1", "2024-01-02T00:00:00Z INFO middle"),
+        ("app.log.2", "2024-01-01T00:00:00Z INFO oldest"),
+    ];
+    for (name, line) in files {
+        std::fs::write(dir.join(name), format!("{line}\n")).unwrap();
+    }
+    // Directory inputs expand sorted by name: app.log, app.log.1, app.log.2
+    let dir_arg = dir.to_str().unwrap();
+    for order in ["time", "rotation"] {
+        let out = run_cli(&["--only", "logs", "--order-files", order, dir_arg], "");
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        let lines: Vec<String> = String::from_utf8(out.stdout).unwrap().lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["line"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(lines, ["2024-01-01T00:00:00Z INFO oldest", "2024-01-02T00:00:00Z INFO middle", "2024-01-03T00:00:00Z INFO newest"], "{order}");
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn tail_keeps_only_the_last_records() {
+    let mut input: String = (0..50).map(|i| format!("2024-01-01T00:00:{:02}Z INFO tick {i}\n", i)).collect();
+    input.push_str("2024-01-01T00:00:50Z ERROR request failed\n    at handler (app.js:10)\n    at main (app.js:1)\n");
+    let out = run_cli(&["--tail", "3", "-"], &input);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(v["summary"]["total_lines"], 3);
+
+    // The stack trace stays one logical record at the tail boundary
+    let out = run_cli(&["--only", "logs", "--tail", "2", "-"], &input);
+    let lines: Vec<String> = String::from_utf8(out.stdout).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["line"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(lines.len(), 2, "{lines:?}");
+    assert!(lines[0].ends_with("tick 49"), "{lines:?}");
+    assert!(lines[1].starts_with("2024-01-01T00:00:50Z ERROR") && lines[1].contains("at main"), "{lines:?}");
+}
+
+#[test]
+fn window_by_emits_per_window_pattern_counts() {
+    let mut input = String::new();
+    for m in 0..3 { input.push_str(&format!("2024-01-01T00:{m:02}:10Z INFO heartbeat ok\n")); }
+    for m in 60..62 { input.push_str(&format!("2024-01-01T01:{:02}:10Z ERROR disk full on /dev/sda{m}\n", m - 60)); }
+    input.push_str("2024-01-01T01:05:00Z INFO heartbeat ok\n");
+    let out = run_cli(&["--window-by", "1h", "-"], &input);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(v["window_seconds"], 3600);
+    let windows = v["windows"].as_array().unwrap();
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows[0]["start"], "2024-01-01T00:00:00Z");
+    assert_eq!(windows[0]["end"], "2024-01-01T01:00:00Z");
+    assert_eq!((windows[0]["total_lines"].as_u64(), windows[0]["new_patterns"].as_u64()), (Some(3), Some(1)));
+    assert_eq!(windows[1]["total_lines"], 3);
+    assert_eq!(windows[1]["new_patterns"], 1);
+    assert_eq!(windows[1]["gone_patterns"], 0);
+    assert_eq!(windows[1]["patterns"][0]["count"], 2);
+    assert!(windows[1]["patterns"][0]["template"].as_str().unwrap().contains("disk full"));
+
+    let bad = run_cli(&["--window-by", "0h", "-"], &input);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn metrics_csv_writes_one_file_per_pattern_and_an_index() {
+    let dir = std::env::temp_dir().join(format!("logoscope_metrics_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let input = "2024-01-01T00:00:00Z INFO batch done: 10 rows, 5 errors\n2024-01-01T00:01:00Z INFO batch done: 12 rows, 0 errors\n";
+    let out = run_cli(&["--quiet", "--metrics-csv", dir.to_str().unwrap(), "-"], input);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let index = std::fs::read_to_string(dir.join("index.csv")).unwrap();
+    assert!(index.starts_with("file,template,rows\nmetrics-001.csv,"), "{index}");
+    assert!(index.trim_end().ends_with(",2"), "{index}");
+    let csv = std::fs::read_to_string(dir.join("metrics-001.csv")).unwrap();
+    assert_eq!(csv, "timestamp,NUM,NUM_2\n2024-01-01T00:00:00.000Z,10,5\n2024-01-01T00:01:00.000Z,12,0\n");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn group_by_file_splits_patterns_by_input_file() {
+    let dir = std::env::temp_dir().join(format!("logoscope_by_file_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let staging = dir.join("staging.log");
+    let prod = dir.join("prod.log");
+    std::fs::write(&staging, "2024-01-01T00:00:00Z INFO cache warmed in 5ms\n2024-01-01T00:00:01Z INFO cache warmed in 7ms\n").unwrap();
+    std::fs::write(&prod, "2024-01-01T00:00:02Z ERROR disk full on /var\n").unwrap();
+    let (staging, prod) = (staging.to_str().unwrap(), prod.to_str().unwrap());
+
+    for extra in [&[][..], &["--max-tracked-patterns", "100"][..]] {
+        let mut args = vec!["--only", "patterns", staging, prod];
+        args.extend_from_slice(extra);
+        let out = run_cli(&args, "");
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        let pats: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        let by_file = |needle: &str| pats.as_array().unwrap().iter()
+            .find(|p| p["template"].as_str().unwrap().contains(needle))
+            .map(|p| p["by_file"].clone())
+            .unwrap();
+        assert_eq!(by_file("cache warmed"), serde_json::json!([{"name": staging, "count": 2}]), "{extra:?}");
+        assert_eq!(by_file("disk full"), serde_json::json!([{"name": prod, "count": 1}]), "{extra:?}");
+    }
+
+    let out = run_cli(&["--only", "patterns", "--format", "table", "--group-by", "file", staging, prod], "");
+    let table = String::from_utf8(out.stdout).unwrap();
+    assert!(table.contains(&format!("# {prod}")) && table.contains(&format!("# {staging}")), "{table}");
+
+    // A single input is not tagged
+    let out = run_cli(&["--only", "patterns", staging], "");
+    let pats: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(pats[0].get("by_file").is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn framing_reads_multiline_json_records_whole() {
+    let records = ["{\n  \"level\": \"error\",\n  \"msg\": \"db timeout\"\n}", "{\n  \"level\": \"error\",\n  \"msg\": \"db timeout\"\n}"];
+    let null_delimited: String = records.iter().map(|r| format!("{r}\0")).collect();
+    let length_prefixed: String = records.iter().map(|r| format!("{}\n{r}\n", r.len())).collect();
+    for (framing, input) in [("null-delimited", null_delimited), ("length-prefixed", length_prefixed)] {
+        let out = run_cli(&["--framing", framing, "--only", "summary", "-"], &input);
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(v["summary"]["total_lines"], 2, "{framing}");
+        assert_eq!(v["summary"]["unique_patterns"], 1, "{framing}");
+    }
+
+    let bad = run_cli(&["--framing", "length-prefixed", "-"], "not-a-length\n{}");
+    assert!(!bad.status.success());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("expected a byte count"));
+
+    let huge = run_cli(&["--framing", "length-prefixed", "-"], "99999999999999\n{}");
+    assert_eq!(huge.status.code(), Some(1), "{}", String::from_utf8_lossy(&huge.stderr));
+    assert!(String::from_utf8_lossy(&huge.stderr).contains("exceeds the"));
+    let short = run_cli(&["--framing", "length-prefixed", "-"], "100\n{}");
+    assert!(String::from_utf8_lossy(&short.stderr).contains("input ended inside"));
+}
+
+#[test]
+fn drain_tuning_flags_reject_out_of_range_values() {
+    for args in [["--drain-similarity", "1.5"], ["--drain-similarity", "-0.1"], ["--drain-depth", "0"], ["--drain-max-children", "0"]] {
+        let out = run_cli(&[args[0], args[1], "-"], "hello\n");
+        assert!(!out.status.success(), "{args:?} accepted");
+    }
+    let ok = run_cli(&["--drain-similarity", "1", "--drain-depth", "1", "-"], "hello\n");
+    assert!(ok.status.success(), "{}", String::from_utf8_lossy(&ok.stderr));
+}
+
+#[test]
+fn verbose_flags_drain_over_merging() {
+    let input: String = ["region alpha", "shard beta", "region gamma", "timeout delta"].iter()
+        .map(|w| format!("2024-01-01T00:00:00Z INFO Cache entry updated {w}\n"))
+        .collect();
+    let loose = run_cli(&["--verbose", "--drain-depth", "2", "-"], &input);
+    let stderr = String::from_utf8_lossy(&loose.stderr);
+    assert!(stderr.contains("merges 4 distinct messages"), "{stderr}");
+    assert!(stderr.contains("raise --drain-similarity (currently 0.1)"), "{stderr}");
+
+    let strict = run_cli(&["--verbose", "--drain-depth", "2", "--drain-similarity", "0.9", "-"], &input);
+    assert!(!String::from_utf8_lossy(&strict.stderr).contains("OVER-MERGING"));
+}
+
+#[test]
+fn out_of_range_windows_are_rejected_not_panics() {
+    for args in [["--burst-window", "99999999999999h"], ["--dedup-window", "99999999999999d"], ["--window-by", "9999999999999999m"]] {
+        let out = run_cli(&[args[0], args[1], "-"], "hello\n");
+        assert_eq!(out.status.code(), Some(2), "{args:?}: {}", String::from_utf8_lossy(&out.stderr));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("out of range"), "{args:?}");
+    }
+}
+
+#[test]
+fn follow_rejects_options_it_cannot_honor() {
+    let out = run_cli(&["--follow", "--max-tracked-patterns", "10"], "hello\n");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--max-tracked-patterns"));
+}
@@ -0,0 +1,58 @@
+use logoscope::ai::{summarize_lines_with_opts, StreamingSummarizer, SummarizeOpts};
+use logoscope::analyzers::{AnalysisContext, AnalysisResult, AnalysisResults, Analyzer, AnalyzerRegistry};
+use std::sync::Arc;
+
+/// Counts lines per pattern whose `user` param is an admin account
+struct AdminUsers;
+
+struct AdminUsersResult(usize);
+
+impl Analyzer for AdminUsers {
+    fn name(&self) -> &'static str {
+        "admin_users"
+    }
+
+    fn analyze(&self, context: &AnalysisContext, _opts: &SummarizeOpts) -> Box<dyn AnalysisResult> {
+        let admins = context
+            .pattern_indices
+            .iter()
+            .filter_map(|&i| context.line_params[i].get("USER"))
+            .filter(|values| values.iter().any(|v| v.starts_with("admin")))
+            .count();
+        Box::new(AdminUsersResult(admins))
+    }
+}
+
+impl AnalysisResult for AdminUsersResult {
+    fn merge_into(self: Box<Self>, results: &mut AnalysisResults) {
+        if self.0 > 0 {
+            results.extensions.insert("admin_users".into(), serde_json::json!({ "lines": self.0 }));
+        }
+    }
+}
+
+#[test]
+fn registered_analyzers_feed_pattern_extensions_in_both_modes() {
+    let lines: Vec<String> = ["alice", "admin-root", "bob", "admin-ops"]
+        .iter()
+        .enumerate()
+        .map(|(i, u)| format!("2024-01-01T00:00:0{i}Z INFO login user={u} ok"))
+        .collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = SummarizeOpts {
+        analyzers: Arc::new(AnalyzerRegistry::new().with_analyzer(Box::new(AdminUsers))),
+        ..Default::default()
+    };
+
+    let direct = summarize_lines_with_opts(&refs, &[], None, &opts);
+    let mut engine = StreamingSummarizer::with_opts(&opts);
+    engine.ingest_chunk(&lines, &[], &opts);
+    let chunked = engine.finalize(None, &opts);
+    for out in [direct, chunked] {
+        assert_eq!(out.patterns.len(), 1);
+        assert_eq!(out.patterns[0].extensions["admin_users"], serde_json::json!({ "lines": 2 }));
+    }
+
+    let plain = summarize_lines_with_opts(&refs, &[], None, &SummarizeOpts::default());
+    assert!(plain.patterns[0].extensions.is_empty());
+}
@@ -3,7 +3,7 @@ fn test_direct_disambiguation_function() {
     // Test the disambiguation function directly to see if it works
     
     // Create input with identical numbers that should trigger the same regex
-    let test_cases = vec![
+    let test_cases = [
         "Processing 42 items and 42 more items for total 42 results",
         "IP 192.168.1.1 connected to 192.168.1.1 forwarding to 192.168.1.1",
         "Request 1234567890abcdef1234 handled by 1234567890abcdef1234 forwarding to 1234567890abcdef1234"
@@ -19,3 +19,42 @@ fn drain_clusters_masked_lines() {
     assert_eq!(clusters[1].size, 1);
 }
 
+#[test]
+fn drain_similarity_controls_cluster_granularity() {
+    let lines = [
+        "Cache entry updated region alpha",
+        "Cache entry updated shard beta",
+        "Cache entry updated region gamma",
+        "Cache entry updated timeout delta",
+    ];
+    let loose = logoscope::ai::SummarizeOpts { drain_depth: 2, ..Default::default() };
+    let strict = logoscope::ai::SummarizeOpts { drain_depth: 2, drain_similarity: 0.7, ..Default::default() };
+    let loose_out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &loose);
+    let strict_out = logoscope::ai::summarize_lines_with_opts(&lines, &[], None, &strict);
+    assert!(strict_out.patterns.len() > loose_out.patterns.len());
+}
+
+#[test]
+fn over_merged_templates_flag_placeholder_heavy_merges() {
+    use logoscope::drain_adapter::over_merged_templates;
+    let pairs: Vec<(String, String)> = [
+        ("user alice logged in", "user alice logged in"),
+        ("user bob logged out", "user alice logged in"),
+        ("user carol changed password", "user alice logged in"),
+        // Many messages, but they only differ in a masked value
+        ("GET <PATH> <NUM>", "GET <PATH> <NUM>"),
+        // Distinct messages sharing mostly literal text
+        ("payment service started worker pool a", "payment service started worker pool a"),
+        ("payment service started worker pool b", "payment service started worker pool a"),
+        ("payment service started worker pool c", "payment service started worker pool a"),
+    ].iter().map(|(m, t)| (m.to_string(), t.to_string())).collect();
+    let flagged = over_merged_templates(pairs.iter().map(|(m, t)| (m, t)));
+    assert_eq!(flagged.len(), 1, "{flagged:?}");
+    assert_eq!(flagged[0].template, "user alice logged in");
+    assert_eq!(flagged[0].merged, "user <*> <*> <*>");
+    assert_eq!(flagged[0].variants, 3);
+    assert_eq!(flagged[0].placeholder_share, 0.75);
+
+    // Two variants are not enough to call it over-merging
+    assert!(over_merged_templates(pairs[..2].iter().map(|(m, t)| (m, t))).is_empty());
+}
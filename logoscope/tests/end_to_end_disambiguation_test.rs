@@ -1,6 +1,6 @@
 #[test]
 fn test_end_to_end_disambiguation() {
-    let input_lines = vec![
+    let input_lines = [
         "2024-01-15T10:30:00Z Processing 42 items with priority 8 and timeout 300",
         "2024-01-15T10:30:01Z Processing 100 items with priority 5 and timeout 200", 
         "2024-01-15T10:30:02Z Processing 1 items with priority 10 and timeout 500",
@@ -1,6 +1,6 @@
 #[test]
 fn collects_malformed_json_errors_but_continues() {
-    let lines = vec![
+    let lines = [
         "{\"level\":\"info\",\"time\":\"2024-01-01T00:00:00Z\",\"msg\":\"ok\"}",
         "{ this is not valid json",
         "{" ,
@@ -13,3 +13,11 @@ fn collects_malformed_json_errors_but_continues() {
     assert!(out.summary.total_lines >= 1);
 }
 
+
+#[test]
+fn empty_input_has_zero_compression_and_diagnostic() {
+    let out = logoscope::ai::summarize_lines(&[]);
+    assert_eq!(out.summary.total_lines, 0);
+    assert_eq!(out.summary.compression_ratio, 0.0);
+    assert!(out.summary.diagnostic.is_some());
+}
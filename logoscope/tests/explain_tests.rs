@@ -0,0 +1,43 @@
+use logoscope::ai::SummarizeOpts;
+use logoscope::explain::explain_clustering;
+
+#[test]
+fn lines_equal_after_masking_share_a_pattern() {
+    let e = explain_clustering(
+        "2024-01-01T00:00:00Z INFO user 42 logged in",
+        "2024-01-01T00:00:01Z INFO user 43 logged in",
+        &SummarizeOpts::default(),
+    );
+    assert!(e.same_pattern);
+    assert_eq!(e.cause, "masking");
+    assert_eq!(e.a.masked, e.b.masked);
+    assert!(e.a.masked.contains("<NUM>"));
+    assert!(e.differing_tokens.is_empty());
+    assert_eq!(e.a.template, e.b.template);
+}
+
+#[test]
+fn split_lines_report_the_differing_tokens() {
+    let e = explain_clustering("INFO user alice logged in", "INFO user bob logged out", &SummarizeOpts::default());
+    assert!(!e.same_pattern);
+    assert_eq!(e.cause, "drain_split");
+    let idx: Vec<usize> = e.differing_tokens.iter().map(|d| d.index).collect();
+    assert_eq!(idx, vec![2, 4]);
+    assert_eq!(e.differing_tokens[0].a, "alice");
+    assert_eq!(e.differing_tokens[0].b, "bob");
+
+    let longer = explain_clustering("INFO user alice logged in", "INFO user bob logged in from web", &SummarizeOpts::default());
+    assert_eq!(longer.cause, "token_count");
+}
+
+#[test]
+fn level_split_is_named_as_the_cause() {
+    let e = explain_clustering(
+        r#"{"level":"info","msg":"cache warmed"}"#,
+        r#"{"level":"error","msg":"cache warmed"}"#,
+        &SummarizeOpts::default(),
+    );
+    assert!(!e.same_pattern);
+    assert_eq!(e.cause, "level");
+    assert_ne!(e.a.level, e.b.level);
+}
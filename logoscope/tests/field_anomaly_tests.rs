@@ -27,5 +27,73 @@ fn detects_categorical_cardinality_explosion() {
     assert_eq!(e.field, "request_id");
     assert!(e.ratio >= 0.8);
     assert_eq!(e.total, 20);
+    // Evidence: the first line of each distinct value, capped
+    assert_eq!(e.line_indices, vec![0, 1, 2]);
+    assert_eq!(e.line_indices.len(), logoscope::field_anomaly::MAX_EVIDENCE_LINES);
 }
 
+
+#[test]
+fn cardinality_rules_suppress_or_override_per_field() {
+    use logoscope::field_anomaly::{analyze_categorical_explosions_with_rules, default_cardinality_rules, field_glob_match, CardinalityRule};
+    let base = |id: i32| format!(r#"{{"level":"info","op":"get","request_id":"req-{:04}","user":"user-{:04}"}}"#, id, id);
+    let lines: Vec<String> = (0..20).map(base).collect();
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+
+    // Defaults suppress naturally-unique *_id fields but still flag `user`
+    let explosions = analyze_categorical_explosions_with_rules(&refs, 0.8, 10, &default_cardinality_rules());
+    assert_eq!(explosions.len(), 1);
+    assert_eq!(explosions[0].field, "user");
+
+    // User rules take precedence: silence `user*`
+    let mut rules = vec![CardinalityRule::parse("user*=off").unwrap()];
+    rules.extend(default_cardinality_rules());
+    assert!(analyze_categorical_explosions_with_rules(&refs, 0.8, 10, &rules).is_empty());
+
+    assert!(field_glob_match("*_id", "Request_ID"));
+    assert!(field_glob_match("trace*", "traceparent"));
+    assert!(!field_glob_match("trace*", "stacktrace"));
+    assert!(CardinalityRule::parse("bad").is_err());
+    assert!(CardinalityRule::parse("x=1.5").is_err());
+}
+
+#[test]
+fn detects_enum_drift_against_known_values() {
+    let base = |region: &str| format!(r#"{{"level":"info","time":"2024-01-01T00:00:00Z","op":"route","region":"{}"}}"#, region);
+    let before: Vec<String> = ["us-east", "eu-west", "us-east"].iter().map(|r| base(r)).collect();
+    let before_refs: Vec<&str> = before.iter().map(|s| s.as_str()).collect();
+    let known = logoscope::field_anomaly::categorical_field_values(&before_refs);
+    assert_eq!(known["region"].len(), 2);
+
+    let after: Vec<String> = ["us-east", "ap-south", "ap-south"].iter().map(|r| base(r)).collect();
+    let after_refs: Vec<&str> = after.iter().map(|s| s.as_str()).collect();
+    let drift = logoscope::field_anomaly::detect_enum_drift(&after_refs, &known);
+    assert_eq!(drift.len(), 1);
+    let region = &drift[0];
+    assert_eq!(region.field, "region");
+    assert_eq!(region.new_values, vec!["ap-south".to_string()]);
+    assert_eq!(region.known_count, 2);
+    assert_eq!(region.total, 2);
+
+    // Nothing drifts without a baseline
+    assert!(logoscope::field_anomaly::detect_enum_drift(&after_refs, &Default::default()).is_empty());
+}
+
+#[test]
+fn remembered_field_values_stop_growing_past_the_enum_cap() {
+    use logoscope::field_anomaly::{remember_field_values, ENUM_MAX_CARDINALITY};
+    let mut known = Default::default();
+    for window in 0..5 {
+        let lines: Vec<String> = (0..20)
+            .map(|i| format!(r#"{{"level":"info","msg":"job done","job":"job-{window}-{i}","region":"us-east"}}"#))
+            .collect();
+        let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        remember_field_values(&mut known, &refs);
+    }
+    assert_eq!(known["job"].len(), ENUM_MAX_CARDINALITY + 1);
+    assert_eq!(known["region"].len(), 1);
+
+    // The capped field is not an enum, so its new values never drift
+    let after = [r#"{"level":"info","msg":"job done","job":"job-new","region":"us-east"}"#];
+    assert!(logoscope::field_anomaly::detect_enum_drift(&after, &known).is_empty());
+}
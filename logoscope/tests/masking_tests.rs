@@ -25,3 +25,139 @@ fn masking_extended_rules_uuid_path_url_hex_b64() {
     assert!(masked.contains("hex=<HEX>"));
     assert!(masked.contains("b64=<B64>"));
 }
+
+#[test]
+fn masking_hostnames_as_single_token() {
+    let a = logoscope::masking::mask_text("connecting to web-prod-03.us-east-1.example.com");
+    let b = logoscope::masking::mask_text("connecting to db-7.eu-west-2.internal.corp");
+    assert_eq!(a, "connecting to <HOSTNAME>");
+    assert_eq!(a, b);
+    assert_eq!(logoscope::masking::mask_text("logger org.apache.kafka.clients"), "logger org.apache.kafka.clients");
+    assert_eq!(logoscope::masking::mask_text("wrote archive.tar.gz"), "wrote archive.tar.gz");
+}
+
+#[test]
+fn sql_masking_normalizes_literals_and_in_lists() {
+    use logoscope::sql_masking::{find_sql_start, mask_sql};
+    let r = mask_sql("SELECT * FROM t1 WHERE id = 12345 AND name = 'O''Brien' AND kind IN (1, 2,3) AND x IN (SELECT y FROM t2)").unwrap();
+    assert_eq!(
+        r.masked_text,
+        "SELECT * FROM t1 WHERE id = <SQL_NUM> AND name = <SQL_STR> AND kind IN (<SQL_LIST>) AND x IN (SELECT y FROM t2)"
+    );
+    assert_eq!(r.extracted_params["SQL_NUM"], vec!["12345"]);
+    assert_eq!(r.extracted_params["SQL_STR"], vec!["O'Brien"]);
+    assert_eq!(r.extracted_params["SQL_LIST"], vec!["3"]);
+
+    let r = mask_sql("LOG: duration: 12 ms statement: update \"users\" set age = 41 where id = 7").unwrap();
+    assert_eq!(r.masked_text, "LOG: duration: <NUM>ms statement: update \"users\" set age = <SQL_NUM> where id = <SQL_NUM_2>");
+
+    assert_eq!(find_sql_start("user selected 3 items from cart"), None);
+    assert_eq!(find_sql_start("Delete request for order 5"), None);
+    assert_eq!(find_sql_start("failed to delete file from disk after 3 attempts"), None);
+    assert_eq!(find_sql_start("please select the newest backup from the list"), None);
+    assert_eq!(find_sql_start("could not update the config set by the operator"), None);
+    assert_eq!(find_sql_start("ran SELECT count(*) AS n, a.id FROM t"), Some(4));
+    assert!(mask_sql("cache hit ratio 0.93").is_none());
+}
+
+#[test]
+fn compound_durations_are_one_param_in_milliseconds() {
+    use logoscope::param_extractor::{duration_ms, mask_and_extract_with_disambiguation};
+    let r = mask_and_extract_with_disambiguation("job finished after 1m30.5s (retry in 1h30m)");
+    assert_eq!(r.masked_text, "job finished after <DURATION> (retry in <DURATION_2>)");
+    assert_eq!(r.extracted_params["DURATION"], vec!["90500"]);
+    assert_eq!(r.extracted_params["DURATION_2"], vec!["5400000"]);
+
+    // Single-unit durations keep their unit placeholder
+    assert_eq!(mask_and_extract_with_disambiguation("took 150ms").masked_text, "took <NUM>ms");
+
+    assert_eq!(duration_ms("2m15s"), Some(135_000.0));
+    assert_eq!(duration_ms("150ms"), Some(150.0));
+    assert_eq!(duration_ms("15"), None);
+    assert_eq!(duration_ms("3 apples"), None);
+}
+
+#[test]
+fn container_images_and_pod_names_keep_their_base_name() {
+    use logoscope::param_extractor::mask_and_extract_with_disambiguation;
+    let digest = "a".repeat(64);
+    let r = mask_and_extract_with_disambiguation(&format!("pulling registry.io/team/app:v1.2.3@sha256:{digest}"));
+    assert_eq!(r.masked_text, "pulling registry.io/team/app:<IMAGE_TAG>");
+    assert_eq!(r.extracted_params["IMAGE_TAG"], vec![format!("v1.2.3@sha256:{digest}")]);
+    assert_eq!(mask_and_extract_with_disambiguation("image team/app:latest ready").masked_text, "image team/app:<IMAGE_TAG> ready");
+    assert_eq!(mask_and_extract_with_disambiguation(&format!("image nginx@sha256:{digest}")).masked_text, "image nginx@<IMAGE_TAG>");
+    assert_eq!(mask_and_extract_with_disambiguation("registry:5000/app:1.25-alpine").masked_text, "registry:5000/app:<IMAGE_TAG>");
+
+    let a = mask_and_extract_with_disambiguation("restarting pod checkout-api-7d8f9c6b5-xk2lp");
+    let b = mask_and_extract_with_disambiguation("restarting pod checkout-api-5f6b7c8d9-q9w8z");
+    assert_eq!(a.masked_text, "restarting pod checkout-api-<POD_SUFFIX>");
+    assert_eq!(a.masked_text, b.masked_text);
+    assert_eq!(a.extracted_params["POD_SUFFIX"], vec!["7d8f9c6b5-xk2lp"]);
+
+    // Plain hyphenated words, host:port and key:value text are not images or pods
+    for text in ["user-admin-login started", "connect localhost:8080", "state:ready", "see /var/lib/app:1",
+        "thread 'main' panicked at src/main.rs:10:5", "File lib/handlers.py:123", "scope users/profile:read granted"] {
+        let r = mask_and_extract_with_disambiguation(text);
+        assert!(!r.extracted_params.contains_key("IMAGE_TAG") && !r.extracted_params.contains_key("POD_SUFFIX"), "{text}: {r:?}");
+    }
+}
+
+#[test]
+fn random_tokens_are_masked_above_the_entropy_threshold() {
+    use logoscope::param_extractor::{canonicalize_for_drain, mask_random_tokens, shannon_entropy, DEFAULT_RANDOM_TOKEN_ENTROPY};
+    let r = mask_random_tokens(canonicalize_for_drain("issued nonce k8Jd92hQpL0zXw3R for aZ3kQ9xP2mL7vB4n"), DEFAULT_RANDOM_TOKEN_ENTROPY);
+    assert_eq!(r.masked_text, "issued nonce <RANDOM> for <RANDOM_2>");
+    assert_eq!(r.extracted_params["RANDOM"], vec!["k8Jd92hQpL0zXw3R"]);
+    assert_eq!(r.extracted_params["RANDOM_2"], vec!["aZ3kQ9xP2mL7vB4n"]);
+
+    // Identifiers, short tokens and low-entropy runs stay literal
+    let text = "UserAuthenticationService getUserProfileById ab12cd aaaa1111aaaa1111 <NUM>";
+    let plain = logoscope::param_extractor::MaskingResult { masked_text: text.to_string(), extracted_params: Default::default() };
+    assert_eq!(mask_random_tokens(plain, DEFAULT_RANDOM_TOKEN_ENTROPY).masked_text, text);
+    assert_eq!(shannon_entropy("aaaa"), 0.0);
+    assert_eq!(shannon_entropy("abcd"), 2.0);
+}
+
+#[test]
+fn bracketed_thread_and_request_ids_keep_their_brackets() {
+    use logoscope::param_extractor::mask_and_extract_with_disambiguation as mask;
+    let a = mask("[thread-42] INFO order placed");
+    let b = mask("[thread-7] INFO order placed");
+    assert_eq!(a.masked_text, "[<THREAD>] INFO order placed");
+    assert_eq!(a.masked_text, b.masked_text);
+    assert_eq!(a.extracted_params["THREAD"], vec!["thread-42"]);
+
+    assert_eq!(mask("[pool-1-thread-3] WARN slow query").masked_text, "[<THREAD>] WARN slow query");
+    assert_eq!(mask("(goroutine 17) panic recovered").masked_text, "(<THREAD>) panic recovered");
+    let r = mask("INFO [req-abc123]: checkout done");
+    assert_eq!(r.masked_text, "INFO [<REQ_ID>]: checkout done");
+    assert_eq!(r.extracted_params["REQ_ID"], vec!["req-abc123"]);
+
+    // Only standalone, paired brackets count
+    assert!(!mask("call foo[thread-42] failed").extracted_params.contains_key("THREAD"));
+    assert!(!mask("see [thread-42) here").extracted_params.contains_key("THREAD"));
+    assert!(!mask("[requests] queued").extracted_params.contains_key("REQ_ID"));
+}
+
+#[test]
+fn quoted_phrases_stay_whole_when_requested() {
+    use logoscope::param_extractor::{mask_and_extract_quoted as mask, QuotedStrings};
+    let line = r#"ERROR: "connection to db-3 failed after 5 retries" on worker 12"#;
+    assert_eq!(mask(line, QuotedStrings::Split).masked_text, r#"ERROR: "connection to db<NUM> failed after <NUM_2> retries" on worker <NUM_3>"#);
+
+    let kept = mask(line, QuotedStrings::Keep);
+    assert_eq!(kept.masked_text, r#"ERROR: "connection to db-3 failed after 5 retries" on worker <NUM>"#);
+    assert!(!kept.extracted_params.contains_key("QUOTED"));
+
+    let masked = mask(line, QuotedStrings::Mask);
+    assert_eq!(masked.masked_text, "ERROR: <QUOTED> on worker <NUM>");
+    assert_eq!(masked.extracted_params["QUOTED"], vec!["connection to db-3 failed after 5 retries"]);
+
+    let two = mask(r#"rename 'old name' to "new name""#, QuotedStrings::Mask);
+    assert_eq!(two.masked_text, "rename <QUOTED> to <QUOTED_2>");
+
+    // Apostrophes, escaped quotes and unbalanced quotes
+    assert_eq!(mask("user's job can't start 3 times", QuotedStrings::Mask).masked_text, "user's job can't start <NUM> times");
+    assert_eq!(mask(r#"got "say \"hi\" 2x" back"#, QuotedStrings::Mask).masked_text, "got <QUOTED> back");
+    assert_eq!(mask(r#"unterminated "quote 42"#, QuotedStrings::Mask).masked_text, r#"unterminated "quote <NUM>"#);
+}
@@ -0,0 +1,32 @@
+use logoscope::ai::SummarizeOpts;
+use logoscope::metrics::extract_metrics;
+
+#[test]
+fn positional_numbers_become_time_series_columns() {
+    let lines = [
+        "2024-01-01T00:00:10Z INFO processed 1523 records in 4.2s (362 rec/s)",
+        "2024-01-01T00:00:00Z INFO processed 900 records in 3s (300 rec/s)",
+        "2024-01-01T00:00:05Z INFO cache warmed",
+        "processed 1 records in 1s (1 rec/s)",
+        r#"{"ts":"2024-01-01T00:00:06Z","count":5}"#,
+    ];
+    let series = extract_metrics(&lines, &[], &SummarizeOpts::default());
+    assert_eq!(series.len(), 1, "{series:?}");
+    let s = &series[0];
+    assert!(s.template.contains("processed <NUM> records"), "{}", s.template);
+    assert_eq!(s.columns, vec!["NUM", "NUM_2", "NUM_S"]);
+    assert_eq!(s.rows.len(), 2);
+    // Oldest first
+    assert_eq!(s.rows[0].values, vec![Some(900.0), Some(300.0), Some(3.0)]);
+    assert_eq!(s.rows[1].values, vec![Some(1523.0), Some(362.0), Some(4.2)]);
+    assert_eq!(
+        s.to_csv(),
+        "timestamp,NUM,NUM_2,NUM_S\n2024-01-01T00:00:00.000Z,900,300,3\n2024-01-01T00:00:10.000Z,1523,362,4.2\n",
+    );
+}
+
+#[test]
+fn csv_fields_are_quoted_when_needed() {
+    assert_eq!(logoscope::metrics::csv_field("plain"), "plain");
+    assert_eq!(logoscope::metrics::csv_field(r#"a, "b""#), r#""a, ""b""""#);
+}
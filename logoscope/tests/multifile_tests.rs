@@ -1,11 +1,11 @@
 #[test]
 fn multi_file_analysis_combines_timespan_and_counts() {
     // Two synthetic files with interleaved timestamps
-    let f1 = vec![
+    let f1 = [
         r#"{"level":"info","time":"2024-01-01T00:00:00Z","op":"a"}"#,
         r#"{"level":"info","time":"2024-01-01T00:02:00Z","op":"a"}"#,
     ];
-    let f2 = vec![
+    let f2 = [
         r#"{"level":"error","time":"2024-01-01T00:01:00Z","op":"b"}"#,
         r#"{"level":"error","time":"2024-01-01T00:03:00Z","op":"b"}"#,
     ];
@@ -19,7 +19,55 @@ fn multi_file_analysis_combines_timespan_and_counts() {
     assert_eq!(out.summary.end_date.as_deref(), Some("2024-01-01T00:03:00Z"));
     // With canonicalization, logs with same structure should cluster together
     // So we expect good compression ratio instead of separate patterns
-    assert!(out.patterns.len() >= 1, "Should have at least one pattern");
+    assert!(!out.patterns.is_empty(), "Should have at least one pattern");
     assert!(out.summary.compression_ratio > 1.0, "Should achieve compression through clustering");
 }
 
+
+#[test]
+fn by_file_breakdown_tracks_source_files_in_batch_and_chunked_modes() {
+    let staging = [
+        "2024-01-01T00:00:00Z ERROR payment gateway timeout after 30s",
+        "2024-01-01T00:00:01Z INFO request served in 12ms",
+    ];
+    let prod = [
+        "2024-01-01T00:00:02Z ERROR payment gateway timeout after 45s",
+        "2024-01-01T00:00:03Z ERROR payment gateway timeout after 31s",
+        r#"[{"level":"info","msg":"batch a"},{"level":"info","msg":"batch b"}]"#,
+    ];
+    let refs: Vec<&str> = staging.iter().chain(prod.iter()).copied().collect();
+    let files = [
+        logoscope::ai::FileSpan { file: "staging.log".into(), lines: staging.len() },
+        logoscope::ai::FileSpan { file: "prod.log".into(), lines: prod.len() },
+    ];
+    let opts = logoscope::ai::SummarizeOpts::default();
+    let batch = logoscope::ai::summarize_lines_by_file(&refs, &files, &[], None, &opts);
+
+    let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    for (name, lines) in [("staging.log", &staging[..]), ("prod.log", &prod[..])] {
+        let owned: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+        engine.set_source_file(Some(name));
+        engine.ingest_chunk(&owned, &[], &opts);
+    }
+    let chunked = engine.finalize(None, &opts);
+
+    for out in [&batch, &chunked] {
+        let by_file = |needle: &str| {
+            let p = out.patterns.iter().find(|p| p.template.contains(needle)).expect(needle);
+            p.by_file.iter().map(|c| (c.name.as_str(), c.count)).collect::<Vec<_>>()
+        };
+        assert_eq!(by_file("gateway timeout"), [("prod.log", 2), ("staging.log", 1)]);
+        assert_eq!(by_file("request served"), [("staging.log", 1)]);
+        // Array-expanded records stay attributed to the file they came from
+        let batch_records: usize = out.patterns.iter()
+            .flat_map(|p| &p.by_file)
+            .filter(|c| c.name == "prod.log")
+            .map(|c| c.count)
+            .sum();
+        assert_eq!(batch_records, 4);
+    }
+
+    // Untagged input has no by_file breakdown
+    let plain = logoscope::ai::summarize_lines(&refs);
+    assert!(plain.patterns.iter().all(|p| p.by_file.is_empty()));
+}
@@ -33,3 +33,43 @@ fn consolidates_multiline_json() {
     assert!(out[0].contains("\"level\": \"info\""));
 }
 
+
+#[test]
+fn reassembles_cri_partial_lines() {
+    let lines = [
+        "2024-01-15T10:30:45.000000001Z stdout F first record",
+        "2024-01-15T10:30:46.000000001Z stdout P a very long ",
+        "2024-01-15T10:30:46.000000002Z stdout P message split ",
+        "2024-01-15T10:30:46.000000003Z stdout F by the runtime",
+        "2024-01-15T10:30:47.000000001Z stderr F Exception in thread main",
+        "2024-01-15T10:30:47.000000002Z stderr F \tat com.example.Main.run(Main.java:10)",
+    ];
+    let mut agg = logoscope::multiline::MultiLineAggregator::default();
+    let mut out = Vec::new();
+    for l in &lines { if let Some(e) = agg.push(l) { out.push(e); } }
+    if let Some(e) = agg.finish() { out.push(e); }
+    assert_eq!(out.len(), 3, "{out:?}");
+    assert_eq!(out[1], "2024-01-15T10:30:46.000000001Z stdout F a very long message split by the runtime");
+    assert!(out[2].contains("Exception in thread main\n\tat com.example.Main.run"));
+
+    let rec = logoscope::parser::parse_line(&out[1], 2);
+    assert_eq!(rec.message, "a very long message split by the runtime");
+}
+
+#[test]
+fn reassembles_docker_json_file_partial_lines() {
+    let lines = [
+        r#"{"log":"a very long ","stream":"stdout","time":"2024-01-15T10:30:46.000000001Z"}"#,
+        r#"{"log":"message split by the driver\n","stream":"stdout","time":"2024-01-15T10:30:46.000000002Z"}"#,
+        r#"{"log":"Exception in thread main\n","stream":"stderr","time":"2024-01-15T10:30:47.000000001Z"}"#,
+        r#"{"log":"\tat com.example.Main.run(Main.java:10)\n","stream":"stderr","time":"2024-01-15T10:30:47.000000002Z"}"#,
+    ];
+    let mut agg = logoscope::multiline::MultiLineAggregator::default();
+    let mut out = Vec::new();
+    for l in &lines { if let Some(e) = agg.push(l) { out.push(e); } }
+    if let Some(e) = agg.finish() { out.push(e); }
+    assert_eq!(out.len(), 2, "{out:?}");
+    let rec = logoscope::parser::parse_line(&out[0], 1);
+    assert_eq!(rec.message, "a very long message split by the driver");
+    assert!(out[1].contains("Exception in thread main\n\tat com.example.Main.run"));
+}
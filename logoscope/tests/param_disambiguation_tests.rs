@@ -122,6 +122,9 @@ fn test_anomaly_detection_with_numbered_parameters() {
         top_ratio: 0.5,
         is_sequence: None,
         sequence_info: None,
+        routes: None,
+        latency_buckets: None,
+        numeric_summary: None,
     });
     
     // NUM_2 with different distribution
@@ -136,6 +139,9 @@ fn test_anomaly_detection_with_numbered_parameters() {
         top_ratio: 0.9,
         is_sequence: None,
         sequence_info: None,
+        routes: None,
+        latency_buckets: None,
+        numeric_summary: None,
     });
     
     let context = AnalysisContext {
@@ -183,4 +189,22 @@ fn test_complex_real_world_example() {
     // Units should be preserved
     assert_eq!(result.extracted_params["NUM_MS"], vec!["150ms"]);  // duration
     assert_eq!(result.extracted_params["NUM_%"], vec!["85%"]);     // percentage
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_param_type_classification() {
+    use logoscope::param_extractor::{is_high_cardinality_numeric, is_time_param, ParamType};
+
+    assert_eq!(ParamType::of("IP_3"), Some(ParamType::Ip));
+    assert_eq!(ParamType::of("NUM_MS"), Some(ParamType::Num));
+    assert_eq!(ParamType::of("USER_ID"), None);
+    assert_eq!(ParamType::Hex.placeholder(), "<HEX>");
+
+    assert!(is_time_param("TIMESTAMP_2"));
+    assert!(is_time_param("ts"));
+    assert!(!is_time_param("TIMEOUT"));
+
+    assert!(is_high_cardinality_numeric("NUM_2", 95, 100));
+    assert!(!is_high_cardinality_numeric("NUM", 50, 100));
+    assert!(is_high_cardinality_numeric("NS", 1, 100));
+}
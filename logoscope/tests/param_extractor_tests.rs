@@ -84,4 +84,243 @@ fn test_param_merging() {
     assert_eq!(merged["IP"].len(), 2);
     assert!(merged["IP"].contains(&"192.168.1.1".to_string()));
     assert!(merged["IP"].contains(&"192.168.1.2".to_string()));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_hostname_masking_keeps_urls_and_emails() {
+    let input = "connecting to web-prod-03.us-east-1.example.com via https://api.example.com/v1 for ops@mail.example.com";
+    let result = logoscope::param_extractor::mask_and_extract(input);
+
+    assert_eq!(result.masked_text, "connecting to <HOSTNAME> via <URL> for <EMAIL>");
+    assert_eq!(result.extracted_params["HOSTNAME"], vec!["web-prod-03.us-east-1.example.com"]);
+
+    // Logger/package names and file names are not hosts; unknown zones need a host key
+    for line in ["INFO org.apache.kafka.clients.NetworkClient ready", "wrote archive.tar.gz", "loaded config.prod.yaml"] {
+        let r = logoscope::param_extractor::mask_and_extract(line);
+        assert!(!r.extracted_params.contains_key("HOSTNAME"), "{line} -> {}", r.masked_text);
+    }
+    let r = logoscope::param_extractor::mask_and_extract("upstream host api.prod.mesh up");
+    assert_eq!(r.extracted_params["HOSTNAME"], vec!["api.prod.mesh"]);
+    let r = logoscope::param_extractor::mask_and_extract("resolved cdn.example.co.uk");
+    assert_eq!(r.extracted_params["HOSTNAME"], vec!["cdn.example.co.uk"]);
+}
+
+#[test]
+fn test_redact_line_only_requested_types() {
+    use logoscope::param_extractor::{redact_line, ParamType};
+    let input = "user bob@example.com from 10.0.0.7 took 250ms on request 42";
+    let redacted = redact_line(input, &[ParamType::Email, ParamType::Ip]);
+    assert_eq!(redacted, "user <EMAIL> from <IP> took 250ms on request 42");
+    assert_eq!(redact_line(input, &[]), input);
+}
+
+#[test]
+fn templatize_path_collapses_rest_ids() {
+    use logoscope::param_extractor::templatize_path;
+    assert_eq!(templatize_path("/users/12345/orders/678"), "/users/{id}/orders/{id}");
+    assert_eq!(
+        templatize_path("https://api.example.com/v1/items/550e8400-e29b-41d4-a716-446655440000?page=2"),
+        "https://api.example.com/v1/items/{id}?page=2"
+    );
+    assert_eq!(templatize_path("/static/app.js"), "/static/app.js");
+}
+
+#[test]
+fn truncate_value_caps_length_on_char_boundaries() {
+    use logoscope::param_extractor::{truncate_value, TRUNCATION_MARKER};
+    assert_eq!(truncate_value("short", 10), "short");
+    assert_eq!(truncate_value("ééééé", 3), format!("ééé{TRUNCATION_MARKER}"));
+    assert_eq!(truncate_value("unlimited", 0), "unlimited");
+}
+
+#[test]
+fn merge_b64_json_params_surfaces_encoded_fields() {
+    use std::collections::HashMap;
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    // {"type":"order.paid","user":"u2"}
+    params.insert("B64".to_string(), vec!["eyJ0eXBlIjoib3JkZXIucGFpZCIsInVzZXIiOiJ1MiJ9".to_string()]);
+    // Not JSON once decoded
+    params.insert("B64_2".to_string(), vec!["aGVsbG8gd29ybGQgaGVsbG8gd29ybGQ=".to_string()]);
+    logoscope::param_extractor::merge_b64_json_params(&mut params);
+    assert_eq!(params["B64_JSON_TYPE"], vec!["order.paid".to_string()]);
+    assert_eq!(params["B64_JSON_USER"], vec!["u2".to_string()]);
+    assert_eq!(params.len(), 4);
+}
+
+#[test]
+fn field_filter_restricts_tracked_kv_params() {
+    use logoscope::param_extractor::{extract_kv_params_filtered, FieldFilter};
+    let fields: std::collections::BTreeMap<String, String> = [
+        ("user_id", "7"), ("order_id", "9"), ("http.path", "/a"), ("http.method", "GET"), ("msg", "ok"),
+    ].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+    let all = extract_kv_params_filtered(&fields, &FieldFilter::default());
+    assert_eq!(all.len(), 5);
+
+    let track = FieldFilter { track: vec!["*_id".into(), "HTTP.*".into()], ignore: vec!["http.method".into()], ..Default::default() };
+    let mut kept: Vec<String> = extract_kv_params_filtered(&fields, &track).into_keys().collect();
+    kept.sort();
+    assert_eq!(kept, vec!["HTTP_PATH", "ORDER_ID", "USER_ID"]);
+
+    let ignore = FieldFilter { track: vec![], ignore: vec!["msg".into()], ..Default::default() };
+    assert!(!extract_kv_params_filtered(&fields, &ignore).contains_key("MSG"));
+}
+
+#[test]
+fn normalized_field_names_merge_naming_conventions() {
+    use logoscope::param_extractor::{extract_kv_params_filtered, normalize_field_name, FieldFilter};
+    for name in ["userId", "user_id", "UserID", "user-id", "USER_ID"] {
+        assert_eq!(normalize_field_name(name), "user_id", "{name}");
+    }
+    assert_eq!(normalize_field_name("HTTPStatus"), "http_status");
+    assert_eq!(normalize_field_name("req.clientIP"), "req.client_ip");
+    assert_eq!(normalize_field_name("retry2Count"), "retry2_count");
+
+    let fields: std::collections::BTreeMap<String, String> = [("userId", "7"), ("user_id", "8")]
+        .iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    assert_eq!(extract_kv_params_filtered(&fields, &FieldFilter::default()).len(), 2);
+    let normalize = FieldFilter { normalize_names: true, ..Default::default() };
+    let params = extract_kv_params_filtered(&fields, &normalize);
+    assert_eq!(params.len(), 1);
+    assert_eq!(params["USER_ID"].len(), 2);
+}
+
+#[test]
+fn generic_masking_recognizes_all_timestamp_formats() {
+    use logoscope::param_extractor::mask_and_extract_with_disambiguation;
+    let cases = [
+        ("2024-01-15T14:20:00.123Z worker started", "2024-01-15T14:20:00.123Z"),
+        ("[10/Oct/2000:13:55:36 -0700] worker started", "10/Oct/2000:13:55:36 -0700"),
+        ("Aug 02 16:06:51 worker started", "Aug 02 16:06:51"),
+        ("at 16:06:51+02:00 worker started", "16:06:51+02:00"),
+    ];
+    for (line, ts) in cases {
+        let r = mask_and_extract_with_disambiguation(line);
+        assert!(r.masked_text.contains("<TIMESTAMP>"), "{line} -> {}", r.masked_text);
+        assert!(!r.masked_text.contains("<NUM>"), "no numeric noise: {}", r.masked_text);
+        assert_eq!(r.extracted_params["TIMESTAMP"], vec![ts.to_string()]);
+    }
+
+    // Digits embedded in ids and versions are not timestamps
+    for line in ["build id=x2024-01-15T14:20:00Z7 done", "release 12024-01-15 14:20:00+0100abc pushed"] {
+        let r = mask_and_extract_with_disambiguation(line);
+        assert!(!r.extracted_params.contains_key("TIMESTAMP"), "{line} -> {}", r.masked_text);
+    }
+}
+
+#[test]
+fn trace_context_is_extracted_as_trace_and_span_ids() {
+    use logoscope::param_extractor::{field_param_name, find_trace_id, mask_and_extract_with_disambiguation};
+    let line = "forwarding request traceparent=00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let r = mask_and_extract_with_disambiguation(line);
+    assert!(r.masked_text.contains("<TRACE_ID>-<SPAN_ID>"), "{}", r.masked_text);
+    assert_eq!(r.extracted_params["TRACE_ID"], vec!["4bf92f3577b34da6a3ce929d0e0e4736".to_string()]);
+    assert_eq!(r.extracted_params["SPAN_ID"], vec!["00f067aa0ba902b7".to_string()]);
+
+    assert_eq!(field_param_name("traceId"), "TRACE_ID");
+    assert_eq!(field_param_name("otel.span_id"), "SPAN_ID");
+    assert_eq!(field_param_name("trace.id"), "TRACE_ID");
+    assert_eq!(field_param_name("user.id"), "USER_ID");
+
+    assert_eq!(find_trace_id(None, line).as_deref(), Some("4bf92f3577b34da6a3ce929d0e0e4736"));
+    assert_eq!(find_trace_id(None, "done trace_id=ABCDEF0123456789 ok").as_deref(), Some("abcdef0123456789"));
+    let fields: std::collections::BTreeMap<String, String> = [("traceId".to_string(), "aaaabbbbccccdddd".to_string())].into();
+    assert_eq!(find_trace_id(Some(&fields), "").as_deref(), Some("aaaabbbbccccdddd"));
+    assert_eq!(find_trace_id(None, "no tracing here"), None);
+}
+
+#[test]
+fn inline_trace_and_span_ids_are_masked_in_free_text() {
+    use logoscope::param_extractor::{find_trace_id, mask_and_extract, mask_and_extract_with_disambiguation};
+    let trace = "4bf92f3577b34da6a3ce929d0e0e4736";
+    let cases = [
+        ("[trace_id:4bf92f3577b34da6a3ce929d0e0e4736 span_id:00f067aa0ba902b7] charge failed", true),
+        ("traceId: 4bf92f3577b34da6a3ce929d0e0e4736 spanId=00f067aa0ba902b7 done", true),
+        ("request trace.id=\"4bf92f3577b34da6a3ce929d0e0e4736\" failed", false),
+        ("retry (trace_id=4bf92f3577b34da6a3ce929d0e0e4736) failed", false),
+        ("charge failed for trace_id 4bf92f3577b34da6a3ce929d0e0e4736 span-id: 00f067aa0ba902b7 at checkout", true),
+    ];
+    for (line, has_span) in cases {
+        for r in [mask_and_extract(line), mask_and_extract_with_disambiguation(line)] {
+            assert!(r.masked_text.contains("<TRACE_ID>"), "{line} -> {}", r.masked_text);
+            assert!(!r.masked_text.contains(trace) && !r.masked_text.contains("<HEX"), "{line} -> {}", r.masked_text);
+            assert_eq!(r.extracted_params.get("TRACE_ID"), Some(&vec![trace.to_string()]), "{line}");
+            if has_span {
+                assert_eq!(r.extracted_params.get("SPAN_ID"), Some(&vec!["00f067aa0ba902b7".to_string()]), "{line}");
+            }
+        }
+    }
+    let r = mask_and_extract("retry (trace_id=4bf92f3577b34da6a3ce929d0e0e4736) failed");
+    assert!(r.masked_text.ends_with(") failed"), "{}", r.masked_text);
+    assert_eq!(find_trace_id(None, "span_id=00f067aa0ba902b7 trace_id=4bf92f3577b34da6a3ce929d0e0e4736").as_deref(), Some(trace));
+}
+
+#[test]
+fn large_integers_are_classified_as_epoch_timestamps_or_ids() {
+    use logoscope::param_extractor::{classify_integer, mask_and_extract, ParamType};
+    assert_eq!(classify_integer("1700000000"), ParamType::Timestamp);
+    assert_eq!(classify_integer("1700000000123"), ParamType::Timestamp);
+    assert_eq!(classify_integer("1700000000123456"), ParamType::Timestamp);
+    // Snowflake IDs and nanosecond-length values are identifiers
+    assert_eq!(classify_integer("1541815603606036480"), ParamType::Id);
+    assert_eq!(classify_integer("9999999999999"), ParamType::Id);
+    assert_eq!(classify_integer("8080"), ParamType::Num);
+
+    let result = mask_and_extract("order 1541815603606036480 placed at 1700000000123");
+    assert_eq!(result.masked_text, "order <ID> placed at <TIMESTAMP>");
+    assert_eq!(result.extracted_params["ID"], vec!["1541815603606036480"]);
+    assert!(logoscope::parser::detect_timestamp_in_text("id=9999999999 at 1700000000").is_some_and(|t| t.timestamp() == 1_700_000_000));
+}
+
+#[test]
+fn query_params_split_url_query_strings_into_params() {
+    let opts = logoscope::ai::SummarizeOpts { query_params: true, ..Default::default() };
+
+    // Free-text URL: the query is not mistaken for key=value pairs and the full URL is kept
+    let r = opts.canonicalize("GET https://api.example.com/v1/items?action=delete&id=5#top took 3ms");
+    assert_eq!(r.masked_text, "GET <URL> took <NUM>ms");
+    assert_eq!(r.extracted_params["URL"], vec!["https://api.example.com/v1/items?action=delete&id=5#top"]);
+    assert_eq!(r.extracted_params["QUERY_action"], vec!["delete"]);
+    assert_eq!(r.extracted_params["QUERY_id"], vec!["5"]);
+
+    let r = opts.canonicalize("GET /v1/items?action=delete&id=5 200");
+    assert_eq!(r.masked_text, "GET <PATH> <NUM>");
+    assert_eq!(r.extracted_params["PATH"], vec!["/v1/items?action=delete&id=5"]);
+    assert_eq!(r.extracted_params["QUERY_action"], vec!["delete"]);
+
+    // Field values holding a URL
+    for line in [r#"{"uri":"/v1/items?action=view","msg":"x"}"#, "method=GET uri=/v1/items?action=view"] {
+        let r = opts.canonicalize(line);
+        assert_eq!(r.extracted_params["URI"], vec!["/v1/items?action=view"], "{line}");
+        assert_eq!(r.extracted_params["QUERY_action"], vec!["view"], "{line}");
+    }
+
+    // Off by default; a `?` outside a URL is left alone
+    let plain = logoscope::ai::SummarizeOpts::default().canonicalize("GET /v1/items?action=delete 200");
+    assert!(!plain.extracted_params.keys().any(|k| k.starts_with("QUERY_")));
+    let r = opts.canonicalize("why? a=b");
+    assert!(!r.extracted_params.keys().any(|k| k.starts_with("QUERY_")), "{:?}", r.extracted_params);
+}
+
+#[test]
+fn test_mask_rule_named_groups_become_params() {
+    use logoscope::param_extractor::{apply_mask_rules, MaskRule};
+    let rule = MaskRule::parse(r"^\[(?P<ts>[^\]]+)\] (?P<component>\w+):").unwrap();
+    let r = apply_mask_rules("[2024-01-15 10:30] billing: charge ok", std::slice::from_ref(&rule)).unwrap();
+    assert_eq!(r.masked_text, "[<TS>] <COMPONENT>: charge ok");
+    assert_eq!(r.extracted_params["TS"], vec!["2024-01-15 10:30"]);
+    assert_eq!(r.extracted_params["COMPONENT"], vec!["billing"]);
+    assert!(apply_mask_rules("no prefix here", std::slice::from_ref(&rule)).is_none());
+
+    // Rules run before built-in masking, and their params survive it
+    let opts = logoscope::ai::SummarizeOpts { mask_rules: vec![rule], ..Default::default() };
+    let a = opts.canonicalize("[2024-01-15 10:30] billing: charged 42 cents");
+    let b = opts.canonicalize("[Mon Jan 15] auth: charged 7 cents");
+    assert_eq!(a.masked_text, b.masked_text);
+    assert!(a.masked_text.starts_with("[<TS>] <COMPONENT>:"), "{}", a.masked_text);
+    assert_eq!(b.extracted_params["COMPONENT"], vec!["auth"]);
+    assert_eq!(b.extracted_params["TS"], vec!["Mon Jan 15"]);
+
+    assert!(MaskRule::parse(r"\d+").unwrap_err().contains("no named groups"));
+    assert!(MaskRule::parse(r"(?P<x>[").unwrap_err().contains("invalid regex"));
+}
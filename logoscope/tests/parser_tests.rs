@@ -38,7 +38,7 @@ fn parse_plaintext_syslog_extracts_timestamp() {
     let year = Utc::now().year();
     let line = "Sep 05 14:20:00 host app[123]: ready";
     let rec = logoscope::parser::parse_line(line, 100);
-    let expected = Utc.with_ymd_and_hms(year as i32, 9, 5, 14, 20, 0).unwrap();
+    let expected = Utc.with_ymd_and_hms(year, 9, 5, 14, 20, 0).unwrap();
     assert_eq!(rec.format, logoscope::parser::LogFormat::Plaintext);
     assert_eq!(rec.timestamp.unwrap(), expected);
 }
@@ -155,3 +155,155 @@ fn parse_json_with_rfc3339_nanoseconds() {
     assert_eq!(ts.second(), 29);
     assert_eq!(ts.nanosecond(), 284151911);
 }
+
+#[test]
+fn parse_cri_prefixed_line_strips_prefix_and_keeps_stream() {
+    let line = "2024-01-15T10:30:45.123456789Z stderr F {\"level\":\"error\",\"msg\":\"boom\"}";
+    let rec = logoscope::parser::parse_line(line, 1);
+    assert_eq!(rec.format, logoscope::parser::LogFormat::Json);
+    let ts = rec.timestamp.expect("CRI timestamp");
+    assert_eq!(ts.nanosecond(), 123_456_789);
+    let cri = rec.cri.expect("cri meta");
+    assert_eq!(cri.stream, "stderr");
+    assert!(!cri.partial);
+
+    let plain = logoscope::parser::parse_line("2024-01-15T10:30:45.000000001Z stdout F server started", 2);
+    assert_eq!(plain.message, "server started");
+    assert_eq!(plain.timestamp.unwrap().second(), 45);
+}
+
+#[test]
+fn input_format_hint_selects_the_parser_path() {
+    use logoscope::parser::{parse_line_as, InputFormat, LogFormat, ParseOpts};
+    let json = ParseOpts { input_format: InputFormat::Json, ..Default::default() };
+    let text = ParseOpts { input_format: InputFormat::Text, ..Default::default() };
+    let prefixed = r#"2024-01-01T00:00:05Z {"level":"info","msg":"ok"}"#;
+    assert_eq!(parse_line_as(prefixed, 1, &[], ParseOpts::default()).format, LogFormat::Plaintext);
+    let rec = parse_line_as(prefixed, 1, &[], json);
+    assert_eq!(rec.format, LogFormat::Json);
+    assert_eq!(rec.flat_fields.unwrap()["level"], "info");
+    assert_eq!(rec.timestamp.unwrap().to_rfc3339(), "2024-01-01T00:00:05+00:00");
+
+    let braces = "{retry} handler failed for job 7";
+    assert!(InputFormat::Auto.expects_json(braces));
+    assert!(!InputFormat::Text.expects_json(braces));
+    assert_eq!(parse_line_as(r#"{"a":1}"#, 1, &[], text).format, LogFormat::Plaintext);
+}
+
+#[test]
+fn ansi_escapes_are_stripped_before_parsing() {
+    use logoscope::parser::{parse_line_as, ParseOpts};
+    let colored = "2024-01-01T00:00:00Z \x1b[31mERROR\x1b[0m build \x1b]8;;http://ci\x07step\x1b]8;;\x07 failed";
+    let rec = logoscope::parser::parse_line(colored, 1);
+    assert_eq!(rec.message, "2024-01-01T00:00:00Z ERROR build step failed");
+    assert!(rec.timestamp.is_some());
+
+    let json = "\x1b[32m{\"level\":\"info\",\"msg\":\"ok\"}\x1b[0m";
+    assert!(logoscope::parser::parse_line(json, 1).flat_fields.is_some());
+
+    let raw = parse_line_as(colored, 1, &[], ParseOpts { strip_ansi: false, ..Default::default() });
+    assert!(raw.message.contains('\x1b'));
+}
+
+#[test]
+fn time_key_hints_resolve_nested_paths_and_skip_unknown_ones() {
+    let line = r#"{"created":"2020-01-01T00:00:00Z","meta":{"event":{"time":"2024-03-01T10:00:00Z"}},"events":[{"ts":"2024-03-02T00:00:00Z"}]}"#;
+    let ts = |keys: &[&str]| logoscope::parser::parse_line_with_hints(line, 1, keys).timestamp.unwrap().to_rfc3339();
+    assert_eq!(ts(&["meta.event.time"]), "2024-03-01T10:00:00+00:00");
+    assert_eq!(ts(&["$.events[0].ts"]), "2024-03-02T00:00:00+00:00");
+    // A hint that does not resolve falls through to the next one, then to scanning all fields
+    assert_eq!(ts(&["meta.missing.time", "meta.event.time"]), "2024-03-01T10:00:00+00:00");
+    assert_eq!(ts(&["nope"]), "2020-01-01T00:00:00+00:00");
+}
+
+#[test]
+fn syslog_priority_is_decoded_into_facility_and_severity() {
+    let rec = logoscope::parser::parse_line("<38>Oct 11 22:14:15 web01 sshd[811]: Accepted password for bob", 1);
+    let pri = rec.syslog.unwrap();
+    assert_eq!((pri.facility, pri.severity), ("auth", "info"));
+    assert_eq!(rec.message, "Oct 11 22:14:15 web01 sshd[811]: Accepted password for bob");
+    assert!(rec.timestamp.is_some());
+
+    let rec = logoscope::parser::parse_line("<83>1 2024-01-01T00:00:00Z web01 sudo - - - auth failure", 1);
+    let pri = rec.syslog.unwrap();
+    assert_eq!((pri.facility, pri.severity, pri.level()), ("authpriv", "err", "ERROR"));
+
+    assert!(logoscope::parser::parse_syslog_pri("<192>too big").is_none());
+    assert!(logoscope::parser::parse_line("<html> not syslog", 1).syslog.is_none());
+}
+
+#[test]
+fn consecutive_dedup_ignores_timestamps_and_respects_window() {
+    let mut d = logoscope::parser::ConsecutiveDedup::new(chrono::Duration::milliseconds(100), &[], Default::default());
+    assert!(d.keep("2024-01-01T00:00:00.000Z user 1 login"));
+    assert!(!d.keep("2024-01-01T00:00:00.020Z user 1 login"));
+    assert!(d.keep("2024-01-01T00:00:00.030Z user 2 login"));
+    assert!(d.keep("2024-01-01T00:00:01.000Z user 2 login"));
+    // Untimed copies always collapse
+    assert!(d.keep("heartbeat"));
+    assert!(!d.keep("heartbeat"));
+    assert_eq!(d.dropped, 2);
+}
+
+#[test]
+fn json_array_lines_split_into_records() {
+    let batch = r#"[{"level":"info","msg":"a"},{"level":"error","msg":"b"}]"#;
+    let records = logoscope::parser::split_json_array(batch).expect("batch");
+    assert_eq!(records.len(), 2);
+    assert!(records[1].contains(r#""level":"error""#));
+    assert!(logoscope::parser::split_json_array("[1,2]").is_none());
+    assert!(logoscope::parser::split_json_array("[]").is_none());
+    assert!(logoscope::parser::split_json_array("[INFO] started").is_none());
+
+    // A single-object array parses as that object
+    let rec = logoscope::parser::parse_line(r#"[{"level":"warn","time":"2024-01-01T00:00:00Z","msg":"slow"}]"#, 1);
+    assert_eq!(rec.flat_fields.unwrap().get("level").map(String::as_str), Some("warn"));
+    assert!(rec.timestamp.is_some());
+
+    let lines = ["plain line", batch];
+    let expanded = logoscope::parser::expand_json_arrays(&lines, logoscope::parser::InputFormat::Auto).unwrap();
+    assert_eq!(expanded.len(), 3);
+    assert_eq!(expanded[0], "plain line");
+    assert!(logoscope::parser::expand_json_arrays(&lines, logoscope::parser::InputFormat::Text).is_none());
+}
+
+#[test]
+fn parse_docker_json_file_record_unwraps_log() {
+    let line = r#"{"log":"\u001b[31mconnection refused to 10.0.0.7\u001b[0m\n","stream":"stderr","time":"2024-01-15T10:30:45.123456789Z"}"#;
+    let rec = logoscope::parser::parse_line(line, 1);
+    assert_eq!(rec.format, logoscope::parser::LogFormat::Plaintext);
+    assert_eq!(rec.message, "connection refused to 10.0.0.7");
+    assert_eq!(rec.timestamp.expect("driver timestamp").nanosecond(), 123_456_789);
+    let meta = rec.cri.expect("stream meta");
+    assert_eq!(meta.stream, "stderr");
+    assert!(!meta.partial);
+
+    let partial = logoscope::parser::parse_docker_json(r#"{"log":"first half","stream":"stdout","time":"2024-01-15T10:30:45Z"}"#).unwrap();
+    assert!(partial.partial);
+    assert_eq!(
+        logoscope::parser::docker_json_as_cri(r#"{"log":"done\n","stream":"stdout","time":"2024-01-15T10:30:45Z"}"#).as_deref(),
+        Some("2024-01-15T10:30:45Z stdout F done"),
+    );
+
+    // Application JSON with a `log` field is not a driver record
+    let app = logoscope::parser::parse_line(r#"{"log":"x","level":"info","time":"2024-01-15T10:30:45Z"}"#, 2);
+    assert_eq!(app.format, logoscope::parser::LogFormat::Json);
+    assert!(app.cri.is_none());
+}
+
+#[test]
+fn detect_time_key_prefers_consistent_timestamp_named_field() {
+    use logoscope::parser::{detect_time_key, ParseOpts};
+    let lines = [
+        r#"{"created_at":"2023-05-01T00:00:00Z","eventTime":"2024-01-15T10:30:45Z","seq":1700000000,"msg":"a"}"#,
+        "plain text continuation",
+        r#"{"created_at":"2023-05-02T00:00:00Z","eventTime":"2024-01-15T10:30:46Z","seq":1700000001,"msg":"b"}"#,
+    ];
+    assert_eq!(detect_time_key(&lines, ParseOpts::default()).as_deref(), Some("eventTime"));
+
+    let custom = [r#"{"logged_at":"2024-01-15T10:30:45Z","count":1700000000}"#, r#"{"logged_at":"2024-01-15T10:30:46Z","count":1700000001}"#];
+    assert_eq!(detect_time_key(&custom, ParseOpts::default()).as_deref(), Some("logged_at"));
+
+    let none = [r#"{"level":"info","msg":"ok"}"#, "not json"];
+    assert_eq!(detect_time_key(&none, ParseOpts::default()), None);
+}
@@ -0,0 +1,53 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use logoscope::pattern_store::{compute_trends, load_records, append_run, pattern_id, StoreRecord};
+
+fn rec(id: &str, count: usize, total: usize, day: u32) -> StoreRecord {
+    StoreRecord {
+        pattern_id: id.to_string(),
+        template: format!("tpl {id}"),
+        severity: None,
+        count,
+        total_lines: total,
+        date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+        run_at: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+    }
+}
+
+#[test]
+fn pattern_id_is_deterministic_and_severity_aware() {
+    assert_eq!(pattern_id("User <NUM> logged in", None), pattern_id("User <NUM> logged in", None));
+    assert_ne!(pattern_id("User <NUM> logged in", Some("ERROR")), pattern_id("User <NUM> logged in", None));
+}
+
+#[test]
+fn trends_compare_current_window_to_previous() {
+    let records = vec![
+        // previous week (Jan 1..7): errors are 10% of traffic
+        rec("err", 10, 100, 2), rec("ok", 90, 100, 2),
+        // current week (Jan 8..14): errors are 40% of traffic
+        rec("err", 40, 100, 10), rec("ok", 60, 100, 10),
+        // outside both windows
+        rec("err", 1000, 1000, 20),
+    ];
+    let trends = compute_trends(&records, NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(), 7);
+    assert_eq!(trends.len(), 2);
+    assert_eq!(trends[0].pattern_id, "err");
+    assert_eq!(trends[0].current_count, 40);
+    assert_eq!(trends[0].previous_count, 10);
+    assert!((trends[0].frequency_delta - 0.3).abs() < 1e-9);
+    assert!(trends[1].frequency_delta < 0.0);
+}
+
+#[test]
+fn append_run_round_trips_through_store() {
+    let dir = std::env::temp_dir().join(format!("logoscope_store_test_{}", std::process::id()));
+    let lines = ["2024-01-01T00:00:00Z User 1 logged in", "2024-01-01T00:00:01Z User 2 logged in"];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let now = Utc::now();
+    append_run(&dir, &out, now.date_naive(), now).unwrap();
+    append_run(&dir, &out, now.date_naive(), now).unwrap();
+    let records = load_records(&dir).unwrap();
+    assert_eq!(records.len(), 2 * out.patterns.len());
+    assert_eq!(records[0].pattern_id, records[out.patterns.len()].pattern_id);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -17,3 +17,18 @@ fn clusters_plaintext_masked_lines_into_templates() {
     assert_eq!(templates[1].0.as_str(), "User <*> logged out from <*> at <*>");
     assert_eq!(templates[1].1, 1);
 }
+
+#[test]
+fn groups_templates_by_token_similarity() {
+    use logoscope::patterns::{group_similar_templates, template_similarity};
+    assert_eq!(template_similarity("User <*> logged in", "User <NUM> logged in"), 1.0);
+    assert_eq!(template_similarity("User <*> logged in", "User <*> logged out"), 0.75);
+    let templates = [
+        "connection timeout to <IP> after <NUM> ms",
+        "cache warmed in <NUM> ms",
+        "connection refused by <IP> after <NUM> ms",
+        "connection reset by <IP> after <NUM> ms",
+    ];
+    assert_eq!(group_similar_templates(&templates, 0.6), vec![vec![0, 2, 3]]);
+    assert!(group_similar_templates(&templates, 0.95).is_empty());
+}
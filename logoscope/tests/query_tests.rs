@@ -17,7 +17,7 @@ fn query_by_pattern_and_time_and_context() {
     assert_eq!(lines, vec![l1, l2]);
 
     // Time range query for first minute returns first two
-    let year = Utc::now().year() as i32;
+    let year = Utc::now().year();
     let day = Utc.with_ymd_and_hms(year, 9, 5, 10, 0, 0).unwrap();
     let hits = idx.get_lines_by_time(day, day + chrono::Duration::minutes(1), None);
     let lines: Vec<&str> = hits.iter().map(|e| e.line.as_str()).collect();
@@ -28,3 +28,20 @@ fn query_by_pattern_and_time_and_context() {
     let lines: Vec<&str> = ctx.iter().map(|e| e.line.as_str()).collect();
     assert_eq!(lines, vec![l1, l2, l3]);
 }
+
+#[test]
+fn trace_timeline_lists_a_trace_in_time_order() {
+    let mut idx = logoscope::query::QueryIndex::new();
+    let lines = [
+        r#"{"time":"2024-01-01T00:00:02Z","service":"billing","msg":"charge ok","trace_id":"aaaaaaaaaaaaaaaa"}"#,
+        r#"{"time":"2024-01-01T00:00:01Z","service":"api","msg":"request received","traceId":"AAAAAAAAAAAAAAAA"}"#,
+        r#"{"time":"2024-01-01T00:00:01Z","service":"api","msg":"request received","traceId":"bbbbbbbbbbbbbbbb"}"#,
+        "2024-01-01T00:00:03Z gateway responded traceparent=00-aaaaaaaaaaaaaaaa0000000000000000-00f067aa0ba902b7-01",
+    ];
+    for l in lines { idx.push_line(l); }
+    let timeline = idx.get_trace_timeline("aaaaaaaaaaaaaaaa");
+    let ids: Vec<usize> = timeline.iter().map(|e| e.id).collect();
+    assert_eq!(ids, vec![1, 0]);
+    assert_eq!(timeline[0].service.as_deref(), Some("api"));
+    assert_eq!(idx.get_trace_timeline("aaaaaaaaaaaaaaaa0000000000000000").len(), 1);
+}
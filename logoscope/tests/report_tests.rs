@@ -0,0 +1,41 @@
+#[test]
+fn markdown_report_has_all_sections() {
+    let lines = [
+        r#"{"level":"info","time":"2024-01-01T00:00:00Z","msg":"request served"}"#,
+        r#"{"level":"info","time":"2024-01-01T00:00:10Z","msg":"request served"}"#,
+        r#"{"level":"error","time":"2024-01-01T00:00:20Z","msg":"db | connection refused"}"#,
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let md = logoscope::report::render_markdown(&out);
+    assert!(md.starts_with("# Logoscope Report"));
+    assert!(md.contains("| Total lines | 3 |"));
+    assert!(md.contains("## Top Insights"));
+    assert!(md.contains("error log entries requiring immediate attention"));
+    assert!(md.contains("## Patterns"));
+    assert!(md.contains("## Anomalies"));
+    // Pipes inside templates must not break the table
+    assert!(!md.lines().any(|l| l.contains("db | connection")));
+}
+
+#[test]
+fn correlation_graph_renders_templates_and_edges() {
+    let mut lines = Vec::new();
+    for i in 0..10 {
+        lines.push(format!("2024-01-01T00:{i:02}:00Z ERROR db \"primary\" timeout"));
+        lines.push(format!("2024-01-01T00:{i:02}:02Z WARN request retried"));
+    }
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    let opts = logoscope::ai::SummarizeOpts { deep: true, ..Default::default() };
+    let out = logoscope::ai::summarize_lines_with_opts(&refs, &[], None, &opts);
+    let dot = logoscope::report::render_correlation_dot(&out);
+    assert!(dot.starts_with("digraph correlations {"), "{dot}");
+    assert!(dot.trim_end().ends_with('}'));
+    assert!(dot.contains("request retried"), "{dot}");
+    assert!(dot.contains(r#"db \"primary\" timeout"#), "{dot}");
+    // Both directions of the same pair collapse into one edge
+    assert_eq!(dot.matches(" -> ").count(), 1, "{dot}");
+
+    // Without deep correlations the graph is empty
+    let dot = logoscope::report::render_correlation_dot(&logoscope::ai::summarize_lines(&refs));
+    assert!(!dot.contains("->"));
+}
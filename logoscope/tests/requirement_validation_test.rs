@@ -6,7 +6,7 @@ fn test_requirements_validation() {
     println!("=== VALIDATING ORIGINAL REQUIREMENTS ===");
     
     // Test the specific examples from the requirements
-    let requirement_cases = vec![
+    let requirement_cases = [
         (
             "2025-08-07T06:41:18.123456Z", 
             "Should detect the full timestamp including microseconds and Z timezone"
@@ -51,7 +51,7 @@ fn test_requirements_validation() {
     
     // Additional timezone format tests
     println!("## Testing Enhanced Timezone Support");
-    let timezone_cases = vec![
+    let timezone_cases = [
         ("2025-01-01T12:00:00Z", "Z timezone"),
         ("2025-01-01T12:00:00+00:00", "+00:00 timezone with colon"),
         ("2025-01-01T12:00:00+0000", "+0000 timezone without colon"),
@@ -79,7 +79,7 @@ fn test_requirements_validation() {
     
     // Fractional seconds precision tests  
     println!("## Testing Enhanced Precision Support");
-    let precision_cases = vec![
+    let precision_cases = [
         ("2025-01-01T12:00:00.1Z", "1 digit fractional"),
         ("2025-01-01T12:00:00.12Z", "2 digits fractional"),
         ("2025-01-01T12:00:00.123Z", "3 digits (milliseconds)"),
@@ -105,7 +105,7 @@ fn test_requirements_validation() {
     
     // Test that time-only is correctly NOT matched (by design)
     println!("## Testing Time-Only Exclusion (By Design)");
-    let time_only_cases = vec![
+    let time_only_cases = [
         "09:20:26.851+0000",
         "12:00:00Z", 
         "14:30:45.999-0800",
@@ -128,7 +128,7 @@ fn test_requirements_validation() {
 fn test_integration_with_real_world_logs() {
     println!("=== TESTING REAL-WORLD LOG INTEGRATION ===");
     
-    let real_world_logs = vec![
+    let real_world_logs = [
         // Nginx access log
         r#"192.168.1.100 - - [2025-01-01T12:00:00.123456Z] "GET /api/users HTTP/1.1" 200 1234"#,
         
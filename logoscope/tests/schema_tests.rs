@@ -33,3 +33,13 @@ fn schema_diff_detects_changes() {
         logoscope::schema::SchemaChange::FieldRemoved{..})));
 }
 
+#[test]
+fn schema_change_impact_names_both_types() {
+    use logoscope::schema::SchemaChange;
+    let to_number = SchemaChange::TypeChanged { field: "status".into(), from_type: "string".into(), to_type: "int".into() };
+    assert_eq!(to_number.impact(), "string→int may break parsers expecting text");
+    let to_text = SchemaChange::TypeChanged { field: "status".into(), from_type: "float".into(), to_type: "string".into() };
+    assert!(to_text.impact().starts_with("float→string may break numeric"));
+    let removed = SchemaChange::FieldRemoved { field: "user.id".into(), old_type: "int".into() };
+    assert!(removed.impact().contains("int field missing"));
+}
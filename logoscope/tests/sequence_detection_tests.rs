@@ -272,6 +272,9 @@ fn create_param_stats_with_sequence_detection(param_type: &str, values: Vec<(&st
         top_ratio,
         is_sequence: None,
         sequence_info: None,
+        routes: None,
+        latency_buckets: None,
+        numeric_summary: None,
     }, param_type)
 }
 
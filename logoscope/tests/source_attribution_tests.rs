@@ -1,6 +1,6 @@
 #[test]
 fn patterns_include_source_breakdown() {
-    let lines = vec![
+    let lines = [
         r#"{"level":"info","time":"2024-01-01T00:00:00Z","service":"auth","host":"h1","op":"A","status":"ok"}"#,
         r#"{"level":"info","time":"2024-01-01T00:01:00Z","service":"auth","host":"h2","op":"A","status":"ok"}"#,
         r#"{"level":"info","time":"2024-01-01T00:02:00Z","service":"billing","host":"h3","action":"B","result":"done"}"#,
@@ -15,7 +15,7 @@ fn patterns_include_source_breakdown() {
     // Find a pattern that has multiple sources
     let mut found_multi_source = false;
     for p in &out.patterns {
-        if p.sources.by_service.len() > 0 || p.sources.by_host.len() > 0 {
+        if !p.sources.by_service.is_empty() || !p.sources.by_host.is_empty() {
             found_multi_source = true;
             // Verify source information is present and reasonable
             for svc in &p.sources.by_service {
@@ -31,3 +31,22 @@ fn patterns_include_source_breakdown() {
     assert!(found_multi_source, "Should have source attribution data");
 }
 
+
+#[test]
+fn patterns_report_dominant_service_and_host() {
+    let lines = [
+        r#"{"level":"error","time":"2024-01-01T00:00:00Z","service":"api","host":"h1","msg":"upstream timeout"}"#,
+        r#"{"level":"error","time":"2024-01-01T00:00:01Z","service":"api","host":"h1","msg":"upstream timeout"}"#,
+        r#"{"level":"error","time":"2024-01-01T00:00:02Z","service":"api","host":"h1","msg":"upstream timeout"}"#,
+        r#"{"level":"error","time":"2024-01-01T00:00:03Z","service":"web","host":"h2","msg":"upstream timeout"}"#,
+    ];
+    let out = logoscope::ai::summarize_lines(&lines);
+    let p = out.patterns.iter().find(|p| p.total_count == 4).expect("single pattern");
+    assert_eq!(p.dominant_service.as_deref(), Some("api"));
+    assert!((p.dominant_service_ratio.unwrap() - 0.75).abs() < 1e-9);
+    assert_eq!(p.dominant_host.as_deref(), Some("h1"));
+
+    let json = serde_json::to_value(p).unwrap();
+    assert_eq!(json["dominant_service"], "api");
+    assert!(json.get("sources").is_none(), "full breakdown stays out of the payload");
+}
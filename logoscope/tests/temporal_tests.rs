@@ -24,6 +24,25 @@ fn detects_simple_burst_periods() {
     assert!(b.severity >= 5.0); // median=1, severity >= 5x
 }
 
+#[test]
+fn burst_severity_ignores_earlier_busy_minutes_in_its_baseline() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut times = Vec::new();
+    // 5 typical minutes of 2 events, 4 busy minutes of 8, then one minute of 20
+    let per_minute = [2, 2, 2, 2, 2, 8, 8, 8, 8, 20];
+    for (m, n) in per_minute.iter().enumerate() {
+        for s in 0..*n {
+            times.push(start + Duration::minutes(m as i64) + Duration::seconds(s));
+        }
+    }
+
+    let bursts = logoscope::temporal::compute_bursts(&times, Duration::minutes(1), 3.0);
+    assert_eq!(bursts.len(), 1);
+    assert_eq!(bursts[0].peak_rate, 20);
+    // all-bucket median is 5 (20 reads as 4x); the typical non-burst minute has 2 events
+    assert!((bursts[0].severity - 10.0).abs() < 1e-9, "{}", bursts[0].severity);
+}
+
 #[test]
 fn detects_large_gaps() {
     let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
@@ -62,3 +81,129 @@ fn detects_frequency_spikes() {
     assert!(s.count >= 10);
     assert!(s.zscore >= 3.0);
 }
+
+#[test]
+fn periodicity_distinguishes_regular_from_bursty() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let regular: Vec<_> = (0..10).map(|i| start + Duration::minutes(5 * i)).collect();
+    let mut bursty: Vec<_> = (0..9).map(|i| start + Duration::seconds(i)).collect();
+    bursty.push(start + Duration::minutes(45));
+
+    let p_regular = logoscope::temporal::compute_periodicity(&regular).unwrap();
+    let p_bursty = logoscope::temporal::compute_periodicity(&bursty).unwrap();
+    assert!((p_regular - 1.0).abs() < 1e-9);
+    assert!(p_bursty < 0.5);
+    assert!(logoscope::temporal::compute_periodicity(&regular[..3]).is_none());
+}
+
+#[test]
+fn deep_temporal_heatmap_only_for_multi_day_spans() {
+    // 2024-01-06 is a Saturday
+    let ts = |d, h| Utc.with_ymd_and_hms(2024, 1, d, h, 0, 0).unwrap();
+    let multi_day = vec![ts(6, 3), ts(6, 3), ts(8, 14), ts(10, 23)];
+    let deep = logoscope::ai::compute_deep_temporal(&multi_day, "t", &[], &[]);
+    let grid = deep.weekday_hour_heatmap.expect("span over two days");
+    assert_eq!(grid.len(), 7);
+    assert!(grid.iter().all(|row| row.len() == 24));
+    assert_eq!(grid[5][3], 2, "Saturday 03:00");
+    assert_eq!(grid[0][14], 1, "Monday 14:00");
+    assert_eq!(grid[2][23], 1, "Wednesday 23:00");
+
+    let one_day = vec![ts(6, 1), ts(6, 20)];
+    assert!(logoscope::ai::compute_deep_temporal(&one_day, "t", &[], &[]).weekday_hour_heatmap.is_none());
+}
+
+#[test]
+fn bursts_below_min_events_are_dropped() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    // Sparse pattern: one event every 10 minutes, plus a 3-event minute
+    let mut times: Vec<_> = (0..10).map(|m| start + Duration::minutes(m * 10)).collect();
+    times.extend((0..3).map(|s| start + Duration::minutes(45) + Duration::seconds(s)));
+
+    let all = logoscope::temporal::compute_bursts(&times, Duration::minutes(1), 3.0);
+    assert_eq!(all.len(), 1);
+    let filtered = logoscope::temporal::compute_bursts_min_events(&times, Duration::minutes(1), 3.0, 5);
+    assert!(filtered.is_empty());
+    let kept = logoscope::temporal::compute_bursts_min_events(&times, Duration::minutes(1), 3.0, 3);
+    assert_eq!(kept, all);
+
+    // The deep burst detail honours the same threshold
+    let deep = |burst_min_events| {
+        let opts = logoscope::ai::SummarizeOpts { burst_min_events, ..Default::default() };
+        logoscope::ai::compute_deep_temporal_with_opts(&times, "t", &[], &[], &opts)
+    };
+    assert!(deep(5).burst_analysis.is_empty());
+    assert_eq!(deep(3).burst_analysis.len(), 1);
+}
+
+#[test]
+fn skew_tracker_reports_significant_backward_jumps() {
+    use logoscope::temporal::SkewTracker;
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut ordered = SkewTracker::default();
+    for s in 0..10 { ordered.observe(start + Duration::seconds(s)); }
+    assert!(ordered.report().is_none());
+
+    let mut skewed = SkewTracker::default();
+    for s in [0, 10, 20, 5, 30, 25] { skewed.observe(start + Duration::seconds(s)); }
+    let r = skewed.report().expect("2 of 6 lines out of order");
+    assert_eq!(r.out_of_order_lines, 2);
+    assert_eq!(r.timestamped_lines, 6);
+    assert_eq!(r.max_backward_seconds, 15);
+}
+
+#[test]
+fn burst_windows_below_a_minute_and_aggregation_buckets() {
+    use logoscope::temporal::aggregation_bucket_ms;
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut times = Vec::new();
+    for s in 0..30 {
+        times.push(start + Duration::seconds(s));
+        times.push(start + Duration::seconds(s) + Duration::milliseconds(500));
+    }
+    for ms in 0..20 {
+        times.push(start + Duration::seconds(12) + Duration::milliseconds(ms * 10));
+    }
+    assert!(logoscope::temporal::compute_bursts(&times, Duration::minutes(1), 3.0).is_empty());
+    let bursts = logoscope::temporal::compute_bursts(&times, Duration::seconds(1), 3.0);
+    assert_eq!(bursts.len(), 1);
+    assert_eq!(bursts[0].start_time, start + Duration::seconds(12));
+    assert_eq!(bursts[0].peak_rate, 22);
+
+    assert_eq!(aggregation_bucket_ms(Duration::hours(1)), 60_000);
+    assert_eq!(aggregation_bucket_ms(Duration::seconds(90)), 30_000);
+    assert_eq!(aggregation_bucket_ms(Duration::milliseconds(250)), 250);
+}
+
+#[test]
+fn bucket_start_handles_epoch_edges() {
+    use chrono::DateTime;
+    use logoscope::temporal::bucket_start;
+    assert_eq!(bucket_start(0, 60_000), Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap());
+    // Pre-epoch buckets are negative
+    assert_eq!(bucket_start(-1, 60_000), Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 0).unwrap());
+    // A bucket floored from the earliest representable instant starts before chrono's range
+    let first = DateTime::<Utc>::MIN_UTC.timestamp_millis().div_euclid(60_000);
+    assert_eq!(bucket_start(first, 60_000), DateTime::<Utc>::MIN_UTC);
+    assert_eq!(bucket_start(i64::MAX, 60_000), DateTime::<Utc>::MAX_UTC);
+    assert_eq!(bucket_start(i64::MIN, 60_000), DateTime::<Utc>::MIN_UTC);
+}
+
+#[test]
+fn chunked_temporal_keeps_counts_around_the_epoch() {
+    let lines = [
+        "1969-12-31T23:59:30Z INFO clock reset",
+        "1970-01-01T00:00:00Z INFO clock reset",
+        "1970-01-01T00:00:30Z INFO clock reset",
+    ];
+    let opts = logoscope::ai::SummarizeOpts::default();
+    let mut engine = logoscope::ai::StreamingSummarizer::with_opts(&opts);
+    let chunk: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+    engine.ingest_chunk(&chunk, &[], &opts);
+    let out = engine.finalize(None, &opts);
+    assert_eq!(out.patterns.len(), 1);
+    let p = &out.patterns[0];
+    assert_eq!(p.total_count, 3);
+    assert_eq!(p.start_time.as_deref(), Some("1969-12-31T23:59:00Z"));
+    assert_eq!(p.end_time.as_deref(), Some("1970-01-01T00:00:00Z"));
+}
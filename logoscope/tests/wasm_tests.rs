@@ -0,0 +1,10 @@
+#![cfg(feature = "wasm")]
+
+#[test]
+fn wasm_export_summarizes_pasted_text_as_json() {
+    let text = "2024-01-01T00:00:00Z INFO user 1 logged in\n\n2024-01-01T00:00:01Z INFO user 2 logged in\n";
+    let json = logoscope::wasm::summarize_lines(text).unwrap();
+    let out: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(out["summary"]["total_lines"], 2);
+    assert_eq!(out["patterns"].as_array().unwrap().len(), 1);
+}